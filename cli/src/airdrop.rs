@@ -0,0 +1,153 @@
+//! Merkle trees of airdrop claims.
+//!
+//! An airdrop is published from a CSV file of `wallet,amount` rows. Each row
+//! becomes the leaf at its position in the file, hashed with the program's
+//! own [`merkle`] functions, so the root and proofs built here are exactly
+//! what the distributor checks on claim.
+
+use std::{collections::HashSet, str::FromStr};
+
+use serde_json::{json, Value};
+use solana_program::pubkey::Pubkey;
+use soul_sanctuary::distributor::merkle;
+
+use crate::CliError;
+
+/// The claim of one wallet and the proof the distributor checks it against
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AirdropClaim {
+    pub index: u64,
+    pub wallet: Pubkey,
+    pub amount: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+/// Every claim of an airdrop and the root committing to them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AirdropTree {
+    pub root: [u8; 32],
+    /// Sum of every claim, the amount the vault is funded with
+    pub max_total_claim: u64,
+    pub claims: Vec<AirdropClaim>,
+}
+
+impl AirdropTree {
+    /// Build the tree of a CSV file of `wallet,amount` rows.
+    ///
+    /// A first row that does not parse as a claim is taken as a header, and
+    /// blank lines are skipped. A wallet may appear only once.
+    pub fn from_csv(text: &str) -> Result<Self, CliError> {
+        let mut rows = Vec::new();
+        let mut wallets = HashSet::new();
+        let first_line = text.lines().position(|line| !line.trim().is_empty());
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let row = parse_row(line)
+                .map_err(|message| CliError::Csv(format!("line {}: {}", number + 1, message)));
+            let (wallet, amount) = match row {
+                Ok(row) => row,
+                Err(_) if Some(number) == first_line => continue,
+                Err(error) => return Err(error),
+            };
+            if !wallets.insert(wallet) {
+                return Err(CliError::Csv(format!("line {}: {} appears twice", number + 1, wallet)));
+            }
+            rows.push((wallet, amount));
+        }
+        Self::new(&rows)
+    }
+
+    /// Build the tree of `(wallet, amount)` claims, indexed in order
+    pub fn new(rows: &[(Pubkey, u64)]) -> Result<Self, CliError> {
+        if rows.is_empty() {
+            return Err(CliError::Csv("no claims".to_string()));
+        }
+        let max_total_claim = rows
+            .iter()
+            .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+            .ok_or_else(|| CliError::Csv("claims overflow a u64".to_string()))?;
+
+        let leaves: Vec<_> = rows
+            .iter()
+            .enumerate()
+            .map(|(index, (wallet, amount))| merkle::leaf_hash(index as u64, wallet, *amount))
+            .collect();
+        let mut proofs = vec![Vec::new(); leaves.len()];
+        // Position of each leaf's ancestor in the current level
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        let mut level = leaves;
+        while level.len() > 1 {
+            for (proof, position) in proofs.iter_mut().zip(positions.iter_mut()) {
+                if let Some(sibling) = level.get(*position ^ 1) {
+                    proof.push(*sibling);
+                }
+                *position /= 2;
+            }
+            // An odd node out is carried up unchanged
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => merkle::node_hash(left, right),
+                    [node] => *node,
+                    _ => unreachable!(),
+                })
+                .collect();
+        }
+
+        let claims = rows
+            .iter()
+            .zip(proofs)
+            .enumerate()
+            .map(|(index, ((wallet, amount), proof))| AirdropClaim {
+                index: index as u64,
+                wallet: *wallet,
+                amount: *amount,
+                proof,
+            })
+            .collect();
+        Ok(Self {
+            root: level[0],
+            max_total_claim,
+            claims,
+        })
+    }
+
+    pub fn num_nodes(&self) -> u64 {
+        self.claims.len() as u64
+    }
+
+    pub fn claim(&self, wallet: &Pubkey) -> Option<&AirdropClaim> {
+        self.claims.iter().find(|claim| claim.wallet == *wallet)
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "merkle_root": hex(&self.root),
+            "max_total_claim": self.max_total_claim,
+            "num_nodes": self.num_nodes(),
+            "claims": self.claims.iter().map(|claim| json!({
+                "index": claim.index,
+                "wallet": claim.wallet.to_string(),
+                "amount": claim.amount,
+                "proof": claim.proof.iter().map(hex).collect::<Vec<_>>(),
+            })).collect::<Vec<_>>(),
+        })
+    }
+}
+
+fn parse_row(line: &str) -> Result<(Pubkey, u64), String> {
+    let mut fields = line.split(',').map(str::trim);
+    let (Some(wallet), Some(amount), None) = (fields.next(), fields.next(), fields.next()) else {
+        return Err("expected wallet,amount".to_string());
+    };
+    let wallet = Pubkey::from_str(wallet).map_err(|error| format!("wallet {}: {}", wallet, error))?;
+    let amount = amount.parse().map_err(|error| format!("amount {}: {}", amount, error))?;
+    Ok((wallet, amount))
+}
+
+fn hex(bytes: &[u8; 32]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,59 @@
+//! Parsers for command-line values.
+
+use std::fmt::Debug;
+
+use borsh::BorshDeserialize;
+
+/// RPC endpoint for a cluster moniker, or `url` itself when it is not one
+pub fn parse_url(url: &str) -> Result<String, String> {
+    Ok(match url {
+        "l" | "localhost" => "http://127.0.0.1:8899",
+        "d" | "devnet" => "https://api.devnet.solana.com",
+        "t" | "testnet" => "https://api.testnet.solana.com",
+        "m" | "mainnet-beta" => "https://api.mainnet-beta.solana.com",
+        _ => url,
+    }
+    .to_string())
+}
+
+/// Parse a 32-byte hash written as 64 hex digits
+pub fn parse_hash(text: &str) -> Result<[u8; 32], String> {
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    if text.len() != 64 || !text.is_ascii() {
+        return Err("expected 64 hex digits".to_string());
+    }
+    let mut hash = [0; 32];
+    for (byte, pair) in hash.iter_mut().zip(text.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|error| error.to_string())?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|error| error.to_string())?;
+    }
+    Ok(hash)
+}
+
+/// Parse a fieldless program enum by variant name, ignoring case, dashes and
+/// underscores.
+///
+/// Variants are enumerated by Borsh-decoding each index in turn, so new
+/// variants are accepted as soon as the program declares them.
+pub fn parse_variant<T: BorshDeserialize + Debug>(text: &str) -> Result<T, String> {
+    let wanted = normalize(text);
+    let mut names = Vec::new();
+    for index in 0..=u8::MAX {
+        let Ok(variant) = T::try_from_slice(&[index]) else {
+            break;
+        };
+        let name = format!("{:?}", variant);
+        if normalize(&name) == wanted {
+            return Ok(variant);
+        }
+        names.push(name);
+    }
+    Err(format!("expected one of {}", names.join(", ")))
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
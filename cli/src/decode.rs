@@ -0,0 +1,170 @@
+//! Decode program accounts into JSON.
+//!
+//! Layouts come from the committed IDL, which a test keeps in step with the
+//! program's state types, so every account type the program writes can be
+//! dumped without a hand-written printer per type.
+
+use std::sync::OnceLock;
+
+use serde_json::{json, Map, Value};
+use soul_sanctuary::account::HEADER_LEN;
+
+use crate::error::CliError;
+
+const IDL: &str = include_str!("../../idl/soul_sanctuary.json");
+
+fn idl() -> &'static Value {
+    static PARSED: OnceLock<Value> = OnceLock::new();
+    PARSED.get_or_init(|| serde_json::from_str(IDL).expect("committed IDL is valid JSON"))
+}
+
+/// Decode a program account, returning its type name and decoded fields
+pub fn decode_account(data: &[u8]) -> Result<(String, Value), CliError> {
+    let idl = idl();
+    if data.len() < HEADER_LEN {
+        return Err(CliError::UnknownAccountType);
+    }
+    let discriminator = &data[..8];
+    let account = idl["accounts"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|account| {
+            account["discriminator"].as_array().is_some_and(|bytes| {
+                bytes
+                    .iter()
+                    .map(Value::as_u64)
+                    .eq(discriminator.iter().map(|byte| Some(u64::from(*byte))))
+            })
+        })
+        .ok_or(CliError::UnknownAccountType)?;
+
+    let name = account["name"].as_str().unwrap_or_default().to_string();
+    if u64::from(data[8]) != account["version"].as_u64().unwrap_or_default() {
+        return Err(CliError::Decode(format!(
+            "{} has layout version {}, this build reads version {}",
+            name, data[8], account["version"]
+        )));
+    }
+
+    let mut reader = Reader {
+        data: &data[HEADER_LEN..],
+    };
+    let value = reader.read(&account["type"])?;
+    Ok((name, value))
+}
+
+/// Borsh reader driven by IDL type descriptions
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CliError> {
+        if self.data.len() < len {
+            return Err(CliError::Decode("account data ends early".to_string()));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], CliError> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+
+    fn length(&mut self) -> Result<usize, CliError> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn read(&mut self, ty: &Value) -> Result<Value, CliError> {
+        if let Some(name) = ty.as_str() {
+            return self.read_primitive(name);
+        }
+        if let Some(inner) = ty.get("vec") {
+            let len = self.length()?;
+            return (0..len).map(|_| self.read(inner)).collect();
+        }
+        if let Some(inner) = ty.get("option") {
+            return match self.take(1)?[0] {
+                0 => Ok(Value::Null),
+                _ => self.read(inner),
+            };
+        }
+        if let Some([inner, len]) = ty.get("array").and_then(Value::as_array).map(Vec::as_slice) {
+            let len = len.as_u64().unwrap_or_default() as usize;
+            // Byte arrays are hashes and are easier to read as hex
+            if inner == "u8" {
+                return Ok(json!(hex(self.take(len)?)));
+            }
+            return (0..len).map(|_| self.read(inner)).collect();
+        }
+        if let Some(name) = ty.get("defined").and_then(Value::as_str) {
+            return self.read_defined(name);
+        }
+        Err(CliError::Decode(format!("unsupported IDL type {}", ty)))
+    }
+
+    fn read_primitive(&mut self, name: &str) -> Result<Value, CliError> {
+        Ok(match name {
+            "bool" => json!(self.take(1)?[0] != 0),
+            "u8" => json!(self.take(1)?[0]),
+            "i8" => json!(self.take(1)?[0] as i8),
+            "u16" => json!(u16::from_le_bytes(self.array()?)),
+            "i16" => json!(i16::from_le_bytes(self.array()?)),
+            "u32" => json!(u32::from_le_bytes(self.array()?)),
+            "i32" => json!(i32::from_le_bytes(self.array()?)),
+            "u64" => json!(u64::from_le_bytes(self.array()?)),
+            "i64" => json!(i64::from_le_bytes(self.array()?)),
+            // JSON numbers lose precision past 2^53, so 128-bit values are strings
+            "u128" => json!(u128::from_le_bytes(self.array()?).to_string()),
+            "i128" => json!(i128::from_le_bytes(self.array()?).to_string()),
+            "publicKey" => json!(bs58::encode(self.take(32)?).into_string()),
+            "String" => {
+                let len = self.length()?;
+                json!(String::from_utf8_lossy(self.take(len)?))
+            }
+            _ => return Err(CliError::Decode(format!("unsupported IDL type {}", name))),
+        })
+    }
+
+    fn read_defined(&mut self, name: &str) -> Result<Value, CliError> {
+        let definition = idl()["types"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|definition| definition["name"] == name)
+            .ok_or_else(|| CliError::Decode(format!("IDL does not define {}", name)))?;
+        let layout = &definition["type"];
+
+        if layout["kind"] == "struct" {
+            return self.read_fields(&layout["fields"]);
+        }
+
+        let index = self.take(1)?[0];
+        let variant = layout["variants"]
+            .get(index as usize)
+            .ok_or_else(|| CliError::Decode(format!("{} has no variant {}", name, index)))?;
+        let variant_name = variant["name"].as_str().unwrap_or_default();
+        if variant["fields"].as_array().is_none_or(Vec::is_empty) {
+            return Ok(json!(variant_name));
+        }
+        Ok(json!({ variant_name: self.read_fields(&variant["fields"])? }))
+    }
+
+    fn read_fields(&mut self, fields: &Value) -> Result<Value, CliError> {
+        let mut object = Map::new();
+        for field in fields.as_array().into_iter().flatten() {
+            let name = field["name"].as_str().unwrap_or_default().to_string();
+            object.insert(name, self.read(&field["type"])?);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// Lowercase hex encoding of `bytes`
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,30 @@
+use solana_client::client_error::ClientError;
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use thiserror::Error;
+
+/// Errors that stop a CLI command
+#[derive(Error, Debug)]
+pub enum CliError {
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("Could not build instruction: {0}")]
+    Program(#[from] ProgramError),
+    #[error("Could not read keypair {path}: {message}")]
+    Keypair { path: String, message: String },
+    #[error("Account {0} does not exist")]
+    AccountNotFound(Pubkey),
+    #[error("Account {0} is not owned by the program")]
+    NotProgramAccount(Pubkey),
+    #[error("Not a SOuL Sanctuary account")]
+    UnknownAccountType,
+    #[error("Could not decode account: {0}")]
+    Decode(String),
+    #[error("Invalid airdrop CSV: {0}")]
+    Csv(String),
+}
+
+impl From<ClientError> for CliError {
+    fn from(error: ClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
@@ -0,0 +1,14 @@
+//! Operator tooling for the SOuL Sanctuary program.
+//!
+//! [`plan`] turns each CLI operation into the instructions to send, built
+//! with the program's own instruction builders, and [`decode`] turns program
+//! accounts back into JSON. [`airdrop`] builds the Merkle tree of an
+//! airdrop from a CSV file of claims.
+
+pub mod airdrop;
+pub mod args;
+pub mod decode;
+pub mod error;
+pub mod plan;
+
+pub use error::CliError;
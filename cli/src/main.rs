@@ -0,0 +1,891 @@
+use std::{path::{Path, PathBuf}, process::ExitCode, str::FromStr};
+
+use clap::{Parser, Subcommand};
+use serde_json::json;
+use solana_client::rpc_client::RpcClient;
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+use soul_cli::{
+    airdrop::AirdropTree,
+    args::{parse_hash, parse_url, parse_variant},
+    decode::decode_account,
+    plan, CliError,
+};
+use soul_sanctuary::{
+    account::AccountState,
+    distributor::Distributor,
+    error::SOuLError,
+    impact::{GeoLocation, ImpactMetrics, ImpactReport},
+    instruction::SOuLInstruction,
+    lst::{LSTPool, LSTType},
+    state::{
+        EmissionBucket, EmissionSchedule, LSTToken, LSTTokenType, RetirementCertificate, SOuLToken,
+        TokenMetadata,
+    },
+    vesting::{VestingSchedule, VestingType},
+};
+use spl_token::state::Mint;
+
+#[derive(Parser)]
+#[command(about = "Operate the SOuL Sanctuary program")]
+struct Cli {
+    /// RPC URL, or one of localhost, devnet, testnet and mainnet-beta
+    #[arg(long, short = 'u', env = "SOUL_RPC_URL", default_value = "localhost", value_parser = parse_url)]
+    url: String,
+
+    /// Keypair that signs and pays for transactions [default: ~/.config/solana/id.json]
+    #[arg(long, short = 'k', env = "SOUL_KEYPAIR")]
+    keypair: Option<PathBuf>,
+
+    /// Address of the deployed program
+    #[arg(long, env = "SOUL_PROGRAM_ID", value_parser = Pubkey::from_str)]
+    program_id: Pubkey,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a SOuL mint, optionally minting an initial supply to the signer
+    InitSoul {
+        #[arg(long, default_value_t = 0)]
+        initial_supply: u64,
+        /// Create the mint under Token-2022, withholding this fee on every transfer
+        #[arg(long)]
+        transfer_fee_basis_points: Option<u16>,
+        /// Largest fee withheld from one transfer
+        #[arg(long, default_value_t = u64::MAX, requires = "transfer_fee_basis_points")]
+        maximum_fee: u64,
+    },
+
+    /// Create the config account of an LST token type
+    InitLst {
+        #[arg(long, value_parser = parse_variant::<LSTTokenType>)]
+        token_type: LSTTokenType,
+        #[arg(long)]
+        total_supply: u64,
+    },
+
+    /// Mint SOuL as the mint authority
+    Mint {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        /// Wallet receiving the tokens [default: the signer]
+        #[arg(long, value_parser = Pubkey::from_str)]
+        to: Option<Pubkey>,
+        amount: u64,
+    },
+
+    /// Burn SOuL from the signer's token account
+    Burn {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        amount: u64,
+    },
+
+    /// Sweep withheld transfer fees into the treasury
+    HarvestFees {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        /// Token accounts holding withheld fees
+        #[arg(value_parser = Pubkey::from_str)]
+        sources: Vec<Pubkey>,
+    },
+
+    /// Fix the maximum supply and the per-epoch emission schedule, once
+    ConfigureEmission {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        #[arg(long)]
+        max_supply: u64,
+        /// First epoch that emits [default: the current epoch]
+        #[arg(long)]
+        start_epoch: Option<u64>,
+        /// Tokens emitted per epoch before the first reduction
+        #[arg(long)]
+        epoch_emission: u64,
+        /// Epochs between reductions of the per-epoch emission
+        #[arg(long)]
+        reduction_interval_epochs: u64,
+        /// Cut applied at each reduction, 5000 halves the emission
+        #[arg(long, default_value_t = 5_000)]
+        reduction_basis_points: u16,
+        /// Wallet owning the staking bucket's token account
+        #[arg(long, value_parser = Pubkey::from_str)]
+        staking: Pubkey,
+        #[arg(long)]
+        staking_basis_points: u16,
+        /// Wallet owning the game rewards bucket's token account
+        #[arg(long, value_parser = Pubkey::from_str)]
+        game_rewards: Pubkey,
+        #[arg(long)]
+        game_rewards_basis_points: u16,
+        /// Wallet owning the verifier fund bucket's token account
+        #[arg(long, value_parser = Pubkey::from_str)]
+        verifier_fund: Pubkey,
+        #[arg(long)]
+        verifier_fund_basis_points: u16,
+    },
+
+    /// Mint the scheduled emission released up to the current epoch
+    Emit {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+    },
+
+    /// Set the metadata URI of the SOuL mint or of an LST token type
+    SetMetadataUri {
+        #[arg(long, value_parser = Pubkey::from_str, required_unless_present = "token_type", conflicts_with = "token_type")]
+        mint: Option<Pubkey>,
+        #[arg(long, value_parser = parse_variant::<LSTTokenType>)]
+        token_type: Option<LSTTokenType>,
+        uri: String,
+    },
+
+    /// Burn SOuL or a category LST to retire carbon credits backed by verified impact reports
+    Retire {
+        #[arg(long, value_parser = Pubkey::from_str, required_unless_present = "lst", conflicts_with = "lst")]
+        mint: Option<Pubkey>,
+        /// Retire the LST of the category's pool instead of SOuL
+        #[arg(long)]
+        lst: bool,
+        #[arg(long, value_parser = parse_variant::<LSTType>)]
+        category: LSTType,
+        /// Distinguishes retirements of the same signer
+        #[arg(long, default_value_t = 0)]
+        nonce: u64,
+        #[arg(long)]
+        amount: u64,
+        /// Hash of the beneficiary's name, as 64 hex digits
+        #[arg(long, value_parser = parse_hash)]
+        beneficiary_hash: [u8; 32],
+        /// Verified impact reports backing the credits
+        #[arg(required = true, value_parser = Pubkey::from_str)]
+        reports: Vec<Pubkey>,
+    },
+
+    #[command(subcommand)]
+    Vesting(VestingCommand),
+
+    #[command(subcommand)]
+    Lst(LstCommand),
+
+    #[command(subcommand)]
+    Impact(ImpactCommand),
+
+    #[command(subcommand)]
+    Airdrop(AirdropCommand),
+
+    /// Print a program account as JSON
+    Account {
+        #[arg(value_parser = Pubkey::from_str)]
+        address: Pubkey,
+    },
+}
+
+/// Vesting schedules
+#[derive(Subcommand)]
+enum VestingCommand {
+    /// Create a schedule and fund it from the signer's token account
+    Create {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        beneficiary: Pubkey,
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        /// Distinguishes schedules of the same beneficiary
+        #[arg(long, default_value_t = 0)]
+        nonce: u64,
+        /// Unix timestamp vesting starts at
+        #[arg(long)]
+        start: i64,
+        #[arg(long)]
+        amount: u64,
+        #[arg(long = "type", value_parser = parse_variant::<VestingType>)]
+        vesting_type: VestingType,
+        /// Seconds after the start before anything vests [default: the type's preset]
+        #[arg(long)]
+        cliff: Option<i64>,
+        /// Seconds after the start everything has vested by [default: the type's preset]
+        #[arg(long)]
+        duration: Option<i64>,
+        /// Seconds between unlocks [default: 1]
+        #[arg(long)]
+        interval: Option<i64>,
+        /// Key that may revoke a Team or Advisor schedule [default: not revocable]
+        #[arg(long, value_parser = Pubkey::from_str)]
+        revoke_authority: Option<Pubkey>,
+    },
+
+    /// Release vested tokens to the signer, who must be the beneficiary
+    Release {
+        #[arg(value_parser = Pubkey::from_str)]
+        vesting_account: Pubkey,
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        /// Verified impact reports reaching milestones of an impact milestone schedule
+        #[arg(long = "report", value_parser = Pubkey::from_str)]
+        reports: Vec<Pubkey>,
+    },
+}
+
+/// LST staking pools
+#[derive(Subcommand)]
+enum LstCommand {
+    /// Create a pool with a new LST mint
+    InitPool {
+        #[arg(long, value_parser = parse_variant::<LSTType>)]
+        pool_type: LSTType,
+        #[arg(long, value_parser = Pubkey::from_str)]
+        soul_mint: Pubkey,
+        /// Unstake fee, scaled by 1e9
+        #[arg(long, default_value_t = 0)]
+        fee_rate: u64,
+    },
+
+    /// Stake SOuL for LST
+    Stake {
+        #[arg(long, value_parser = parse_variant::<LSTType>)]
+        pool_type: LSTType,
+        amount: u64,
+    },
+
+    /// Start the unstake cooldown
+    RequestUnstake {
+        #[arg(long, value_parser = parse_variant::<LSTType>)]
+        pool_type: LSTType,
+        amount: u64,
+    },
+
+    /// Finish an unstake once its cooldown has passed
+    ExecuteUnstake {
+        #[arg(long, value_parser = parse_variant::<LSTType>)]
+        pool_type: LSTType,
+    },
+}
+
+/// Impact reports and verifiers
+#[derive(Subcommand)]
+enum ImpactCommand {
+    /// Submit a report, creating the signer's metrics account on first use
+    SubmitReport {
+        #[arg(long, allow_negative_numbers = true)]
+        latitude: f64,
+        #[arg(long, allow_negative_numbers = true)]
+        longitude: f64,
+        /// Meters
+        #[arg(long, default_value_t = 0, allow_negative_numbers = true)]
+        altitude: i32,
+        /// Hash of the supporting documents, as 64 hex digits
+        #[arg(long, value_parser = parse_hash)]
+        proof_hash: [u8; 32],
+        /// The category of credits the report's carbon offset backs
+        #[arg(long, value_parser = parse_variant::<LSTType>)]
+        category: LSTType,
+        /// Square meters
+        #[arg(long, default_value_t = 0)]
+        forest_area: u64,
+        /// Kilograms
+        #[arg(long, default_value_t = 0)]
+        carbon_offset: u64,
+        /// Liters
+        #[arg(long, default_value_t = 0)]
+        water_saved: u64,
+        /// 0 to 1000
+        #[arg(long, default_value_t = 0)]
+        biodiversity_score: u16,
+        /// Kilowatt hours
+        #[arg(long, default_value_t = 0)]
+        energy_saved: u64,
+        /// Kilograms
+        #[arg(long, default_value_t = 0)]
+        waste_recycled: u64,
+    },
+
+    /// Register the signer as a verifier, staking SOuL
+    RegisterVerifier {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        #[arg(long)]
+        stake_amount: u64,
+    },
+
+    /// Approve or reject a pending report as the signer
+    VerifyReport {
+        #[arg(value_parser = Pubkey::from_str)]
+        report: Pubkey,
+        /// Reject the report instead of approving it
+        #[arg(long)]
+        reject: bool,
+        /// Hash of the verification notes, as 64 hex digits
+        #[arg(long, value_parser = parse_hash)]
+        notes_hash: [u8; 32],
+    },
+}
+
+/// Merkle airdrops, built from a CSV file of `wallet,amount` rows
+#[derive(Subcommand)]
+enum AirdropCommand {
+    /// Print the root and every proof of the airdrop in a CSV file
+    Tree { csv: PathBuf },
+
+    /// Publish the airdrop in a CSV file and fund it from the signer's token account
+    Create {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        /// Distinguishes airdrops of the same admin
+        #[arg(long, default_value_t = 0)]
+        nonce: u64,
+        /// Unix timestamp claims close and the rest can be clawed back at
+        #[arg(long)]
+        expiry: i64,
+        csv: PathBuf,
+    },
+
+    /// Claim the signer's share of an airdrop, proven from its CSV file
+    Claim {
+        #[arg(value_parser = Pubkey::from_str)]
+        distributor: Pubkey,
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+        csv: PathBuf,
+    },
+
+    /// Return the unclaimed tokens of an expired airdrop to the signer
+    Clawback {
+        #[arg(value_parser = Pubkey::from_str)]
+        distributor: Pubkey,
+        #[arg(long, value_parser = Pubkey::from_str)]
+        mint: Pubkey,
+    },
+}
+
+/// Connection and signer shared by every command
+struct Context {
+    client: RpcClient,
+    payer: Keypair,
+    program_id: Pubkey,
+}
+
+impl Context {
+    fn send(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Signature, CliError> {
+        let blockhash = self.client.get_latest_blockhash()?;
+        let mut all_signers = vec![&self.payer];
+        all_signers.extend_from_slice(signers);
+        let transaction = Transaction::new_signed_with_payer(
+            instructions,
+            Some(&self.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        );
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        eprintln!("signature: {}", signature);
+        Ok(signature)
+    }
+
+    fn mint_rent(&self) -> Result<u64, CliError> {
+        self.rent(Mint::LEN)
+    }
+
+    fn rent(&self, space: usize) -> Result<u64, CliError> {
+        Ok(self.client.get_minimum_balance_for_rent_exemption(space)?)
+    }
+
+    fn account_data(&self, address: &Pubkey) -> Result<Option<Vec<u8>>, CliError> {
+        Ok(self
+            .client
+            .get_account_with_commitment(address, self.client.commitment())?
+            .value
+            .map(|account| account.data))
+    }
+
+    fn soul_token(&self, mint: &Pubkey) -> Result<SOuLToken, CliError> {
+        let (address, _) = SOuLToken::find_address(mint, &self.program_id);
+        let data = self
+            .account_data(&address)?
+            .ok_or(CliError::AccountNotFound(address))?;
+        Ok(SOuLToken::unpack_account(&data)?)
+    }
+
+    /// Token program owning `mint`
+    fn token_program(&self, mint: &Pubkey) -> Result<Pubkey, CliError> {
+        Ok(self
+            .client
+            .get_account_with_commitment(mint, self.client.commitment())?
+            .value
+            .ok_or(CliError::AccountNotFound(*mint))?
+            .owner)
+    }
+
+    fn pool(&self, pool_type: LSTType) -> Result<LSTPool, CliError> {
+        let (address, _) = LSTPool::find_address(pool_type, &self.program_id);
+        let data = self
+            .account_data(&address)?
+            .ok_or(CliError::AccountNotFound(address))?;
+        Ok(LSTPool::unpack_account(&data)?)
+    }
+
+    fn report(&self, address: &Pubkey) -> Result<ImpactReport, CliError> {
+        let data = self
+            .account_data(address)?
+            .ok_or(CliError::AccountNotFound(*address))?;
+        Ok(ImpactReport::unpack_account(&data)?)
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), CliError> {
+    // Building a tree needs neither a signer nor a cluster
+    if let Command::Airdrop(AirdropCommand::Tree { csv }) = &cli.command {
+        print_json(&read_airdrop(csv)?.to_json());
+        return Ok(());
+    }
+
+    let keypair = cli.keypair.unwrap_or_else(|| {
+        PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".config/solana/id.json")
+    });
+    let payer = read_keypair_file(&keypair).map_err(|error| CliError::Keypair {
+        path: keypair.display().to_string(),
+        message: error.to_string(),
+    })?;
+    let context = Context {
+        client: RpcClient::new_with_commitment(cli.url, CommitmentConfig::confirmed()),
+        payer,
+        program_id: cli.program_id,
+    };
+    let program_id = &context.program_id;
+    let signer = context.payer.pubkey();
+
+    match cli.command {
+        Command::InitSoul {
+            initial_supply,
+            transfer_fee_basis_points,
+            maximum_fee,
+        } => {
+            let mint = Keypair::new();
+            let (instructions, token_program) = match transfer_fee_basis_points {
+                Some(basis_points) => (
+                    plan::initialize_soul_with_transfer_fee(
+                        program_id,
+                        &signer,
+                        &mint.pubkey(),
+                        context.rent(plan::transfer_fee_mint_len()?)?,
+                        basis_points,
+                        maximum_fee,
+                    )?,
+                    spl_token_2022::id(),
+                ),
+                None => (
+                    plan::initialize_soul(
+                        program_id,
+                        &signer,
+                        &mint.pubkey(),
+                        context.mint_rent()?,
+                    )?,
+                    spl_token::id(),
+                ),
+            };
+            context.send(&instructions, &[&mint])?;
+            if initial_supply > 0 {
+                let instructions = plan::mint(
+                    program_id,
+                    &token_program,
+                    &signer,
+                    &mint.pubkey(),
+                    &signer,
+                    initial_supply,
+                )?;
+                context.send(&instructions, &[])?;
+            }
+            let (token, _) = SOuLToken::find_address(&mint.pubkey(), program_id);
+            print_json(&json!({ "mint": mint.pubkey().to_string(), "token": token.to_string() }));
+        }
+        Command::InitLst {
+            token_type,
+            total_supply,
+        } => {
+            let instruction =
+                SOuLInstruction::initialize_lst(program_id, &signer, token_type, total_supply)?;
+            context.send(&[instruction], &[])?;
+            let (token, _) = LSTToken::find_address(token_type, program_id);
+            print_json(&json!({ "token": token.to_string() }));
+        }
+        Command::Mint { mint, to, amount } => {
+            let owner = to.unwrap_or(signer);
+            let token = context.soul_token(&mint)?;
+            context.send(
+                &plan::mint(
+                    program_id,
+                    &token.token_program,
+                    &signer,
+                    &mint,
+                    &owner,
+                    amount,
+                )?,
+                &[],
+            )?;
+        }
+        Command::Burn { mint, amount } => {
+            let token = context.soul_token(&mint)?;
+            context.send(
+                &plan::burn(program_id, &token.token_program, &signer, &mint, amount)?,
+                &[],
+            )?;
+        }
+        Command::HarvestFees { mint, sources } => {
+            context.send(&plan::harvest_fees(program_id, &mint, &sources)?, &[])?;
+            let (token, _) = SOuLToken::find_address(&mint, program_id);
+            let (treasury, _) = SOuLToken::find_treasury(&token, program_id);
+            print_json(&json!({ "treasury": treasury.to_string() }));
+        }
+        Command::ConfigureEmission {
+            mint,
+            max_supply,
+            start_epoch,
+            epoch_emission,
+            reduction_interval_epochs,
+            reduction_basis_points,
+            staking,
+            staking_basis_points,
+            game_rewards,
+            game_rewards_basis_points,
+            verifier_fund,
+            verifier_fund_basis_points,
+        } => {
+            let token = context.soul_token(&mint)?;
+            let bucket = |owner: &Pubkey, basis_points| EmissionBucket {
+                destination: plan::token_account_with_program(owner, &mint, &token.token_program),
+                basis_points,
+            };
+            let start_epoch = match start_epoch {
+                Some(epoch) => epoch,
+                None => context.client.get_epoch_info()?.epoch,
+            };
+            let schedule = EmissionSchedule {
+                start_epoch,
+                epoch_emission,
+                reduction_interval_epochs,
+                reduction_basis_points,
+                staking: bucket(&staking, staking_basis_points),
+                game_rewards: bucket(&game_rewards, game_rewards_basis_points),
+                verifier_fund: bucket(&verifier_fund, verifier_fund_basis_points),
+                emitted: 0,
+            };
+            context.send(
+                &plan::configure_emission(
+                    program_id,
+                    &token.token_program,
+                    &signer,
+                    &mint,
+                    max_supply,
+                    schedule,
+                    [staking, game_rewards, verifier_fund],
+                )?,
+                &[],
+            )?;
+        }
+        Command::Emit { mint } => {
+            let token = context.soul_token(&mint)?;
+            let schedule = token
+                .emission
+                .ok_or(ProgramError::from(SOuLError::EmissionNotConfigured))?;
+            context.send(
+                &plan::emit(program_id, &token.token_program, &mint, &schedule)?,
+                &[],
+            )?;
+        }
+        Command::SetMetadataUri {
+            mint,
+            token_type,
+            uri,
+        } => {
+            let (token, _) = match (mint, token_type) {
+                (Some(mint), _) => SOuLToken::find_address(&mint, program_id),
+                (None, Some(token_type)) => LSTToken::find_address(token_type, program_id),
+                (None, None) => unreachable!("clap requires --mint or --token-type"),
+            };
+            context.send(
+                &plan::update_metadata_uri(program_id, &signer, &token, uri)?,
+                &[],
+            )?;
+            let (metadata, _) = TokenMetadata::find_address(&token, program_id);
+            print_json(&json!({ "metadata": metadata.to_string() }));
+        }
+        Command::Retire {
+            mint,
+            category,
+            nonce,
+            amount,
+            beneficiary_hash,
+            reports,
+            ..
+        } => {
+            // Without a mint, clap guarantees `--lst`
+            let (mint, token_config) = match mint {
+                Some(mint) => (mint, SOuLToken::find_address(&mint, program_id).0),
+                None => (
+                    context.pool(category)?.lst_mint,
+                    LSTPool::find_address(category, program_id).0,
+                ),
+            };
+            let token_program = context.token_program(&mint)?;
+            let instructions = plan::retire(
+                program_id,
+                &token_program,
+                &signer,
+                &mint,
+                &token_config,
+                nonce,
+                amount,
+                category,
+                beneficiary_hash,
+                &reports,
+            )?;
+            context.send(&instructions, &[])?;
+            let (certificate, _) = RetirementCertificate::find_address(&signer, nonce, program_id);
+            print_json(&json!({ "certificate": certificate.to_string() }));
+        }
+        Command::Vesting(VestingCommand::Create {
+            beneficiary,
+            mint,
+            nonce,
+            start,
+            amount,
+            vesting_type,
+            cliff,
+            duration,
+            interval,
+            revoke_authority,
+        }) => {
+            let token_program = context.token_program(&mint)?;
+            let instructions = plan::create_vesting(
+                program_id,
+                &token_program,
+                &signer,
+                &beneficiary,
+                &mint,
+                nonce,
+                start,
+                amount,
+                vesting_type,
+                cliff,
+                duration,
+                interval,
+                revoke_authority,
+            )?;
+            context.send(&instructions, &[])?;
+            let (vesting, _) = VestingSchedule::find_address(&beneficiary, nonce, program_id);
+            print_json(&json!({ "vesting_account": vesting.to_string() }));
+        }
+        Command::Vesting(VestingCommand::Release {
+            vesting_account,
+            mint,
+            reports,
+        }) => {
+            let token_program = context.token_program(&mint)?;
+            let instructions =
+                plan::release_vesting(program_id, &token_program, &signer, &vesting_account, &mint, &reports)?;
+            context.send(&instructions, &[])?;
+        }
+        Command::Lst(LstCommand::InitPool {
+            pool_type,
+            soul_mint,
+            fee_rate,
+        }) => {
+            let lst_mint = Keypair::new();
+            let instructions = plan::initialize_pool(
+                program_id,
+                &signer,
+                pool_type,
+                &soul_mint,
+                &lst_mint.pubkey(),
+                fee_rate,
+                context.mint_rent()?,
+            )?;
+            context.send(&instructions, &[&lst_mint])?;
+            let (pool, _) = LSTPool::find_address(pool_type, program_id);
+            print_json(&json!({
+                "pool": pool.to_string(),
+                "lst_mint": lst_mint.pubkey().to_string(),
+            }));
+        }
+        Command::Lst(LstCommand::Stake { pool_type, amount }) => {
+            let pool = context.pool(pool_type)?;
+            context.send(&plan::stake(program_id, &signer, &pool, amount)?, &[])?;
+        }
+        Command::Lst(LstCommand::RequestUnstake { pool_type, amount }) => {
+            let pool = context.pool(pool_type)?;
+            context.send(
+                &plan::request_unstake(program_id, &signer, &pool, amount)?,
+                &[],
+            )?;
+        }
+        Command::Lst(LstCommand::ExecuteUnstake { pool_type }) => {
+            let pool = context.pool(pool_type)?;
+            context.send(&plan::execute_unstake(program_id, &signer, &pool)?, &[])?;
+        }
+        Command::Impact(ImpactCommand::SubmitReport {
+            latitude,
+            longitude,
+            altitude,
+            proof_hash,
+            category,
+            forest_area,
+            carbon_offset,
+            water_saved,
+            biodiversity_score,
+            energy_saved,
+            waste_recycled,
+        }) => {
+            let (metrics_account, bump) = ImpactMetrics::find_address(&signer, program_id);
+            let initialize_metrics = context.account_data(&metrics_account)?.is_none();
+            let location = GeoLocation {
+                latitude: microdegrees(latitude),
+                longitude: microdegrees(longitude),
+                altitude,
+            };
+            let metrics = ImpactMetrics {
+                is_initialized: true,
+                authority: signer,
+                last_update: 0,
+                forest_area,
+                carbon_offset,
+                water_saved,
+                biodiversity_score,
+                energy_saved,
+                waste_recycled,
+                impact_score: 0,
+                bump,
+            };
+            let instructions = plan::submit_report(
+                program_id,
+                &signer,
+                location,
+                metrics,
+                proof_hash,
+                category,
+                initialize_metrics,
+            )?;
+            context.send(&instructions, &[])?;
+            let (report, _) = ImpactReport::find_address(&metrics_account, &proof_hash, program_id);
+            print_json(&json!({ "report": report.to_string() }));
+        }
+        Command::Impact(ImpactCommand::RegisterVerifier { mint, stake_amount }) => {
+            let instructions = plan::register_verifier(program_id, &signer, &mint, stake_amount)?;
+            context.send(&instructions, &[])?;
+        }
+        Command::Impact(ImpactCommand::VerifyReport {
+            report,
+            reject,
+            notes_hash,
+        }) => {
+            let metrics_account = context.report(&report)?.metrics_account;
+            let instructions =
+                plan::verify_report(program_id, &signer, &report, &metrics_account, !reject, notes_hash)?;
+            context.send(&instructions, &[])?;
+        }
+        Command::Airdrop(AirdropCommand::Tree { .. }) => unreachable!("handled before connecting"),
+        Command::Airdrop(AirdropCommand::Create {
+            mint,
+            nonce,
+            expiry,
+            csv,
+        }) => {
+            let tree = read_airdrop(&csv)?;
+            let token_program = context.token_program(&mint)?;
+            let instructions = plan::create_distributor(
+                program_id,
+                &token_program,
+                &signer,
+                &mint,
+                nonce,
+                expiry,
+                &tree,
+            )?;
+            context.send(&instructions, &[])?;
+            let (distributor, _) = Distributor::find_address(&signer, nonce, program_id);
+            print_json(&json!({
+                "distributor": distributor.to_string(),
+                "merkle_root": tree.to_json()["merkle_root"],
+                "max_total_claim": tree.max_total_claim,
+            }));
+        }
+        Command::Airdrop(AirdropCommand::Claim {
+            distributor,
+            mint,
+            csv,
+        }) => {
+            let tree = read_airdrop(&csv)?;
+            let claim = tree
+                .claim(&signer)
+                .ok_or_else(|| CliError::Csv(format!("no claim for {}", signer)))?;
+            let token_program = context.token_program(&mint)?;
+            context.send(
+                &plan::claim_airdrop(program_id, &token_program, &distributor, &mint, claim)?,
+                &[],
+            )?;
+        }
+        Command::Airdrop(AirdropCommand::Clawback { distributor, mint }) => {
+            let token_program = context.token_program(&mint)?;
+            context.send(
+                &plan::clawback_airdrop(program_id, &token_program, &signer, &distributor, &mint)?,
+                &[],
+            )?;
+        }
+        Command::Account { address } => {
+            let account = context
+                .client
+                .get_account_with_commitment(&address, context.client.commitment())?
+                .value
+                .ok_or(CliError::AccountNotFound(address))?;
+            if account.owner != *program_id {
+                return Err(CliError::NotProgramAccount(address));
+            }
+            let (account_type, fields) = decode_account(&account.data)?;
+            print_json(&json!({
+                "address": address.to_string(),
+                "type": account_type,
+                "lamports": account.lamports,
+                "data": fields,
+            }));
+        }
+    }
+    Ok(())
+}
+
+fn read_airdrop(path: &Path) -> Result<AirdropTree, CliError> {
+    let text = std::fs::read_to_string(path)
+        .map_err(|error| CliError::Csv(format!("{}: {}", path.display(), error)))?;
+    AirdropTree::from_csv(&text)
+}
+
+/// Degrees to the millionths of a degree stored in `GeoLocation`
+fn microdegrees(degrees: f64) -> i64 {
+    (degrees * 1_000_000.0).round() as i64
+}
+
+fn print_json(value: &serde_json::Value) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).expect("values serialize to JSON")
+    );
+}
@@ -0,0 +1,525 @@
+//! Instructions for each CLI operation.
+//!
+//! Every operation is built from the program's own instruction builders plus
+//! the SPL and system instructions needed around them: mint accounts are
+//! allocated before the program initializes them, and token accounts are the
+//! associated token accounts of their owners, created on demand.
+
+use solana_program::{
+    instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    system_instruction,
+};
+use soul_sanctuary::{
+    distributor::DistributorInstruction,
+    impact::{GeoLocation, ImpactInstruction, ImpactMetrics, VerifierState},
+    instruction::SOuLInstruction,
+    lst::{LSTInstruction, LSTPool, LSTType},
+    state::EmissionSchedule,
+    vesting::{VestingInstruction, VestingSchedule, VestingType},
+};
+use spl_associated_token_account::{
+    get_associated_token_address, get_associated_token_address_with_program_id,
+    instruction::create_associated_token_account_idempotent,
+};
+use spl_token::state::Mint;
+use spl_token_2022::extension::ExtensionType;
+
+use crate::airdrop::{AirdropClaim, AirdropTree};
+
+/// Token account of `owner` for `mint`
+pub fn token_account(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, mint)
+}
+
+/// Token account of `owner` for a `mint` owned by `token_program`
+pub fn token_account_with_program(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+fn create_token_account(payer: &Pubkey, owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    create_associated_token_account_idempotent(payer, owner, mint, &spl_token::id())
+}
+
+fn create_token_account_with_program(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Instruction {
+    create_associated_token_account_idempotent(payer, owner, mint, token_program)
+}
+
+/// Space of a Token-2022 mint with the transfer fee extension
+pub fn transfer_fee_mint_len() -> Result<usize, ProgramError> {
+    ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::TransferFeeConfig,
+    ])
+}
+
+/// Allocate `mint` and initialize it as a SOuL token with no supply.
+///
+/// `mint_rent` is the rent-exempt balance of an SPL mint. Supply is minted
+/// afterwards with [`mint`], once the mint exists and token accounts can be
+/// opened against it.
+pub fn initialize_soul(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_rent: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            mint_rent,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        SOuLInstruction::initialize_soul(program_id, payer, mint, &token_account(payer, mint), 0)?,
+    ])
+}
+
+/// Allocate `mint` under Token-2022 and initialize it as a SOuL token that
+/// withholds a transfer fee, with no supply.
+///
+/// `mint_rent` is the rent-exempt balance of [`transfer_fee_mint_len`] bytes.
+pub fn initialize_soul_with_transfer_fee(
+    program_id: &Pubkey,
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_rent: u64,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        system_instruction::create_account(
+            payer,
+            mint,
+            mint_rent,
+            transfer_fee_mint_len()? as u64,
+            &spl_token_2022::id(),
+        ),
+        SOuLInstruction::initialize_soul_with_transfer_fee(
+            program_id,
+            payer,
+            mint,
+            &token_account_with_program(payer, mint, &spl_token_2022::id()),
+            0,
+            transfer_fee_basis_points,
+            maximum_fee,
+        )?,
+    ])
+}
+
+/// Mint SOuL into the token account of `owner`, opening it if needed
+pub fn mint(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    authority: &Pubkey,
+    mint: &Pubkey,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        create_associated_token_account_idempotent(authority, owner, mint, token_program),
+        SOuLInstruction::mint(
+            program_id,
+            token_program,
+            authority,
+            mint,
+            &token_account_with_program(owner, mint, token_program),
+            amount,
+        )?,
+    ])
+}
+
+/// Burn SOuL from the token account of `owner`
+pub fn burn(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![SOuLInstruction::burn(
+        program_id,
+        token_program,
+        &token_account_with_program(owner, mint, token_program),
+        owner,
+        mint,
+        amount,
+    )?])
+}
+
+/// Sweep the fees withheld in `sources` and in the mint into the treasury
+pub fn harvest_fees(
+    program_id: &Pubkey,
+    mint: &Pubkey,
+    sources: &[Pubkey],
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![SOuLInstruction::harvest_fees(
+        program_id, mint, sources,
+    )?])
+}
+
+/// Point the metadata of a SOuL or LST token config at `uri`
+pub fn update_metadata_uri(
+    program_id: &Pubkey,
+    update_authority: &Pubkey,
+    token: &Pubkey,
+    uri: String,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![SOuLInstruction::update_metadata_uri(
+        program_id,
+        update_authority,
+        token,
+        uri,
+    )?])
+}
+
+/// Open the token accounts of the emission buckets and fix the maximum
+/// supply and emission schedule of `mint`
+pub fn configure_emission(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    mint_authority: &Pubkey,
+    mint: &Pubkey,
+    max_supply: u64,
+    schedule: EmissionSchedule,
+    bucket_owners: [Pubkey; 3],
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = bucket_owners
+        .iter()
+        .map(|owner| {
+            create_associated_token_account_idempotent(mint_authority, owner, mint, token_program)
+        })
+        .collect::<Vec<_>>();
+    instructions.push(SOuLInstruction::configure_emission(
+        program_id,
+        mint_authority,
+        mint,
+        max_supply,
+        schedule,
+    )?);
+    Ok(instructions)
+}
+
+/// Mint the emission released so far into the buckets of `schedule`
+pub fn emit(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+    schedule: &EmissionSchedule,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![SOuLInstruction::emit(
+        program_id,
+        token_program,
+        mint,
+        schedule,
+    )?])
+}
+
+/// Create a vesting schedule funded from the authority's token account
+#[allow(clippy::too_many_arguments)]
+pub fn create_vesting(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    authority: &Pubkey,
+    beneficiary: &Pubkey,
+    mint: &Pubkey,
+    nonce: u64,
+    start_timestamp: i64,
+    total_amount: u64,
+    vesting_type: VestingType,
+    cliff_seconds: Option<i64>,
+    duration_seconds: Option<i64>,
+    release_interval_seconds: Option<i64>,
+    revoke_authority: Option<Pubkey>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![VestingInstruction::initialize(
+        program_id,
+        token_program,
+        authority,
+        beneficiary,
+        mint,
+        &token_account_with_program(authority, mint, token_program),
+        nonce,
+        start_timestamp,
+        total_amount,
+        vesting_type,
+        cliff_seconds,
+        duration_seconds,
+        release_interval_seconds,
+        revoke_authority,
+    )?])
+}
+
+/// Release whatever has vested to the beneficiary's token account, with
+/// `reports` as evidence for an impact milestone schedule
+pub fn release_vesting(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    beneficiary: &Pubkey,
+    vesting_account: &Pubkey,
+    mint: &Pubkey,
+    reports: &[Pubkey],
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        create_token_account_with_program(beneficiary, beneficiary, mint, token_program),
+        VestingInstruction::release(
+            program_id,
+            token_program,
+            vesting_account,
+            &VestingSchedule::find_vault(vesting_account, program_id).0,
+            mint,
+            &token_account_with_program(beneficiary, mint, token_program),
+            beneficiary,
+            reports,
+        )?,
+    ])
+}
+
+/// Allocate the LST mint and create the pool with its SOuL vault
+pub fn initialize_pool(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    pool_type: LSTType,
+    soul_mint: &Pubkey,
+    lst_mint: &Pubkey,
+    fee_rate: u64,
+    mint_rent: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        system_instruction::create_account(
+            authority,
+            lst_mint,
+            mint_rent,
+            Mint::LEN as u64,
+            &spl_token::id(),
+        ),
+        LSTInstruction::initialize_pool(
+            program_id, authority, soul_mint, lst_mint, pool_type, fee_rate,
+        )?,
+    ])
+}
+
+/// Stake SOuL into `pool`, opening the staker's LST account if needed
+pub fn stake(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    pool: &LSTPool,
+    amount: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        create_token_account(staker, staker, &pool.lst_mint),
+        LSTInstruction::stake(
+            program_id,
+            staker,
+            pool.pool_type,
+            &token_account(staker, &pool.soul_mint),
+            &pool.soul_vault,
+            &token_account(staker, &pool.lst_mint),
+            &pool.lst_mint,
+            amount,
+        )?,
+    ])
+}
+
+/// Start the cooldown for unstaking `amount` LST
+pub fn request_unstake(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    pool: &LSTPool,
+    amount: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![LSTInstruction::request_unstake(
+        program_id,
+        staker,
+        pool.pool_type,
+        &token_account(staker, &pool.lst_mint),
+        amount,
+    )?])
+}
+
+/// Burn the LST of a finished unstake request and return the SOuL
+pub fn execute_unstake(
+    program_id: &Pubkey,
+    staker: &Pubkey,
+    pool: &LSTPool,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![LSTInstruction::execute_unstake(
+        program_id,
+        staker,
+        pool.pool_type,
+        &token_account(staker, &pool.lst_mint),
+        &token_account(staker, &pool.soul_mint),
+        &pool.soul_vault,
+        &pool.lst_mint,
+    )?])
+}
+
+/// Submit an impact report, creating the authority's metrics account first
+/// when `initialize_metrics` is set
+pub fn submit_report(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    location: GeoLocation,
+    metrics: ImpactMetrics,
+    proof_hash: [u8; 32],
+    category: LSTType,
+    initialize_metrics: bool,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = Vec::new();
+    if initialize_metrics {
+        instructions.push(ImpactInstruction::initialize_metrics(
+            program_id, authority,
+        )?);
+    }
+    instructions.push(ImpactInstruction::submit_report(
+        program_id, authority, location, metrics, proof_hash, category,
+    )?);
+    Ok(instructions)
+}
+
+/// Register `verifier`, locking `stake_amount` SOuL in a vault owned by its
+/// verifier state
+pub fn register_verifier(
+    program_id: &Pubkey,
+    verifier: &Pubkey,
+    mint: &Pubkey,
+    stake_amount: u64,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let (verifier_state, _) = VerifierState::find_address(verifier, program_id);
+    Ok(vec![
+        create_token_account(verifier, &verifier_state, mint),
+        ImpactInstruction::register_verifier(
+            program_id,
+            verifier,
+            mint,
+            &token_account(verifier, mint),
+            &token_account(&verifier_state, mint),
+            stake_amount,
+        )?,
+    ])
+}
+
+/// Approve or reject a pending report filed against `metrics_account`
+pub fn verify_report(
+    program_id: &Pubkey,
+    verifier: &Pubkey,
+    report: &Pubkey,
+    metrics_account: &Pubkey,
+    approved: bool,
+    notes_hash: [u8; 32],
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![ImpactInstruction::verify_report(
+        program_id,
+        verifier,
+        report,
+        metrics_account,
+        approved,
+        notes_hash,
+    )?])
+}
+
+/// Burn `amount` from the owner's token account to retire the credits it
+/// stands for. `token_config` is the SOuL token config of `mint`, or the LST
+/// pool of `category` when retiring its LST.
+#[allow(clippy::too_many_arguments)]
+pub fn retire(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_config: &Pubkey,
+    nonce: u64,
+    amount: u64,
+    category: LSTType,
+    beneficiary_hash: [u8; 32],
+    reports: &[Pubkey],
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![SOuLInstruction::retire(
+        program_id,
+        token_program,
+        owner,
+        &token_account_with_program(owner, mint, token_program),
+        mint,
+        token_config,
+        nonce,
+        amount,
+        category,
+        beneficiary_hash,
+        reports,
+    )?])
+}
+
+/// Publish the root of `tree` and fund its vault from the admin's token
+/// account with the sum of every claim
+pub fn create_distributor(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    admin: &Pubkey,
+    mint: &Pubkey,
+    nonce: u64,
+    expiry: i64,
+    tree: &AirdropTree,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![DistributorInstruction::new_distributor(
+        program_id,
+        token_program,
+        admin,
+        mint,
+        &token_account_with_program(admin, mint, token_program),
+        nonce,
+        tree.root,
+        tree.max_total_claim,
+        tree.num_nodes(),
+        expiry,
+    )?])
+}
+
+/// Claim an airdrop to the claimant's token account
+pub fn claim_airdrop(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    distributor: &Pubkey,
+    mint: &Pubkey,
+    claim: &AirdropClaim,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let claimant = &claim.wallet;
+    Ok(vec![
+        create_token_account_with_program(claimant, claimant, mint, token_program),
+        DistributorInstruction::claim(
+            program_id,
+            token_program,
+            claimant,
+            distributor,
+            mint,
+            &token_account_with_program(claimant, mint, token_program),
+            claim.index,
+            claim.amount,
+            claim.proof.clone(),
+        )?,
+    ])
+}
+
+/// Return what is left in an expired airdrop's vault to the admin
+pub fn clawback_airdrop(
+    program_id: &Pubkey,
+    token_program: &Pubkey,
+    admin: &Pubkey,
+    distributor: &Pubkey,
+    mint: &Pubkey,
+) -> Result<Vec<Instruction>, ProgramError> {
+    Ok(vec![
+        create_token_account_with_program(admin, admin, mint, token_program),
+        DistributorInstruction::clawback(
+            program_id,
+            token_program,
+            admin,
+            distributor,
+            mint,
+            &token_account_with_program(admin, mint, token_program),
+        )?,
+    ])
+}
@@ -0,0 +1,260 @@
+use solana_program::{pubkey::Pubkey, system_program};
+use soul_cli::{
+    airdrop::AirdropTree,
+    args::{parse_hash, parse_variant},
+    decode::decode_account,
+    plan, CliError,
+};
+use soul_sanctuary::{
+    access::CanonicalMint,
+    account::AccountState,
+    distributor::merkle,
+    impact::{GeoLocation, ImpactMetrics, ImpactReport, VerificationStatus},
+    lst::LSTType,
+    state::{LSTTokenType, RetirementCertificate},
+    vesting::{TrancheMode, VestingSchedule, VestingTranche, VestingType},
+};
+
+fn account_data<T: AccountState>(state: &T) -> Vec<u8> {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    data.push(T::VERSION);
+    data.extend(state.try_to_vec().unwrap());
+    // Accounts are allocated with room to spare
+    data.resize(T::SPACE.max(data.len()), 0);
+    data
+}
+
+#[test]
+fn test_decode_account_uses_program_layouts() {
+    let beneficiary = Pubkey::new_unique();
+    let schedule = VestingSchedule {
+        is_initialized: true,
+        beneficiary,
+        start_timestamp: -5,
+        end_timestamp: 100,
+        total_amount: 1_000,
+        released_amount: 250,
+        vesting_type: VestingType::Advisor,
+        original_beneficiary: beneficiary,
+        nonce: 7,
+        bump: 254,
+        payer: Pubkey::new_unique(),
+        cliff_seconds: 0,
+        duration_seconds: 105,
+        release_interval_seconds: 1,
+        tranches: vec![VestingTranche {
+            unlock_timestamp: 100,
+            amount: 1_000,
+            mode: TrancheMode::Linear,
+        }],
+        project: Pubkey::default(),
+        milestones: vec![],
+        revoke_authority: None,
+        revoked_at: None,
+        mint: Pubkey::new_unique(),
+        vault: Pubkey::new_unique(),
+    };
+
+    let (name, fields) = decode_account(&account_data(&schedule)).unwrap();
+    assert_eq!(name, "VestingSchedule");
+    assert_eq!(fields["beneficiary"], beneficiary.to_string());
+    assert_eq!(fields["start_timestamp"], -5);
+    assert_eq!(fields["released_amount"], 250);
+    assert_eq!(fields["vesting_type"], "Advisor");
+    assert_eq!(fields["bump"], 254);
+    assert_eq!(fields["tranches"][0]["mode"], "Linear");
+
+    let report = ImpactReport {
+        metrics_account: Pubkey::new_unique(),
+        timestamp: 1_700_000_000,
+        location: GeoLocation {
+            latitude: -1_300_000,
+            longitude: 36_800_000,
+            altitude: 12,
+        },
+        metrics: ImpactMetrics {
+            is_initialized: true,
+            authority: Pubkey::new_unique(),
+            last_update: 0,
+            forest_area: 10_000,
+            carbon_offset: 0,
+            water_saved: 0,
+            biodiversity_score: 900,
+            energy_saved: 0,
+            waste_recycled: 0,
+            impact_score: 30,
+            bump: 0,
+        },
+        verifier: Pubkey::default(),
+        verification_status: VerificationStatus::Disputed,
+        proof_hash: [0xab; 32],
+        bump: 255,
+        verified_at: Some(1_700_000_600),
+        category: Some(LSTType::Forest),
+    };
+    let (name, fields) = decode_account(&account_data(&report)).unwrap();
+    assert_eq!(name, "ImpactReport");
+    assert_eq!(fields["location"]["latitude"], -1_300_000);
+    assert_eq!(fields["metrics"]["biodiversity_score"], 900);
+    assert_eq!(fields["verification_status"], "Disputed");
+    assert_eq!(fields["proof_hash"], "ab".repeat(32));
+}
+
+#[test]
+fn test_decode_account_rejects_foreign_data() {
+    assert!(matches!(
+        decode_account(&[0; 64]),
+        Err(CliError::UnknownAccountType)
+    ));
+    assert!(matches!(
+        decode_account(&VestingSchedule::DISCRIMINATOR),
+        Err(CliError::UnknownAccountType)
+    ));
+
+    // A header from a different layout version is not misread
+    let mut data = VestingSchedule::DISCRIMINATOR.to_vec();
+    data.push(VestingSchedule::VERSION + 1);
+    data.resize(VestingSchedule::SPACE, 0);
+    assert!(matches!(decode_account(&data), Err(CliError::Decode(_))));
+}
+
+#[test]
+fn test_argument_parsers() {
+    assert_eq!(parse_variant::<LSTType>("forest"), Ok(LSTType::Forest));
+    assert_eq!(parse_variant::<LSTType>("PES"), Ok(LSTType::PES));
+    assert!(matches!(
+        parse_variant::<LSTTokenType>("forest-soul"),
+        Ok(LSTTokenType::ForestSOuL)
+    ));
+    assert!(matches!(
+        parse_variant::<VestingType>("private_sale"),
+        Ok(VestingType::PrivateSale)
+    ));
+    let error = parse_variant::<VestingType>("founder").unwrap_err();
+    assert!(error.contains("Team, Advisor"), "{}", error);
+
+    assert_eq!(parse_hash(&"0f".repeat(32)), Ok([0x0f; 32]));
+    assert_eq!(
+        parse_hash(&format!("0x{}", "ff".repeat(32))),
+        Ok([0xff; 32])
+    );
+    assert!(parse_hash("abcd").is_err());
+    assert!(parse_hash(&"zz".repeat(32)).is_err());
+}
+
+#[test]
+fn test_plans_use_program_builders_and_token_accounts() {
+    let program_id = Pubkey::new_unique();
+    let payer = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+
+    let instructions = plan::initialize_soul(&program_id, &payer, &mint, 1_000).unwrap();
+    assert_eq!(instructions[0].program_id, system_program::id());
+    assert_eq!(instructions[1].program_id, program_id);
+    assert_eq!(instructions[1].data[..2], [0, 0]);
+
+    let beneficiary = Pubkey::new_unique();
+    let instructions = plan::create_vesting(
+        &program_id,
+        &spl_token::id(),
+        &payer,
+        &beneficiary,
+        &mint,
+        3,
+        0,
+        500,
+        VestingType::Team,
+        None,
+        Some(100),
+        None,
+        Some(payer),
+    )
+    .unwrap();
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 3, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    assert_eq!(instructions.len(), 1);
+    assert_eq!(instructions[0].accounts[1].pubkey, vesting_account);
+    // Funds move from the authority's token account into the schedule's vault
+    assert_eq!(instructions[0].accounts[3].pubkey, vault);
+    assert_eq!(
+        instructions[0].accounts[5].pubkey,
+        plan::token_account(&payer, &mint)
+    );
+
+    let report = Pubkey::new_unique();
+    let retire = plan::retire(
+        &program_id,
+        &spl_token::id(),
+        &payer,
+        &mint,
+        &Pubkey::new_unique(),
+        2,
+        100,
+        LSTType::Forest,
+        [1; 32],
+        &[report],
+    )
+    .unwrap()
+    .pop()
+    .unwrap();
+    assert_eq!(retire.accounts[1].pubkey, plan::token_account(&payer, &mint));
+    let (certificate, _) = RetirementCertificate::find_address(&payer, 2, &program_id);
+    assert_eq!(retire.accounts[5].pubkey, certificate);
+    assert_eq!(retire.accounts[7].pubkey, CanonicalMint::find_address(&program_id).0);
+    assert_eq!(retire.accounts[8].pubkey, report);
+
+    let release = plan::release_vesting(&program_id, &spl_token::id(), &beneficiary, &vesting_account, &mint, &[])
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(release.accounts[1].pubkey, vault);
+    assert_eq!(release.accounts[2].pubkey, mint);
+    assert_eq!(
+        release.accounts[3].pubkey,
+        plan::token_account(&beneficiary, &mint)
+    );
+}
+
+#[test]
+fn test_airdrop_tree_proves_every_claim() {
+    let wallets: Vec<_> = (0..5).map(|_| Pubkey::new_unique()).collect();
+    let csv = format!(
+        "wallet,amount\n{},100\n\n{}, 200\n{},300\n{},400\n{},500\n",
+        wallets[0], wallets[1], wallets[2], wallets[3], wallets[4]
+    );
+    let tree = AirdropTree::from_csv(&csv).unwrap();
+    assert_eq!(tree.num_nodes(), 5);
+    assert_eq!(tree.max_total_claim, 1_500);
+    for (index, wallet) in wallets.iter().enumerate() {
+        let claim = tree.claim(wallet).unwrap();
+        assert_eq!(claim.index, index as u64);
+        assert_eq!(claim.amount, 100 * (index as u64 + 1));
+        let leaf = merkle::leaf_hash(claim.index, wallet, claim.amount);
+        assert!(merkle::verify(&claim.proof, &tree.root, leaf));
+        let forged = merkle::leaf_hash(claim.index, wallet, claim.amount + 1);
+        assert!(!merkle::verify(&claim.proof, &tree.root, forged));
+    }
+    assert!(tree.claim(&Pubkey::new_unique()).is_none());
+    assert_eq!(tree.to_json()["claims"][4]["proof"].as_array().unwrap().len(), 1);
+
+    // A single claim is its own root
+    let tree = AirdropTree::from_csv(&format!("{},7", wallets[0])).unwrap();
+    assert_eq!(tree.root, merkle::leaf_hash(0, &wallets[0], 7));
+    assert!(tree.claims[0].proof.is_empty());
+
+    for csv in [
+        String::new(),
+        "wallet,amount\n".to_string(),
+        format!("{},1\n{},2", wallets[0], wallets[0]),
+        format!("{},1\n{},lots", wallets[0], wallets[1]),
+        format!("{},1\nnot-a-wallet,2", wallets[0]),
+        format!("{},1,extra", wallets[0]),
+        format!("{},{}\n{},1", wallets[0], u64::MAX, wallets[1]),
+    ] {
+        assert!(
+            matches!(AirdropTree::from_csv(&csv), Err(CliError::Csv(_))),
+            "{:?}",
+            csv
+        );
+    }
+}
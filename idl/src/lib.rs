@@ -0,0 +1,166 @@
+//! JSON IDL of the SOuL Sanctuary program.
+//!
+//! Instruction layouts, account lists and Borsh types are read from the
+//! program's source, so the IDL follows the declared enums and structs and
+//! their doc comments. Error codes and account discriminators come from the
+//! compiled program itself.
+
+pub mod source;
+
+use std::{collections::BTreeSet, path::Path};
+
+use serde_json::{json, Value};
+use soul_sanctuary::{
+    access::{AccessError, AuthorityTransfer, CircuitBreaker, ProgramConfig, Roles},
+    account::AccountState,
+    distributor::{Distributor, DistributorError},
+    error::SOuLError,
+    event::SoulEvent,
+    game::{GameError, GameState, PlayerState},
+    impact::{DisputeCase, ImpactError, ImpactMetrics, ImpactReport, VerifierState},
+    instruction::ModuleTag,
+    lst::{LSTError, LSTPool, StakerInfo},
+    multisig::{Multisig, MultisigError, Proposal},
+    state::{
+        ComplianceHistory, ComplianceRecord, LSTToken, ReportRetirement, RetirementCertificate, SOuLToken,
+        TokenMetadata,
+    },
+    vesting::{VestingError, VestingSchedule},
+};
+use thiserror::Error;
+
+use crate::source::ProgramSource;
+
+/// Errors that stop IDL generation
+#[derive(Error, Debug)]
+pub enum IdlError {
+    #[error("Could not read {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Could not parse {0}: {1}")]
+    Parse(String, syn::Error),
+    #[error("No Borsh type named {0}")]
+    UnknownType(String),
+    #[error("{0} is declared more than once with different layouts")]
+    ConflictingType(String),
+    #[error("Unsupported field type {0}")]
+    UnsupportedType(String),
+    #[error("Instruction {0} does not document its accounts")]
+    MissingAccounts(String),
+}
+
+/// Instruction enum of each module, in `ModuleTag` order
+pub const MODULES: [(ModuleTag, &str); 8] = [
+    (ModuleTag::Token, "SOuLInstruction"),
+    (ModuleTag::Vesting, "VestingInstruction"),
+    (ModuleTag::Impact, "ImpactInstruction"),
+    (ModuleTag::LST, "LSTInstruction"),
+    (ModuleTag::Game, "GameInstruction"),
+    (ModuleTag::Multisig, "MultisigInstruction"),
+    (ModuleTag::Access, "AccessInstruction"),
+    (ModuleTag::Distributor, "DistributorInstruction"),
+];
+
+macro_rules! errors {
+    ($error:ty) => {
+        <$error>::ALL
+            .iter()
+            .map(|error| {
+                json!({
+                    "code": error.code(),
+                    "name": format!("{:?}", error),
+                    "msg": error.message(),
+                })
+            })
+            .collect::<Vec<_>>()
+    };
+}
+
+macro_rules! account {
+    ($account:ty) => {
+        json!({
+            "name": stringify!($account),
+            "discriminator": <$account>::DISCRIMINATOR,
+            "version": <$account>::VERSION,
+            "space": <$account>::SPACE,
+            "type": { "defined": stringify!($account) },
+        })
+    };
+}
+
+/// Build the IDL from the program crate rooted at `program_dir`
+pub fn generate(program_dir: &Path) -> Result<Value, IdlError> {
+    let source = ProgramSource::load(&program_dir.join("src"))?;
+    let mut referenced = BTreeSet::new();
+
+    let errors = [
+        errors!(SOuLError),
+        errors!(VestingError),
+        errors!(ImpactError),
+        errors!(LSTError),
+        errors!(GameError),
+        errors!(MultisigError),
+        errors!(AccessError),
+        errors!(DistributorError),
+    ];
+    let mut modules = Vec::new();
+    for ((tag, enum_name), errors) in MODULES.iter().zip(errors) {
+        modules.push(json!({
+            "name": format!("{:?}", tag),
+            "tag": *tag as u8,
+            "instructionEnum": enum_name,
+            "instructions": source.instructions(enum_name, &mut referenced)?,
+            "errors": errors,
+        }));
+    }
+
+    let accounts = vec![
+        account!(SOuLToken),
+        account!(LSTToken),
+        account!(TokenMetadata),
+        account!(ComplianceRecord),
+        account!(ComplianceHistory),
+        account!(RetirementCertificate),
+        account!(ReportRetirement),
+        account!(VestingSchedule),
+        account!(LSTPool),
+        account!(StakerInfo),
+        account!(ImpactMetrics),
+        account!(ImpactReport),
+        account!(VerifierState),
+        account!(DisputeCase),
+        account!(GameState),
+        account!(PlayerState),
+        account!(Multisig),
+        account!(Proposal),
+        account!(Roles),
+        account!(AuthorityTransfer),
+        account!(ProgramConfig),
+        account!(CircuitBreaker),
+        account!(Distributor),
+    ];
+    for account in &accounts {
+        referenced.insert(account["name"].as_str().unwrap_or_default().to_string());
+    }
+    referenced.insert("SoulEvent".to_string());
+
+    Ok(json!({
+        "name": "soul_sanctuary",
+        "version": env!("CARGO_PKG_VERSION"),
+        "instructionEncoding": "one module tag byte followed by the Borsh-encoded module instruction",
+        "accountHeader": "8-byte discriminator and 1-byte layout version before the Borsh-encoded account",
+        "modules": modules,
+        "accounts": accounts,
+        "events": {
+            "tag": SoulEvent::TAG,
+            "type": { "defined": "SoulEvent" },
+        },
+        "types": source.types(&mut referenced)?,
+    }))
+}
+
+/// The IDL as written to disk: pretty-printed with a trailing newline
+pub fn render(idl: &Value) -> String {
+    let mut text = serde_json::to_string_pretty(idl).expect("IDL serializes to JSON");
+    text.push('\n');
+    text
+}
@@ -0,0 +1,50 @@
+use std::{fs, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use soul_idl::{generate, render};
+
+#[derive(Parser)]
+#[command(about = "Generate the JSON IDL of the SOuL Sanctuary program")]
+struct Cli {
+    /// Fail instead of writing when the IDL file is out of date
+    #[arg(long)]
+    check: bool,
+
+    /// IDL file to write or check
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/soul_sanctuary.json"))]
+    out: PathBuf,
+
+    /// Root of the program crate
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../program"))]
+    program_dir: PathBuf,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let idl = match generate(&cli.program_dir) {
+        Ok(idl) => render(&idl),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if cli.check {
+        if fs::read_to_string(&cli.out).ok().as_deref() == Some(idl.as_str()) {
+            return ExitCode::SUCCESS;
+        }
+        eprintln!(
+            "error: {} is out of date, run `cargo run -p soul-idl` to regenerate it",
+            cli.out.display()
+        );
+        return ExitCode::FAILURE;
+    }
+
+    match fs::write(&cli.out, idl) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: could not write {}: {}", cli.out.display(), error);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,256 @@
+//! Read instruction enums and Borsh types out of the program's source.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::Path,
+};
+
+use quote::ToTokens;
+use serde_json::{json, Value};
+use syn::{Attribute, Expr, Fields, Item, Lit, Type};
+
+use crate::IdlError;
+
+/// Borsh-encoded structs and enums declared in the program, by name
+pub struct ProgramSource {
+    items: BTreeMap<String, Vec<Item>>,
+}
+
+impl ProgramSource {
+    /// Parse every `.rs` file under `src_dir`
+    pub fn load(src_dir: &Path) -> Result<Self, IdlError> {
+        let mut source = Self {
+            items: BTreeMap::new(),
+        };
+        source.load_dir(src_dir)?;
+        Ok(source)
+    }
+
+    fn load_dir(&mut self, dir: &Path) -> Result<(), IdlError> {
+        let mut entries = fs::read_dir(dir)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .map_err(|error| IdlError::Read(dir.display().to_string(), error))?;
+        entries.sort_by_key(|entry| entry.path());
+
+        for entry in entries {
+            let path = entry.path();
+            if path.is_dir() {
+                self.load_dir(&path)?;
+            } else if path.extension().is_some_and(|extension| extension == "rs") {
+                let text = fs::read_to_string(&path)
+                    .map_err(|error| IdlError::Read(path.display().to_string(), error))?;
+                let file = syn::parse_file(&text)
+                    .map_err(|error| IdlError::Parse(path.display().to_string(), error))?;
+                for item in file.items {
+                    let name = match &item {
+                        Item::Struct(item) if derives_borsh(&item.attrs) => item.ident.to_string(),
+                        Item::Enum(item) if derives_borsh(&item.attrs) => item.ident.to_string(),
+                        _ => continue,
+                    };
+                    self.items.entry(name).or_default().push(item);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn item(&self, name: &str) -> Result<&Item, IdlError> {
+        self.items
+            .get(name)
+            .and_then(|items| items.first())
+            .ok_or_else(|| IdlError::UnknownType(name.to_string()))
+    }
+
+    /// The instructions of a module's instruction enum, in Borsh variant order
+    pub fn instructions(
+        &self,
+        enum_name: &str,
+        referenced: &mut BTreeSet<String>,
+    ) -> Result<Vec<Value>, IdlError> {
+        let Item::Enum(item) = self.item(enum_name)? else {
+            return Err(IdlError::UnknownType(enum_name.to_string()));
+        };
+
+        item.variants
+            .iter()
+            .enumerate()
+            .map(|(index, variant)| {
+                let docs = doc_lines(&variant.attrs);
+                let name = variant.ident.to_string();
+                let accounts =
+                    accounts(&docs).ok_or_else(|| IdlError::MissingAccounts(name.clone()))?;
+                let description: Vec<_> = docs
+                    .iter()
+                    .take_while(|line| *line != ACCOUNTS_HEADING)
+                    .filter(|line| !line.is_empty())
+                    .collect();
+                Ok(json!({
+                    "name": name,
+                    "index": index,
+                    "docs": description,
+                    "accounts": accounts,
+                    "args": fields(&variant.fields, referenced)?,
+                }))
+            })
+            .collect()
+    }
+
+    /// Definitions of every type in `referenced` and the types they use in turn
+    pub fn types(&self, referenced: &mut BTreeSet<String>) -> Result<Vec<Value>, IdlError> {
+        let mut definitions = BTreeMap::new();
+        while let Some(name) = referenced
+            .iter()
+            .find(|name| !definitions.contains_key(*name))
+            .cloned()
+        {
+            let mut layouts = Vec::new();
+            for item in self
+                .items
+                .get(&name)
+                .ok_or_else(|| IdlError::UnknownType(name.clone()))?
+            {
+                layouts.push(layout(item, referenced)?);
+            }
+            // Modules may declare the same type; that is fine while the layouts agree
+            if layouts.windows(2).any(|pair| pair[0] != pair[1]) {
+                return Err(IdlError::ConflictingType(name));
+            }
+            definitions.insert(
+                name.clone(),
+                json!({ "name": name, "type": layouts.remove(0) }),
+            );
+        }
+        Ok(definitions.into_values().collect())
+    }
+}
+
+const ACCOUNTS_HEADING: &str = "Accounts expected:";
+
+fn derives_borsh(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("derive")
+            && attr
+                .meta
+                .to_token_stream()
+                .to_string()
+                .contains("BorshSerialize")
+    })
+}
+
+fn doc_lines(attrs: &[Attribute]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .filter_map(|attr| match &attr.meta.require_name_value().ok()?.value {
+            Expr::Lit(expr) => match &expr.lit {
+                Lit::Str(text) => Some(text.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Parse the `Accounts expected:` list of an instruction's doc comment
+fn accounts(docs: &[String]) -> Option<Vec<Value>> {
+    let start = docs.iter().position(|line| line == ACCOUNTS_HEADING)?;
+    docs[start + 1..]
+        .iter()
+        .take_while(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+        .enumerate()
+        .map(|(index, line)| {
+            let (_, rest) = line.split_once(". ")?;
+            let rest = rest.trim_start_matches('`').strip_prefix('[')?;
+            let (flags, description) = rest.split_once(']')?;
+            let flags: Vec<_> = flags.split(',').map(str::trim).collect();
+            Some(json!({
+                "index": index,
+                "writable": flags.contains(&"writable"),
+                "signer": flags.contains(&"signer"),
+                "description": description.trim_start_matches('`').trim(),
+            }))
+        })
+        .collect::<Option<Vec<_>>>()
+        .filter(|accounts| !accounts.is_empty())
+}
+
+fn layout(item: &Item, referenced: &mut BTreeSet<String>) -> Result<Value, IdlError> {
+    match item {
+        Item::Struct(item) => Ok(json!({
+            "kind": "struct",
+            "fields": fields(&item.fields, referenced)?,
+        })),
+        Item::Enum(item) => {
+            let variants = item
+                .variants
+                .iter()
+                .enumerate()
+                .map(|(index, variant)| {
+                    Ok(json!({
+                        "name": variant.ident.to_string(),
+                        "index": index,
+                        "fields": fields(&variant.fields, referenced)?,
+                    }))
+                })
+                .collect::<Result<Vec<_>, IdlError>>()?;
+            Ok(json!({ "kind": "enum", "variants": variants }))
+        }
+        _ => unreachable!("only structs and enums are collected"),
+    }
+}
+
+fn fields(fields: &Fields, referenced: &mut BTreeSet<String>) -> Result<Vec<Value>, IdlError> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(index, field)| {
+            let name = field
+                .ident
+                .as_ref()
+                .map_or_else(|| index.to_string(), ToString::to_string);
+            Ok(json!({ "name": name, "type": idl_type(&field.ty, referenced)? }))
+        })
+        .collect()
+}
+
+/// The IDL spelling of a field type
+fn idl_type(ty: &Type, referenced: &mut BTreeSet<String>) -> Result<Value, IdlError> {
+    let unsupported = || IdlError::UnsupportedType(ty.to_token_stream().to_string());
+    match ty {
+        Type::Array(array) => {
+            let Expr::Lit(length) = &array.len else {
+                return Err(unsupported());
+            };
+            let Lit::Int(length) = &length.lit else {
+                return Err(unsupported());
+            };
+            let length: usize = length.base10_parse().map_err(|_| unsupported())?;
+            Ok(json!({ "array": [idl_type(&array.elem, referenced)?, length] }))
+        }
+        Type::Path(path) => {
+            let segment = path.path.segments.last().ok_or_else(unsupported)?;
+            let name = segment.ident.to_string();
+            let argument = || match &segment.arguments {
+                syn::PathArguments::AngleBracketed(arguments) => match arguments.args.first() {
+                    Some(syn::GenericArgument::Type(ty)) => Ok(ty.clone()),
+                    _ => Err(unsupported()),
+                },
+                _ => Err(unsupported()),
+            };
+            match name.as_str() {
+                "u8" | "u16" | "u32" | "u64" | "u128" | "i8" | "i16" | "i32" | "i64" | "i128"
+                | "bool" | "String" => Ok(json!(name)),
+                "UnixTimestamp" => Ok(json!("i64")),
+                "Pubkey" => Ok(json!("publicKey")),
+                "Vec" => Ok(json!({ "vec": idl_type(&argument()?, referenced)? })),
+                "Option" => Ok(json!({ "option": idl_type(&argument()?, referenced)? })),
+                _ => {
+                    referenced.insert(name.clone());
+                    Ok(json!({ "defined": name }))
+                }
+            }
+        }
+        _ => Err(unsupported()),
+    }
+}
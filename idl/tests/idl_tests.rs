@@ -0,0 +1,432 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use solana_program::{instruction::Instruction, pubkey::Pubkey};
+use soul_idl::{generate, render};
+use soul_sanctuary::{
+    access::{AccessInstruction, Role},
+    account::{AccountState, AccountType},
+    distributor::DistributorInstruction,
+    impact::ImpactInstruction,
+    instruction::{ModuleTag, SOuLInstruction},
+    lst::{LSTInstruction, LSTType},
+    multisig::MultisigInstruction,
+    state::{EmissionBucket, EmissionSchedule, LSTTokenType},
+    vesting::{VestingInstruction, VestingType},
+};
+
+fn idl() -> Value {
+    generate(&Path::new(env!("CARGO_MANIFEST_DIR")).join("../program")).unwrap()
+}
+
+/// Check a builder's output against the IDL entry of the instruction it encodes
+fn assert_matches_idl(idl: &Value, module: &str, name: &str, instruction: &Instruction) {
+    let module = idl["modules"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["name"] == module)
+        .unwrap();
+    let entry = module["instructions"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|entry| entry["name"] == name)
+        .unwrap_or_else(|| panic!("{} is missing from the IDL", name));
+
+    assert_eq!(
+        u64::from(instruction.data[0]),
+        module["tag"].as_u64().unwrap()
+    );
+    assert_eq!(
+        u64::from(instruction.data[1]),
+        entry["index"].as_u64().unwrap(),
+        "{}",
+        name
+    );
+
+    let documented = entry["accounts"].as_array().unwrap();
+    assert_eq!(instruction.accounts.len(), documented.len(), "{}", name);
+    for (meta, account) in instruction.accounts.iter().zip(documented) {
+        assert_eq!(
+            meta.is_writable, account["writable"],
+            "{} {}",
+            name, account
+        );
+        assert_eq!(meta.is_signer, account["signer"], "{} {}", name, account);
+    }
+}
+
+#[test]
+fn test_committed_idl_is_current() {
+    let committed =
+        fs::read_to_string(Path::new(env!("CARGO_MANIFEST_DIR")).join("soul_sanctuary.json"))
+            .unwrap();
+    assert!(
+        committed == render(&idl()),
+        "idl/soul_sanctuary.json is out of date, run `cargo run -p soul-idl` to regenerate it"
+    );
+}
+
+#[test]
+fn test_idl_describes_errors_and_accounts() {
+    let idl = idl();
+    let vesting = &idl["modules"][1];
+    assert_eq!(vesting["name"], "Vesting");
+    assert_eq!(vesting["errors"][0]["code"], 1000);
+    assert_eq!(vesting["errors"][0]["name"], "InvalidInstruction");
+
+    let schedule = idl["accounts"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|account| account["name"] == "VestingSchedule")
+        .unwrap();
+    assert_eq!(
+        schedule["discriminator"],
+        serde_json::json!(soul_sanctuary::vesting::VestingSchedule::DISCRIMINATOR)
+    );
+
+    // Types used by instructions and accounts are defined exactly once
+    let types = idl["types"].as_array().unwrap();
+    for name in ["VestingType", "GeoLocation", "SoulEvent", "PlayerState"] {
+        assert_eq!(
+            types.iter().filter(|entry| entry["name"] == name).count(),
+            1,
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_builders_match_documented_accounts() {
+    let idl = idl();
+    let program_id = Pubkey::new_unique();
+    let key = Pubkey::new_unique;
+
+    let schedule = EmissionSchedule {
+        start_epoch: 0,
+        epoch_emission: 1,
+        reduction_interval_epochs: 1,
+        reduction_basis_points: 5_000,
+        staking: EmissionBucket {
+            destination: key(),
+            basis_points: 5_000,
+        },
+        game_rewards: EmissionBucket {
+            destination: key(),
+            basis_points: 3_000,
+        },
+        verifier_fund: EmissionBucket {
+            destination: key(),
+            basis_points: 2_000,
+        },
+        emitted: 0,
+    };
+    let token = [
+        (
+            "InitializeSOuL",
+            SOuLInstruction::initialize_soul(&program_id, &key(), &key(), &key(), 1).unwrap(),
+        ),
+        (
+            "InitializeLST",
+            SOuLInstruction::initialize_lst(&program_id, &key(), LSTTokenType::AirSOuL, 1).unwrap(),
+        ),
+        (
+            "Transfer",
+            SOuLInstruction::transfer(&program_id, &key(), &key(), &key(), &key(), &key(), 1)
+                .unwrap(),
+        ),
+        (
+            "Mint",
+            SOuLInstruction::mint(&program_id, &key(), &key(), &key(), &key(), 1).unwrap(),
+        ),
+        (
+            "Burn",
+            SOuLInstruction::burn(&program_id, &key(), &key(), &key(), &key(), 1).unwrap(),
+        ),
+        (
+            "Migrate",
+            SOuLInstruction::migrate(
+                &program_id,
+                &key(),
+                &key(),
+                AccountType::StakerInfo,
+                Some(&key()),
+            )
+            .unwrap(),
+        ),
+        (
+            "InitializeSOuLWithTransferFee",
+            SOuLInstruction::initialize_soul_with_transfer_fee(
+                &program_id,
+                &key(),
+                &key(),
+                &key(),
+                1,
+                50,
+                1_000,
+            )
+            .unwrap(),
+        ),
+        (
+            "HarvestFees",
+            SOuLInstruction::harvest_fees(&program_id, &key(), &[key()]).unwrap(),
+        ),
+        (
+            "SetTransferFee",
+            SOuLInstruction::set_transfer_fee(&program_id, &key(), &key(), 50, 1_000).unwrap(),
+        ),
+        (
+            "UpdateMetadataUri",
+            SOuLInstruction::update_metadata_uri(&program_id, &key(), &key(), String::new())
+                .unwrap(),
+        ),
+        (
+            "ConfigureEmission",
+            SOuLInstruction::configure_emission(&program_id, &key(), &key(), 1, schedule.clone())
+                .unwrap(),
+        ),
+        (
+            "Emit",
+            SOuLInstruction::emit(&program_id, &key(), &key(), &schedule).unwrap(),
+        ),
+        (
+            "Freeze",
+            SOuLInstruction::freeze(&program_id, &key(), &key(), &key(), &key(), [7; 32], Some(0)).unwrap(),
+        ),
+        (
+            "Thaw",
+            SOuLInstruction::thaw(&program_id, &key(), &key(), &key(), &key(), [7; 32], Some(0)).unwrap(),
+        ),
+        (
+            "Retire",
+            SOuLInstruction::retire(
+                &program_id,
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                0,
+                1,
+                LSTType::Forest,
+                [7; 32],
+                &[key()],
+            )
+            .unwrap(),
+        ),
+    ];
+    for (name, instruction) in &token {
+        assert_matches_idl(&idl, "Token", name, instruction);
+    }
+
+    let vesting = [
+        (
+            "Initialize",
+            VestingInstruction::initialize(
+                &program_id,
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                0,
+                0,
+                1,
+                VestingType::Team,
+                None,
+                None,
+                None,
+                None,
+            )
+            .unwrap(),
+        ),
+        (
+            "Release",
+            VestingInstruction::release(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &[key()])
+                .unwrap(),
+        ),
+        (
+            "Revoke",
+            VestingInstruction::revoke(&program_id, &key(), &key(), &key(), &key(), &key(), &key(), &key(), &[key()])
+                .unwrap(),
+        ),
+        (
+            "CloseSchedule",
+            VestingInstruction::close_schedule(&program_id, &key(), &key(), &key(), &key(), &key(), &key()).unwrap(),
+        ),
+        (
+            "InitializeTranched",
+            VestingInstruction::initialize_tranched(
+                &program_id,
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                0,
+                0,
+                VestingType::Ecosystem,
+                vec![],
+                None,
+            )
+            .unwrap(),
+        ),
+        (
+            "InitializeMilestones",
+            VestingInstruction::initialize_milestones(
+                &program_id,
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                0,
+                &key(),
+                vec![],
+                None,
+            )
+            .unwrap(),
+        ),
+    ];
+    for (name, instruction) in &vesting {
+        assert_matches_idl(&idl, "Vesting", name, instruction);
+    }
+
+    assert_matches_idl(
+        &idl,
+        "Impact",
+        "CloseDispute",
+        &ImpactInstruction::close_dispute(&program_id, &key(), &key(), &key(), &key(), &key(), &key()).unwrap(),
+    );
+
+    let lst = [
+        (
+            "Stake",
+            LSTInstruction::stake(
+                &program_id,
+                &key(),
+                LSTType::Forest,
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                1,
+            )
+            .unwrap(),
+        ),
+        (
+            "CloseStakerInfo",
+            LSTInstruction::close_staker_info(&program_id, LSTType::Forest, &key(), &key()).unwrap(),
+        ),
+    ];
+    for (name, instruction) in &lst {
+        assert_matches_idl(&idl, "LST", name, instruction);
+    }
+
+    let proposed =
+        SOuLInstruction::update_metadata_uri(&program_id, &key(), &key(), String::new()).unwrap();
+    let multisig = [
+        (
+            "Create",
+            MultisigInstruction::create(&program_id, &key(), 0, vec![key()], 1).unwrap(),
+        ),
+        (
+            "Propose",
+            MultisigInstruction::propose(&program_id, &key(), &key(), 0, &proposed).unwrap(),
+        ),
+        (
+            "Approve",
+            MultisigInstruction::approve(&program_id, &key(), &key(), &key()).unwrap(),
+        ),
+        (
+            "SetSigners",
+            MultisigInstruction::set_signers(&program_id, &key(), vec![key()], 1).unwrap(),
+        ),
+        (
+            "CloseProposal",
+            MultisigInstruction::close_proposal(&program_id, &key(), &key(), &key()).unwrap(),
+        ),
+    ];
+    for (name, instruction) in &multisig {
+        assert_matches_idl(&idl, "Multisig", name, instruction);
+    }
+
+    let access = [
+        (
+            "InitializeRoles",
+            AccessInstruction::initialize_roles(&program_id, &key()).unwrap(),
+        ),
+        (
+            "GrantRole",
+            AccessInstruction::grant_role(&program_id, &key(), &key(), Role::Minter).unwrap(),
+        ),
+        (
+            "RevokeRole",
+            AccessInstruction::revoke_role(&program_id, &key(), &key(), Role::Minter).unwrap(),
+        ),
+        (
+            "ProposeAuthority",
+            AccessInstruction::propose_authority(&program_id, &key(), &key(), &key()).unwrap(),
+        ),
+        (
+            "AcceptAuthority",
+            AccessInstruction::accept_authority(&program_id, &key(), &key(), &key()).unwrap(),
+        ),
+        (
+            "SetPaused",
+            AccessInstruction::set_paused(&program_id, &key(), ModuleTag::LST, true).unwrap(),
+        ),
+        (
+            "ConfigureCircuitBreaker",
+            AccessInstruction::configure_circuit_breaker(
+                &program_id,
+                &key(),
+                ModuleTag::LST,
+                9_000,
+                1_000_000,
+            )
+            .unwrap(),
+        ),
+    ];
+    for (name, instruction) in &access {
+        assert_matches_idl(&idl, "Access", name, instruction);
+    }
+
+    let distributor = [
+        (
+            "NewDistributor",
+            DistributorInstruction::new_distributor(
+                &program_id,
+                &key(),
+                &key(),
+                &key(),
+                &key(),
+                0,
+                [1; 32],
+                1_000,
+                4,
+                1_700_000_000,
+            )
+            .unwrap(),
+        ),
+        (
+            "Claim",
+            DistributorInstruction::claim(&program_id, &key(), &key(), &key(), &key(), &key(), 0, 250, vec![[2; 32]])
+                .unwrap(),
+        ),
+        (
+            "Clawback",
+            DistributorInstruction::clawback(&program_id, &key(), &key(), &key(), &key(), &key()).unwrap(),
+        ),
+        (
+            "CloseDistributor",
+            DistributorInstruction::close_distributor(&program_id, &key(), &key(), &key(), &key(), &key()).unwrap(),
+        ),
+    ];
+    for (name, instruction) in &distributor {
+        assert_matches_idl(&idl, "Distributor", name, instruction);
+    }
+}
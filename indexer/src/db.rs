@@ -0,0 +1,1343 @@
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::Serialize;
+use solana_program::pubkey::Pubkey;
+use soul_sanctuary::{
+    access::Role,
+    account::AccountState,
+    event::SoulEvent,
+    game::PlayerState,
+    impact::{ImpactReport, VerificationStatus},
+    instruction::ModuleTag,
+    lst::LSTType,
+    state::ComplianceAction,
+};
+
+use crate::error::IndexerError;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS transactions (
+    signature TEXT PRIMARY KEY,
+    slot INTEGER NOT NULL,
+    block_time INTEGER
+);
+
+-- SOuL transfers, mints, burns, retirements and transfer fee harvests
+CREATE TABLE IF NOT EXISTS transfers (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    source TEXT,
+    destination TEXT,
+    authority TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    total_supply INTEGER,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Vesting payouts to the beneficiary and revocations back to the authority
+CREATE TABLE IF NOT EXISTS vesting_releases (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    vesting_account TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    released_amount INTEGER,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Latest known state of each report; location comes from the report account
+CREATE TABLE IF NOT EXISTS impact_reports (
+    address TEXT PRIMARY KEY,
+    metrics_account TEXT NOT NULL,
+    authority TEXT,
+    proof_hash TEXT NOT NULL,
+    status TEXT NOT NULL,
+    verifier TEXT,
+    latitude INTEGER,
+    longitude INTEGER,
+    altitude INTEGER,
+    impact_score INTEGER,
+    submitted_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS impact_reports_location ON impact_reports (latitude, longitude);
+
+CREATE TABLE IF NOT EXISTS verifications (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    report TEXT NOT NULL,
+    verifier TEXT NOT NULL,
+    approved INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS disputes (
+    address TEXT PRIMARY KEY,
+    report TEXT NOT NULL,
+    challenger TEXT NOT NULL,
+    stake_amount INTEGER NOT NULL,
+    evidence_hash TEXT NOT NULL,
+    opened_at INTEGER NOT NULL,
+    resolved_by TEXT,
+    in_favor_of_report INTEGER,
+    recipient TEXT,
+    resolved_at INTEGER
+);
+
+-- Stakes, unstake requests, unstakes and reward claims
+CREATE TABLE IF NOT EXISTS stakes (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    pool TEXT NOT NULL,
+    staker TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    lst_amount INTEGER,
+    fee INTEGER,
+    unlock_time INTEGER,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE INDEX IF NOT EXISTS stakes_staker ON stakes (staker);
+
+-- Latest known state of each player account
+CREATE TABLE IF NOT EXISTS player_progress (
+    player TEXT PRIMARY KEY,
+    address TEXT NOT NULL,
+    level INTEGER NOT NULL,
+    experience INTEGER NOT NULL,
+    quests_completed INTEGER NOT NULL,
+    rewards_earned INTEGER NOT NULL,
+    collection_power INTEGER NOT NULL,
+    achievements INTEGER NOT NULL,
+    updated_slot INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS quest_completions (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    player TEXT NOT NULL,
+    quest_id INTEGER NOT NULL,
+    experience INTEGER NOT NULL,
+    eco_tokens INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+CREATE TABLE IF NOT EXISTS item_trades (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    player TEXT NOT NULL,
+    target TEXT NOT NULL,
+    offered_items TEXT NOT NULL,
+    requested_items TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Scheduled emissions and their split across the allocation buckets
+CREATE TABLE IF NOT EXISTS emissions (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    mint TEXT NOT NULL,
+    epoch INTEGER NOT NULL,
+    staking INTEGER NOT NULL,
+    game_rewards INTEGER NOT NULL,
+    verifier_fund INTEGER NOT NULL,
+    emitted INTEGER NOT NULL,
+    total_supply INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Multisig proposals, approvals and executions
+CREATE TABLE IF NOT EXISTS multisig_actions (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    multisig TEXT NOT NULL,
+    proposal TEXT NOT NULL,
+    signer TEXT,
+    approvals INTEGER,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Role grants and revocations, and authorities handed over to new keys
+CREATE TABLE IF NOT EXISTS access_changes (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    account TEXT,
+    member TEXT NOT NULL,
+    role TEXT,
+    authority TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Modules paused and resumed by guardians
+CREATE TABLE IF NOT EXISTS pauses (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    module TEXT NOT NULL,
+    paused INTEGER NOT NULL,
+    guardian TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Compliance freezes and thaws of SOuL token accounts
+CREATE TABLE IF NOT EXISTS compliance_holds (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    token_account TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    reason_hash TEXT NOT NULL,
+    officer TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Airdrops published, claimed from and clawed back
+CREATE TABLE IF NOT EXISTS airdrops (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    distributor TEXT NOT NULL,
+    wallet TEXT NOT NULL,
+    leaf_index INTEGER,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Retirement certificates of burned carbon credits
+CREATE TABLE IF NOT EXISTS retirements (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    certificate TEXT NOT NULL,
+    owner TEXT NOT NULL,
+    mint TEXT NOT NULL,
+    category TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    beneficiary_hash TEXT NOT NULL,
+    reports TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- Finished accounts closed and the rent returned to their payers
+CREATE TABLE IF NOT EXISTS closed_accounts (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    module TEXT NOT NULL,
+    account TEXT NOT NULL,
+    payer TEXT NOT NULL,
+    lamports INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+
+-- SOuL taken out of LST pools by their authorities in emergencies
+CREATE TABLE IF NOT EXISTS emergency_withdrawals (
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    pool TEXT NOT NULL,
+    authority TEXT NOT NULL,
+    destination TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+";
+
+/// A report returned by [`Database::reports_in_box`]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReportRow {
+    pub address: String,
+    pub metrics_account: String,
+    pub status: String,
+    pub verifier: Option<String>,
+    pub latitude: i64,
+    pub longitude: i64,
+    pub impact_score: Option<i64>,
+    pub submitted_at: i64,
+}
+
+/// A row of [`Database::staking_history`]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct StakeRow {
+    pub signature: String,
+    pub slot: u64,
+    pub kind: String,
+    pub pool: String,
+    pub amount: u64,
+    pub lst_amount: Option<u64>,
+    pub fee: Option<u64>,
+    pub unlock_time: Option<i64>,
+    pub timestamp: i64,
+}
+
+/// A row of [`Database::leaderboard`]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlayerRow {
+    pub player: String,
+    pub level: u16,
+    pub experience: u64,
+    pub quests_completed: u64,
+    pub rewards_earned: u64,
+    pub collection_power: u64,
+    pub achievements: u64,
+}
+
+/// Inclusive latitude and longitude bounds, in millionths of a degree
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub min_latitude: i64,
+    pub min_longitude: i64,
+    pub max_latitude: i64,
+    pub max_longitude: i64,
+}
+
+/// SQLite store for indexed program history
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    /// Open or create the database at `path`
+    pub fn open(path: &str) -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    pub fn open_in_memory() -> Result<Self, IndexerError> {
+        Self::with_connection(Connection::open_in_memory()?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, IndexerError> {
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    /// Most recently indexed transaction signature
+    pub fn latest_signature(&self) -> Result<Option<String>, IndexerError> {
+        Ok(self
+            .connection
+            .query_row(
+                "SELECT signature FROM transactions ORDER BY rowid DESC LIMIT 1",
+                [],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// Store a transaction and its events. Returns false if it was already indexed.
+    pub fn record_transaction(
+        &mut self,
+        signature: &str,
+        slot: u64,
+        block_time: Option<i64>,
+        events: &[SoulEvent],
+    ) -> Result<bool, IndexerError> {
+        let tx = self.connection.transaction()?;
+        let inserted = tx.execute(
+            "INSERT OR IGNORE INTO transactions (signature, slot, block_time) VALUES (?1, ?2, ?3)",
+            params![signature, slot, block_time],
+        )?;
+        if inserted == 0 {
+            return Ok(false);
+        }
+        for (index, event) in events.iter().enumerate() {
+            record_event(&tx, signature, index, event)?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    /// Store the current state of a program account.
+    ///
+    /// Reports and players are kept; other account types are ignored.
+    pub fn record_account(
+        &mut self,
+        address: &Pubkey,
+        slot: u64,
+        data: &[u8],
+    ) -> Result<(), IndexerError> {
+        if ImpactReport::is_current(data) {
+            let Ok(report) = ImpactReport::unpack_account(data) else {
+                return Ok(());
+            };
+            let verifier =
+                (report.verifier != Pubkey::default()).then(|| report.verifier.to_string());
+            self.connection.execute(
+                "INSERT INTO impact_reports (address, metrics_account, proof_hash, status, verifier,
+                    latitude, longitude, altitude, impact_score, submitted_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+                 ON CONFLICT (address) DO UPDATE SET status = ?4, verifier = COALESCE(?5, verifier), latitude = ?6,
+                    longitude = ?7, altitude = ?8, impact_score = ?9",
+                params![
+                    address.to_string(),
+                    report.metrics_account.to_string(),
+                    hex(&report.proof_hash),
+                    status_name(&report.verification_status),
+                    verifier,
+                    report.location.latitude,
+                    report.location.longitude,
+                    report.location.altitude,
+                    report.metrics.calculate_impact_score(),
+                    report.timestamp,
+                ],
+            )?;
+        } else if PlayerState::is_current(data) {
+            let Ok(player) = PlayerState::unpack_account(data) else {
+                return Ok(());
+            };
+            self.connection.execute(
+                "INSERT INTO player_progress (player, address, level, experience, quests_completed,
+                    rewards_earned, collection_power, achievements, updated_slot)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                 ON CONFLICT (player) DO UPDATE SET level = ?3, experience = ?4, quests_completed = ?5,
+                    rewards_earned = ?6, collection_power = ?7, achievements = ?8, updated_slot = ?9
+                 WHERE updated_slot <= ?9",
+                params![
+                    player.owner.to_string(),
+                    address.to_string(),
+                    player.level,
+                    player.experience,
+                    player.quests_completed,
+                    player.rewards_earned,
+                    player.collection_power,
+                    player.achievements.len(),
+                    slot,
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Reports located inside `bounds`, optionally limited to one status
+    pub fn reports_in_box(
+        &self,
+        bounds: BoundingBox,
+        status: Option<&str>,
+    ) -> Result<Vec<ReportRow>, IndexerError> {
+        let mut statement = self.connection.prepare(
+            "SELECT address, metrics_account, status, verifier, latitude, longitude, impact_score, submitted_at
+             FROM impact_reports
+             WHERE latitude BETWEEN ?1 AND ?2 AND longitude BETWEEN ?3 AND ?4
+                AND (?5 IS NULL OR status = ?5)
+             ORDER BY submitted_at, address",
+        )?;
+        let rows = statement.query_map(
+            params![
+                bounds.min_latitude,
+                bounds.max_latitude,
+                bounds.min_longitude,
+                bounds.max_longitude,
+                status,
+            ],
+            |row| {
+                Ok(ReportRow {
+                    address: row.get(0)?,
+                    metrics_account: row.get(1)?,
+                    status: row.get(2)?,
+                    verifier: row.get(3)?,
+                    latitude: row.get(4)?,
+                    longitude: row.get(5)?,
+                    impact_score: row.get(6)?,
+                    submitted_at: row.get(7)?,
+                })
+            },
+        )?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Every stake, unstake and reward claim made by `staker`, oldest first
+    pub fn staking_history(&self, staker: &Pubkey) -> Result<Vec<StakeRow>, IndexerError> {
+        let mut statement = self.connection.prepare(
+            "SELECT stakes.signature, transactions.slot, kind, pool, amount, lst_amount, fee, unlock_time, timestamp
+             FROM stakes JOIN transactions ON transactions.signature = stakes.signature
+             WHERE staker = ?1
+             ORDER BY transactions.rowid, event_index",
+        )?;
+        let rows = statement.query_map(params![staker.to_string()], |row| {
+            Ok(StakeRow {
+                signature: row.get(0)?,
+                slot: row.get(1)?,
+                kind: row.get(2)?,
+                pool: row.get(3)?,
+                amount: row.get(4)?,
+                lst_amount: row.get(5)?,
+                fee: row.get(6)?,
+                unlock_time: row.get(7)?,
+                timestamp: row.get(8)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Players with the most experience
+    pub fn leaderboard(&self, limit: u32) -> Result<Vec<PlayerRow>, IndexerError> {
+        let mut statement = self.connection.prepare(
+            "SELECT player, level, experience, quests_completed, rewards_earned, collection_power, achievements
+             FROM player_progress
+             ORDER BY experience DESC, player
+             LIMIT ?1",
+        )?;
+        let rows = statement.query_map(params![limit], |row| {
+            Ok(PlayerRow {
+                player: row.get(0)?,
+                level: row.get(1)?,
+                experience: row.get(2)?,
+                quests_completed: row.get(3)?,
+                rewards_earned: row.get(4)?,
+                collection_power: row.get(5)?,
+                achievements: row.get(6)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+}
+
+fn record_event(
+    tx: &Transaction,
+    signature: &str,
+    index: usize,
+    event: &SoulEvent,
+) -> Result<(), IndexerError> {
+    match event {
+        SoulEvent::Transferred {
+            mint,
+            source,
+            destination,
+            authority,
+            amount,
+            timestamp,
+        } => {
+            insert_transfer(
+                tx,
+                signature,
+                index,
+                "transfer",
+                mint,
+                Some(source),
+                Some(destination),
+                authority,
+                *amount,
+                None,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::Minted {
+            mint,
+            destination,
+            authority,
+            amount,
+            total_supply,
+            timestamp,
+        } => {
+            insert_transfer(
+                tx,
+                signature,
+                index,
+                "mint",
+                mint,
+                None,
+                Some(destination),
+                authority,
+                *amount,
+                Some(*total_supply),
+                *timestamp,
+            )?;
+        }
+        SoulEvent::Burned {
+            mint,
+            source,
+            authority,
+            amount,
+            total_supply,
+            timestamp,
+        } => {
+            insert_transfer(
+                tx,
+                signature,
+                index,
+                "burn",
+                mint,
+                Some(source),
+                None,
+                authority,
+                *amount,
+                Some(*total_supply),
+                *timestamp,
+            )?;
+        }
+        SoulEvent::VestingReleased {
+            vesting_account,
+            beneficiary,
+            amount,
+            released_amount,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO vesting_releases VALUES (?1, ?2, 'release', ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    vesting_account.to_string(),
+                    beneficiary.to_string(),
+                    amount,
+                    released_amount,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::VestingRevoked {
+            vesting_account,
+            recovery_account,
+            amount,
+            timestamp,
+            ..
+        } => {
+            tx.execute(
+                "INSERT INTO vesting_releases VALUES (?1, ?2, 'revoke', ?3, ?4, ?5, NULL, ?6)",
+                params![
+                    signature,
+                    index,
+                    vesting_account.to_string(),
+                    recovery_account.to_string(),
+                    amount,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::ReportSubmitted {
+            report,
+            metrics_account,
+            authority,
+            proof_hash,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO impact_reports (address, metrics_account, authority, proof_hash, status, submitted_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT (address) DO UPDATE SET authority = ?3",
+                params![
+                    report.to_string(),
+                    metrics_account.to_string(),
+                    authority.to_string(),
+                    hex(proof_hash),
+                    status_name(&VerificationStatus::Pending),
+                    timestamp,
+                ],
+            )?;
+        }
+        SoulEvent::ReportVerified {
+            report,
+            verifier,
+            approved,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO verifications VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    signature,
+                    index,
+                    report.to_string(),
+                    verifier.to_string(),
+                    approved,
+                    timestamp
+                ],
+            )?;
+            let status = if *approved {
+                VerificationStatus::Verified
+            } else {
+                VerificationStatus::Rejected
+            };
+            update_report_status(tx, report, &status, Some(verifier))?;
+        }
+        SoulEvent::DisputeOpened {
+            dispute,
+            report,
+            challenger,
+            stake_amount,
+            evidence_hash,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT OR REPLACE INTO disputes (address, report, challenger, stake_amount, evidence_hash, opened_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![dispute.to_string(), report.to_string(), challenger.to_string(), stake_amount, hex(evidence_hash), timestamp],
+            )?;
+            update_report_status(tx, report, &VerificationStatus::Disputed, None)?;
+        }
+        SoulEvent::DisputeResolved {
+            dispute,
+            report,
+            authority,
+            in_favor_of_report,
+            recipient,
+            timestamp,
+            ..
+        } => {
+            tx.execute(
+                "UPDATE disputes SET resolved_by = ?2, in_favor_of_report = ?3, recipient = ?4, resolved_at = ?5
+                 WHERE address = ?1",
+                params![dispute.to_string(), authority.to_string(), in_favor_of_report, recipient.to_string(), timestamp],
+            )?;
+            let status = if *in_favor_of_report {
+                VerificationStatus::Verified
+            } else {
+                VerificationStatus::Rejected
+            };
+            update_report_status(tx, report, &status, None)?;
+        }
+        SoulEvent::Staked {
+            pool,
+            staker,
+            amount,
+            lst_amount,
+            timestamp,
+            ..
+        } => {
+            insert_stake(
+                tx,
+                signature,
+                index,
+                "stake",
+                pool,
+                staker,
+                *amount,
+                Some(*lst_amount),
+                None,
+                None,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::UnstakeRequested {
+            pool,
+            staker,
+            amount,
+            unlock_time,
+            timestamp,
+        } => {
+            insert_stake(
+                tx,
+                signature,
+                index,
+                "unstake_request",
+                pool,
+                staker,
+                *amount,
+                None,
+                None,
+                Some(*unlock_time),
+                *timestamp,
+            )?;
+        }
+        SoulEvent::Unstaked {
+            pool,
+            staker,
+            amount,
+            lst_amount,
+            fee,
+            timestamp,
+            ..
+        } => {
+            insert_stake(
+                tx,
+                signature,
+                index,
+                "unstake",
+                pool,
+                staker,
+                *amount,
+                Some(*lst_amount),
+                Some(*fee),
+                None,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::RewardsClaimed {
+            pool,
+            staker,
+            amount,
+            timestamp,
+        } => {
+            insert_stake(
+                tx, signature, index, "claim", pool, staker, *amount, None, None, None, *timestamp,
+            )?;
+        }
+        SoulEvent::QuestCompleted {
+            player,
+            quest_id,
+            experience,
+            eco_tokens,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO quest_completions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    player.to_string(),
+                    quest_id,
+                    experience,
+                    eco_tokens,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::ItemsTraded {
+            player,
+            target,
+            offered_items,
+            requested_items,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO item_trades VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    player.to_string(),
+                    target.to_string(),
+                    serde_json::to_string(offered_items).unwrap_or_default(),
+                    serde_json::to_string(requested_items).unwrap_or_default(),
+                    timestamp,
+                ],
+            )?;
+        }
+        SoulEvent::FeesHarvested {
+            mint,
+            treasury,
+            amount,
+            timestamp,
+        } => {
+            // Anyone may harvest, so the treasury stands in as the authority
+            insert_transfer(
+                tx,
+                signature,
+                index,
+                "fee_harvest",
+                mint,
+                None,
+                Some(treasury),
+                treasury,
+                *amount,
+                None,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::ProposalCreated {
+            multisig,
+            proposal,
+            proposer,
+            timestamp,
+            ..
+        } => {
+            insert_multisig_action(
+                tx,
+                signature,
+                index,
+                "propose",
+                multisig,
+                proposal,
+                Some(proposer),
+                None,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::ProposalApproved {
+            multisig,
+            proposal,
+            signer,
+            approvals,
+            timestamp,
+        } => {
+            insert_multisig_action(
+                tx,
+                signature,
+                index,
+                "approve",
+                multisig,
+                proposal,
+                Some(signer),
+                Some(*approvals),
+                *timestamp,
+            )?;
+        }
+        SoulEvent::ProposalExecuted {
+            multisig,
+            proposal,
+            timestamp,
+        } => {
+            insert_multisig_action(
+                tx, signature, index, "execute", multisig, proposal, None, None, *timestamp,
+            )?;
+        }
+        SoulEvent::Emitted {
+            mint,
+            epoch,
+            staking,
+            game_rewards,
+            verifier_fund,
+            emitted,
+            total_supply,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO emissions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    signature,
+                    index,
+                    mint.to_string(),
+                    epoch,
+                    staking,
+                    game_rewards,
+                    verifier_fund,
+                    emitted,
+                    total_supply,
+                    timestamp,
+                ],
+            )?;
+        }
+        SoulEvent::RoleGranted {
+            member,
+            role,
+            admin,
+            timestamp,
+        } => {
+            insert_access_change(
+                tx,
+                signature,
+                index,
+                "grant",
+                None,
+                member,
+                Some(role),
+                admin,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::RoleRevoked {
+            member,
+            role,
+            admin,
+            timestamp,
+        } => {
+            insert_access_change(
+                tx,
+                signature,
+                index,
+                "revoke",
+                None,
+                member,
+                Some(role),
+                admin,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::AuthorityTransferred {
+            account,
+            previous_authority,
+            new_authority,
+            timestamp,
+        } => {
+            // The new authority is the member, the one it replaced the authority
+            insert_access_change(
+                tx,
+                signature,
+                index,
+                "authority",
+                Some(account),
+                new_authority,
+                None,
+                previous_authority,
+                *timestamp,
+            )?;
+        }
+        SoulEvent::PauseSet {
+            module,
+            paused,
+            guardian,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO pauses VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    signature,
+                    index,
+                    module_name(module),
+                    paused,
+                    guardian.to_string(),
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::ComplianceHold {
+            token_account,
+            mint,
+            action,
+            reason_hash,
+            officer,
+            timestamp,
+        } => {
+            let action = match action {
+                ComplianceAction::Freeze => "freeze",
+                ComplianceAction::Thaw => "thaw",
+            };
+            tx.execute(
+                "INSERT INTO compliance_holds VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    signature,
+                    index,
+                    action,
+                    token_account.to_string(),
+                    mint.to_string(),
+                    hex(reason_hash),
+                    officer.to_string(),
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::DistributorCreated {
+            distributor,
+            admin,
+            max_total_claim,
+            timestamp,
+            ..
+        } => {
+            tx.execute(
+                "INSERT INTO airdrops VALUES (?1, ?2, 'create', ?3, ?4, NULL, ?5, ?6)",
+                params![
+                    signature,
+                    index,
+                    distributor.to_string(),
+                    admin.to_string(),
+                    max_total_claim,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::AirdropClaimed {
+            distributor,
+            claimant,
+            index: leaf_index,
+            amount,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO airdrops VALUES (?1, ?2, 'claim', ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    distributor.to_string(),
+                    claimant.to_string(),
+                    leaf_index,
+                    amount,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::AirdropClawedBack {
+            distributor,
+            destination,
+            amount,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO airdrops VALUES (?1, ?2, 'clawback', ?3, ?4, NULL, ?5, ?6)",
+                params![
+                    signature,
+                    index,
+                    distributor.to_string(),
+                    destination.to_string(),
+                    amount,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::Retired {
+            certificate,
+            owner,
+            source,
+            mint,
+            category,
+            amount,
+            beneficiary_hash,
+            reports,
+            timestamp,
+        } => {
+            // A retirement burns, so it also counts against the mint's supply
+            insert_transfer(
+                tx,
+                signature,
+                index,
+                "retire",
+                mint,
+                Some(source),
+                None,
+                owner,
+                *amount,
+                None,
+                *timestamp,
+            )?;
+            let reports: Vec<_> = reports.iter().map(Pubkey::to_string).collect();
+            tx.execute(
+                "INSERT INTO retirements VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                params![
+                    signature,
+                    index,
+                    certificate.to_string(),
+                    owner.to_string(),
+                    mint.to_string(),
+                    category_name(category),
+                    amount,
+                    hex(beneficiary_hash),
+                    serde_json::to_string(&reports).unwrap_or_default(),
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::AccountClosed {
+            module,
+            account,
+            payer,
+            lamports,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO closed_accounts VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    module_name(module),
+                    account.to_string(),
+                    payer.to_string(),
+                    lamports,
+                    timestamp
+                ],
+            )?;
+        }
+        SoulEvent::EmergencyWithdrawn {
+            pool,
+            authority,
+            destination,
+            amount,
+            timestamp,
+        } => {
+            tx.execute(
+                "INSERT INTO emergency_withdrawals VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    signature,
+                    index,
+                    pool.to_string(),
+                    authority.to_string(),
+                    destination.to_string(),
+                    amount,
+                    timestamp
+                ],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_transfer(
+    tx: &Transaction,
+    signature: &str,
+    index: usize,
+    kind: &str,
+    mint: &Pubkey,
+    source: Option<&Pubkey>,
+    destination: Option<&Pubkey>,
+    authority: &Pubkey,
+    amount: u64,
+    total_supply: Option<u64>,
+    timestamp: i64,
+) -> Result<(), IndexerError> {
+    tx.execute(
+        "INSERT INTO transfers VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            signature,
+            index,
+            kind,
+            mint.to_string(),
+            source.map(Pubkey::to_string),
+            destination.map(Pubkey::to_string),
+            authority.to_string(),
+            amount,
+            total_supply,
+            timestamp,
+        ],
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_stake(
+    tx: &Transaction,
+    signature: &str,
+    index: usize,
+    kind: &str,
+    pool: &Pubkey,
+    staker: &Pubkey,
+    amount: u64,
+    lst_amount: Option<u64>,
+    fee: Option<u64>,
+    unlock_time: Option<i64>,
+    timestamp: i64,
+) -> Result<(), IndexerError> {
+    tx.execute(
+        "INSERT INTO stakes VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        params![
+            signature,
+            index,
+            kind,
+            pool.to_string(),
+            staker.to_string(),
+            amount,
+            lst_amount,
+            fee,
+            unlock_time,
+            timestamp,
+        ],
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_multisig_action(
+    tx: &Transaction,
+    signature: &str,
+    index: usize,
+    kind: &str,
+    multisig: &Pubkey,
+    proposal: &Pubkey,
+    signer: Option<&Pubkey>,
+    approvals: Option<u32>,
+    timestamp: i64,
+) -> Result<(), IndexerError> {
+    tx.execute(
+        "INSERT INTO multisig_actions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            signature,
+            index,
+            kind,
+            multisig.to_string(),
+            proposal.to_string(),
+            signer.map(Pubkey::to_string),
+            approvals,
+            timestamp,
+        ],
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn insert_access_change(
+    tx: &Transaction,
+    signature: &str,
+    index: usize,
+    kind: &str,
+    account: Option<&Pubkey>,
+    member: &Pubkey,
+    role: Option<&Role>,
+    authority: &Pubkey,
+    timestamp: i64,
+) -> Result<(), IndexerError> {
+    tx.execute(
+        "INSERT INTO access_changes VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            signature,
+            index,
+            kind,
+            account.map(Pubkey::to_string),
+            member.to_string(),
+            role.map(role_name),
+            authority.to_string(),
+            timestamp,
+        ],
+    )?;
+    Ok(())
+}
+
+fn update_report_status(
+    tx: &Transaction,
+    report: &Pubkey,
+    status: &VerificationStatus,
+    verifier: Option<&Pubkey>,
+) -> Result<(), IndexerError> {
+    tx.execute(
+        "UPDATE impact_reports SET status = ?2, verifier = COALESCE(?3, verifier) WHERE address = ?1",
+        params![report.to_string(), status_name(status), verifier.map(Pubkey::to_string)],
+    )?;
+    Ok(())
+}
+
+/// Name stored in the `status` column of `impact_reports`
+pub fn status_name(status: &VerificationStatus) -> &'static str {
+    match status {
+        VerificationStatus::Pending => "pending",
+        VerificationStatus::Verified => "verified",
+        VerificationStatus::Rejected => "rejected",
+        VerificationStatus::Disputed => "disputed",
+    }
+}
+
+/// Name stored in the `role` column of `access_changes`
+pub fn role_name(role: &Role) -> &'static str {
+    match role {
+        Role::Admin => "admin",
+        Role::Minter => "minter",
+        Role::PoolManager => "pool_manager",
+        Role::GameMaster => "game_master",
+        Role::DisputeResolver => "dispute_resolver",
+        Role::Guardian => "guardian",
+        Role::Compliance => "compliance",
+    }
+}
+
+/// Name stored in the `category` column of `retirements`
+pub fn category_name(category: &LSTType) -> &'static str {
+    match category {
+        LSTType::Air => "air",
+        LSTType::Forest => "forest",
+        LSTType::Land => "land",
+        LSTType::Water => "water",
+        LSTType::Ocean => "ocean",
+        LSTType::Wildlife => "wildlife",
+        LSTType::Energy => "energy",
+        LSTType::Life => "life",
+        LSTType::Sanctuary => "sanctuary",
+        LSTType::PES => "pes",
+        LSTType::PPP => "ppp",
+        LSTType::SSS => "sss",
+    }
+}
+
+/// Name stored in the `module` column of `pauses`
+pub fn module_name(module: &ModuleTag) -> &'static str {
+    match module {
+        ModuleTag::Token => "token",
+        ModuleTag::Vesting => "vesting",
+        ModuleTag::Impact => "impact",
+        ModuleTag::LST => "lst",
+        ModuleTag::Game => "game",
+        ModuleTag::Multisig => "multisig",
+        ModuleTag::Access => "access",
+        ModuleTag::Distributor => "distributor",
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,25 @@
+use solana_client::client_error::ClientError;
+use soul_sanctuary::event::decoder::EventDecodeError;
+use thiserror::Error;
+
+/// Errors that stop the indexer
+#[derive(Error, Debug)]
+pub enum IndexerError {
+    #[error("RPC request failed: {0}")]
+    Rpc(Box<ClientError>),
+    #[error("Database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Could not decode events in {signature}: {source}")]
+    Event {
+        signature: String,
+        source: EventDecodeError,
+    },
+    #[error("Invalid signature {0}")]
+    InvalidSignature(String),
+}
+
+impl From<ClientError> for IndexerError {
+    fn from(error: ClientError) -> Self {
+        Self::Rpc(Box::new(error))
+    }
+}
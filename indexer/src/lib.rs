@@ -0,0 +1,12 @@
+//! Indexer for SOuL Sanctuary program history.
+//!
+//! [`sync::Indexer`] reads transactions and program accounts from a
+//! [`source::Source`], decodes the program's events and account states, and
+//! writes them to the SQLite tables kept by [`db::Database`].
+
+pub mod db;
+pub mod error;
+pub mod source;
+pub mod sync;
+
+pub use error::IndexerError;
@@ -0,0 +1,141 @@
+use std::{process::ExitCode, str::FromStr, thread, time::Duration};
+
+use clap::{Parser, Subcommand};
+use solana_program::pubkey::Pubkey;
+use soul_indexer::{
+    db::{BoundingBox, Database},
+    source::RpcSource,
+    sync::Indexer,
+    IndexerError,
+};
+
+#[derive(Parser)]
+#[command(about = "Index SOuL Sanctuary program history into SQLite")]
+struct Cli {
+    /// SQLite database file
+    #[arg(long, default_value = "soul-index.sqlite")]
+    db: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Read new transactions and current accounts from an RPC node
+    Sync {
+        #[arg(long, value_parser = Pubkey::from_str)]
+        program_id: Pubkey,
+
+        #[arg(long, default_value = "http://127.0.0.1:8899")]
+        rpc_url: String,
+
+        /// Keep polling instead of exiting after one pass
+        #[arg(long)]
+        follow: bool,
+
+        /// Seconds between passes with --follow
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+
+    /// Print the reports inside a bounding box, in degrees, as JSON
+    Reports {
+        #[arg(long, allow_negative_numbers = true)]
+        min_lat: f64,
+        #[arg(long, allow_negative_numbers = true)]
+        min_lon: f64,
+        #[arg(long, allow_negative_numbers = true)]
+        max_lat: f64,
+        #[arg(long, allow_negative_numbers = true)]
+        max_lon: f64,
+
+        /// Only reports with this status: pending, verified, rejected or disputed
+        #[arg(long)]
+        status: Option<String>,
+    },
+
+    /// Print the players with the most experience as JSON
+    Leaderboard {
+        #[arg(long, default_value_t = 10)]
+        limit: u32,
+    },
+
+    /// Print a wallet's stakes, unstakes and reward claims as JSON
+    StakingHistory {
+        #[arg(value_parser = Pubkey::from_str)]
+        wallet: Pubkey,
+    },
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), IndexerError> {
+    let db = Database::open(&cli.db)?;
+
+    match cli.command {
+        Command::Sync {
+            program_id,
+            rpc_url,
+            follow,
+            interval,
+        } => {
+            let mut indexer = Indexer::new(program_id, RpcSource::new(&rpc_url), db);
+            loop {
+                let stats = indexer.sync()?;
+                eprintln!(
+                    "indexed {} transactions, {} events, {} accounts",
+                    stats.transactions, stats.events, stats.accounts
+                );
+                if !follow {
+                    return Ok(());
+                }
+                thread::sleep(Duration::from_secs(interval));
+            }
+        }
+        Command::Reports {
+            min_lat,
+            min_lon,
+            max_lat,
+            max_lon,
+            status,
+        } => {
+            let bounds = BoundingBox {
+                min_latitude: microdegrees(min_lat),
+                min_longitude: microdegrees(min_lon),
+                max_latitude: microdegrees(max_lat),
+                max_longitude: microdegrees(max_lon),
+            };
+            print_json(&db.reports_in_box(bounds, status.as_deref())?);
+            Ok(())
+        }
+        Command::Leaderboard { limit } => {
+            print_json(&db.leaderboard(limit)?);
+            Ok(())
+        }
+        Command::StakingHistory { wallet } => {
+            print_json(&db.staking_history(&wallet)?);
+            Ok(())
+        }
+    }
+}
+
+/// Degrees to the millionths of a degree stored in `GeoLocation`
+fn microdegrees(degrees: f64) -> i64 {
+    (degrees * 1_000_000.0).round() as i64
+}
+
+fn print_json<T: serde::Serialize>(value: &T) {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(value).expect("rows serialize to JSON")
+    );
+}
@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use solana_client::{
+    rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient},
+    rpc_config::RpcTransactionConfig,
+};
+use solana_program::pubkey::Pubkey;
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::error::IndexerError;
+
+/// A transaction that invoked the program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransactionRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Whether the transaction failed, in which case its events were rolled back
+    pub failed: bool,
+    pub log_messages: Vec<String>,
+}
+
+/// Accounts owned by the program, as read at `slot`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProgramAccounts {
+    pub slot: u64,
+    pub accounts: Vec<(Pubkey, Vec<u8>)>,
+}
+
+/// Where the indexer reads program history from
+pub trait Source {
+    /// Signatures of transactions that invoked `program_id` after `until`, oldest first
+    fn signatures_since(
+        &self,
+        program_id: &Pubkey,
+        until: Option<&str>,
+    ) -> Result<Vec<String>, IndexerError>;
+
+    fn transaction(&self, signature: &str) -> Result<TransactionRecord, IndexerError>;
+
+    /// Every account owned by `program_id`
+    fn program_accounts(&self, program_id: &Pubkey) -> Result<ProgramAccounts, IndexerError>;
+}
+
+/// Reads from a validator's JSON RPC endpoint
+pub struct RpcSource {
+    client: RpcClient,
+}
+
+impl RpcSource {
+    /// Signatures requested per `getSignaturesForAddress` page
+    const PAGE_SIZE: usize = 1_000;
+
+    pub fn new(url: &str) -> Self {
+        Self {
+            client: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+        }
+    }
+}
+
+impl Source for RpcSource {
+    fn signatures_since(
+        &self,
+        program_id: &Pubkey,
+        until: Option<&str>,
+    ) -> Result<Vec<String>, IndexerError> {
+        let until = until.map(parse_signature).transpose()?;
+        let mut signatures = Vec::new();
+        let mut before = None;
+
+        loop {
+            let page = self.client.get_signatures_for_address_with_config(
+                program_id,
+                GetConfirmedSignaturesForAddress2Config {
+                    before,
+                    until,
+                    limit: Some(Self::PAGE_SIZE),
+                    commitment: Some(self.client.commitment()),
+                },
+            )?;
+            let Some(last) = page.last() else {
+                break;
+            };
+            before = Some(parse_signature(&last.signature)?);
+            let full_page = page.len() == Self::PAGE_SIZE;
+            signatures.extend(page.into_iter().map(|status| status.signature));
+            if !full_page {
+                break;
+            }
+        }
+
+        // The RPC lists newest first
+        signatures.reverse();
+        Ok(signatures)
+    }
+
+    fn transaction(&self, signature: &str) -> Result<TransactionRecord, IndexerError> {
+        let transaction = self.client.get_transaction_with_config(
+            &parse_signature(signature)?,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(self.client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )?;
+        let meta = transaction.transaction.meta;
+        Ok(TransactionRecord {
+            signature: signature.to_string(),
+            slot: transaction.slot,
+            block_time: transaction.block_time,
+            failed: meta.as_ref().is_some_and(|meta| meta.err.is_some()),
+            log_messages: meta
+                .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+                .unwrap_or_default(),
+        })
+    }
+
+    fn program_accounts(&self, program_id: &Pubkey) -> Result<ProgramAccounts, IndexerError> {
+        let slot = self.client.get_slot()?;
+        let accounts = self.client.get_program_accounts(program_id)?;
+        Ok(ProgramAccounts {
+            slot,
+            accounts: accounts
+                .into_iter()
+                .map(|(address, account)| (address, account.data))
+                .collect(),
+        })
+    }
+}
+
+fn parse_signature(signature: &str) -> Result<Signature, IndexerError> {
+    Signature::from_str(signature)
+        .map_err(|_| IndexerError::InvalidSignature(signature.to_string()))
+}
@@ -0,0 +1,74 @@
+use solana_program::pubkey::Pubkey;
+use soul_sanctuary::event::decoder::decode_logs;
+
+use crate::{db::Database, error::IndexerError, source::Source};
+
+/// What one [`Indexer::sync`] pass wrote
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SyncStats {
+    pub transactions: usize,
+    pub events: usize,
+    pub accounts: usize,
+}
+
+/// Copies program history from a [`Source`] into a [`Database`]
+pub struct Indexer<S: Source> {
+    program_id: Pubkey,
+    source: S,
+    db: Database,
+}
+
+impl<S: Source> Indexer<S> {
+    pub fn new(program_id: Pubkey, source: S, db: Database) -> Self {
+        Self {
+            program_id,
+            source,
+            db,
+        }
+    }
+
+    pub fn db(&self) -> &Database {
+        &self.db
+    }
+
+    /// Index transactions since the last sync, then refresh account state
+    pub fn sync(&mut self) -> Result<SyncStats, IndexerError> {
+        let mut stats = SyncStats::default();
+
+        let until = self.db.latest_signature()?;
+        for signature in self
+            .source
+            .signatures_since(&self.program_id, until.as_deref())?
+        {
+            let transaction = self.source.transaction(&signature)?;
+            let events = if transaction.failed {
+                Vec::new()
+            } else {
+                decode_logs(&self.program_id, &transaction.log_messages).map_err(|source| {
+                    IndexerError::Event {
+                        signature: signature.clone(),
+                        source,
+                    }
+                })?
+            };
+            if self.db.record_transaction(
+                &signature,
+                transaction.slot,
+                transaction.block_time,
+                &events,
+            )? {
+                stats.transactions += 1;
+                stats.events += events.len();
+            }
+        }
+
+        let program_accounts = self.source.program_accounts(&self.program_id)?;
+        for (address, data) in &program_accounts.accounts {
+            self.db
+                .record_account(address, program_accounts.slot, data)?;
+        }
+        stats.accounts = program_accounts.accounts.len();
+
+        Ok(stats)
+    }
+}
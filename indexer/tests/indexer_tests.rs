@@ -0,0 +1,337 @@
+use std::cell::RefCell;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use solana_program::pubkey::Pubkey;
+use soul_indexer::{
+    db::{BoundingBox, Database, StakeRow},
+    source::{ProgramAccounts, Source, TransactionRecord},
+    sync::{Indexer, SyncStats},
+    IndexerError,
+};
+use soul_sanctuary::{
+    account::AccountState,
+    event::SoulEvent,
+    game::PlayerState,
+    impact::{GeoLocation, ImpactMetrics, ImpactReport, VerificationStatus},
+    lst::LSTType,
+};
+
+/// In-memory chain with the transactions and accounts a test adds
+#[derive(Default)]
+struct FakeSource {
+    transactions: Vec<TransactionRecord>,
+    accounts: RefCell<Vec<(Pubkey, Vec<u8>)>>,
+    requested_until: RefCell<Vec<Option<String>>>,
+}
+
+impl FakeSource {
+    fn add_transaction(
+        &mut self,
+        program_id: &Pubkey,
+        events: &[SoulEvent],
+        failed: bool,
+    ) -> String {
+        let signature = format!("sig{}", self.transactions.len());
+        let mut log_messages = vec![format!("Program {} invoke [1]", program_id)];
+        for event in events {
+            log_messages.push(format!(
+                "Program data: {} {}",
+                STANDARD.encode(SoulEvent::TAG),
+                STANDARD.encode(event.try_to_vec().unwrap())
+            ));
+        }
+        log_messages.push(format!("Program {} success", program_id));
+
+        self.transactions.push(TransactionRecord {
+            signature: signature.clone(),
+            slot: 10 + self.transactions.len() as u64,
+            block_time: Some(1_700_000_000),
+            failed,
+            log_messages,
+        });
+        signature
+    }
+}
+
+impl Source for &FakeSource {
+    fn signatures_since(
+        &self,
+        _program_id: &Pubkey,
+        until: Option<&str>,
+    ) -> Result<Vec<String>, IndexerError> {
+        self.requested_until
+            .borrow_mut()
+            .push(until.map(str::to_string));
+        let start = until
+            .and_then(|until| {
+                self.transactions
+                    .iter()
+                    .position(|tx| tx.signature == until)
+            })
+            .map_or(0, |index| index + 1);
+        Ok(self.transactions[start..]
+            .iter()
+            .map(|tx| tx.signature.clone())
+            .collect())
+    }
+
+    fn transaction(&self, signature: &str) -> Result<TransactionRecord, IndexerError> {
+        Ok(self
+            .transactions
+            .iter()
+            .find(|tx| tx.signature == signature)
+            .unwrap()
+            .clone())
+    }
+
+    fn program_accounts(&self, _program_id: &Pubkey) -> Result<ProgramAccounts, IndexerError> {
+        Ok(ProgramAccounts {
+            slot: 100,
+            accounts: self.accounts.borrow().clone(),
+        })
+    }
+}
+
+fn report_account(latitude: i64, longitude: i64, status: VerificationStatus) -> Vec<u8> {
+    let report = ImpactReport {
+        metrics_account: Pubkey::new_unique(),
+        timestamp: 1_700_000_000,
+        location: GeoLocation {
+            latitude,
+            longitude,
+            altitude: 0,
+        },
+        metrics: ImpactMetrics {
+            is_initialized: true,
+            authority: Pubkey::new_unique(),
+            last_update: 0,
+            forest_area: 10_000,
+            carbon_offset: 0,
+            water_saved: 0,
+            biodiversity_score: 0,
+            energy_saved: 0,
+            waste_recycled: 0,
+            impact_score: 0,
+            bump: 0,
+        },
+        verifier: Pubkey::default(),
+        verification_status: status,
+        proof_hash: [1; 32],
+        bump: 255,
+        verified_at: None,
+        category: Some(LSTType::Forest),
+    };
+    let mut data = ImpactReport::DISCRIMINATOR.to_vec();
+    data.push(ImpactReport::VERSION);
+    data.extend(report.try_to_vec().unwrap());
+    data
+}
+
+fn everywhere() -> BoundingBox {
+    BoundingBox {
+        min_latitude: -90_000_000,
+        min_longitude: -180_000_000,
+        max_latitude: 90_000_000,
+        max_longitude: 180_000_000,
+    }
+}
+
+#[test]
+fn test_sync_stores_staking_history_once() {
+    let program_id = Pubkey::new_unique();
+    let staker = Pubkey::new_unique();
+    let pool = Pubkey::new_unique();
+    let mut source = FakeSource::default();
+
+    let stake = source.add_transaction(
+        &program_id,
+        &[SoulEvent::Staked {
+            pool,
+            staker,
+            amount: 400,
+            lst_amount: 400,
+            total_staked: 400,
+            timestamp: 1,
+        }],
+        false,
+    );
+    // Events from a failed transaction were rolled back on chain
+    source.add_transaction(
+        &program_id,
+        &[SoulEvent::RewardsClaimed {
+            pool,
+            staker,
+            amount: 9,
+            timestamp: 2,
+        }],
+        true,
+    );
+
+    let mut indexer = Indexer::new(program_id, &source, Database::open_in_memory().unwrap());
+    assert_eq!(
+        indexer.sync().unwrap(),
+        SyncStats {
+            transactions: 2,
+            events: 1,
+            accounts: 0,
+        }
+    );
+    assert_eq!(
+        indexer.db().staking_history(&staker).unwrap(),
+        vec![StakeRow {
+            signature: stake,
+            slot: 10,
+            kind: "stake".to_string(),
+            pool: pool.to_string(),
+            amount: 400,
+            lst_amount: Some(400),
+            fee: None,
+            unlock_time: None,
+            timestamp: 1,
+        }]
+    );
+
+    // The next pass starts after the newest indexed signature
+    assert_eq!(indexer.sync().unwrap().transactions, 0);
+    assert_eq!(
+        *source.requested_until.borrow(),
+        vec![None, Some("sig1".to_string())]
+    );
+    assert!(indexer
+        .db()
+        .staking_history(&Pubkey::new_unique())
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_report_status_follows_events_and_accounts() {
+    let program_id = Pubkey::new_unique();
+    let report = Pubkey::new_unique();
+    let verifier = Pubkey::new_unique();
+    let mut source = FakeSource::default();
+    source.add_transaction(
+        &program_id,
+        &[SoulEvent::ReportSubmitted {
+            report,
+            metrics_account: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            proof_hash: [1; 32],
+            timestamp: 5,
+        }],
+        false,
+    );
+    source.add_transaction(
+        &program_id,
+        &[SoulEvent::ReportVerified {
+            report,
+            verifier,
+            approved: true,
+            timestamp: 6,
+        }],
+        false,
+    );
+
+    let mut indexer = Indexer::new(program_id, &source, Database::open_in_memory().unwrap());
+    indexer.sync().unwrap();
+
+    // Without the account the location is unknown
+    assert!(indexer
+        .db()
+        .reports_in_box(everywhere(), None)
+        .unwrap()
+        .is_empty());
+
+    source.accounts.borrow_mut().push((
+        report,
+        report_account(45_500_000, -122_600_000, VerificationStatus::Verified),
+    ));
+    indexer.sync().unwrap();
+
+    let rows = indexer
+        .db()
+        .reports_in_box(everywhere(), Some("verified"))
+        .unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].address, report.to_string());
+    assert_eq!(rows[0].verifier, Some(verifier.to_string()));
+    assert_eq!(rows[0].latitude, 45_500_000);
+    assert!(indexer
+        .db()
+        .reports_in_box(everywhere(), Some("pending"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_reports_in_box_filters_by_location() {
+    let mut db = Database::open_in_memory().unwrap();
+    let portland = Pubkey::new_unique();
+    let nairobi = Pubkey::new_unique();
+    db.record_account(
+        &portland,
+        1,
+        &report_account(45_500_000, -122_600_000, VerificationStatus::Verified),
+    )
+    .unwrap();
+    db.record_account(
+        &nairobi,
+        1,
+        &report_account(-1_300_000, 36_800_000, VerificationStatus::Pending),
+    )
+    .unwrap();
+    // Accounts of other types are skipped
+    db.record_account(&Pubkey::new_unique(), 1, &[0; 64])
+        .unwrap();
+
+    let pacific_northwest = BoundingBox {
+        min_latitude: 42_000_000,
+        min_longitude: -125_000_000,
+        max_latitude: 49_000_000,
+        max_longitude: -116_000_000,
+    };
+    let rows = db.reports_in_box(pacific_northwest, None).unwrap();
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].address, portland.to_string());
+    assert_eq!(rows[0].status, "verified");
+    assert_eq!(rows[0].impact_score, Some(30));
+
+    assert_eq!(db.reports_in_box(everywhere(), None).unwrap().len(), 2);
+    assert_eq!(
+        db.reports_in_box(everywhere(), Some("pending")).unwrap()[0].address,
+        nairobi.to_string()
+    );
+}
+
+#[test]
+fn test_leaderboard_keeps_the_newest_player_state() {
+    let mut db = Database::open_in_memory().unwrap();
+    let player_account = |owner: Pubkey, experience: u64, level: u16| {
+        let mut player = PlayerState::new(owner, 255);
+        player.experience = experience;
+        player.level = level;
+        let mut data = PlayerState::DISCRIMINATOR.to_vec();
+        data.push(PlayerState::VERSION);
+        data.extend(player.try_to_vec().unwrap());
+        data
+    };
+
+    let leader = Pubkey::new_unique();
+    let runner_up = Pubkey::new_unique();
+    db.record_account(&Pubkey::new_unique(), 5, &player_account(leader, 1_000, 3))
+        .unwrap();
+    db.record_account(&Pubkey::new_unique(), 5, &player_account(runner_up, 500, 2))
+        .unwrap();
+    // A stale read does not overwrite newer progress
+    db.record_account(&Pubkey::new_unique(), 4, &player_account(leader, 100, 1))
+        .unwrap();
+
+    let rows = db.leaderboard(10).unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].player, leader.to_string());
+    assert_eq!(rows[0].experience, 1_000);
+    assert_eq!(rows[0].level, 3);
+    assert_eq!(rows[1].player, runner_up.to_string());
+    assert_eq!(db.leaderboard(1).unwrap().len(), 1);
+}
@@ -0,0 +1,315 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    bpf_loader_upgradeable,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use crate::{error::module_error, instruction::ModuleTag, state::SOuLToken};
+use super::state::{AuthorityTransfer, CanonicalMint, CircuitBreaker, ProgramConfig, Role, Roles};
+
+/// Privileged instructions check the signer against the authority recorded
+/// on the account they act on. Passing the roles account after their own
+/// accounts also lets a holder of the instruction's role sign in its place.
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum AccessInstruction {
+    /// Create the roles account. Only the program's upgrade authority may
+    /// create it, and it becomes the admin.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` The upgrade authority, pays rent
+    /// 1. `[writable]` The roles PDA to create (`["roles"]`)
+    /// 2. `[]` The program's ProgramData account
+    /// 3. `[]` The system program
+    InitializeRoles,
+
+    /// Grant `role` to `member`
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The admin or a holder of the Admin role
+    /// 1. `[writable]` The roles account
+    GrantRole {
+        member: Pubkey,
+        role: Role,
+    },
+
+    /// Take `role` away from `member`
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The admin or a holder of the Admin role
+    /// 1. `[writable]` The roles account
+    RevokeRole {
+        member: Pubkey,
+        role: Role,
+    },
+
+    /// Propose a new authority for a SOuL token, LST pool, game state,
+    /// impact metrics, token metadata or the roles account. Proposing again
+    /// replaces the pending authority. An Admin role holder may propose in
+    /// place of the current authority when the roles account is passed last.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` The current authority, pays rent
+    /// 1. `[]` The account whose authority changes hands
+    /// 2. `[writable]` The transfer PDA (`["authority_transfer", account]`)
+    /// 3. `[]` The system program
+    ProposeAuthority {
+        new_authority: Pubkey,
+    },
+
+    /// Accept a proposed authority, closing the transfer account
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The proposed authority
+    /// 1. `[writable]` The account whose authority changes hands
+    /// 2. `[writable]` The transfer PDA
+    /// 3. `[writable]` The payer of the transfer account, refunded its rent
+    AcceptAuthority,
+
+    /// Pause or resume a module. The program config is created on first use.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` A Guardian, pays rent
+    /// 1. `[writable]` The program config PDA (`["config"]`)
+    /// 2. `[]` The roles account
+    /// 3. `[]` The system program
+    SetPaused {
+        module: ModuleTag,
+        paused: bool,
+    },
+
+    /// Set how much a module may let out within a window of slots. The
+    /// breaker is created on first use.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` An Admin, pays rent
+    /// 1. `[writable]` The breaker PDA (`["circuit_breaker", module]`)
+    /// 2. `[]` The roles account
+    /// 3. `[]` The system program
+    ConfigureCircuitBreaker {
+        module: ModuleTag,
+        window_slots: u64,
+        max_outflow: u64,
+    },
+
+    /// Pin the canonical SOuL mint. It can be pinned only once.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` An Admin, pays rent
+    /// 1. `[writable]` The canonical mint PDA to create (`["canonical_mint"]`)
+    /// 2. `[]` The SOuL token config (`["soul_token", mint]`) of the mint
+    /// 3. `[]` The roles account
+    /// 4. `[]` The system program
+    PinSoulMint,
+}
+
+impl AccessInstruction {
+    pub fn initialize_roles(
+        program_id: &Pubkey,
+        admin: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (roles, _) = Roles::find_address(program_id);
+        let (program_data, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*admin, true),
+                AccountMeta::new(roles, false),
+                AccountMeta::new_readonly(program_data, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Access.pack(&AccessInstruction::InitializeRoles)?,
+        })
+    }
+
+    pub fn grant_role(
+        program_id: &Pubkey,
+        admin: &Pubkey,
+        member: &Pubkey,
+        role: Role,
+    ) -> Result<Instruction, ProgramError> {
+        Self::update_role(
+            program_id,
+            admin,
+            AccessInstruction::GrantRole { member: *member, role },
+        )
+    }
+
+    pub fn revoke_role(
+        program_id: &Pubkey,
+        admin: &Pubkey,
+        member: &Pubkey,
+        role: Role,
+    ) -> Result<Instruction, ProgramError> {
+        Self::update_role(
+            program_id,
+            admin,
+            AccessInstruction::RevokeRole { member: *member, role },
+        )
+    }
+
+    pub fn propose_authority(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        account: &Pubkey,
+        new_authority: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (transfer, _) = AuthorityTransfer::find_address(account, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new_readonly(*account, false),
+                AccountMeta::new(transfer, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Access.pack(&AccessInstruction::ProposeAuthority {
+                new_authority: *new_authority,
+            })?,
+        })
+    }
+
+    pub fn accept_authority(
+        program_id: &Pubkey,
+        new_authority: &Pubkey,
+        account: &Pubkey,
+        payer: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (transfer, _) = AuthorityTransfer::find_address(account, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*new_authority, true),
+                AccountMeta::new(*account, false),
+                AccountMeta::new(transfer, false),
+                AccountMeta::new(*payer, false),
+            ],
+            data: ModuleTag::Access.pack(&AccessInstruction::AcceptAuthority)?,
+        })
+    }
+
+    pub fn set_paused(
+        program_id: &Pubkey,
+        guardian: &Pubkey,
+        module: ModuleTag,
+        paused: bool,
+    ) -> Result<Instruction, ProgramError> {
+        let (config, _) = ProgramConfig::find_address(program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*guardian, true),
+                AccountMeta::new(config, false),
+                AccountMeta::new_readonly(roles, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Access.pack(&AccessInstruction::SetPaused { module, paused })?,
+        })
+    }
+
+    pub fn configure_circuit_breaker(
+        program_id: &Pubkey,
+        admin: &Pubkey,
+        module: ModuleTag,
+        window_slots: u64,
+        max_outflow: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (breaker, _) = CircuitBreaker::find_address(module, program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*admin, true),
+                AccountMeta::new(breaker, false),
+                AccountMeta::new_readonly(roles, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Access.pack(&AccessInstruction::ConfigureCircuitBreaker {
+                module,
+                window_slots,
+                max_outflow,
+            })?,
+        })
+    }
+
+    pub fn pin_soul_mint(
+        program_id: &Pubkey,
+        admin: &Pubkey,
+        mint: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (canonical_mint, _) = CanonicalMint::find_address(program_id);
+        let (soul_token, _) = SOuLToken::find_address(mint, program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*admin, true),
+                AccountMeta::new(canonical_mint, false),
+                AccountMeta::new_readonly(soul_token, false),
+                AccountMeta::new_readonly(roles, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Access.pack(&AccessInstruction::PinSoulMint)?,
+        })
+    }
+
+    /// Add the roles account to a privileged instruction, so a holder of its
+    /// role can sign in place of the recorded authority. It goes after the
+    /// instruction's own accounts and before the program config.
+    pub fn with_roles(program_id: &Pubkey, mut instruction: Instruction) -> Instruction {
+        let (roles, _) = Roles::find_address(program_id);
+        let (config, _) = ProgramConfig::find_address(program_id);
+        let index = match instruction.accounts.last() {
+            Some(last) if last.pubkey == config => instruction.accounts.len() - 1,
+            _ => instruction.accounts.len(),
+        };
+        instruction.accounts.insert(index, AccountMeta::new_readonly(roles, false));
+        instruction
+    }
+
+    fn update_role(
+        program_id: &Pubkey,
+        admin: &Pubkey,
+        instruction: AccessInstruction,
+    ) -> Result<Instruction, ProgramError> {
+        let (roles, _) = Roles::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*admin, true),
+                AccountMeta::new(roles, false),
+            ],
+            data: ModuleTag::Access.pack(&instruction)?,
+        })
+    }
+}
+
+module_error! {
+    pub enum AccessError in Access {
+        InvalidInstruction => "Invalid Instruction",
+        AlreadyInitialized => "Roles Already Initialized",
+        NotAuthorized => "Missing Required Role",
+        TooManyGrants => "Too Many Role Grants",
+        RoleAlreadyGranted => "Role Already Granted",
+        RoleNotGranted => "Role Not Granted",
+        UnsupportedAccount => "Account Has No Transferable Authority",
+        InvalidTransfer => "Invalid Authority Transfer",
+        InvalidConfig => "Invalid Program Config Account",
+        ModulePaused => "Module Paused",
+        InvalidModule => "Module Cannot Be Paused",
+        InvalidCircuitBreaker => "Invalid Circuit Breaker",
+        OutflowLimitExceeded => "Outflow Limit Exceeded",
+        InvalidProgramData => "Invalid Program Data Account",
+        SoulMintAlreadyPinned => "SOuL Mint Already Pinned",
+        InvalidCanonicalMint => "Invalid Canonical Mint Account",
+    }
+}
@@ -0,0 +1,572 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+    account::AccountState,
+    event::SoulEvent,
+    game::GameState,
+    impact::ImpactMetrics,
+    instruction::ModuleTag,
+    lst::{LSTError, LSTPool},
+    pda,
+    state::{SOuLToken, TokenMetadata},
+};
+
+use super::{
+    instruction::{AccessError, AccessInstruction},
+    state::{AuthorityTransfer, CanonicalMint, CircuitBreaker, ProgramConfig, Role, RoleGrant, Roles},
+};
+
+pub struct Processor;
+
+impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = AccessInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        match instruction {
+            AccessInstruction::InitializeRoles => Self::process_initialize_roles(accounts, program_id),
+            AccessInstruction::GrantRole { member, role } => {
+                Self::process_grant_role(accounts, member, role, program_id)
+            }
+            AccessInstruction::RevokeRole { member, role } => {
+                Self::process_revoke_role(accounts, member, role, program_id)
+            }
+            AccessInstruction::ProposeAuthority { new_authority } => {
+                Self::process_propose_authority(accounts, new_authority, program_id)
+            }
+            AccessInstruction::AcceptAuthority => Self::process_accept_authority(accounts, program_id),
+            AccessInstruction::SetPaused { module, paused } => {
+                Self::process_set_paused(accounts, module, paused, program_id)
+            }
+            AccessInstruction::ConfigureCircuitBreaker {
+                module,
+                window_slots,
+                max_outflow,
+            } => Self::process_configure_circuit_breaker(
+                accounts,
+                module,
+                window_slots,
+                max_outflow,
+                program_id,
+            ),
+            AccessInstruction::PinSoulMint => Self::process_pin_soul_mint(accounts, program_id),
+        }
+    }
+
+    fn process_initialize_roles(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
+        let program_data_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !admin_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if roles_info.owner == program_id {
+            return Err(AccessError::AlreadyInitialized.into());
+        }
+
+        // The admin holds every role, so only whoever deployed the program
+        // may claim it
+        if upgrade_authority(program_data_info, program_id)? != Some(*admin_info.key) {
+            return Err(AccessError::NotAuthorized.into());
+        }
+
+        let bump = pda::expect_pda(roles_info.key, &[Roles::SEED], program_id)?;
+        pda::create_pda_account(
+            admin_info,
+            roles_info,
+            system_program_info,
+            program_id,
+            Roles::SPACE,
+            &[Roles::SEED, &[bump]],
+        )?;
+
+        let roles = Roles {
+            is_initialized: true,
+            admin: *admin_info.key,
+            grants: Vec::new(),
+            bump,
+        };
+
+        roles.save(roles_info)
+    }
+
+    fn process_grant_role(
+        accounts: &[AccountInfo],
+        member: Pubkey,
+        role: Role,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
+
+        let mut roles = load_roles_as(admin_info, roles_info, Role::Admin, program_id)?;
+        let grant = RoleGrant { member, role };
+        if roles.grants.contains(&grant) {
+            return Err(AccessError::RoleAlreadyGranted.into());
+        }
+        if roles.grants.len() >= Roles::MAX_GRANTS {
+            return Err(AccessError::TooManyGrants.into());
+        }
+        roles.grants.push(grant);
+        roles.save(roles_info)?;
+
+        SoulEvent::RoleGranted {
+            member,
+            role,
+            admin: *admin_info.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_revoke_role(
+        accounts: &[AccountInfo],
+        member: Pubkey,
+        role: Role,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
+
+        let mut roles = load_roles_as(admin_info, roles_info, Role::Admin, program_id)?;
+        let grant = RoleGrant { member, role };
+        let index = roles
+            .grants
+            .iter()
+            .position(|granted| *granted == grant)
+            .ok_or(AccessError::RoleNotGranted)?;
+        roles.grants.remove(index);
+        roles.save(roles_info)?;
+
+        SoulEvent::RoleRevoked {
+            member,
+            role,
+            admin: *admin_info.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_propose_authority(
+        accounts: &[AccountInfo],
+        new_authority: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let account_info = next_account_info(account_info_iter)?;
+        let transfer_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+        let roles_info = account_info_iter.next();
+
+        let current_authority = transfer_authority(account_info, program_id, None)?;
+        if !is_authorized(authority_info, &current_authority, Role::Admin, roles_info, program_id)? {
+            return Err(AccessError::NotAuthorized.into());
+        }
+
+        let seeds: &[&[u8]] = &[AuthorityTransfer::SEED, account_info.key.as_ref()];
+        let bump = pda::expect_pda(transfer_info.key, seeds, program_id)?;
+        let payer = if transfer_info.owner == program_id {
+            // A pending proposal is replaced, and its payer is still refunded
+            AuthorityTransfer::load(transfer_info, program_id)?.payer
+        } else {
+            pda::create_pda_account(
+                authority_info,
+                transfer_info,
+                system_program_info,
+                program_id,
+                AuthorityTransfer::SPACE,
+                &[AuthorityTransfer::SEED, account_info.key.as_ref(), &[bump]],
+            )?;
+            *authority_info.key
+        };
+
+        let transfer = AuthorityTransfer {
+            account: *account_info.key,
+            pending_authority: new_authority,
+            payer,
+            bump,
+        };
+
+        transfer.save(transfer_info)
+    }
+
+    fn process_accept_authority(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let new_authority_info = next_account_info(account_info_iter)?;
+        let account_info = next_account_info(account_info_iter)?;
+        let transfer_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+
+        if !new_authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let transfer = AuthorityTransfer::load(transfer_info, program_id)?;
+        if transfer.account != *account_info.key || transfer.payer != *payer_info.key {
+            return Err(AccessError::InvalidTransfer.into());
+        }
+        if transfer.pending_authority != *new_authority_info.key {
+            return Err(AccessError::NotAuthorized.into());
+        }
+
+        let previous_authority =
+            transfer_authority(account_info, program_id, Some(transfer.pending_authority))?;
+
+        pda::close_account(transfer_info, payer_info)?;
+
+        SoulEvent::AuthorityTransferred {
+            account: *account_info.key,
+            previous_authority,
+            new_authority: transfer.pending_authority,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_set_paused(
+        accounts: &[AccountInfo],
+        module: ModuleTag,
+        paused: bool,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let guardian_info = next_account_info(account_info_iter)?;
+        let config_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        load_roles_as(guardian_info, roles_info, Role::Guardian, program_id)?;
+        if !module.is_pausable() {
+            return Err(AccessError::InvalidModule.into());
+        }
+
+        let mut config = if config_info.owner == program_id {
+            ProgramConfig::load(config_info, program_id)?
+        } else {
+            let bump = pda::expect_pda(config_info.key, &[ProgramConfig::SEED], program_id)?;
+            pda::create_pda_account(
+                guardian_info,
+                config_info,
+                system_program_info,
+                program_id,
+                ProgramConfig::SPACE,
+                &[ProgramConfig::SEED, &[bump]],
+            )?;
+            ProgramConfig {
+                is_initialized: true,
+                paused_modules: 0,
+                bump,
+            }
+        };
+        config.set_paused(module, paused);
+        config.save(config_info)?;
+
+        SoulEvent::PauseSet {
+            module,
+            paused,
+            guardian: *guardian_info.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_configure_circuit_breaker(
+        accounts: &[AccountInfo],
+        module: ModuleTag,
+        window_slots: u64,
+        max_outflow: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        load_roles_as(admin_info, roles_info, Role::Admin, program_id)?;
+        if !module.is_pausable() {
+            return Err(AccessError::InvalidModule.into());
+        }
+        if window_slots == 0 {
+            return Err(AccessError::InvalidCircuitBreaker.into());
+        }
+
+        let mut breaker = if breaker_info.owner == program_id {
+            CircuitBreaker::load(breaker_info, program_id)?
+        } else {
+            let seeds: &[&[u8]] = &[CircuitBreaker::SEED, &[module as u8]];
+            let bump = pda::expect_pda(breaker_info.key, seeds, program_id)?;
+            pda::create_pda_account(
+                admin_info,
+                breaker_info,
+                system_program_info,
+                program_id,
+                CircuitBreaker::SPACE,
+                &[CircuitBreaker::SEED, &[module as u8], &[bump]],
+            )?;
+            CircuitBreaker {
+                module,
+                window_slots,
+                max_outflow,
+                window_start: Clock::get()?.slot,
+                outflow: 0,
+                bump,
+            }
+        };
+        if breaker.module != module {
+            return Err(AccessError::InvalidCircuitBreaker.into());
+        }
+        breaker.window_slots = window_slots;
+        breaker.max_outflow = max_outflow;
+
+        breaker.save(breaker_info)
+    }
+
+    fn process_pin_soul_mint(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let canonical_mint_info = next_account_info(account_info_iter)?;
+        let soul_token_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        load_roles_as(admin_info, roles_info, Role::Admin, program_id)?;
+        if canonical_mint_info.owner == program_id {
+            return Err(AccessError::SoulMintAlreadyPinned.into());
+        }
+
+        // Only a mint the program already backs with a SOuL token config
+        let soul_token = SOuLToken::load(soul_token_info, program_id)?;
+        if !soul_token.is_initialized
+            || *soul_token_info.key != SOuLToken::find_address(&soul_token.mint, program_id).0
+        {
+            return Err(AccessError::InvalidCanonicalMint.into());
+        }
+
+        let bump = pda::expect_pda(canonical_mint_info.key, &[CanonicalMint::SEED], program_id)?;
+        pda::create_pda_account(
+            admin_info,
+            canonical_mint_info,
+            system_program_info,
+            program_id,
+            CanonicalMint::SPACE,
+            &[CanonicalMint::SEED, &[bump]],
+        )?;
+
+        CanonicalMint {
+            mint: soul_token.mint,
+            bump,
+        }
+        .save(canonical_mint_info)
+    }
+}
+
+/// The canonical SOuL mint pinned in `canonical_mint_info`
+pub fn load_canonical_mint(
+    canonical_mint_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    if *canonical_mint_info.key != CanonicalMint::find_address(program_id).0 || canonical_mint_info.owner != program_id {
+        return Err(AccessError::InvalidCanonicalMint.into());
+    }
+    Ok(CanonicalMint::load(canonical_mint_info, program_id)?.mint)
+}
+
+/// Fail if `module` is paused. Every instruction of a pausable module passes
+/// the config last; before the config is created nothing is paused.
+pub fn check_not_paused(
+    config_info: &AccountInfo,
+    module: ModuleTag,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if *config_info.key != ProgramConfig::find_address(program_id).0 {
+        return Err(AccessError::InvalidConfig.into());
+    }
+    if config_info.owner != program_id {
+        return Ok(());
+    }
+    if ProgramConfig::load(config_info, program_id)?.is_paused(module) {
+        return Err(match module {
+            ModuleTag::LST => LSTError::PoolPaused.into(),
+            _ => AccessError::ModulePaused.into(),
+        });
+    }
+    Ok(())
+}
+
+/// Count `amount` against the breaker of `module`, failing once the current
+/// window's outflow would exceed its limit. A module whose breaker was never
+/// configured has no limit.
+pub fn record_outflow(
+    breaker_info: &AccountInfo,
+    module: ModuleTag,
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if *breaker_info.key != CircuitBreaker::find_address(module, program_id).0 {
+        return Err(AccessError::InvalidCircuitBreaker.into());
+    }
+    if breaker_info.owner != program_id {
+        return Ok(());
+    }
+    let mut breaker = CircuitBreaker::load(breaker_info, program_id)?;
+    if !breaker.record_outflow(Clock::get()?.slot, amount) {
+        return Err(AccessError::OutflowLimitExceeded.into());
+    }
+    breaker.save(breaker_info)
+}
+
+/// Whether `signer_info` may act where `authority` is recorded: as that
+/// authority, or as a holder of `role` in `roles_info` when it is passed
+pub fn is_authorized(
+    signer_info: &AccountInfo,
+    authority: &Pubkey,
+    role: Role,
+    roles_info: Option<&AccountInfo>,
+    program_id: &Pubkey,
+) -> Result<bool, ProgramError> {
+    if !signer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if signer_info.key == authority {
+        return Ok(true);
+    }
+    match roles_info {
+        // Only InitializeRoles writes a roles account, and only at the PDA
+        Some(roles_info) => Ok(Roles::load(roles_info, program_id)?.has_role(signer_info.key, role)),
+        None => Ok(false),
+    }
+}
+
+/// Load the roles account for an instruction signed by a holder of `role`
+pub fn load_roles_as(
+    signer_info: &AccountInfo,
+    roles_info: &AccountInfo,
+    role: Role,
+    program_id: &Pubkey,
+) -> Result<Roles, ProgramError> {
+    load_roles_as_any(signer_info, roles_info, &[role], program_id)
+}
+
+/// Like [`load_roles_as`], for a signer holding any one of `roles`
+pub fn load_roles_as_any(
+    signer_info: &AccountInfo,
+    roles_info: &AccountInfo,
+    roles: &[Role],
+    program_id: &Pubkey,
+) -> Result<Roles, ProgramError> {
+    if !signer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let loaded = Roles::load(roles_info, program_id)?;
+    if !roles.iter().any(|role| loaded.has_role(signer_info.key, *role)) {
+        return Err(AccessError::NotAuthorized.into());
+    }
+    Ok(loaded)
+}
+
+/// The upgrade authority recorded in the ProgramData account of `program_id`,
+/// or `None` once the program is immutable
+fn upgrade_authority(
+    program_data_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Option<Pubkey>, ProgramError> {
+    let (program_data, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if *program_data_info.key != program_data || *program_data_info.owner != bpf_loader_upgradeable::id() {
+        return Err(AccessError::InvalidProgramData.into());
+    }
+    // Bincode layout of `UpgradeableLoaderState::ProgramData`: a u32 variant
+    // tag of 3, the u64 deployment slot, then an optional authority
+    let data = program_data_info.data.borrow();
+    match (data.get(..4), data.get(12), data.get(13..45)) {
+        (Some([3, 0, 0, 0]), Some(0), _) => Ok(None),
+        (Some([3, 0, 0, 0]), Some(1), Some(authority)) => Ok(Some(Pubkey::try_from(authority).unwrap())),
+        _ => Err(AccessError::InvalidProgramData.into()),
+    }
+}
+
+/// Return the authority recorded in `info`, replacing it with
+/// `new_authority` when one is given
+fn transfer_authority(
+    info: &AccountInfo,
+    program_id: &Pubkey,
+    new_authority: Option<Pubkey>,
+) -> Result<Pubkey, ProgramError> {
+    if info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let discriminator = info
+        .data
+        .borrow()
+        .get(..8)
+        .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+        .ok_or(AccessError::UnsupportedAccount)?;
+
+    match discriminator {
+        SOuLToken::DISCRIMINATOR => {
+            replace_authority::<SOuLToken>(info, program_id, new_authority, |token| &mut token.mint_authority)
+        }
+        LSTPool::DISCRIMINATOR => {
+            replace_authority::<LSTPool>(info, program_id, new_authority, |pool| &mut pool.authority)
+        }
+        GameState::DISCRIMINATOR => {
+            replace_authority::<GameState>(info, program_id, new_authority, |game| &mut game.authority)
+        }
+        ImpactMetrics::DISCRIMINATOR => {
+            replace_authority::<ImpactMetrics>(info, program_id, new_authority, |metrics| &mut metrics.authority)
+        }
+        TokenMetadata::DISCRIMINATOR => replace_authority::<TokenMetadata>(
+            info,
+            program_id,
+            new_authority,
+            |metadata| &mut metadata.update_authority,
+        ),
+        Roles::DISCRIMINATOR => {
+            replace_authority::<Roles>(info, program_id, new_authority, |roles| &mut roles.admin)
+        }
+        _ => Err(AccessError::UnsupportedAccount.into()),
+    }
+}
+
+fn replace_authority<T: AccountState>(
+    info: &AccountInfo,
+    program_id: &Pubkey,
+    new_authority: Option<Pubkey>,
+    authority: impl Fn(&mut T) -> &mut Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    let mut state = T::load(info, program_id)?;
+    let current = *authority(&mut state);
+    if let Some(new_authority) = new_authority {
+        *authority(&mut state) = new_authority;
+        state.save(info)?;
+    }
+    Ok(current)
+}
@@ -0,0 +1,221 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::{
+    account::{AccountState, HEADER_LEN},
+    instruction::ModuleTag,
+};
+
+/// A privilege that privileged instructions accept in place of the
+/// authority recorded on the account they act on
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    /// Grants and revokes roles, configures tokens and edits metadata
+    Admin,
+    /// Mints SOuL
+    Minter,
+    /// Updates LST pools and withdraws from them in an emergency
+    PoolManager,
+    /// Updates player stats
+    GameMaster,
+    /// Resolves impact report disputes
+    DisputeResolver,
+    /// Stops the program when something goes wrong
+    Guardian,
+    /// Freezes and thaws SOuL token accounts under a compliance hold
+    Compliance,
+}
+
+/// A role held by one key
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoleGrant {
+    pub member: Pubkey,
+    pub role: Role,
+}
+
+/// The program-wide roles account (`["roles"]`).
+///
+/// The admin holds every role. Other keys hold the roles granted to them.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Roles {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub grants: Vec<RoleGrant>,
+    pub bump: u8,
+}
+
+/// A pending hand-over of an account's authority, accepted by the new
+/// authority and closed once accepted
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct AuthorityTransfer {
+    /// The account whose authority changes hands
+    pub account: Pubkey,
+    pub pending_authority: Pubkey,
+    /// Paid the rent, refunded when the transfer is accepted
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+/// The program-wide config (`["config"]`). Until it is created no module is
+/// paused.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct ProgramConfig {
+    pub is_initialized: bool,
+    /// Bit `tag` is set while the module with that `ModuleTag` is paused
+    pub paused_modules: u8,
+    pub bump: u8,
+}
+
+/// The canonical SOuL mint (`["canonical_mint"]`), pinned once by the
+/// admin. Verifier and dispute stakes and SOuL retirements only accept this
+/// mint, since anyone can create a SOuL token config for a mint of their own.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CanonicalMint {
+    pub mint: Pubkey,
+    pub bump: u8,
+}
+
+/// Caps what a module lets out of the program within a window of slots.
+///
+/// An outflow that would take the window's total above `max_outflow` is
+/// rejected; smaller outflows still pass while the window has room for them.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct CircuitBreaker {
+    pub module: ModuleTag,
+    pub window_slots: u64,
+    pub max_outflow: u64,
+    /// First slot of the current window
+    pub window_start: u64,
+    /// Outflow recorded in the current window
+    pub outflow: u64,
+    pub bump: u8,
+}
+
+impl Roles {
+    pub const MAX_GRANTS: usize = 32;
+
+    pub const LEN: usize = 1 + 32 + 4 + (32 + 1) * Self::MAX_GRANTS + 1;
+
+    /// Seed of the roles PDA (`["roles"]`)
+    pub const SEED: &'static [u8] = b"roles";
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    /// Whether `key` is the admin or was granted `role`
+    pub fn has_role(&self, key: &Pubkey, role: Role) -> bool {
+        *key == self.admin
+            || self
+                .grants
+                .iter()
+                .any(|grant| grant.member == *key && grant.role == role)
+    }
+}
+
+impl AuthorityTransfer {
+    pub const LEN: usize = 32 + 32 + 32 + 1;
+
+    /// Seed of the transfer PDA (`["authority_transfer", account]`)
+    pub const SEED: &'static [u8] = b"authority_transfer";
+
+    pub fn find_address(account: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, account.as_ref()], program_id)
+    }
+}
+
+impl ProgramConfig {
+    pub const LEN: usize = 1 + 1 + 1;
+
+    /// Seed of the config PDA (`["config"]`)
+    pub const SEED: &'static [u8] = b"config";
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+
+    /// The config as the last account of an instruction of a pausable module
+    pub fn account_meta(program_id: &Pubkey) -> AccountMeta {
+        AccountMeta::new_readonly(Self::find_address(program_id).0, false)
+    }
+
+    pub fn is_paused(&self, module: ModuleTag) -> bool {
+        self.paused_modules & (1 << module as u8) != 0
+    }
+
+    pub fn set_paused(&mut self, module: ModuleTag, paused: bool) {
+        if paused {
+            self.paused_modules |= 1 << module as u8;
+        } else {
+            self.paused_modules &= !(1 << module as u8);
+        }
+    }
+}
+
+impl CanonicalMint {
+    pub const LEN: usize = 32 + 1;
+
+    /// Seed of the canonical mint PDA (`["canonical_mint"]`)
+    pub const SEED: &'static [u8] = b"canonical_mint";
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+impl CircuitBreaker {
+    pub const LEN: usize = 1 + 8 + 8 + 8 + 8 + 1;
+
+    /// Seed of a module's breaker PDA (`["circuit_breaker", module]`)
+    pub const SEED: &'static [u8] = b"circuit_breaker";
+
+    pub fn find_address(module: ModuleTag, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &[module as u8]], program_id)
+    }
+
+    /// Add `amount` to the outflow of the window holding `slot`, or return
+    /// `false` if that would exceed `max_outflow`
+    pub fn record_outflow(&mut self, slot: u64, amount: u64) -> bool {
+        if slot >= self.window_start.saturating_add(self.window_slots) {
+            self.window_start = slot;
+            self.outflow = 0;
+        }
+        match self.outflow.checked_add(amount) {
+            Some(outflow) if outflow <= self.max_outflow => {
+                self.outflow = outflow;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl AccountState for Roles {
+    const DISCRIMINATOR: [u8; 8] = *b"rolesacc";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl AccountState for AuthorityTransfer {
+    const DISCRIMINATOR: [u8; 8] = *b"authxfer";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl AccountState for ProgramConfig {
+    const DISCRIMINATOR: [u8; 8] = *b"progconf";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl AccountState for CanonicalMint {
+    const DISCRIMINATOR: [u8; 8] = *b"canonmnt";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl AccountState for CircuitBreaker {
+    const DISCRIMINATOR: [u8; 8] = *b"circbrkr";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
@@ -0,0 +1,83 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::error::SOuLError;
+
+/// Bytes in front of every account's Borsh body: an 8-byte discriminator
+/// followed by a one-byte layout version
+pub const HEADER_LEN: usize = 8 + 1;
+
+/// A program-owned account with a type tag and a versioned layout
+pub trait AccountState: BorshSerialize + BorshDeserialize {
+    /// Tag stored in the first 8 bytes of every account of this type
+    const DISCRIMINATOR: [u8; 8];
+
+    /// Layout version written by this build of the program
+    const VERSION: u8;
+
+    /// Space to allocate for the account, header included
+    const SPACE: usize;
+
+    /// Space the account needs to hold `self`, header included. Types with
+    /// variable-length fields size each account to its value.
+    fn space_for(&self) -> usize {
+        Self::SPACE
+    }
+
+    /// Check the header and decode the body of `data`
+    fn unpack_account(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < HEADER_LEN || data[..8] != Self::DISCRIMINATOR {
+            return Err(SOuLError::InvalidAccountType.into());
+        }
+        if data[8] != Self::VERSION {
+            return Err(SOuLError::AccountVersionMismatch.into());
+        }
+        Self::deserialize(&mut &data[HEADER_LEN..]).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Load an account owned by `program_id`, checking its type and version
+    fn load(info: &AccountInfo, program_id: &Pubkey) -> Result<Self, ProgramError> {
+        if info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Self::unpack_account(&info.data.borrow())
+    }
+
+    /// Write the header and body into the account
+    fn save(&self, info: &AccountInfo) -> ProgramResult {
+        let mut data = info.data.borrow_mut();
+        if data.len() < HEADER_LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        data[..8].copy_from_slice(&Self::DISCRIMINATOR);
+        data[8] = Self::VERSION;
+        self.serialize(&mut &mut data[HEADER_LEN..])?;
+        Ok(())
+    }
+
+    /// Whether `data` already holds this type at the current layout version
+    fn is_current(data: &[u8]) -> bool {
+        data.len() >= HEADER_LEN && data[..8] == Self::DISCRIMINATOR && data[8] == Self::VERSION
+    }
+}
+
+/// Account types that can be named in a `Migrate` instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    SOuLToken,
+    LSTToken,
+    VestingSchedule,
+    LSTPool,
+    StakerInfo,
+    ImpactMetrics,
+    ImpactReport,
+    VerifierState,
+    DisputeCase,
+    GameState,
+    PlayerState,
+}
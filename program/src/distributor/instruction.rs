@@ -0,0 +1,222 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    clock::UnixTimestamp,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use crate::{access::{CircuitBreaker, ProgramConfig}, error::module_error, instruction::ModuleTag};
+use super::state::Distributor;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum DistributorInstruction {
+    /// Publish the Merkle root of an airdrop and fund its vault with
+    /// `max_total_claim` tokens from the admin, who also pays any transfer
+    /// fee of the mint
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` The admin, pays rent and funds the vault
+    /// 1. `[writable]` The distributor PDA to create (`["distributor", admin, nonce]`)
+    /// 2. `[writable]` The vault PDA to create (`["distributor_vault", distributor]`)
+    /// 3. `[]` The mint of the airdropped token
+    /// 4. `[writable]` The admin's token account funding the vault
+    /// 5. `[]` The token program that owns the mint
+    /// 6. `[]` The system program
+    /// 7. `[]` The program config (`["config"]`)
+    NewDistributor {
+        nonce: u64,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+        num_nodes: u64,
+        expiry: UnixTimestamp,
+    },
+
+    /// Claim the `amount` committed to at leaf `index` for the signer
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The claimant named in the leaf
+    /// 1. `[writable]` The distributor account
+    /// 2. `[writable]` The vault
+    /// 3. `[writable]` The token account receiving the claim
+    /// 4. `[]` The mint of the airdropped token
+    /// 5. `[]` The token program that owns the mint
+    /// 6. `[writable]` The Distributor circuit breaker (`["circuit_breaker", module]`)
+    /// 7. `[]` The program config (`["config"]`)
+    Claim {
+        index: u64,
+        amount: u64,
+        /// Sibling hashes from the leaf up to the root
+        proof: Vec<[u8; 32]>,
+    },
+
+    /// Move what is left in the vault to the admin once the airdrop expired
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The admin
+    /// 1. `[writable]` The distributor account
+    /// 2. `[writable]` The vault
+    /// 3. `[writable]` The token account receiving the unclaimed tokens
+    /// 4. `[]` The mint of the airdropped token
+    /// 5. `[]` The token program that owns the mint
+    /// 6. `[]` The program config (`["config"]`)
+    Clawback,
+
+    /// Close a clawed back distributor and its vault, returning their rent
+    /// to the admin. Anyone may close. Tokens sent to the vault after the
+    /// clawback go to the admin first.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The admin
+    /// 1. `[writable]` The distributor account
+    /// 2. `[writable]` The vault
+    /// 3. `[writable]` The admin's token account, which takes anything left in the vault
+    /// 4. `[writable]` The mint of the airdropped token, which takes any transfer fees withheld in the vault
+    /// 5. `[]` The token program that owns the vault
+    /// 6. `[]` The program config (`["config"]`)
+    CloseDistributor,
+}
+
+impl DistributorInstruction {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_distributor(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        admin: &Pubkey,
+        mint: &Pubkey,
+        source: &Pubkey,
+        nonce: u64,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+        num_nodes: u64,
+        expiry: UnixTimestamp,
+    ) -> Result<Instruction, ProgramError> {
+        let (distributor, _) = Distributor::find_address(admin, nonce, program_id);
+        let (vault, _) = Distributor::find_vault(&distributor, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*admin, true),
+                AccountMeta::new(distributor, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*source, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Distributor.pack(&DistributorInstruction::NewDistributor {
+                nonce,
+                merkle_root,
+                max_total_claim,
+                num_nodes,
+                expiry,
+            })?,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn claim(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        claimant: &Pubkey,
+        distributor: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<Instruction, ProgramError> {
+        let (vault, _) = Distributor::find_vault(distributor, program_id);
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::Distributor, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*claimant, true),
+                AccountMeta::new(*distributor, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                AccountMeta::new(breaker, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Distributor.pack(&DistributorInstruction::Claim {
+                index,
+                amount,
+                proof,
+            })?,
+        })
+    }
+
+    pub fn clawback(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        admin: &Pubkey,
+        distributor: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (vault, _) = Distributor::find_vault(distributor, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*admin, true),
+                AccountMeta::new(*distributor, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Distributor.pack(&DistributorInstruction::Clawback)?,
+        })
+    }
+
+    pub fn close_distributor(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        admin: &Pubkey,
+        distributor: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (vault, _) = Distributor::find_vault(distributor, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*admin, false),
+                AccountMeta::new(*distributor, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new(*destination, false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Distributor.pack(&DistributorInstruction::CloseDistributor)?,
+        })
+    }
+}
+
+module_error! {
+    pub enum DistributorError in Distributor {
+        InvalidInstruction => "Invalid Instruction",
+        InvalidDistributor => "Invalid Distributor Parameters",
+        InvalidAuthority => "Invalid Authority",
+        InvalidVault => "Invalid Vault",
+        InvalidIndex => "Leaf Index Out Of Range",
+        InvalidProof => "Invalid Merkle Proof",
+        AlreadyClaimed => "Already Claimed",
+        ExceedsMaxClaim => "Claims Exceed The Funded Total",
+        ClaimExpired => "Airdrop Expired",
+        NotExpired => "Airdrop Not Expired",
+        AlreadyClawedBack => "Already Clawed Back",
+        NotClawedBack => "Airdrop Not Clawed Back",
+        InvalidDestination => "Invalid Destination Token Account",
+        VaultUnderfunded => "Vault Holds Less Than The Funded Total",
+    }
+}
@@ -0,0 +1,33 @@
+//! Hashing of airdrop Merkle trees.
+//!
+//! A leaf commits to a claim's index, wallet and amount. Inner nodes hash
+//! their two children in sorted order, so a proof is just the list of
+//! siblings from the leaf up to the root. Leaves and nodes are hashed with
+//! different prefixes, so a node can never be passed off as a leaf.
+
+use solana_program::{keccak::hashv, pubkey::Pubkey};
+
+const LEAF_PREFIX: &[u8] = &[0];
+const NODE_PREFIX: &[u8] = &[1];
+
+/// Hash of the claim of `amount` by `claimant` at leaf `index`
+pub fn leaf_hash(index: u64, claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[
+        LEAF_PREFIX,
+        &index.to_le_bytes(),
+        claimant.as_ref(),
+        &amount.to_le_bytes(),
+    ])
+    .0
+}
+
+/// Hash of the parent of two nodes, whichever side each is on
+pub fn node_hash(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[NODE_PREFIX, left, right]).0
+}
+
+/// Whether `proof` leads from `leaf` up to `root`
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    proof.iter().fold(leaf, |hash, sibling| node_hash(&hash, sibling)) == *root
+}
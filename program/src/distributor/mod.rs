@@ -0,0 +1,8 @@
+pub mod instruction;
+pub mod merkle;
+pub mod processor;
+pub mod state;
+
+pub use instruction::*;
+pub use processor::*;
+pub use state::*;
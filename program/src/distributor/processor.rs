@@ -0,0 +1,408 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::UnixTimestamp,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+
+use crate::{access, account::AccountState, error::SOuLError, event::SoulEvent, instruction::ModuleTag, pda};
+
+use super::{
+    instruction::{DistributorError, DistributorInstruction},
+    merkle,
+    state::Distributor,
+};
+
+pub struct Processor;
+
+impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = DistributorInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        match instruction {
+            DistributorInstruction::NewDistributor {
+                nonce,
+                merkle_root,
+                max_total_claim,
+                num_nodes,
+                expiry,
+            } => Self::process_new_distributor(
+                accounts,
+                nonce,
+                merkle_root,
+                max_total_claim,
+                num_nodes,
+                expiry,
+                program_id,
+            ),
+            DistributorInstruction::Claim { index, amount, proof } => {
+                Self::process_claim(accounts, index, amount, &proof, program_id)
+            }
+            DistributorInstruction::Clawback => Self::process_clawback(accounts, program_id),
+            DistributorInstruction::CloseDistributor => Self::process_close_distributor(accounts, program_id),
+        }
+    }
+
+    fn process_new_distributor(
+        accounts: &[AccountInfo],
+        nonce: u64,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+        num_nodes: u64,
+        expiry: UnixTimestamp,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let distributor_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !admin_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+        if num_nodes == 0 || num_nodes > Distributor::MAX_NODES || max_total_claim == 0 {
+            return Err(DistributorError::InvalidDistributor.into());
+        }
+        let clock = Clock::get()?;
+        if expiry <= clock.unix_timestamp {
+            return Err(DistributorError::InvalidDistributor.into());
+        }
+        if mint_info.owner != token_program_info.key
+            || (*token_program_info.key != spl_token::id() && *token_program_info.key != spl_token_2022::id())
+        {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let nonce_bytes = nonce.to_le_bytes();
+        let bump = pda::expect_pda(
+            distributor_info.key,
+            &[Distributor::SEED, admin_info.key.as_ref(), &nonce_bytes],
+            program_id,
+        )?;
+        pda::create_pda_account(
+            admin_info,
+            distributor_info,
+            system_program_info,
+            program_id,
+            Distributor::space(num_nodes),
+            &[Distributor::SEED, admin_info.key.as_ref(), &nonce_bytes, &[bump]],
+        )?;
+
+        // The vault owns itself, so only this program can move its tokens.
+        // It carries whatever account extensions the mint requires. A
+        // transfer fee mint is sent enough to cover its fee.
+        let vault_seeds: &[&[u8]] = &[Distributor::VAULT_SEED, distributor_info.key.as_ref()];
+        let vault_bump = pda::expect_pda(vault_info.key, vault_seeds, program_id)?;
+        let (decimals, space, funding) = {
+            let mint_data = mint_info.data.borrow();
+            let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+            let required = ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+            let funding = match mint.get_extension::<TransferFeeConfig>() {
+                Ok(fee_config) => fee_config
+                    .get_epoch_fee(clock.epoch)
+                    .calculate_pre_fee_amount(max_total_claim)
+                    .ok_or(DistributorError::InvalidDistributor)?,
+                Err(_) => max_total_claim,
+            };
+            (mint.base.decimals, ExtensionType::try_calculate_account_len::<TokenAccount>(&required)?, funding)
+        };
+        pda::create_pda_account(
+            admin_info,
+            vault_info,
+            system_program_info,
+            token_program_info.key,
+            space,
+            &[Distributor::VAULT_SEED, distributor_info.key.as_ref(), &[vault_bump]],
+        )?;
+        let init_vault_ix = spl_token_2022::instruction::initialize_account3(
+            token_program_info.key,
+            vault_info.key,
+            mint_info.key,
+            vault_info.key,
+        )?;
+        invoke(
+            &init_vault_ix,
+            &[vault_info.clone(), mint_info.clone(), token_program_info.clone()],
+        )?;
+
+        let fund_ix = spl_token_2022::instruction::transfer_checked(
+            token_program_info.key,
+            source_info.key,
+            mint_info.key,
+            vault_info.key,
+            admin_info.key,
+            &[],
+            funding,
+            decimals,
+        )?;
+        invoke(
+            &fund_ix,
+            &[
+                source_info.clone(),
+                mint_info.clone(),
+                vault_info.clone(),
+                admin_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        // Whatever the mint takes in transit, every claim must be backed
+        let funded = StateWithExtensions::<TokenAccount>::unpack(&vault_info.data.borrow())?.base.amount;
+        if funded < max_total_claim {
+            return Err(DistributorError::VaultUnderfunded.into());
+        }
+
+        let distributor = Distributor {
+            is_initialized: true,
+            admin: *admin_info.key,
+            mint: *mint_info.key,
+            token_program: *token_program_info.key,
+            nonce,
+            merkle_root,
+            max_total_claim,
+            total_claimed: 0,
+            num_nodes,
+            num_claimed: 0,
+            expiry,
+            clawed_back: false,
+            bump,
+            vault_bump,
+            claimed: vec![0; Distributor::bitmap_len(num_nodes)],
+        };
+        distributor.save(distributor_info)?;
+
+        SoulEvent::DistributorCreated {
+            distributor: *distributor_info.key,
+            mint: *mint_info.key,
+            admin: *admin_info.key,
+            merkle_root,
+            max_total_claim,
+            num_nodes,
+            expiry,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_claim(
+        accounts: &[AccountInfo],
+        index: u64,
+        amount: u64,
+        proof: &[[u8; 32]],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let claimant_info = next_account_info(account_info_iter)?;
+        let distributor_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+
+        if !claimant_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut distributor = load_distributor(distributor_info, vault_info, mint_info, token_program_info, program_id)?;
+        let clock = Clock::get()?;
+        if distributor.clawed_back || clock.unix_timestamp >= distributor.expiry {
+            return Err(DistributorError::ClaimExpired.into());
+        }
+        if index >= distributor.num_nodes {
+            return Err(DistributorError::InvalidIndex.into());
+        }
+        if distributor.is_claimed(index) {
+            return Err(DistributorError::AlreadyClaimed.into());
+        }
+        let leaf = merkle::leaf_hash(index, claimant_info.key, amount);
+        if !merkle::verify(proof, &distributor.merkle_root, leaf) {
+            return Err(DistributorError::InvalidProof.into());
+        }
+
+        distributor.total_claimed = distributor
+            .total_claimed
+            .checked_add(amount)
+            .filter(|total| *total <= distributor.max_total_claim)
+            .ok_or(DistributorError::ExceedsMaxClaim)?;
+        distributor.num_claimed += 1;
+        distributor.set_claimed(index);
+        access::record_outflow(breaker_info, ModuleTag::Distributor, amount, program_id)?;
+
+        pay_from_vault(&distributor, distributor_info, vault_info, mint_info, destination_info, token_program_info, amount)?;
+        distributor.save(distributor_info)?;
+
+        SoulEvent::AirdropClaimed {
+            distributor: *distributor_info.key,
+            claimant: *claimant_info.key,
+            index,
+            amount,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_clawback(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let distributor_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        if !admin_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut distributor = load_distributor(distributor_info, vault_info, mint_info, token_program_info, program_id)?;
+        if distributor.admin != *admin_info.key {
+            return Err(DistributorError::InvalidAuthority.into());
+        }
+        let clock = Clock::get()?;
+        if clock.unix_timestamp < distributor.expiry {
+            return Err(DistributorError::NotExpired.into());
+        }
+        if distributor.clawed_back {
+            return Err(DistributorError::AlreadyClawedBack.into());
+        }
+
+        let amount = StateWithExtensions::<TokenAccount>::unpack(&vault_info.data.borrow())?.base.amount;
+        pay_from_vault(&distributor, distributor_info, vault_info, mint_info, destination_info, token_program_info, amount)?;
+        distributor.clawed_back = true;
+        distributor.save(distributor_info)?;
+
+        SoulEvent::AirdropClawedBack {
+            distributor: *distributor_info.key,
+            destination: *destination_info.key,
+            amount,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_close_distributor(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let admin_info = next_account_info(account_info_iter)?;
+        let distributor_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let destination_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let distributor = load_distributor(distributor_info, vault_info, mint_info, token_program_info, program_id)?;
+        if distributor.admin != *admin_info.key {
+            return Err(SOuLError::RentPayerMismatch.into());
+        }
+        if !distributor.clawed_back {
+            return Err(DistributorError::NotClawedBack.into());
+        }
+        let destination = StateWithExtensions::<TokenAccount>::unpack(&destination_info.data.borrow())?.base;
+        if destination.owner != distributor.admin || destination.mint != distributor.mint {
+            return Err(DistributorError::InvalidDestination.into());
+        }
+
+        pda::close_token_vault(
+            vault_info,
+            mint_info,
+            vault_info,
+            destination_info,
+            admin_info,
+            token_program_info,
+            &[Distributor::VAULT_SEED, distributor_info.key.as_ref(), &[distributor.vault_bump]],
+        )?;
+
+        let lamports = pda::close_to_payer(distributor_info, admin_info, &distributor.admin)?;
+
+        SoulEvent::AccountClosed {
+            module: ModuleTag::Distributor,
+            account: *distributor_info.key,
+            payer: *admin_info.key,
+            lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+}
+
+/// Load a distributor and check the vault, mint and token program passed
+/// alongside it
+fn load_distributor(
+    distributor_info: &AccountInfo,
+    vault_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+    program_id: &Pubkey,
+) -> Result<Distributor, ProgramError> {
+    let distributor = Distributor::load(distributor_info, program_id)?;
+    if distributor.mint != *mint_info.key || distributor.token_program != *token_program_info.key {
+        return Err(DistributorError::InvalidDistributor.into());
+    }
+    let vault = Pubkey::create_program_address(
+        &[Distributor::VAULT_SEED, distributor_info.key.as_ref(), &[distributor.vault_bump]],
+        program_id,
+    )?;
+    if vault != *vault_info.key {
+        return Err(DistributorError::InvalidVault.into());
+    }
+    Ok(distributor)
+}
+
+/// Move `amount` out of the vault, which signs for itself
+fn pay_from_vault<'a>(
+    distributor: &Distributor,
+    distributor_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint_info.data.borrow())?.base.decimals;
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        token_program_info.key,
+        vault_info.key,
+        mint_info.key,
+        destination_info.key,
+        vault_info.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &transfer_ix,
+        &[
+            vault_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[Distributor::VAULT_SEED, distributor_info.key.as_ref(), &[distributor.vault_bump]]],
+    )
+}
@@ -0,0 +1,82 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{clock::UnixTimestamp, pubkey::Pubkey};
+
+use crate::account::{AccountState, HEADER_LEN};
+
+/// An airdrop of `max_total_claim` tokens to the wallets committed to by a
+/// Merkle root (`["distributor", admin, nonce]`).
+///
+/// The tokens sit in the vault PDA (`["distributor_vault", distributor]`), a
+/// token account that owns itself. The claim bitmap follows the fixed fields
+/// and is sized for `num_nodes` when the distributor is created, so the
+/// account takes `Distributor::space(num_nodes)` bytes rather than `SPACE`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Distributor {
+    pub is_initialized: bool,
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    /// The token program that owns the mint and the vault
+    pub token_program: Pubkey,
+    pub nonce: u64,
+    pub merkle_root: [u8; 32],
+    pub max_total_claim: u64,
+    pub total_claimed: u64,
+    /// Leaves in the tree
+    pub num_nodes: u64,
+    pub num_claimed: u64,
+    /// Claims close and the admin may claw back what is left from here on
+    pub expiry: UnixTimestamp,
+    pub clawed_back: bool,
+    pub bump: u8,
+    pub vault_bump: u8,
+    /// Bit `index` is set once leaf `index` has been claimed
+    pub claimed: Vec<u8>,
+}
+
+impl Distributor {
+    /// Most leaves a single distributor's bitmap can track. An 8 KiB bitmap
+    /// keeps the account within the `MAX_PERMITTED_DATA_INCREASE` a PDA
+    /// created by CPI can be allocated.
+    pub const MAX_NODES: u64 = 1 << 16;
+
+    /// Fixed fields, and the length prefix of an empty bitmap
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 1 + 1 + 4;
+
+    /// Seed of the distributor PDA (`["distributor", admin, nonce]`)
+    pub const SEED: &'static [u8] = b"distributor";
+
+    /// Seed of the vault PDA (`["distributor_vault", distributor]`)
+    pub const VAULT_SEED: &'static [u8] = b"distributor_vault";
+
+    pub fn find_address(admin: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, admin.as_ref(), &nonce.to_le_bytes()], program_id)
+    }
+
+    pub fn find_vault(distributor: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::VAULT_SEED, distributor.as_ref()], program_id)
+    }
+
+    /// Bytes of a claim bitmap covering `num_nodes` leaves
+    pub fn bitmap_len(num_nodes: u64) -> usize {
+        num_nodes.div_ceil(8) as usize
+    }
+
+    /// Space of a distributor with `num_nodes` leaves, header included
+    pub fn space(num_nodes: u64) -> usize {
+        Self::SPACE + Self::bitmap_len(num_nodes)
+    }
+
+    pub fn is_claimed(&self, index: u64) -> bool {
+        self.claimed[(index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u64) {
+        self.claimed[(index / 8) as usize] |= 1 << (index % 8);
+    }
+}
+
+impl AccountState for Distributor {
+    const DISCRIMINATOR: [u8; 8] = *b"distribu";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
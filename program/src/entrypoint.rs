@@ -2,18 +2,12 @@ use solana_program::{
     account_info::AccountInfo,
     entrypoint,
     entrypoint::ProgramResult,
-    pubkey::Pubkey,
     program_error::ProgramError,
+    pubkey::Pubkey,
     msg,
 };
-use borsh::BorshDeserialize;
 
-mod instruction;
-mod error;
-mod state;
-mod processor;
-
-use instruction::SOuLInstruction;
+use crate::{error::decode_error, processor};
 
 entrypoint!(process_instruction);
 
@@ -23,9 +17,10 @@ pub fn process_instruction(
     instruction_data: &[u8],
 ) -> ProgramResult {
     msg!("SOuL Sanctuary Token Program Entry");
-    
-    let instruction = SOuLInstruction::try_from_slice(instruction_data)
-        .map_err(|_| ProgramError::InvalidInstructionData)?;
-        
-    processor::process_instruction(program_id, accounts, instruction)
+
+    let result = processor::process_instruction(program_id, accounts, instruction_data);
+    if let Err(ProgramError::Custom(code)) = result {
+        msg!("Error {}: {}", code, decode_error(code));
+    }
+    result
 }
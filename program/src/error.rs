@@ -1,35 +1,119 @@
-use solana_program::program_error::ProgramError;
-use thiserror::Error;
-
-#[derive(Error, Debug, Copy, Clone)]
-pub enum SOuLError {
-    #[error("Invalid Instruction")]
-    InvalidInstruction,
-    
-    #[error("Not Rent Exempt")]
-    NotRentExempt,
-    
-    #[error("Expected Amount Mismatch")]
-    ExpectedAmountMismatch,
-    
-    #[error("Amount Overflow")]
-    AmountOverflow,
-
-    #[error("Invalid Token Type")]
-    InvalidTokenType,
-
-    #[error("Invalid Authority")]
-    InvalidAuthority,
-
-    #[error("Insufficient Funds")]
-    InsufficientFunds,
-
-    #[error("Token Already Initialized")]
-    AlreadyInitialized,
+//! Custom error codes shared by every module.
+//!
+//! Each module owns a range of `ERROR_RANGE` codes starting at its
+//! `ModuleTag` times `ERROR_RANGE`, so a bare `Custom(code)` identifies both
+//! the module that raised it and the error: token errors are 0-999, vesting
+//! 1000-1999, impact 2000-2999, LST 3000-3999, game 4000-4999, multisig
+//! 5000-5999, access 6000-6999 and distributor 7000-7999. Variants are
+//! numbered in declaration order, so new ones go at the end.
+
+use crate::{
+    access::AccessError, distributor::DistributorError, game::GameError, impact::ImpactError,
+    lst::LSTError, multisig::MultisigError, vesting::VestingError,
+};
+
+/// Number of custom error codes reserved for each module
+pub const ERROR_RANGE: u32 = 1000;
+
+/// Declare a module's error enum along with its place in the error space
+macro_rules! module_error {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident in $module:ident {
+            $($variant:ident => $message:literal,)*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(thiserror::Error, Debug, Clone, Copy, PartialEq, Eq)]
+        pub enum $name {
+            $(
+                #[error($message)]
+                $variant,
+            )*
+        }
+
+        impl $name {
+            /// Every variant, in code order
+            pub const ALL: &'static [Self] = &[$(Self::$variant),*];
+
+            /// First code of this module's range
+            pub const BASE: u32 =
+                $crate::instruction::ModuleTag::$module as u32 * $crate::error::ERROR_RANGE;
+
+            /// Code reported in `ProgramError::Custom`
+            pub fn code(self) -> u32 {
+                Self::BASE + self as u32
+            }
+
+            /// The error reported under `code`, if it falls in this module's range
+            pub fn from_code(code: u32) -> Option<Self> {
+                let index = code.checked_sub(Self::BASE)?;
+                Self::ALL.get(index as usize).copied()
+            }
+
+            pub fn message(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $message,)*
+                }
+            }
+        }
+
+        impl From<$name> for solana_program::program_error::ProgramError {
+            fn from(e: $name) -> Self {
+                solana_program::program_error::ProgramError::Custom(e.code())
+            }
+        }
+
+        impl<T> solana_program::decode_error::DecodeError<T> for $name {
+            fn type_of() -> &'static str {
+                stringify!($name)
+            }
+        }
+    };
 }
 
-impl From<SOuLError> for ProgramError {
-    fn from(e: SOuLError) -> Self {
-        ProgramError::Custom(e as u32)
+pub(crate) use module_error;
+
+module_error! {
+    pub enum SOuLError in Token {
+        InvalidInstruction => "Invalid Instruction",
+        NotRentExempt => "Not Rent Exempt",
+        ExpectedAmountMismatch => "Expected Amount Mismatch",
+        AmountOverflow => "Amount Overflow",
+        InvalidTokenType => "Invalid Token Type",
+        InvalidAuthority => "Invalid Authority",
+        InsufficientFunds => "Insufficient Funds",
+        AlreadyInitialized => "Token Already Initialized",
+        InvalidAccountType => "Account Type Mismatch",
+        AccountVersionMismatch => "Account Layout Version Mismatch",
+        InvalidTransferFee => "Invalid Transfer Fee",
+        TransferFeeNotEnabled => "Transfer Fee Not Enabled",
+        InvalidMetadata => "Invalid Token Metadata",
+        SupplyCapExceeded => "Supply Cap Exceeded",
+        InvalidEmissionSchedule => "Invalid Emission Schedule",
+        EmissionAlreadyConfigured => "Emission Already Configured",
+        EmissionNotConfigured => "Emission Not Configured",
+        NothingToEmit => "Nothing To Emit",
+        AccountFrozen => "Token Account Frozen",
+        AccountNotFrozen => "Token Account Not Frozen",
+        InvalidRetirement => "Invalid Retirement",
+        ReportNotVerified => "Impact Report Not Verified",
+        RentPayerMismatch => "Rent Payer Mismatch",
+        ReportNotFinal => "Impact Report Still Open To Dispute",
+        RetirementExceedsOffset => "Retirement Exceeds Verified Offset",
     }
 }
+
+/// Describe a custom error code raised by any module of the program
+pub fn decode_error(code: u32) -> &'static str {
+    SOuLError::from_code(code)
+        .map(SOuLError::message)
+        .or_else(|| VestingError::from_code(code).map(VestingError::message))
+        .or_else(|| ImpactError::from_code(code).map(ImpactError::message))
+        .or_else(|| LSTError::from_code(code).map(LSTError::message))
+        .or_else(|| GameError::from_code(code).map(GameError::message))
+        .or_else(|| MultisigError::from_code(code).map(MultisigError::message))
+        .or_else(|| AccessError::from_code(code).map(AccessError::message))
+        .or_else(|| DistributorError::from_code(code).map(DistributorError::message))
+        .unwrap_or("Unknown Error")
+}
@@ -0,0 +1,78 @@
+//! Turn transaction log messages back into [`SoulEvent`]s.
+//!
+//! The runtime writes each `sol_log_data` call as a `Program data:` line of
+//! space-separated base64 fields. Lines are attributed to the program on top
+//! of the invocation stack, so data logged by other programs, including ones
+//! this program calls through CPI, is skipped.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshDeserialize;
+use solana_program::pubkey::Pubkey;
+use thiserror::Error;
+
+use super::SoulEvent;
+
+const INVOKE_PREFIX: &str = "Program ";
+const DATA_PREFIX: &str = "Program data: ";
+
+/// Errors from decoding events out of transaction logs
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EventDecodeError {
+    #[error("Program data is not valid base64")]
+    InvalidBase64,
+    #[error("Program data is tagged as an event but does not decode")]
+    InvalidEvent,
+}
+
+/// Decode every event logged by `program_id` in a transaction's log messages
+pub fn decode_logs<S: AsRef<str>>(
+    program_id: &Pubkey,
+    logs: &[S],
+) -> Result<Vec<SoulEvent>, EventDecodeError> {
+    let program_id = program_id.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for line in logs {
+        let line = line.as_ref();
+        if let Some(data) = line.strip_prefix(DATA_PREFIX) {
+            if invocations.last() == Some(&program_id.as_str()) {
+                if let Some(event) = decode_data(data)? {
+                    events.push(event);
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix(INVOKE_PREFIX) {
+            let mut words = rest.split(' ');
+            let (Some(program), Some(status)) = (words.next(), words.next()) else {
+                continue;
+            };
+            match status {
+                "invoke" => invocations.push(program),
+                "success" | "failed:" => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+/// Decode the base64 fields of one `Program data:` line.
+///
+/// Returns `None` for data that is not tagged as a [`SoulEvent`].
+pub fn decode_data(data: &str) -> Result<Option<SoulEvent>, EventDecodeError> {
+    let fields = data
+        .split(' ')
+        .map(|field| STANDARD.decode(field))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| EventDecodeError::InvalidBase64)?;
+
+    match fields.as_slice() {
+        [tag, event] if *tag == SoulEvent::TAG => SoulEvent::try_from_slice(event)
+            .map(Some)
+            .map_err(|_| EventDecodeError::InvalidEvent),
+        _ => Ok(None),
+    }
+}
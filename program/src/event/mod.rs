@@ -0,0 +1,328 @@
+//! Structured events logged with `sol_log_data`.
+//!
+//! Each event is written as two data fields: the 8-byte [`SoulEvent::TAG`]
+//! followed by the Borsh-encoded [`SoulEvent`]. Off-chain consumers can turn
+//! transaction logs back into events with the [`decoder`] module.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{entrypoint::ProgramResult, log::sol_log_data, pubkey::Pubkey};
+
+use crate::{access::Role, instruction::ModuleTag, lst::LSTType, state::ComplianceAction};
+
+#[cfg(not(target_os = "solana"))]
+pub mod decoder;
+
+/// A state change made by the program
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SoulEvent {
+    /// SOuL moved between token accounts
+    Transferred {
+        mint: Pubkey,
+        source: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+
+    /// SOuL minted by the mint authority
+    Minted {
+        mint: Pubkey,
+        destination: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+        total_supply: u64,
+        timestamp: i64,
+    },
+
+    /// SOuL burned from a token account
+    Burned {
+        mint: Pubkey,
+        source: Pubkey,
+        authority: Pubkey,
+        amount: u64,
+        total_supply: u64,
+        timestamp: i64,
+    },
+
+    /// Vested tokens paid out to the beneficiary
+    VestingReleased {
+        vesting_account: Pubkey,
+        beneficiary: Pubkey,
+        amount: u64,
+        released_amount: u64,
+        timestamp: i64,
+    },
+
+    /// A vesting schedule revoked and its balance recovered
+    VestingRevoked {
+        vesting_account: Pubkey,
+        authority: Pubkey,
+        recovery_account: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+
+    /// An impact report submitted against a metrics account
+    ReportSubmitted {
+        report: Pubkey,
+        metrics_account: Pubkey,
+        authority: Pubkey,
+        proof_hash: [u8; 32],
+        timestamp: i64,
+    },
+
+    /// A pending report approved or rejected by a verifier
+    ReportVerified {
+        report: Pubkey,
+        verifier: Pubkey,
+        approved: bool,
+        timestamp: i64,
+    },
+
+    /// A verified report challenged, with the stake held in escrow
+    DisputeOpened {
+        dispute: Pubkey,
+        report: Pubkey,
+        challenger: Pubkey,
+        stake_amount: u64,
+        evidence_hash: [u8; 32],
+        timestamp: i64,
+    },
+
+    /// A dispute settled and its escrowed stake paid to `recipient`
+    DisputeResolved {
+        dispute: Pubkey,
+        report: Pubkey,
+        authority: Pubkey,
+        in_favor_of_report: bool,
+        recipient: Pubkey,
+        stake_amount: u64,
+        timestamp: i64,
+    },
+
+    /// SOuL staked into an LST pool
+    Staked {
+        pool: Pubkey,
+        staker: Pubkey,
+        amount: u64,
+        lst_amount: u64,
+        total_staked: u64,
+        timestamp: i64,
+    },
+
+    /// An unstake request started its cooldown
+    UnstakeRequested {
+        pool: Pubkey,
+        staker: Pubkey,
+        amount: u64,
+        unlock_time: i64,
+        timestamp: i64,
+    },
+
+    /// LST burned and SOuL, less the pool fee, returned to the staker
+    Unstaked {
+        pool: Pubkey,
+        staker: Pubkey,
+        amount: u64,
+        lst_amount: u64,
+        fee: u64,
+        total_staked: u64,
+        timestamp: i64,
+    },
+
+    /// Staking rewards paid out of a pool
+    RewardsClaimed {
+        pool: Pubkey,
+        staker: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+
+    /// A player finished their active quest
+    QuestCompleted {
+        player: Pubkey,
+        quest_id: u64,
+        experience: u64,
+        eco_tokens: u64,
+        timestamp: i64,
+    },
+
+    /// Items swapped between two players
+    ItemsTraded {
+        player: Pubkey,
+        target: Pubkey,
+        offered_items: Vec<u64>,
+        requested_items: Vec<u64>,
+        timestamp: i64,
+    },
+
+    /// Withheld transfer fees swept into the treasury
+    FeesHarvested {
+        mint: Pubkey,
+        treasury: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+
+    /// An instruction proposed to a multisig
+    ProposalCreated {
+        multisig: Pubkey,
+        proposal: Pubkey,
+        proposer: Pubkey,
+        index: u64,
+        timestamp: i64,
+    },
+
+    /// A multisig signer approved a proposal
+    ProposalApproved {
+        multisig: Pubkey,
+        proposal: Pubkey,
+        signer: Pubkey,
+        approvals: u32,
+        timestamp: i64,
+    },
+
+    /// A proposal executed under the multisig's signature
+    ProposalExecuted {
+        multisig: Pubkey,
+        proposal: Pubkey,
+        timestamp: i64,
+    },
+
+    /// Scheduled SOuL emission minted into the allocation buckets
+    Emitted {
+        mint: Pubkey,
+        epoch: u64,
+        staking: u64,
+        game_rewards: u64,
+        verifier_fund: u64,
+        emitted: u64,
+        total_supply: u64,
+        timestamp: i64,
+    },
+
+    /// A role granted in the roles account
+    RoleGranted {
+        member: Pubkey,
+        role: Role,
+        admin: Pubkey,
+        timestamp: i64,
+    },
+
+    /// A role taken away in the roles account
+    RoleRevoked {
+        member: Pubkey,
+        role: Role,
+        admin: Pubkey,
+        timestamp: i64,
+    },
+
+    /// The authority of an account handed over to a new key
+    AuthorityTransferred {
+        account: Pubkey,
+        previous_authority: Pubkey,
+        new_authority: Pubkey,
+        timestamp: i64,
+    },
+
+    /// A module paused or resumed by a guardian
+    PauseSet {
+        module: ModuleTag,
+        paused: bool,
+        guardian: Pubkey,
+        timestamp: i64,
+    },
+
+    /// A SOuL token account frozen or thawed under a compliance hold
+    ComplianceHold {
+        token_account: Pubkey,
+        mint: Pubkey,
+        action: ComplianceAction,
+        reason_hash: [u8; 32],
+        officer: Pubkey,
+        timestamp: i64,
+    },
+
+    /// An airdrop's Merkle root published and its vault funded
+    DistributorCreated {
+        distributor: Pubkey,
+        mint: Pubkey,
+        admin: Pubkey,
+        merkle_root: [u8; 32],
+        max_total_claim: u64,
+        num_nodes: u64,
+        expiry: i64,
+        timestamp: i64,
+    },
+
+    /// An airdrop leaf claimed by its wallet
+    AirdropClaimed {
+        distributor: Pubkey,
+        claimant: Pubkey,
+        index: u64,
+        amount: u64,
+        timestamp: i64,
+    },
+
+    /// What was left in an expired airdrop's vault returned by its admin
+    AirdropClawedBack {
+        distributor: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+
+    /// SOuL or LST burned to retire carbon credits under a certificate
+    Retired {
+        certificate: Pubkey,
+        owner: Pubkey,
+        source: Pubkey,
+        mint: Pubkey,
+        category: LSTType,
+        amount: u64,
+        beneficiary_hash: [u8; 32],
+        reports: Vec<Pubkey>,
+        timestamp: i64,
+    },
+
+    /// A finished account closed and its rent returned to the payer
+    AccountClosed {
+        module: ModuleTag,
+        account: Pubkey,
+        payer: Pubkey,
+        lamports: u64,
+        timestamp: i64,
+    },
+
+    /// SOuL taken out of a pool's vault by its authority in an emergency
+    EmergencyWithdrawn {
+        pool: Pubkey,
+        authority: Pubkey,
+        destination: Pubkey,
+        amount: u64,
+        timestamp: i64,
+    },
+}
+
+impl SoulEvent {
+    /// First data field of every event, telling it apart from other logged data
+    pub const TAG: [u8; 8] = *b"soulevnt";
+
+    /// Log the event as program data
+    pub fn emit(&self) -> ProgramResult {
+        let data = self.try_to_vec()?;
+        sol_log_data(&[&Self::TAG, &data]);
+        Ok(())
+    }
+
+    /// Decode an event from the data fields of one `sol_log_data` call.
+    ///
+    /// Returns `None` for data that was not logged by [`SoulEvent::emit`].
+    pub fn from_log_data<T: AsRef<[u8]>>(fields: &[T]) -> Option<Self> {
+        match fields {
+            [tag, data] if tag.as_ref() == Self::TAG => Self::try_from_slice(data.as_ref()).ok(),
+            _ => None,
+        }
+    }
+}
@@ -1,23 +1,33 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
-use super::state::{Quest, Item, Collectible, GeoLocation, EnvironmentalImpact};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+use crate::{access::ProgramConfig, error::module_error, instruction::ModuleTag};
+use super::state::{EnvironmentalImpact, GameState, GeoLocation, PlayerState};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum GameInstruction {
     /// Initialize the game state
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority initializing the game
-    /// 1. `[writable]` The game state account
+    /// 0. `[writable, signer]` The authority initializing the game, pays rent
+    /// 1. `[writable]` The game state PDA to create (`["game"]`)
     /// 2. `[]` The system program
+    /// 3. `[]` The program config (`["config"]`)
     InitializeGame,
 
     /// Initialize a new player
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The player
-    /// 1. `[writable]` The player state account
-    /// 2. `[]` The system program
+    /// 0. `[writable, signer]` The player, pays rent
+    /// 1. `[writable]` The player state PDA to create (`["player", player]`)
+    /// 2. `[writable]` The game state account
+    /// 3. `[]` The system program
+    /// 4. `[]` The program config (`["config"]`)
     InitializePlayer,
 
     /// Start a new quest
@@ -27,6 +37,7 @@ pub enum GameInstruction {
     /// 1. `[writable]` The player state account
     /// 2. `[writable]` The game state account
     /// 3. `[]` The clock sysvar
+    /// 4. `[]` The program config (`["config"]`)
     StartQuest {
         quest_id: u64,
     },
@@ -37,9 +48,8 @@ pub enum GameInstruction {
     /// 0. `[signer]` The player
     /// 1. `[writable]` The player state account
     /// 2. `[writable]` The game state account
-    /// 3. `[writable]` The player's token account
-    /// 4. `[]` The token program
-    /// 5. `[]` The clock sysvar
+    /// 3. `[]` The clock sysvar
+    /// 4. `[]` The program config (`["config"]`)
     CompleteQuest {
         quest_id: u64,
         environmental_data: EnvironmentalImpact,
@@ -53,6 +63,7 @@ pub enum GameInstruction {
     /// 1. `[writable]` The player state account
     /// 2. `[writable]` The game state account
     /// 3. `[]` The clock sysvar
+    /// 4. `[]` The program config (`["config"]`)
     CollectData {
         data_type: DataType,
         location: GeoLocation,
@@ -64,17 +75,17 @@ pub enum GameInstruction {
     /// Accounts expected:
     /// 0. `[signer]` The player
     /// 1. `[writable]` The player state account
+    /// 2. `[]` The program config (`["config"]`)
     UseItem {
         item_id: u64,
     },
 
-    /// Craft a new item
+    /// Craft a new item from research points and energy crystals
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The player
     /// 1. `[writable]` The player state account
-    /// 2. `[writable]` The player's token account
-    /// 3. `[]` The token program
+    /// 2. `[]` The program config (`["config"]`)
     CraftItem {
         recipe_id: u64,
     },
@@ -85,7 +96,8 @@ pub enum GameInstruction {
     /// 0. `[signer]` The initiating player
     /// 1. `[writable]` The initiating player's state account
     /// 2. `[writable]` The target player's state account
-    /// 3. `[]` The clock sysvar
+    /// 3. `[signer]` The target player
+    /// 4. `[]` The program config (`["config"]`)
     TradeItems {
         offered_items: Vec<u64>,
         requested_items: Vec<u64>,
@@ -97,28 +109,29 @@ pub enum GameInstruction {
     /// Accounts expected:
     /// 0. `[signer]` The player
     /// 1. `[writable]` The player state account
-    /// 2. `[writable]` The player's token account
-    /// 3. `[]` The token program
+    /// 2. `[]` The program config (`["config"]`)
     ClaimAchievement {
         achievement_id: u64,
     },
 
-    /// Restore player energy
+    /// Restore player energy by spending energy crystals
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The player
     /// 1. `[writable]` The player state account
-    /// 2. `[writable]` The player's token account
-    /// 3. `[]` The token program
+    /// 2. `[]` The program config (`["config"]`)
     RestoreEnergy {
         amount: u8,
     },
 
-    /// Update player stats
+    /// Update player stats. A GameMaster may sign in place of the game
+    /// authority when the roles account is passed before the program config.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The authority
     /// 1. `[writable]` The player state account
+    /// 2. `[]` The game state account
+    /// 3. `[]` The program config (`["config"]`)
     UpdatePlayerStats {
         experience_gain: u64,
         level_up: bool,
@@ -136,21 +149,234 @@ pub enum DataType {
     WeatherData,
 }
 
-#[derive(Debug)]
-pub enum GameError {
-    InvalidInstruction,
-    NotRentExempt,
-    AlreadyInitialized,
-    NotInitialized,
-    InvalidPlayer,
-    InvalidQuest,
-    QuestNotStarted,
-    QuestAlreadyCompleted,
-    InsufficientEnergy,
-    InsufficientLevel,
-    InvalidItem,
-    InvalidTrade,
-    InvalidAchievement,
-    InvalidLocation,
-    InvalidEnvironmentalData,
+impl GameInstruction {
+    pub fn initialize_game(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (game_state, _) = GameState::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(game_state, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Game.pack(&GameInstruction::InitializeGame)?,
+        })
+    }
+
+    pub fn initialize_player(
+        program_id: &Pubkey,
+        player: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (player_state, _) = PlayerState::find_address(player, program_id);
+        let (game_state, _) = GameState::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*player, true),
+                AccountMeta::new(player_state, false),
+                AccountMeta::new(game_state, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Game.pack(&GameInstruction::InitializePlayer)?,
+        })
+    }
+
+    pub fn start_quest(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        quest_id: u64,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::quest_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::StartQuest { quest_id })?,
+        })
+    }
+
+    pub fn complete_quest(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        quest_id: u64,
+        environmental_data: EnvironmentalImpact,
+        location: GeoLocation,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::quest_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::CompleteQuest {
+                quest_id,
+                environmental_data,
+                location,
+            })?,
+        })
+    }
+
+    pub fn collect_data(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        data_type: DataType,
+        location: GeoLocation,
+        impact: EnvironmentalImpact,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::quest_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::CollectData {
+                data_type,
+                location,
+                impact,
+            })?,
+        })
+    }
+
+    pub fn use_item(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        item_id: u64,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::player_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::UseItem { item_id })?,
+        })
+    }
+
+    pub fn craft_item(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        recipe_id: u64,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::player_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::CraftItem { recipe_id })?,
+        })
+    }
+
+    /// Both players sign the trade
+    pub fn trade_items(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        target_player: &Pubkey,
+        offered_items: Vec<u64>,
+        requested_items: Vec<u64>,
+    ) -> Result<Instruction, ProgramError> {
+        let (player_state, _) = PlayerState::find_address(player, program_id);
+        let (target_state, _) = PlayerState::find_address(target_player, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*player, true),
+                AccountMeta::new(player_state, false),
+                AccountMeta::new(target_state, false),
+                AccountMeta::new_readonly(*target_player, true),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Game.pack(&GameInstruction::TradeItems {
+                offered_items,
+                requested_items,
+                target_player: *target_player,
+            })?,
+        })
+    }
+
+    pub fn claim_achievement(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        achievement_id: u64,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::player_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::ClaimAchievement { achievement_id })?,
+        })
+    }
+
+    pub fn restore_energy(
+        program_id: &Pubkey,
+        player: &Pubkey,
+        amount: u8,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: Self::player_accounts(program_id, player),
+            data: ModuleTag::Game.pack(&GameInstruction::RestoreEnergy { amount })?,
+        })
+    }
+
+    pub fn update_player_stats(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        player: &Pubkey,
+        experience_gain: u64,
+        level_up: bool,
+    ) -> Result<Instruction, ProgramError> {
+        let (player_state, _) = PlayerState::find_address(player, program_id);
+        let (game_state, _) = GameState::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(player_state, false),
+                AccountMeta::new_readonly(game_state, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Game.pack(&GameInstruction::UpdatePlayerStats {
+                experience_gain,
+                level_up,
+            })?,
+        })
+    }
+
+    /// The player signing for their own state account
+    fn player_accounts(program_id: &Pubkey, player: &Pubkey) -> Vec<AccountMeta> {
+        let (player_state, _) = PlayerState::find_address(player, program_id);
+        vec![
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new(player_state, false),
+            ProgramConfig::account_meta(program_id),
+        ]
+    }
+
+    /// The player accounts followed by the game state and the clock
+    fn quest_accounts(program_id: &Pubkey, player: &Pubkey) -> Vec<AccountMeta> {
+        let (player_state, _) = PlayerState::find_address(player, program_id);
+        let (game_state, _) = GameState::find_address(program_id);
+        vec![
+            AccountMeta::new_readonly(*player, true),
+            AccountMeta::new(player_state, false),
+            AccountMeta::new(game_state, false),
+            AccountMeta::new_readonly(sysvar::clock::id(), false),
+            ProgramConfig::account_meta(program_id),
+        ]
+    }
+}
+
+module_error! {
+    pub enum GameError in Game {
+        InvalidInstruction => "Invalid Instruction",
+        NotRentExempt => "Not Rent Exempt",
+        AlreadyInitialized => "Already Initialized",
+        NotInitialized => "Not Initialized",
+        InvalidPlayer => "Invalid Player",
+        InvalidQuest => "Invalid Quest",
+        QuestNotStarted => "Quest Not Started",
+        QuestAlreadyCompleted => "Quest Already Completed",
+        InsufficientEnergy => "Insufficient Energy",
+        InsufficientLevel => "Insufficient Level",
+        InvalidItem => "Invalid Item",
+        InvalidTrade => "Invalid Trade",
+        InvalidAchievement => "Invalid Achievement",
+        InvalidLocation => "Invalid Location",
+        InvalidEnvironmentalData => "Invalid Environmental Data",
+    }
 }
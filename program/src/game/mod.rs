@@ -0,0 +1,7 @@
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+pub use instruction::*;
+pub use processor::*;
+pub use state::*;
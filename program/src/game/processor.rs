@@ -0,0 +1,607 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{
+    access::{is_authorized, Role},
+    account::AccountState,
+    event::SoulEvent,
+    pda,
+};
+
+use super::{
+    instruction::{DataType, GameError, GameInstruction},
+    state::{
+        Achievement, Collectible, CollectibleType, EnvironmentalImpact, GameState, GeoLocation,
+        Item, ItemType, PlayerState, Quest, Rarity,
+    },
+};
+
+pub struct Processor;
+
+impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = GameInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        match instruction {
+            GameInstruction::InitializeGame => {
+                Self::process_initialize_game(accounts, program_id)
+            }
+            GameInstruction::InitializePlayer => {
+                Self::process_initialize_player(accounts, program_id)
+            }
+            GameInstruction::StartQuest { quest_id } => {
+                Self::process_start_quest(accounts, quest_id, program_id)
+            }
+            GameInstruction::CompleteQuest { quest_id, environmental_data, location } => {
+                Self::process_complete_quest(accounts, quest_id, environmental_data, location, program_id)
+            }
+            GameInstruction::CollectData { data_type, location, impact } => {
+                Self::process_collect_data(accounts, data_type, location, impact, program_id)
+            }
+            GameInstruction::UseItem { item_id } => {
+                Self::process_use_item(accounts, item_id, program_id)
+            }
+            GameInstruction::CraftItem { recipe_id } => {
+                Self::process_craft_item(accounts, recipe_id, program_id)
+            }
+            GameInstruction::TradeItems { offered_items, requested_items, target_player } => {
+                Self::process_trade_items(accounts, offered_items, requested_items, target_player, program_id)
+            }
+            GameInstruction::ClaimAchievement { achievement_id } => {
+                Self::process_claim_achievement(accounts, achievement_id, program_id)
+            }
+            GameInstruction::RestoreEnergy { amount } => {
+                Self::process_restore_energy(accounts, amount, program_id)
+            }
+            GameInstruction::UpdatePlayerStats { experience_gain, level_up } => {
+                Self::process_update_player_stats(accounts, experience_gain, level_up, program_id)
+            }
+        }
+    }
+
+    /// Load a player account owned by the program and check it belongs to `player`
+    fn load_player(
+        player_state_info: &AccountInfo,
+        player: &Pubkey,
+        program_id: &Pubkey,
+    ) -> Result<PlayerState, ProgramError> {
+        if player_state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let player_state = PlayerState::load(player_state_info, program_id)?;
+        if player_state.owner != *player {
+            return Err(ProgramError::from(GameError::InvalidPlayer));
+        }
+
+        Ok(player_state)
+    }
+
+    fn load_game(game_state_info: &AccountInfo, program_id: &Pubkey) -> Result<GameState, ProgramError> {
+        if game_state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let game_state = GameState::load(game_state_info, program_id)?;
+        if !game_state.is_initialized {
+            return Err(ProgramError::from(GameError::NotInitialized));
+        }
+
+        Ok(game_state)
+    }
+
+    fn process_initialize_game(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let game_state_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if game_state_info.owner == program_id {
+            return Err(ProgramError::from(GameError::AlreadyInitialized));
+        }
+
+        let bump = pda::expect_pda(game_state_info.key, &[GameState::SEED], program_id)?;
+        pda::create_pda_account(
+            authority_info,
+            game_state_info,
+            system_program_info,
+            program_id,
+            GameState::SPACE,
+            &[GameState::SEED, &[bump]],
+        )?;
+
+        let game_state = GameState {
+            is_initialized: true,
+            authority: *authority_info.key,
+            total_players: 0,
+            total_quests_completed: 0,
+            total_rewards_distributed: 0,
+            last_update: Clock::get()?.unix_timestamp,
+            bump,
+        };
+
+        game_state.save(game_state_info)?;
+        Ok(())
+    }
+
+    fn process_initialize_player(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+        let game_state_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if player_state_info.owner == program_id {
+            return Err(ProgramError::from(GameError::AlreadyInitialized));
+        }
+
+        let mut game_state = Self::load_game(game_state_info, program_id)?;
+        game_state.total_players = game_state.total_players.saturating_add(1);
+
+        let bump = pda::expect_pda(
+            player_state_info.key,
+            &[PlayerState::SEED, player_info.key.as_ref()],
+            program_id,
+        )?;
+        pda::create_pda_account(
+            player_info,
+            player_state_info,
+            system_program_info,
+            program_id,
+            PlayerState::SPACE,
+            &[PlayerState::SEED, player_info.key.as_ref(), &[bump]],
+        )?;
+
+        let player_state = PlayerState::new(*player_info.key, bump);
+
+        player_state.save(player_state_info)?;
+        game_state.save(game_state_info)?;
+        Ok(())
+    }
+
+    fn process_start_quest(
+        accounts: &[AccountInfo],
+        quest_id: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+        let game_state_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+        Self::load_game(game_state_info, program_id)?;
+
+        let quest = Quest::from_id(quest_id).ok_or(GameError::InvalidQuest)?;
+        if player_state.active_quest.is_some() {
+            return Err(ProgramError::from(GameError::InvalidQuest));
+        }
+        if player_state.level < quest.requirements.min_level {
+            return Err(ProgramError::from(GameError::InsufficientLevel));
+        }
+        if !player_state.can_start_quest(&quest) {
+            return Err(ProgramError::from(GameError::InsufficientEnergy));
+        }
+
+        player_state.energy -= quest.requirements.energy_cost;
+        player_state.active_quest = Some(quest_id);
+        player_state.last_quest_time = Clock::from_account_info(clock_info)?.unix_timestamp;
+
+        player_state.save(player_state_info)?;
+        Ok(())
+    }
+
+    fn process_complete_quest(
+        accounts: &[AccountInfo],
+        quest_id: u64,
+        environmental_data: EnvironmentalImpact,
+        location: GeoLocation,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+        let game_state_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+        let mut game_state = Self::load_game(game_state_info, program_id)?;
+
+        if player_state.active_quest != Some(quest_id) {
+            return Err(ProgramError::from(GameError::QuestNotStarted));
+        }
+        if !location.is_valid() {
+            return Err(ProgramError::from(GameError::InvalidLocation));
+        }
+        if environmental_data.biodiversity_impact > 1000 {
+            return Err(ProgramError::from(GameError::InvalidEnvironmentalData));
+        }
+
+        let quest = Quest::from_id(quest_id).ok_or(GameError::InvalidQuest)?;
+        let clock = Clock::from_account_info(clock_info)?;
+
+        player_state.add_experience(quest.rewards.experience);
+        player_state.inventory.resources.eco_tokens = player_state
+            .inventory
+            .resources
+            .eco_tokens
+            .saturating_add(quest.rewards.eco_tokens);
+        player_state.rewards_earned = player_state.rewards_earned.saturating_add(quest.rewards.eco_tokens);
+        player_state.quests_completed = player_state.quests_completed.saturating_add(1);
+        player_state.active_quest = None;
+        player_state.collection_power = player_state.calculate_collection_power();
+
+        game_state.total_quests_completed = game_state.total_quests_completed.saturating_add(1);
+        game_state.total_rewards_distributed = game_state
+            .total_rewards_distributed
+            .saturating_add(quest.rewards.eco_tokens);
+        game_state.last_update = clock.unix_timestamp;
+
+        player_state.save(player_state_info)?;
+        game_state.save(game_state_info)?;
+
+        SoulEvent::QuestCompleted {
+            player: *player_info.key,
+            quest_id,
+            experience: quest.rewards.experience,
+            eco_tokens: quest.rewards.eco_tokens,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_collect_data(
+        accounts: &[AccountInfo],
+        data_type: DataType,
+        location: GeoLocation,
+        impact: EnvironmentalImpact,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+        let game_state_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+        let mut game_state = Self::load_game(game_state_info, program_id)?;
+
+        if !location.is_valid() {
+            return Err(ProgramError::from(GameError::InvalidLocation));
+        }
+        if impact.biodiversity_impact > 1000 {
+            return Err(ProgramError::from(GameError::InvalidEnvironmentalData));
+        }
+
+        const COLLECTION_ENERGY_COST: u8 = 5;
+        if player_state.energy < COLLECTION_ENERGY_COST {
+            return Err(ProgramError::from(GameError::InsufficientEnergy));
+        }
+        if player_state.inventory.collectibles.len() >= PlayerState::MAX_COLLECTIBLES {
+            return Err(ProgramError::from(GameError::InvalidItem));
+        }
+
+        let collectible_type = match data_type {
+            DataType::AirQuality | DataType::WeatherData => CollectibleType::Conservation,
+            DataType::WaterQuality => CollectibleType::WaterSource,
+            DataType::SoilComposition => CollectibleType::Ecosystem,
+            DataType::Biodiversity => CollectibleType::Wildlife,
+            DataType::ForestDensity => CollectibleType::Tree,
+            DataType::EnergyUsage => CollectibleType::CleanEnergy,
+        };
+
+        let clock = Clock::from_account_info(clock_info)?;
+        let collectible = Collectible {
+            id: player_state.inventory.collectibles.len() as u64,
+            collectible_type,
+            rarity: Rarity::Common,
+            power: 10,
+            location,
+            timestamp: clock.unix_timestamp,
+            environmental_impact: impact,
+        };
+
+        player_state.energy -= COLLECTION_ENERGY_COST;
+        player_state.inventory.collectibles.push(collectible);
+        player_state.inventory.resources.research_points =
+            player_state.inventory.resources.research_points.saturating_add(10);
+        player_state.add_experience(25);
+        player_state.collection_power = player_state.calculate_collection_power();
+        game_state.last_update = clock.unix_timestamp;
+
+        player_state.save(player_state_info)?;
+        game_state.save(game_state_info)?;
+        Ok(())
+    }
+
+    fn process_use_item(
+        accounts: &[AccountInfo],
+        item_id: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+
+        let index = player_state
+            .inventory
+            .items
+            .iter()
+            .position(|item| item.id == item_id)
+            .ok_or(GameError::InvalidItem)?;
+
+        // Items are consumed and restore energy in proportion to their power
+        let item = player_state.inventory.items.remove(index);
+        let restored = (item.power / 10).min(PlayerState::MAX_ENERGY as u16) as u8;
+        player_state.energy = player_state.energy.saturating_add(restored).min(PlayerState::MAX_ENERGY);
+        player_state.collection_power = player_state.calculate_collection_power();
+
+        player_state.save(player_state_info)?;
+        Ok(())
+    }
+
+    fn process_craft_item(
+        accounts: &[AccountInfo],
+        recipe_id: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+
+        let item_type = match recipe_id {
+            0 => ItemType::Sensor,
+            1 => ItemType::Drone,
+            2 => ItemType::WaterTester,
+            3 => ItemType::SoilAnalyzer,
+            4 => ItemType::BiodiversityScanner,
+            5 => ItemType::EnergyMeter,
+            6 => ItemType::WeatherStation,
+            _ => return Err(ProgramError::from(GameError::InvalidItem)),
+        };
+
+        let research_cost = 100 * (recipe_id + 1);
+        let crystal_cost = 10 * (recipe_id + 1);
+        let resources = &mut player_state.inventory.resources;
+        if resources.research_points < research_cost || resources.energy_crystals < crystal_cost {
+            return Err(ProgramError::from(GameError::InvalidItem));
+        }
+        if player_state.inventory.items.len() >= PlayerState::MAX_ITEMS {
+            return Err(ProgramError::from(GameError::InvalidItem));
+        }
+
+        resources.research_points -= research_cost;
+        resources.energy_crystals -= crystal_cost;
+
+        let next_id = player_state
+            .inventory
+            .items
+            .iter()
+            .map(|item| item.id + 1)
+            .max()
+            .unwrap_or(0);
+        player_state.inventory.items.push(Item {
+            id: next_id,
+            item_type,
+            rarity: Rarity::Common,
+            power: 50 + 10 * recipe_id as u16,
+            attributes: Vec::new(),
+        });
+        player_state.collection_power = player_state.calculate_collection_power();
+
+        player_state.save(player_state_info)?;
+        Ok(())
+    }
+
+    fn process_trade_items(
+        accounts: &[AccountInfo],
+        offered_items: Vec<u64>,
+        requested_items: Vec<u64>,
+        target_player: Pubkey,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+        let target_state_info = next_account_info(account_info_iter)?;
+        let target_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer || !target_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if *target_info.key != target_player || player_state_info.key == target_state_info.key {
+            return Err(ProgramError::from(GameError::InvalidTrade));
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+        let mut target_state = Self::load_player(target_state_info, target_info.key, program_id)?;
+
+        let offered = Self::take_items(&mut player_state, &offered_items)?;
+        let requested = Self::take_items(&mut target_state, &requested_items)?;
+
+        if player_state.inventory.items.len() + requested.len() > PlayerState::MAX_ITEMS
+            || target_state.inventory.items.len() + offered.len() > PlayerState::MAX_ITEMS
+        {
+            return Err(ProgramError::from(GameError::InvalidTrade));
+        }
+
+        player_state.inventory.items.extend(requested);
+        target_state.inventory.items.extend(offered);
+        player_state.collection_power = player_state.calculate_collection_power();
+        target_state.collection_power = target_state.calculate_collection_power();
+
+        player_state.save(player_state_info)?;
+        target_state.save(target_state_info)?;
+
+        SoulEvent::ItemsTraded {
+            player: *player_info.key,
+            target: target_player,
+            offered_items,
+            requested_items,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    /// Remove the listed items from a player's inventory
+    fn take_items(player_state: &mut PlayerState, item_ids: &[u64]) -> Result<Vec<Item>, ProgramError> {
+        let mut taken = Vec::with_capacity(item_ids.len());
+        for item_id in item_ids {
+            let index = player_state
+                .inventory
+                .items
+                .iter()
+                .position(|item| item.id == *item_id)
+                .ok_or(GameError::InvalidTrade)?;
+            taken.push(player_state.inventory.items.remove(index));
+        }
+        Ok(taken)
+    }
+
+    fn process_claim_achievement(
+        accounts: &[AccountInfo],
+        achievement_id: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+
+        let (achievement_type, reward) = Achievement::from_id(achievement_id).ok_or(GameError::InvalidAchievement)?;
+        if player_state.achievements.iter().any(|achievement| achievement.id == achievement_id)
+            || player_state.achievements.len() >= PlayerState::MAX_ACHIEVEMENTS
+            || !player_state.meets_achievement(&achievement_type)
+        {
+            return Err(ProgramError::from(GameError::InvalidAchievement));
+        }
+
+        player_state.achievements.push(Achievement {
+            id: achievement_id,
+            achievement_type,
+            timestamp: Clock::get()?.unix_timestamp,
+            reward,
+        });
+        player_state.inventory.resources.eco_tokens =
+            player_state.inventory.resources.eco_tokens.saturating_add(reward);
+        player_state.rewards_earned = player_state.rewards_earned.saturating_add(reward);
+
+        player_state.save(player_state_info)?;
+        Ok(())
+    }
+
+    fn process_restore_energy(
+        accounts: &[AccountInfo],
+        amount: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let player_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+
+        if !player_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut player_state = Self::load_player(player_state_info, player_info.key, program_id)?;
+
+        let restored = amount.min(PlayerState::MAX_ENERGY - player_state.energy);
+        let resources = &mut player_state.inventory.resources;
+        if resources.energy_crystals < restored as u64 {
+            return Err(ProgramError::from(GameError::InsufficientEnergy));
+        }
+
+        resources.energy_crystals -= restored as u64;
+        player_state.energy += restored;
+
+        player_state.save(player_state_info)?;
+        Ok(())
+    }
+
+    fn process_update_player_stats(
+        accounts: &[AccountInfo],
+        experience_gain: u64,
+        level_up: bool,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let player_state_info = next_account_info(account_info_iter)?;
+        let game_state_info = next_account_info(account_info_iter)?;
+        let roles_info = account_info_iter.next();
+
+        if player_state_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let game_state = Self::load_game(game_state_info, program_id)?;
+        if !is_authorized(authority_info, &game_state.authority, Role::GameMaster, roles_info, program_id)? {
+            return Err(ProgramError::from(GameError::InvalidPlayer));
+        }
+
+        let mut player_state = PlayerState::load(player_state_info, program_id)?;
+        player_state.add_experience(experience_gain);
+        if level_up {
+            player_state.level = player_state.level.saturating_add(1);
+        }
+
+        player_state.save(player_state_info)?;
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@ use solana_program::{
     clock::UnixTimestamp,
 };
 
+use crate::account::{AccountState, HEADER_LEN};
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct GameState {
     pub is_initialized: bool,
@@ -12,6 +14,7 @@ pub struct GameState {
     pub total_quests_completed: u64,
     pub total_rewards_distributed: u64,
     pub last_update: UnixTimestamp,
+    pub bump: u8,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -26,6 +29,8 @@ pub struct PlayerState {
     pub last_quest_time: UnixTimestamp,
     pub energy: u8,  // 0-100
     pub collection_power: u64,
+    pub active_quest: Option<u64>,
+    pub bump: u8,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -176,7 +181,166 @@ pub enum Rarity {
     Legendary,
 }
 
+impl GameState {
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    /// Seed of the singleton game state PDA (`["game"]`)
+    pub const SEED: &'static [u8] = b"game";
+
+    pub fn find_address(program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED], program_id)
+    }
+}
+
+impl AccountState for GameState {
+    const DISCRIMINATOR: [u8; 8] = *b"gamestat";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl GeoLocation {
+    pub const LEN: usize = 8 + 8 + 4;
+
+    pub fn is_valid(&self) -> bool {
+        (-90_000_000..=90_000_000).contains(&self.latitude)
+            && (-180_000_000..=180_000_000).contains(&self.longitude)
+    }
+}
+
+impl EnvironmentalImpact {
+    pub const LEN: usize = 8 + 8 + 8 + 2;
+}
+
+impl Item {
+    pub const MAX_ATTRIBUTES: usize = 4;
+    pub const LEN: usize = 8 + 1 + 1 + 2 + 4 + Self::MAX_ATTRIBUTES * (1 + 2);
+}
+
+impl Collectible {
+    pub const LEN: usize = 8 + 1 + 1 + 2 + GeoLocation::LEN + 8 + EnvironmentalImpact::LEN;
+}
+
+impl Achievement {
+    pub const LEN: usize = 8 + 1 + 8 + 8;
+
+    /// Map an achievement id to its type and eco-token reward
+    pub fn from_id(achievement_id: u64) -> Option<(AchievementType, u64)> {
+        let achievement_type = match achievement_id {
+            0 => AchievementType::DataCollector,
+            1 => AchievementType::Conservationist,
+            2 => AchievementType::WaterGuardian,
+            3 => AchievementType::EnergyInnovator,
+            4 => AchievementType::BiodiversityHero,
+            5 => AchievementType::CommunityLeader,
+            _ => return None,
+        };
+        Some((achievement_type, (achievement_id + 1) * 100))
+    }
+}
+
+impl Quest {
+    /// Built-in quest catalog, indexed by quest id
+    pub fn from_id(quest_id: u64) -> Option<Quest> {
+        let (quest_type, difficulty, min_level) = match quest_id {
+            0 => (QuestType::DataCollection, 1, 1),
+            1 => (QuestType::Conservation, 2, 2),
+            2 => (QuestType::Research, 3, 3),
+            3 => (QuestType::Community, 2, 1),
+            4 => (QuestType::Special, 5, 5),
+            _ => return None,
+        };
+
+        Some(Quest {
+            id: quest_id,
+            quest_type,
+            difficulty,
+            rewards: QuestRewards {
+                experience: 100 * difficulty as u64,
+                eco_tokens: 10 * difficulty as u64,
+                items: Vec::new(),
+                achievement: None,
+            },
+            requirements: QuestRequirements {
+                min_level,
+                required_items: Vec::new(),
+                required_collectibles: Vec::new(),
+                energy_cost: 10 * difficulty,
+            },
+            location: None,
+            time_limit: None,
+        })
+    }
+}
+
 impl PlayerState {
+    pub const MAX_ITEMS: usize = 32;
+    pub const MAX_COLLECTIBLES: usize = 32;
+    pub const MAX_ACHIEVEMENTS: usize = 16;
+    pub const MAX_ENERGY: u8 = 100;
+    pub const EXPERIENCE_PER_LEVEL: u64 = 1_000;
+
+    pub const LEN: usize = 32 + 8 + 2 + 8 + 8
+        + 4 + Self::MAX_ITEMS * Item::LEN
+        + 4 + Self::MAX_COLLECTIBLES * Collectible::LEN
+        + 8 + 8 + 8
+        + 4 + Self::MAX_ACHIEVEMENTS * Achievement::LEN
+        + 8 + 1 + 8 + 1 + 8 + 1;
+
+    /// Seed of the player state PDA (`["player", owner]`)
+    pub const SEED: &'static [u8] = b"player";
+
+    pub fn find_address(owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, owner.as_ref()], program_id)
+    }
+
+    pub fn new(owner: Pubkey, bump: u8) -> Self {
+        Self {
+            owner,
+            experience: 0,
+            level: 1,
+            quests_completed: 0,
+            rewards_earned: 0,
+            inventory: PlayerInventory {
+                items: Vec::new(),
+                collectibles: Vec::new(),
+                resources: Resources {
+                    energy_crystals: 0,
+                    eco_tokens: 0,
+                    research_points: 0,
+                },
+            },
+            achievements: Vec::new(),
+            last_quest_time: 0,
+            energy: Self::MAX_ENERGY,
+            collection_power: 0,
+            active_quest: None,
+            bump,
+        }
+    }
+
+    pub fn add_experience(&mut self, experience: u64) {
+        self.experience = self.experience.saturating_add(experience);
+        let level = 1 + self.experience / Self::EXPERIENCE_PER_LEVEL;
+        self.level = self.level.max(level.min(u16::MAX as u64) as u16);
+    }
+
+    pub fn count_collectibles(&self, collectible_type: &CollectibleType) -> usize {
+        self.inventory.collectibles.iter()
+            .filter(|collectible| collectible.collectible_type == *collectible_type)
+            .count()
+    }
+
+    pub fn meets_achievement(&self, achievement_type: &AchievementType) -> bool {
+        match achievement_type {
+            AchievementType::DataCollector => self.inventory.collectibles.len() >= 10,
+            AchievementType::Conservationist => self.quests_completed >= 10,
+            AchievementType::WaterGuardian => self.count_collectibles(&CollectibleType::WaterSource) >= 5,
+            AchievementType::EnergyInnovator => self.count_collectibles(&CollectibleType::CleanEnergy) >= 5,
+            AchievementType::BiodiversityHero => self.count_collectibles(&CollectibleType::Wildlife) >= 5,
+            AchievementType::CommunityLeader => self.quests_completed >= 25,
+        }
+    }
+
     pub fn can_start_quest(&self, quest: &Quest) -> bool {
         self.level >= quest.requirements.min_level &&
         self.energy >= quest.requirements.energy_cost &&
@@ -210,3 +374,9 @@ impl PlayerState {
         item_power + collectible_power + (self.level as u64 * 100)
     }
 }
+
+impl AccountState for PlayerState {
+    const DISCRIMINATOR: [u8; 8] = *b"playerst";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
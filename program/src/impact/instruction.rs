@@ -1,76 +1,106 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
-use super::state::{ImpactMetrics, GeoLocation};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+use crate::{access::{CanonicalMint, ProgramConfig, Roles}, error::module_error, instruction::ModuleTag, lst::LSTType, state::SOuLToken};
+use super::state::{DisputeCase, GeoLocation, ImpactMetrics, ImpactReport, VerifierState};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum ImpactInstruction {
     /// Initialize a new impact metrics account
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority initializing the metrics
-    /// 1. `[writable]` The impact metrics account to initialize
+    /// 0. `[writable, signer]` The authority initializing the metrics, pays rent
+    /// 1. `[writable]` The impact metrics PDA to create (`["metrics", authority]`)
     /// 2. `[]` The system program
+    /// 3. `[]` The program config (`["config"]`)
     InitializeMetrics,
 
-    /// Submit a new impact report
+    /// Submit a new impact report, whose carbon offset backs retired
+    /// credits of `category` once it is final
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority submitting the report
-    /// 1. `[writable]` The impact report account
+    /// 0. `[writable, signer]` The authority submitting the report, pays rent
+    /// 1. `[writable]` The impact report PDA to create (`["report", metrics, proof_hash]`)
     /// 2. `[writable]` The impact metrics account to update
     /// 3. `[]` The clock sysvar
+    /// 4. `[]` The system program
+    /// 5. `[]` The program config (`["config"]`)
     SubmitReport {
         location: GeoLocation,
         metrics: ImpactMetrics,
         proof_hash: [u8; 32],
+        category: LSTType,
     },
 
-    /// Register as a verifier
+    /// Register as a verifier, staking at least `VerifierState::MIN_STAKE`
+    /// whole tokens of the canonical SOuL mint
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The verifier to register
-    /// 1. `[writable]` The verifier state account
-    /// 2. `[writable]` The stake token account
-    /// 3. `[]` The token program
+    /// 0. `[writable, signer]` The verifier to register, pays rent
+    /// 1. `[writable]` The verifier state PDA to create (`["verifier", verifier]`)
+    /// 2. `[writable]` The verifier's token account the stake is taken from
+    /// 3. `[writable]` The stake vault token account, owned by the verifier state PDA
+    /// 4. `[]` The SOuL token config (`["soul_token", mint]`) of the staked mint
+    /// 5. `[]` The canonical SOuL mint (`["canonical_mint"]`)
+    /// 6. `[]` The token program
+    /// 7. `[]` The system program
+    /// 8. `[]` The program config (`["config"]`)
     RegisterVerifier {
         stake_amount: u64,
     },
 
-    /// Verify an impact report
+    /// Verify an impact report. A verifier cannot verify reports filed
+    /// against their own impact metrics account.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The verifier
     /// 1. `[writable]` The impact report account
     /// 2. `[writable]` The verifier state account
-    /// 3. `[]` The clock sysvar
+    /// 3. `[]` The impact metrics account the report was submitted against
+    /// 4. `[]` The clock sysvar
+    /// 5. `[]` The program config (`["config"]`)
     VerifyReport {
         approved: bool,
         verification_notes_hash: [u8; 32],
     },
 
-    /// Submit a dispute for an impact report
+    /// Submit a dispute for a verified impact report, within
+    /// `ImpactReport::DISPUTE_WINDOW_SECONDS` of its verification
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The challenger
-    /// 1. `[writable]` The dispute case account
+    /// 0. `[writable, signer]` The challenger, pays rent
+    /// 1. `[writable]` The dispute case PDA to create (`["dispute", report, challenger]`)
     /// 2. `[writable]` The impact report account
     /// 3. `[writable]` The challenger's stake account
-    /// 4. `[]` The token program
+    /// 4. `[writable]` The dispute escrow token account, owned by the dispute case PDA
+    /// 5. `[]` The token program
+    /// 6. `[]` The system program
+    /// 7. `[]` The canonical SOuL mint (`["canonical_mint"]`), the mint of the escrow
+    /// 8. `[]` The program config (`["config"]`)
     SubmitDispute {
         evidence_hash: [u8; 32],
         stake_amount: u64,
     },
 
-    /// Resolve a dispute case
+    /// Resolve a dispute case as a DisputeResolver, paying the escrowed
+    /// stake to a token account of the winner in the escrow's mint
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority resolving the dispute
+    /// 0. `[signer]` A DisputeResolver
     /// 1. `[writable]` The dispute case account
     /// 2. `[writable]` The impact report account
     /// 3. `[writable]` The verifier state account
-    /// 4. `[writable]` The challenger's stake account
-    /// 5. `[writable]` The verifier's stake account
+    /// 4. `[writable]` The challenger's stake account, paid if the report is rejected
+    /// 5. `[writable]` The verifier's stake account, paid if the report stands
     /// 6. `[]` The token program
+    /// 7. `[writable]` The dispute escrow token account
+    /// 8. `[]` The roles account
+    /// 9. `[]` The program config (`["config"]`)
     ResolveDispute {
         in_favor_of_report: bool,
         resolution_notes_hash: [u8; 32],
@@ -82,23 +112,265 @@ pub enum ImpactInstruction {
     /// 0. `[signer]` The authority updating the metrics
     /// 1. `[writable]` The impact metrics account
     /// 2. `[]` The clock sysvar
+    /// 3. `[]` The program config (`["config"]`)
     UpdateMetrics {
         new_metrics: ImpactMetrics,
     },
+
+    /// Close a resolved dispute case and its escrow, returning their rent to
+    /// the payer of the dispute. Tokens sent to the escrow after the stake
+    /// was paid out go to the challenger first.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The dispute case account
+    /// 1. `[writable]` The dispute escrow token account
+    /// 2. `[]` The mint of the escrow
+    /// 3. `[writable]` The challenger's token account, which takes anything left in the escrow
+    /// 4. `[writable]` The payer recorded in the dispute case
+    /// 5. `[]` The token program
+    /// 6. `[]` The program config (`["config"]`)
+    CloseDispute,
+}
+
+impl ImpactInstruction {
+    pub fn initialize_metrics(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (metrics_account, _) = ImpactMetrics::find_address(authority, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(metrics_account, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::InitializeMetrics)?,
+        })
+    }
+
+    /// Files a report against the metrics account of `authority`
+    pub fn submit_report(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        location: GeoLocation,
+        metrics: ImpactMetrics,
+        proof_hash: [u8; 32],
+        category: LSTType,
+    ) -> Result<Instruction, ProgramError> {
+        let (metrics_account, _) = ImpactMetrics::find_address(authority, program_id);
+        let (report, _) = ImpactReport::find_address(&metrics_account, &proof_hash, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(report, false),
+                AccountMeta::new(metrics_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::SubmitReport {
+                location,
+                metrics,
+                proof_hash,
+                category,
+            })?,
+        })
+    }
+
+    pub fn register_verifier(
+        program_id: &Pubkey,
+        verifier: &Pubkey,
+        mint: &Pubkey,
+        stake_account: &Pubkey,
+        stake_vault: &Pubkey,
+        stake_amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (verifier_state, _) = VerifierState::find_address(verifier, program_id);
+        let (soul_token, _) = SOuLToken::find_address(mint, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*verifier, true),
+                AccountMeta::new(verifier_state, false),
+                AccountMeta::new(*stake_account, false),
+                AccountMeta::new(*stake_vault, false),
+                AccountMeta::new_readonly(soul_token, false),
+                AccountMeta::new_readonly(CanonicalMint::find_address(program_id).0, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::RegisterVerifier { stake_amount })?,
+        })
+    }
+
+    pub fn verify_report(
+        program_id: &Pubkey,
+        verifier: &Pubkey,
+        report: &Pubkey,
+        metrics_account: &Pubkey,
+        approved: bool,
+        verification_notes_hash: [u8; 32],
+    ) -> Result<Instruction, ProgramError> {
+        let (verifier_state, _) = VerifierState::find_address(verifier, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*verifier, true),
+                AccountMeta::new(*report, false),
+                AccountMeta::new(verifier_state, false),
+                AccountMeta::new_readonly(*metrics_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::VerifyReport {
+                approved,
+                verification_notes_hash,
+            })?,
+        })
+    }
+
+    pub fn submit_dispute(
+        program_id: &Pubkey,
+        challenger: &Pubkey,
+        report: &Pubkey,
+        challenger_stake_account: &Pubkey,
+        escrow: &Pubkey,
+        evidence_hash: [u8; 32],
+        stake_amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (dispute, _) = DisputeCase::find_address(report, challenger, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*challenger, true),
+                AccountMeta::new(dispute, false),
+                AccountMeta::new(*report, false),
+                AccountMeta::new(*challenger_stake_account, false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(CanonicalMint::find_address(program_id).0, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::SubmitDispute {
+                evidence_hash,
+                stake_amount,
+            })?,
+        })
+    }
+
+    /// `verifier` is the verifier of the disputed report
+    #[allow(clippy::too_many_arguments)]
+    pub fn resolve_dispute(
+        program_id: &Pubkey,
+        resolver: &Pubkey,
+        report: &Pubkey,
+        challenger: &Pubkey,
+        verifier: &Pubkey,
+        challenger_stake_account: &Pubkey,
+        verifier_stake_account: &Pubkey,
+        escrow: &Pubkey,
+        in_favor_of_report: bool,
+        resolution_notes_hash: [u8; 32],
+    ) -> Result<Instruction, ProgramError> {
+        let (dispute, _) = DisputeCase::find_address(report, challenger, program_id);
+        let (verifier_state, _) = VerifierState::find_address(verifier, program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*resolver, true),
+                AccountMeta::new(dispute, false),
+                AccountMeta::new(*report, false),
+                AccountMeta::new(verifier_state, false),
+                AccountMeta::new(*challenger_stake_account, false),
+                AccountMeta::new(*verifier_stake_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new_readonly(roles, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::ResolveDispute {
+                in_favor_of_report,
+                resolution_notes_hash,
+            })?,
+        })
+    }
+
+    pub fn update_metrics(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        new_metrics: ImpactMetrics,
+    ) -> Result<Instruction, ProgramError> {
+        let (metrics_account, _) = ImpactMetrics::find_address(authority, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(metrics_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::UpdateMetrics { new_metrics })?,
+        })
+    }
+
+    pub fn close_dispute(
+        program_id: &Pubkey,
+        report: &Pubkey,
+        challenger: &Pubkey,
+        escrow: &Pubkey,
+        mint: &Pubkey,
+        challenger_token_account: &Pubkey,
+        payer: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (dispute, _) = DisputeCase::find_address(report, challenger, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(dispute, false),
+                AccountMeta::new(*escrow, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*challenger_token_account, false),
+                AccountMeta::new(*payer, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Impact.pack(&ImpactInstruction::CloseDispute)?,
+        })
+    }
 }
 
-#[derive(Debug)]
-pub enum ImpactError {
-    InvalidInstruction,
-    NotRentExempt,
-    AlreadyInitialized,
-    InvalidAuthority,
-    InvalidVerifier,
-    InsufficientStake,
-    InvalidReport,
-    InvalidDispute,
-    DisputeAlreadyResolved,
-    InvalidMetricsUpdate,
-    InvalidProof,
-    InvalidStakeAmount,
+module_error! {
+    pub enum ImpactError in Impact {
+        InvalidInstruction => "Invalid Instruction",
+        NotRentExempt => "Not Rent Exempt",
+        AlreadyInitialized => "Already Initialized",
+        InvalidAuthority => "Invalid Authority",
+        InvalidVerifier => "Invalid Verifier",
+        InsufficientStake => "Insufficient Stake",
+        InvalidReport => "Invalid Report",
+        InvalidDispute => "Invalid Dispute",
+        DisputeAlreadyResolved => "Dispute Already Resolved",
+        InvalidMetricsUpdate => "Invalid Metrics Update",
+        InvalidProof => "Invalid Proof",
+        InvalidStakeAmount => "Invalid Stake Amount",
+        DisputeNotResolved => "Dispute Not Resolved",
+        DisputeWindowClosed => "Dispute Window Closed",
+        InvalidStakeMint => "Stake Must Be SOuL",
+        SelfVerification => "Verifier Cannot Verify Their Own Report",
+        InvalidRecipient => "Stake Recipient Must Be The Winner's Token Account",
+    }
 }
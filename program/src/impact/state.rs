@@ -4,6 +4,11 @@ use solana_program::{
     clock::UnixTimestamp,
 };
 
+use crate::{
+    account::{AccountState, HEADER_LEN},
+    lst::LSTType,
+};
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ImpactMetrics {
     pub is_initialized: bool,
@@ -16,6 +21,7 @@ pub struct ImpactMetrics {
     pub energy_saved: u64,       // in kWh
     pub waste_recycled: u64,     // in kg
     pub impact_score: u64,       // calculated score
+    pub bump: u8,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -26,16 +32,25 @@ pub struct VerifierState {
     pub reputation_score: u16,   // 0-1000
     pub verified_reports: u64,
     pub stake_amount: u64,       // staked SOuL tokens
+    pub bump: u8,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ImpactReport {
+    /// The impact metrics account the report was submitted against
+    pub metrics_account: Pubkey,
     pub timestamp: UnixTimestamp,
     pub location: GeoLocation,
     pub metrics: ImpactMetrics,
     pub verifier: Pubkey,
     pub verification_status: VerificationStatus,
     pub proof_hash: [u8; 32],   // IPFS hash of supporting documents
+    pub bump: u8,
+    /// When the report was last upheld, which opens its dispute window
+    pub verified_at: Option<UnixTimestamp>,
+    /// The category of the credits its carbon offset backs; `None` for a
+    /// report filed before reports named one, which backs no credits
+    pub category: Option<LSTType>,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -45,6 +60,17 @@ pub struct GeoLocation {
     pub altitude: i32,  // in meters
 }
 
+/// One of the measured fields of `ImpactMetrics`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImpactMetric {
+    ForestArea,
+    CarbonOffset,
+    WaterSaved,
+    BiodiversityScore,
+    EnergySaved,
+    WasteRecycled,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub enum VerificationStatus {
     Pending,
@@ -61,9 +87,12 @@ pub struct DisputeCase {
     pub evidence_hash: [u8; 32], // IPFS hash of dispute evidence
     pub status: DisputeStatus,
     pub resolution_timestamp: Option<UnixTimestamp>,
+    pub bump: u8,
+    /// Challenger that paid the dispute's rent, refunded when it is closed
+    pub payer: Pubkey,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
 pub enum DisputeStatus {
     Active,
     ResolvedInFavorOfReport,
@@ -72,7 +101,14 @@ pub enum DisputeStatus {
 }
 
 impl ImpactMetrics {
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 8 + 2 + 8 + 8 + 8;
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 8 + 2 + 8 + 8 + 8 + 1;
+
+    /// Seed of the metrics PDA (`["metrics", authority]`)
+    pub const SEED: &'static [u8] = b"metrics";
+
+    pub fn find_address(authority: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, authority.as_ref()], program_id)
+    }
 
     pub fn calculate_impact_score(&self) -> u64 {
         // Base weights for different metrics
@@ -94,10 +130,94 @@ impl ImpactMetrics {
         // Sum all weighted scores
         forest_score + carbon_score + water_score + biodiversity_score + energy_score + waste_score
     }
+
+    /// Value of one measured field
+    pub fn value(&self, metric: ImpactMetric) -> u64 {
+        match metric {
+            ImpactMetric::ForestArea => self.forest_area,
+            ImpactMetric::CarbonOffset => self.carbon_offset,
+            ImpactMetric::WaterSaved => self.water_saved,
+            ImpactMetric::BiodiversityScore => self.biodiversity_score as u64,
+            ImpactMetric::EnergySaved => self.energy_saved,
+            ImpactMetric::WasteRecycled => self.waste_recycled,
+        }
+    }
+}
+
+impl AccountState for ImpactMetrics {
+    const DISCRIMINATOR: [u8; 8] = *b"impmetrc";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl ImpactReport {
+    pub const LEN: usize = 32 + 8 + GeoLocation::LEN + ImpactMetrics::LEN + 32 + 1 + 32 + 1 + 9 + 2;
+
+    /// Seed of the report PDA (`["report", metrics, proof_hash]`)
+    pub const SEED: &'static [u8] = b"report";
+
+    /// A verified report can be disputed for this long after it was upheld
+    pub const DISPUTE_WINDOW_SECONDS: i64 = 604_800; // 7 days
+
+    pub fn find_address(metrics: &Pubkey, proof_hash: &[u8; 32], program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, metrics.as_ref(), proof_hash], program_id)
+    }
+
+    /// Whether the report is verified and can no longer be disputed
+    pub fn is_final(&self, current_timestamp: UnixTimestamp) -> bool {
+        let window_passed = |verified_at: UnixTimestamp| {
+            current_timestamp >= verified_at.saturating_add(Self::DISPUTE_WINDOW_SECONDS)
+        };
+        self.verification_status == VerificationStatus::Verified && self.verified_at.is_some_and(window_passed)
+    }
+}
+
+impl AccountState for ImpactReport {
+    const DISCRIMINATOR: [u8; 8] = *b"imprport";
+    const VERSION: u8 = 3;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl GeoLocation {
+    pub const LEN: usize = 8 + 8 + 4;
+}
+
+impl DisputeCase {
+    pub const LEN: usize = 32 + 32 + 8 + 32 + 1 + 1 + 8 + 1 + 32;
+
+    /// Seed of the dispute PDA (`["dispute", report, challenger]`), which
+    /// also owns the dispute's stake escrow
+    pub const SEED: &'static [u8] = b"dispute";
+
+    pub fn find_address(report: &Pubkey, challenger: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, report.as_ref(), challenger.as_ref()], program_id)
+    }
+}
+
+impl AccountState for DisputeCase {
+    const DISCRIMINATOR: [u8; 8] = *b"dispcase";
+    const VERSION: u8 = 2;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
 }
 
 impl VerifierState {
-    pub const LEN: usize = 1 + 32 + 32 + 2 + 8 + 8;
+    pub const LEN: usize = 1 + 32 + 32 + 2 + 8 + 8 + 1;
+
+    /// Seed of the verifier PDA (`["verifier", verifier]`), which also owns
+    /// the verifier's stake vault
+    pub const SEED: &'static [u8] = b"verifier";
+
+    pub fn find_address(verifier: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, verifier.as_ref()], program_id)
+    }
+
+    /// Whole SOuL a verifier must stake to register
+    pub const MIN_STAKE: u64 = 1_000;
+
+    /// `MIN_STAKE` in base units of a mint with `decimals`
+    pub fn min_stake(decimals: u8) -> Option<u64> {
+        10u64.checked_pow(decimals as u32)?.checked_mul(Self::MIN_STAKE)
+    }
 
     pub fn update_reputation(&mut self, successful_verifications: u64, disputes_won: u64, disputes_lost: u64) {
         const BASE_SCORE: u16 = 500;
@@ -114,4 +234,18 @@ impl VerifierState {
             .saturating_sub(negative_score)
             .min(1000);
     }
+
+    pub fn record_dispute_outcome(&mut self, upheld: bool) {
+        self.reputation_score = if upheld {
+            self.reputation_score.saturating_add(20).min(1000)
+        } else {
+            self.reputation_score.saturating_sub(30)
+        };
+    }
+}
+
+impl AccountState for VerifierState {
+    const DISCRIMINATOR: [u8; 8] = *b"verifier";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
 }
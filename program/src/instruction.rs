@@ -6,24 +6,100 @@ use solana_program::{
     system_program,
 };
 
+use crate::{
+    access::{CanonicalMint, CircuitBreaker, ProgramConfig, Roles},
+    account::AccountType,
+    error::SOuLError,
+    lst::LSTType,
+    state::{
+        ComplianceHistory, ComplianceRecord, EmissionSchedule, LSTToken, LSTTokenType, ReportRetirement,
+        RetirementCertificate, SOuLToken, TokenMetadata,
+    },
+};
+
+/// Module tag that prefixes every instruction sent to the program.
+///
+/// The bytes after the tag are the Borsh encoding of that module's
+/// instruction enum, so one program ID serves every module.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ModuleTag {
+    Token = 0,
+    Vesting = 1,
+    Impact = 2,
+    LST = 3,
+    Game = 4,
+    Multisig = 5,
+    Access = 6,
+    Distributor = 7,
+}
+
+impl ModuleTag {
+    /// Split instruction data into its module tag and module payload
+    pub fn unpack(input: &[u8]) -> Result<(Self, &[u8]), ProgramError> {
+        let (tag, payload) = input
+            .split_first()
+            .ok_or(ProgramError::InvalidInstructionData)?;
+        let module = match tag {
+            0 => ModuleTag::Token,
+            1 => ModuleTag::Vesting,
+            2 => ModuleTag::Impact,
+            3 => ModuleTag::LST,
+            4 => ModuleTag::Game,
+            5 => ModuleTag::Multisig,
+            6 => ModuleTag::Access,
+            7 => ModuleTag::Distributor,
+            _ => return Err(SOuLError::InvalidInstruction.into()),
+        };
+        Ok((module, payload))
+    }
+
+    /// Whether the module can be paused. Instructions of a pausable module
+    /// carry the program config (`["config"]`) as their last account.
+    pub fn is_pausable(self) -> bool {
+        matches!(
+            self,
+            ModuleTag::Token
+                | ModuleTag::Vesting
+                | ModuleTag::Impact
+                | ModuleTag::LST
+                | ModuleTag::Game
+                | ModuleTag::Distributor
+        )
+    }
+
+    /// Encode a module instruction behind this tag
+    pub fn pack<T: BorshSerialize>(self, instruction: &T) -> Result<Vec<u8>, ProgramError> {
+        let mut data = vec![self as u8];
+        instruction.serialize(&mut data)?;
+        Ok(data)
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum SOuLInstruction {
-    /// Initialize a new SOuL token
+    /// Initialize a new SOuL token backed by an SPL mint
     /// Accounts expected:
-    /// 1. [signer] The account of the person initializing the token
-    /// 2. [writable] The token account to initialize
-    /// 3. [] The rent sysvar
-    /// 4. [] The token program
+    /// 1. [writable, signer] The account of the person initializing the token, pays rent
+    /// 2. [writable] The SOuL token config PDA to create (`["soul_token", mint]`)
+    /// 3. [writable] The uninitialized SPL mint
+    /// 4. [writable] The token account receiving the initial supply
+    /// 5. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 6. [] The token program
+    /// 7. [] The system program
+    /// 8. [writable] The token metadata PDA to create (`["metadata", token]`)
+    /// 9. [] The program config (`["config"]`)
     InitializeSOuL {
         total_supply: u64,
     },
 
-    /// Initialize a new LST token
+    /// Initialize a new LST token as an Admin or PoolManager
     /// Accounts expected:
-    /// 1. [signer] The account of the person initializing the token
-    /// 2. [writable] The token account to initialize
-    /// 3. [] The rent sysvar
-    /// 4. [] The token program
+    /// 1. [writable, signer] An Admin or PoolManager initializing the token, pays rent
+    /// 2. [writable] The LST token config PDA to create (`["lst_token", token_type]`)
+    /// 3. [] The system program
+    /// 4. [writable] The token metadata PDA to create (`["metadata", token]`)
+    /// 5. [] The roles account
+    /// 6. [] The program config (`["config"]`)
     InitializeLST {
         token_type: u8,
         total_supply: u64,
@@ -31,47 +107,424 @@ pub enum SOuLInstruction {
 
     /// Transfer tokens
     /// Accounts expected:
-    /// 1. [signer] The account of the person transferring tokens
-    /// 2. [writable] The source token account
-    /// 3. [writable] The destination token account
+    /// 1. [writable] The source token account
+    /// 2. [writable] The destination token account
+    /// 3. [signer] The owner of the source account
+    /// 4. [] The SOuL token config account
+    /// 5. [] The SPL mint
+    /// 6. [] The token program
+    /// 7. [] The program config (`["config"]`)
     Transfer {
         amount: u64,
     },
 
-    /// Mint new tokens
+    /// Mint new tokens. A Minter may sign in place of the mint authority
+    /// when the roles account is passed before the program config.
     /// Accounts expected:
-    /// 1. [signer] The mint authority
-    /// 2. [writable] The mint account
-    /// 3. [writable] The destination account
+    /// 1. [signer] The mint authority recorded in the SOuL token config
+    /// 2. [writable] The SOuL token config account
+    /// 3. [writable] The SPL mint
+    /// 4. [writable] The destination token account
+    /// 5. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 6. [] The token program
+    /// 7. [writable] The token circuit breaker (`["circuit_breaker", module]`)
+    /// 8. [] The program config (`["config"]`)
     Mint {
         amount: u64,
     },
 
     /// Burn tokens
     /// Accounts expected:
-    /// 1. [signer] The token owner
-    /// 2. [writable] The token account
+    /// 1. [writable] The token account to burn from
+    /// 2. [signer] The owner of the token account
+    /// 3. [writable] The SOuL token config account
+    /// 4. [writable] The SPL mint
+    /// 5. [] The token program
+    /// 6. [] The program config (`["config"]`)
     Burn {
         amount: u64,
     },
+
+    /// Upgrade an account written with an older layout to the current one.
+    /// Anyone may call this; the account grows if the new layout needs it.
+    /// A vesting schedule may only be migrated by its revoke authority, or its creator if it has none,
+    /// and an LST pool only by its authority.
+    /// Accounts expected:
+    /// 1. [writable, signer] The payer of any additional rent
+    /// 2. [writable] The account to migrate
+    /// 3. [] The system program
+    /// 4. [] The LST pool, metrics account, vesting token account or pool SOuL vault the migrated account belongs to
+    /// 5. [] The program config (`["config"]`)
+    Migrate {
+        account_type: AccountType,
+    },
+
+    /// Initialize a new SOuL token backed by a Token-2022 mint that withholds
+    /// a fee on every transfer
+    /// Accounts expected:
+    /// 1. [writable, signer] The account of the person initializing the token, pays rent
+    /// 2. [writable] The SOuL token config PDA to create (`["soul_token", mint]`)
+    /// 3. [writable] The uninitialized Token-2022 mint, sized for the transfer fee extension
+    /// 4. [writable] The token account receiving the initial supply
+    /// 5. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 6. [writable] The treasury token account PDA to create (`["treasury", token]`)
+    /// 7. [] The Token-2022 program
+    /// 8. [] The system program
+    /// 9. [writable] The token metadata PDA to create (`["metadata", token]`)
+    /// 10. [] The program config (`["config"]`)
+    InitializeSOuLWithTransferFee {
+        total_supply: u64,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    },
+
+    /// Sweep withheld transfer fees into the treasury. Anyone may call this.
+    /// Accounts expected:
+    /// 1. [] The SOuL token config account
+    /// 2. [writable] The Token-2022 mint
+    /// 3. [writable] The treasury token account (`["treasury", token]`)
+    /// 4. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 5. [] The Token-2022 program
+    /// 6. [writable] Any number of token accounts holding withheld fees
+    /// 7. [] The program config (`["config"]`)
+    HarvestFees,
+
+    /// Change the transfer fee. Token-2022 applies it two epochs later. An
+    /// Admin may sign in place of the mint authority when the roles account
+    /// is passed before the program config.
+    /// Accounts expected:
+    /// 1. [signer] The mint authority recorded in the SOuL token config
+    /// 2. [writable] The SOuL token config account
+    /// 3. [writable] The Token-2022 mint
+    /// 4. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 5. [] The Token-2022 program
+    /// 6. [] The program config (`["config"]`)
+    SetTransferFee {
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    },
+
+    /// Point a token's metadata at a new off-chain JSON document. An Admin
+    /// may sign in place of the update authority when the roles account is
+    /// passed before the program config.
+    /// Accounts expected:
+    /// 1. [signer] The update authority recorded in the metadata
+    /// 2. [writable] The token metadata account
+    /// 3. [] The program config (`["config"]`)
+    UpdateMetadataUri {
+        uri: String,
+    },
+
+    /// Fix the maximum supply and the emission schedule. This can only be
+    /// done once, and the schedule's `emitted` must be zero. An Admin may
+    /// sign in place of the mint authority when the roles account is passed
+    /// before the program config.
+    /// Accounts expected:
+    /// 1. [signer] The mint authority recorded in the SOuL token config
+    /// 2. [writable] The SOuL token config account
+    /// 3. [] The SPL mint
+    /// 4. [] The program config (`["config"]`)
+    ConfigureEmission {
+        max_supply: u64,
+        schedule: EmissionSchedule,
+    },
+
+    /// Mint what the emission schedule has released through the current
+    /// epoch into its buckets, up to the maximum supply. Anyone may call this.
+    /// Accounts expected:
+    /// 1. [writable] The SOuL token config account
+    /// 2. [writable] The SPL mint
+    /// 3. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 4. [] The token program that owns the mint
+    /// 5. [writable] The staking bucket's token account
+    /// 6. [writable] The game rewards bucket's token account
+    /// 7. [writable] The verifier fund bucket's token account
+    /// 8. [] The program config (`["config"]`)
+    Emit,
+
+    /// Freeze a SOuL token account under a compliance hold, recording the
+    /// hold and the hash of its reason in the account's compliance record.
+    /// The mint authority PDA is the mint's freeze authority. Runs while the
+    /// Token module is paused.
+    /// Accounts expected:
+    /// 1. [writable, signer] A Compliance role holder, pays rent for a new record
+    /// 2. [] The SOuL token config account
+    /// 3. [] The SPL mint
+    /// 4. [writable] The token account to freeze
+    /// 5. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 6. [] The token program that owns the mint
+    /// 7. [writable] The compliance record PDA (`["compliance", token_account]`)
+    /// 8. [] The roles account
+    /// 9. [] The system program
+    /// 10. [writable] When the record is full, the history page to create (`["compliance_history", token_account, page]`)
+    /// 11. [] The program config (`["config"]`)
+    Freeze {
+        reason_hash: [u8; 32],
+    },
+
+    /// Lift a compliance hold from a frozen SOuL token account, recording
+    /// the hash of the reason in the account's compliance record. Runs while
+    /// the Token module is paused.
+    /// Accounts expected:
+    /// 1. [writable, signer] A Compliance role holder, pays rent for a new record
+    /// 2. [] The SOuL token config account
+    /// 3. [] The SPL mint
+    /// 4. [writable] The token account to thaw
+    /// 5. [] The mint authority PDA (`["mint_authority", token]`)
+    /// 6. [] The token program that owns the mint
+    /// 7. [writable] The compliance record PDA (`["compliance", token_account]`)
+    /// 8. [] The roles account
+    /// 9. [] The system program
+    /// 10. [writable] When the record is full, the history page to create (`["compliance_history", token_account, page]`)
+    /// 11. [] The program config (`["config"]`)
+    Thaw {
+        reason_hash: [u8; 32],
+    },
+
+    /// Burn SOuL or a category LST to retire the carbon credits it stands
+    /// for, creating a retirement certificate that links the impact reports
+    /// behind them. An LST is retired under its pool's category. The reports
+    /// must be submitted for the category, verified and past their dispute
+    /// window, and back the credits with carbon they offset that was not
+    /// retired before.
+    /// Accounts expected:
+    /// 1. [writable, signer] The owner of the token account, pays rent for the certificate
+    /// 2. [writable] The token account to burn from
+    /// 3. [writable] The SOuL or LST mint
+    /// 4. [writable] The SOuL token config of the mint, or the LST pool whose mint it is
+    /// 5. [] The token program that owns the mint
+    /// 6. [writable] The retirement certificate PDA to create (`["retirement", owner, nonce]`)
+    /// 7. [] The system program
+    /// 8. [] The canonical SOuL mint (`["canonical_mint"]`), the only SOuL mint that can be retired
+    /// 9. [] One to `MAX_REPORTS` verified impact reports backing the credits
+    /// 10. [writable] The retirement tally of each report (`["report_retirement", report]`), following it
+    /// 11. [] The program config (`["config"]`)
+    Retire {
+        nonce: u64,
+        amount: u64,
+        category: LSTType,
+        /// Hash of the name of the company or person the credits are retired for
+        beneficiary_hash: [u8; 32],
+    },
 }
 
 impl SOuLInstruction {
+    /// Whether the instruction runs while the Token module is paused.
+    /// Compliance holds must stay available during an incident.
+    pub fn runs_while_paused(&self) -> bool {
+        matches!(self, SOuLInstruction::Freeze { .. } | SOuLInstruction::Thaw { .. })
+    }
+
     pub fn initialize_soul(
         program_id: &Pubkey,
         initializer: &Pubkey,
-        token_account: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        total_supply: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::InitializeSOuL {
+            total_supply,
+        })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+        let (metadata, _) = TokenMetadata::find_address(&token_account, program_id);
+
+        let accounts = vec![
+            AccountMeta::new(*initializer, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(metadata, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn initialize_lst(
+        program_id: &Pubkey,
+        initializer: &Pubkey,
+        token_type: LSTTokenType,
         total_supply: u64,
     ) -> Result<Instruction, ProgramError> {
-        let data = SOuLInstruction::InitializeSOuL {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::InitializeLST {
+            token_type: token_type as u8,
             total_supply,
+        })?;
+        let (token_account, _) = LSTToken::find_address(token_type, program_id);
+        let (metadata, _) = TokenMetadata::find_address(&token_account, program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        let accounts = vec![
+            AccountMeta::new(*initializer, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(metadata, false),
+            AccountMeta::new_readonly(roles, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn transfer(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        source: &Pubkey,
+        destination: &Pubkey,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Transfer { amount })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+
+        let accounts = vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new_readonly(token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn mint(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        mint_authority: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Mint { amount })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority_pda, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::Token, program_id);
+
+        let accounts = vec![
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(mint_authority_pda, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new(breaker, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn burn(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        source: &Pubkey,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Burn { amount })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+
+        let accounts = vec![
+            AccountMeta::new(*source, false),
+            AccountMeta::new_readonly(*owner, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    /// `parent` is the LST pool when migrating staker info, the metrics
+    /// account when migrating an impact report and the token account
+    /// holding its tokens when migrating a vesting schedule or LST pool
+    pub fn migrate(
+        program_id: &Pubkey,
+        payer: &Pubkey,
+        account: &Pubkey,
+        account_type: AccountType,
+        parent: Option<&Pubkey>,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Migrate { account_type })?;
+
+        let mut accounts = vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if let Some(parent) = parent {
+            accounts.push(AccountMeta::new_readonly(*parent, false));
         }
-        .try_to_vec()?;
+        accounts.push(ProgramConfig::account_meta(program_id));
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_soul_with_transfer_fee(
+        program_id: &Pubkey,
+        initializer: &Pubkey,
+        mint: &Pubkey,
+        destination: &Pubkey,
+        total_supply: u64,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::InitializeSOuLWithTransferFee {
+            total_supply,
+            transfer_fee_basis_points,
+            maximum_fee,
+        })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+        let (treasury, _) = SOuLToken::find_treasury(&token_account, program_id);
+        let (metadata, _) = TokenMetadata::find_address(&token_account, program_id);
 
         let accounts = vec![
             AccountMeta::new(*initializer, true),
-            AccountMeta::new(*token_account, false),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
             AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new(metadata, false),
+            ProgramConfig::account_meta(program_id),
         ];
 
         Ok(Instruction {
@@ -80,4 +533,259 @@ impl SOuLInstruction {
             data,
         })
     }
-}
+
+    /// `sources` are the token accounts whose withheld fees are harvested;
+    /// fees already withheld in the mint are swept either way
+    pub fn harvest_fees(
+        program_id: &Pubkey,
+        mint: &Pubkey,
+        sources: &[Pubkey],
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::HarvestFees)?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+        let (treasury, _) = SOuLToken::find_treasury(&token_account, program_id);
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(treasury, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+        ];
+        accounts.extend(sources.iter().map(|source| AccountMeta::new(*source, false)));
+        accounts.push(ProgramConfig::account_meta(program_id));
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn set_transfer_fee(
+        program_id: &Pubkey,
+        mint_authority: &Pubkey,
+        mint: &Pubkey,
+        transfer_fee_basis_points: u16,
+        maximum_fee: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::SetTransferFee {
+            transfer_fee_basis_points,
+            maximum_fee,
+        })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority_pda, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+
+        let accounts = vec![
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(mint_authority_pda, false),
+            AccountMeta::new_readonly(spl_token_2022::id(), false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    /// `token` is the SOuL or LST token config the metadata belongs to
+    pub fn update_metadata_uri(
+        program_id: &Pubkey,
+        update_authority: &Pubkey,
+        token: &Pubkey,
+        uri: String,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::UpdateMetadataUri { uri })?;
+        let (metadata, _) = TokenMetadata::find_address(token, program_id);
+
+        let accounts = vec![
+            AccountMeta::new_readonly(*update_authority, true),
+            AccountMeta::new(metadata, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    pub fn configure_emission(
+        program_id: &Pubkey,
+        mint_authority: &Pubkey,
+        mint: &Pubkey,
+        max_supply: u64,
+        schedule: EmissionSchedule,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::ConfigureEmission {
+            max_supply,
+            schedule,
+        })?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+
+        let accounts = vec![
+            AccountMeta::new_readonly(*mint_authority, true),
+            AccountMeta::new(token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    /// Crank the emission of `mint` into the buckets of its `schedule`
+    pub fn emit(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        mint: &Pubkey,
+        schedule: &EmissionSchedule,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Emit)?;
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+
+        let accounts = vec![
+            AccountMeta::new(token_account, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new(schedule.staking.destination, false),
+            AccountMeta::new(schedule.game_rewards.destination, false),
+            AccountMeta::new(schedule.verifier_fund.destination, false),
+            ProgramConfig::account_meta(program_id),
+        ];
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    /// `archive_page` is `ComplianceRecord::archive_page` of the account's
+    /// record, which names the history page to create once it is full
+    #[allow(clippy::too_many_arguments)]
+    pub fn freeze(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        officer: &Pubkey,
+        mint: &Pubkey,
+        account: &Pubkey,
+        reason_hash: [u8; 32],
+        archive_page: Option<u64>,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Freeze { reason_hash })?;
+        Ok(Self::compliance_hold(program_id, token_program_id, officer, mint, account, archive_page, data))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn thaw(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        officer: &Pubkey,
+        mint: &Pubkey,
+        account: &Pubkey,
+        reason_hash: [u8; 32],
+        archive_page: Option<u64>,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Thaw { reason_hash })?;
+        Ok(Self::compliance_hold(program_id, token_program_id, officer, mint, account, archive_page, data))
+    }
+
+    /// `token_config` is the SOuL token config of `mint` when retiring SOuL,
+    /// and the LST pool of `category` when retiring its LST
+    #[allow(clippy::too_many_arguments)]
+    pub fn retire(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        owner: &Pubkey,
+        source: &Pubkey,
+        mint: &Pubkey,
+        token_config: &Pubkey,
+        nonce: u64,
+        amount: u64,
+        category: LSTType,
+        beneficiary_hash: [u8; 32],
+        reports: &[Pubkey],
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Token.pack(&SOuLInstruction::Retire {
+            nonce,
+            amount,
+            category,
+            beneficiary_hash,
+        })?;
+        let (certificate, _) = RetirementCertificate::find_address(owner, nonce, program_id);
+
+        let mut accounts = vec![
+            AccountMeta::new(*owner, true),
+            AccountMeta::new(*source, false),
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*token_config, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new(certificate, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(CanonicalMint::find_address(program_id).0, false),
+        ];
+        for report in reports {
+            let (tally, _) = ReportRetirement::find_address(report, program_id);
+            accounts.push(AccountMeta::new_readonly(*report, false));
+            accounts.push(AccountMeta::new(tally, false));
+        }
+        accounts.push(ProgramConfig::account_meta(program_id));
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        })
+    }
+
+    /// The accounts shared by `Freeze` and `Thaw`
+    fn compliance_hold(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        officer: &Pubkey,
+        mint: &Pubkey,
+        account: &Pubkey,
+        archive_page: Option<u64>,
+        data: Vec<u8>,
+    ) -> Instruction {
+        let (token_account, _) = SOuLToken::find_address(mint, program_id);
+        let (mint_authority, _) = SOuLToken::find_mint_authority(&token_account, program_id);
+        let (record, _) = ComplianceRecord::find_address(account, program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        let mut accounts = vec![
+            AccountMeta::new(*officer, true),
+            AccountMeta::new_readonly(token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*account, false),
+            AccountMeta::new_readonly(mint_authority, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new(record, false),
+            AccountMeta::new_readonly(roles, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if let Some(page) = archive_page {
+            let (history, _) = ComplianceHistory::find_address(account, page, program_id);
+            accounts.push(AccountMeta::new(history, false));
+        }
+        accounts.push(ProgramConfig::account_meta(program_id));
+
+        Instruction {
+            program_id: *program_id,
+            accounts,
+            data,
+        }
+    }
+}
\ No newline at end of file
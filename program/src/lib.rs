@@ -0,0 +1,19 @@
+pub mod account;
+pub mod error;
+pub mod event;
+pub mod instruction;
+pub mod migrate;
+pub mod pda;
+pub mod processor;
+pub mod state;
+
+pub mod access;
+pub mod distributor;
+pub mod game;
+pub mod impact;
+pub mod lst;
+pub mod multisig;
+pub mod vesting;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint;
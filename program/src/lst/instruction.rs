@@ -1,18 +1,29 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::pubkey::Pubkey;
-use super::state::LSTType;
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+    sysvar,
+};
+use crate::{access::{CircuitBreaker, ProgramConfig, Roles}, error::module_error, instruction::ModuleTag};
+use super::state::{LSTPool, LSTType, StakerInfo};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum LSTInstruction {
-    /// Initialize a new LST pool
+    /// Initialize a new LST pool as an Admin or PoolManager, who becomes
+    /// its authority
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority creating the pool
-    /// 1. `[writable]` The LST pool account
+    /// 0. `[writable, signer]` An Admin or PoolManager creating the pool, pays rent
+    /// 1. `[writable]` The LST pool PDA to create (`["pool", lst_type]`)
     /// 2. `[]` The SOuL mint
-    /// 3. `[writable]` The LST mint to be created
-    /// 4. `[]` The token program
-    /// 5. `[]` The system program
+    /// 3. `[writable]` The pool's SOuL vault PDA to create (`["pool_vault", pool]`)
+    /// 4. `[writable]` The uninitialized LST mint, whose mint authority becomes the pool PDA
+    /// 5. `[]` The token program
+    /// 6. `[]` The system program
+    /// 7. `[]` The roles account
+    /// 8. `[]` The program config (`["config"]`)
     InitializePool {
         pool_type: LSTType,
         fee_rate: u64,
@@ -21,14 +32,16 @@ pub enum LSTInstruction {
     /// Stake SOuL tokens to receive LST
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The staker
+    /// 0. `[writable, signer]` The staker, pays rent for a new staker info account
     /// 1. `[writable]` The LST pool account
     /// 2. `[writable]` The staker's SOuL token account
-    /// 3. `[writable]` The pool's SOuL token account
+    /// 3. `[writable]` The pool's SOuL vault
     /// 4. `[writable]` The staker's LST token account
     /// 5. `[writable]` The LST mint
-    /// 6. `[writable]` The staker info account
+    /// 6. `[writable]` The staker info PDA (`["staker", pool, owner]`), created on first stake
     /// 7. `[]` The token program
+    /// 8. `[]` The system program
+    /// 9. `[]` The program config (`["config"]`)
     Stake {
         amount: u64,
     },
@@ -41,6 +54,7 @@ pub enum LSTInstruction {
     /// 2. `[writable]` The staker info account
     /// 3. `[writable]` The staker's LST token account
     /// 4. `[]` The clock sysvar
+    /// 5. `[]` The program config (`["config"]`)
     RequestUnstake {
         amount: u64,
     },
@@ -53,10 +67,12 @@ pub enum LSTInstruction {
     /// 2. `[writable]` The staker info account
     /// 3. `[writable]` The staker's LST token account
     /// 4. `[writable]` The staker's SOuL token account
-    /// 5. `[writable]` The pool's SOuL token account
+    /// 5. `[writable]` The pool's SOuL vault
     /// 6. `[writable]` The LST mint
     /// 7. `[]` The token program
     /// 8. `[]` The clock sysvar
+    /// 9. `[writable]` The LST circuit breaker (`["circuit_breaker", module]`)
+    /// 10. `[]` The program config (`["config"]`)
     ExecuteUnstake,
 
     /// Claim staking rewards
@@ -66,46 +82,279 @@ pub enum LSTInstruction {
     /// 1. `[writable]` The LST pool account
     /// 2. `[writable]` The staker info account
     /// 3. `[writable]` The staker's SOuL token account
-    /// 4. `[writable]` The pool's reward token account
+    /// 4. `[writable]` The pool's SOuL vault, which pays rewards from what it holds beyond the stake
     /// 5. `[]` The token program
     /// 6. `[]` The clock sysvar
+    /// 7. `[writable]` The LST circuit breaker (`["circuit_breaker", module]`)
+    /// 8. `[]` The program config (`["config"]`)
     ClaimRewards,
 
-    /// Update pool parameters
+    /// Update pool parameters. A PoolManager may sign in place of the pool
+    /// authority when the roles account is passed before the program config.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The pool authority
     /// 1. `[writable]` The LST pool account
+    /// 2. `[]` The program config (`["config"]`)
     UpdatePool {
         new_fee_rate: Option<u64>,
     },
 
-    /// Emergency withdraw (only for pool authority)
+    /// Emergency withdraw (only for pool authority). A PoolManager may sign
+    /// in place of the pool authority when the roles account is passed
+    /// before the program config.
     /// 
     /// Accounts expected:
     /// 0. `[signer]` The pool authority
     /// 1. `[writable]` The LST pool account
-    /// 2. `[writable]` The pool's SOuL token account
+    /// 2. `[writable]` The pool's SOuL vault
     /// 3. `[writable]` The authority's SOuL token account
     /// 4. `[]` The token program
+    /// 5. `[writable]` The LST circuit breaker (`["circuit_breaker", module]`)
+    /// 6. `[]` The program config (`["config"]`)
     EmergencyWithdraw {
         amount: u64,
     },
+
+    /// Close a staker info with nothing staked, waiting to be unstaked or
+    /// left to claim, returning its rent to the payer of the staker info
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The staker info account
+    /// 1. `[writable]` The payer recorded in the staker info
+    /// 2. `[]` The program config (`["config"]`)
+    CloseStakerInfo,
+}
+
+impl LSTInstruction {
+    pub fn initialize_pool(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        soul_mint: &Pubkey,
+        lst_mint: &Pubkey,
+        pool_type: LSTType,
+        fee_rate: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (soul_vault, _) = LSTPool::find_vault(&pool, program_id);
+        let (roles, _) = Roles::find_address(program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new_readonly(*soul_mint, false),
+                AccountMeta::new(soul_vault, false),
+                AccountMeta::new(*lst_mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new_readonly(roles, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::InitializePool { pool_type, fee_rate })?,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn stake(
+        program_id: &Pubkey,
+        staker: &Pubkey,
+        pool_type: LSTType,
+        staker_soul_account: &Pubkey,
+        pool_soul_account: &Pubkey,
+        staker_lst_account: &Pubkey,
+        lst_mint: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (staker_info, _) = StakerInfo::find_address(&pool, staker, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*staker, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new(*staker_soul_account, false),
+                AccountMeta::new(*pool_soul_account, false),
+                AccountMeta::new(*staker_lst_account, false),
+                AccountMeta::new(*lst_mint, false),
+                AccountMeta::new(staker_info, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::Stake { amount })?,
+        })
+    }
+
+    pub fn request_unstake(
+        program_id: &Pubkey,
+        staker: &Pubkey,
+        pool_type: LSTType,
+        staker_lst_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (staker_info, _) = StakerInfo::find_address(&pool, staker, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*staker, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new(staker_info, false),
+                AccountMeta::new(*staker_lst_account, false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::RequestUnstake { amount })?,
+        })
+    }
+
+    pub fn execute_unstake(
+        program_id: &Pubkey,
+        staker: &Pubkey,
+        pool_type: LSTType,
+        staker_lst_account: &Pubkey,
+        staker_soul_account: &Pubkey,
+        pool_soul_account: &Pubkey,
+        lst_mint: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (staker_info, _) = StakerInfo::find_address(&pool, staker, program_id);
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::LST, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*staker, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new(staker_info, false),
+                AccountMeta::new(*staker_lst_account, false),
+                AccountMeta::new(*staker_soul_account, false),
+                AccountMeta::new(*pool_soul_account, false),
+                AccountMeta::new(*lst_mint, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new(breaker, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::ExecuteUnstake)?,
+        })
+    }
+
+    pub fn claim_rewards(
+        program_id: &Pubkey,
+        staker: &Pubkey,
+        pool_type: LSTType,
+        staker_soul_account: &Pubkey,
+        pool_reward_account: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (staker_info, _) = StakerInfo::find_address(&pool, staker, program_id);
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::LST, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*staker, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new(staker_info, false),
+                AccountMeta::new(*staker_soul_account, false),
+                AccountMeta::new(*pool_reward_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(sysvar::clock::id(), false),
+                AccountMeta::new(breaker, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::ClaimRewards)?,
+        })
+    }
+
+    pub fn update_pool(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        pool_type: LSTType,
+        new_fee_rate: Option<u64>,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(pool, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::UpdatePool { new_fee_rate })?,
+        })
+    }
+
+    pub fn emergency_withdraw(
+        program_id: &Pubkey,
+        authority: &Pubkey,
+        pool_type: LSTType,
+        pool_soul_account: &Pubkey,
+        authority_soul_account: &Pubkey,
+        amount: u64,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::LST, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*authority, true),
+                AccountMeta::new(pool, false),
+                AccountMeta::new(*pool_soul_account, false),
+                AccountMeta::new(*authority_soul_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new(breaker, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::EmergencyWithdraw { amount })?,
+        })
+    }
+
+    pub fn close_staker_info(
+        program_id: &Pubkey,
+        pool_type: LSTType,
+        owner: &Pubkey,
+        payer: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        let (pool, _) = LSTPool::find_address(pool_type, program_id);
+        let (staker_info, _) = StakerInfo::find_address(&pool, owner, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(staker_info, false),
+                AccountMeta::new(*payer, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::LST.pack(&LSTInstruction::CloseStakerInfo)?,
+        })
+    }
 }
 
-#[derive(Debug)]
-pub enum LSTError {
-    InvalidInstruction,
-    NotRentExempt,
-    AlreadyInitialized,
-    InvalidAuthority,
-    InvalidPoolType,
-    InvalidAmount,
-    InsufficientFunds,
-    InvalidUnstakeRequest,
-    UnstakeRequestNotReady,
-    NoRewardsToHarvest,
-    PoolPaused,
-    InvalidFeeRate,
-    ExchangeRateError,
+module_error! {
+    pub enum LSTError in LST {
+        InvalidInstruction => "Invalid Instruction",
+        NotRentExempt => "Not Rent Exempt",
+        AlreadyInitialized => "Already Initialized",
+        InvalidAuthority => "Invalid Authority",
+        InvalidPoolType => "Invalid Pool Type",
+        InvalidAmount => "Invalid Amount",
+        InsufficientFunds => "Insufficient Funds",
+        InvalidUnstakeRequest => "Invalid Unstake Request",
+        UnstakeRequestNotReady => "Unstake Request Not Ready",
+        NoRewardsToHarvest => "No Rewards To Harvest",
+        PoolPaused => "Pool Paused",
+        InvalidFeeRate => "Invalid Fee Rate",
+        ExchangeRateError => "Exchange Rate Error",
+        StakeNotEmpty => "Staker Still Has Stake",
+        InvalidVault => "Invalid Vault",
+        InvalidMint => "Invalid Mint",
+    }
 }
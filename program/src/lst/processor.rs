@@ -1,19 +1,26 @@
+use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
-    rent::Rent,
     sysvar::{clock::Clock, Sysvar},
 };
-use spl_token::state::{Account as TokenAccount, Mint};
+use spl_token::state::Account as TokenAccount;
+
+use crate::{
+    access::{self, is_authorized, Role},
+    account::AccountState,
+    event::SoulEvent,
+    instruction::ModuleTag,
+    pda,
+};
 
 use super::{
     instruction::{LSTInstruction, LSTError},
-    state::{LSTPool, StakerInfo, LSTType},
+    state::{LSTPool, StakerInfo, LSTType, UnstakeRequest},
 };
 
 pub struct Processor;
@@ -49,6 +56,9 @@ impl Processor {
             LSTInstruction::EmergencyWithdraw { amount } => {
                 Self::process_emergency_withdraw(accounts, amount, program_id)
             }
+            LSTInstruction::CloseStakerInfo => {
+                Self::process_close_staker_info(accounts, program_id)
+            }
         }
     }
 
@@ -62,28 +72,75 @@ impl Processor {
         let authority_info = next_account_info(account_info_iter)?;
         let pool_account_info = next_account_info(account_info_iter)?;
         let soul_mint_info = next_account_info(account_info_iter)?;
+        let soul_vault_info = next_account_info(account_info_iter)?;
         let lst_mint_info = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
+        let roles_info = next_account_info(account_info_iter)?;
 
-        if !authority_info.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        access::load_roles_as_any(authority_info, roles_info, &[Role::Admin, Role::PoolManager], program_id)?;
 
-        let rent = Rent::get()?;
-        if !rent.is_exempt(pool_account_info.lamports(), LSTPool::LEN) {
-            return Err(ProgramError::AccountNotRentExempt);
-        }
+        Self::check_token_program(token_program_info)?;
 
         // Validate fee rate (max 10%)
         if fee_rate > 100_000_000 {
             return Err(ProgramError::from(LSTError::InvalidFeeRate));
         }
 
+        let bump = pda::expect_pda(
+            pool_account_info.key,
+            &[LSTPool::SEED, &[pool_type as u8]],
+            program_id,
+        )?;
+        pda::create_pda_account(
+            authority_info,
+            pool_account_info,
+            system_program_info,
+            program_id,
+            LSTPool::SPACE,
+            &[LSTPool::SEED, &[pool_type as u8], &[bump]],
+        )?;
+
+        // The pool PDA mints LST on stake, so it holds the mint authority
+        let init_mint_ix = spl_token::instruction::initialize_mint2(
+            token_program_info.key,
+            lst_mint_info.key,
+            pool_account_info.key,
+            None,
+            9,
+        )?;
+        invoke(&init_mint_ix, &[lst_mint_info.clone(), token_program_info.clone()])?;
+
+        // Staked SOuL is held in a vault only the pool PDA can move out of
+        let vault_bump = pda::expect_pda(
+            soul_vault_info.key,
+            &[LSTPool::VAULT_SEED, pool_account_info.key.as_ref()],
+            program_id,
+        )?;
+        pda::create_pda_account(
+            authority_info,
+            soul_vault_info,
+            system_program_info,
+            token_program_info.key,
+            TokenAccount::LEN,
+            &[LSTPool::VAULT_SEED, pool_account_info.key.as_ref(), &[vault_bump]],
+        )?;
+        let init_vault_ix = spl_token::instruction::initialize_account3(
+            token_program_info.key,
+            soul_vault_info.key,
+            soul_mint_info.key,
+            pool_account_info.key,
+        )?;
+        invoke(
+            &init_vault_ix,
+            &[soul_vault_info.clone(), soul_mint_info.clone(), token_program_info.clone()],
+        )?;
+
         let pool = LSTPool {
             is_initialized: true,
             authority: *authority_info.key,
             soul_mint: *soul_mint_info.key,
+            soul_vault: *soul_vault_info.key,
             lst_mint: *lst_mint_info.key,
             total_staked: 0,
             exchange_rate: 1_000_000_000, // 1:1 initial rate
@@ -91,9 +148,10 @@ impl Processor {
             last_update_time: Clock::get()?.unix_timestamp,
             pool_type,
             fee_rate,
+            bump,
         };
 
-        pool.serialize(&mut *pool_account_info.data.borrow_mut())?;
+        pool.save(pool_account_info)?;
         Ok(())
     }
 
@@ -111,13 +169,60 @@ impl Processor {
         let lst_mint_info = next_account_info(account_info_iter)?;
         let staker_info_account = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
 
         if !staker_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let mut pool = LSTPool::try_from_slice(&pool_account_info.data.borrow())?;
-        let mut staker_data = StakerInfo::try_from_slice(&staker_info_account.data.borrow())?;
+        if pool_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        if amount == 0 {
+            return Err(ProgramError::from(LSTError::InvalidAmount));
+        }
+
+        let mut pool = LSTPool::load(pool_account_info, program_id)?;
+        if pool.lst_mint != *lst_mint_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::check_token_program(token_program_info)?;
+        Self::check_vault(&pool, pool_soul_account)?;
+        Self::check_soul_account(&pool, staker_soul_account)?;
+
+        let current_time = Clock::get()?.unix_timestamp;
+        let mut staker_data = if staker_info_account.owner == program_id {
+            Self::load_staker(program_id, pool_account_info, staker_info, staker_info_account)?
+        } else {
+            let seeds: &[&[u8]] = &[
+                StakerInfo::SEED,
+                pool_account_info.key.as_ref(),
+                staker_info.key.as_ref(),
+            ];
+            let bump = pda::expect_pda(staker_info_account.key, seeds, program_id)?;
+            pda::create_pda_account(
+                staker_info,
+                staker_info_account,
+                system_program_info,
+                program_id,
+                StakerInfo::SPACE,
+                &[seeds[0], seeds[1], seeds[2], &[bump]],
+            )?;
+            StakerInfo {
+                owner: *staker_info.key,
+                pool: *pool_account_info.key,
+                staked_amount: 0,
+                rewards_debt: 0,
+                last_stake_time: 0,
+                bump,
+                unstake_request: None,
+                payer: *staker_info.key,
+                accrued_rewards: 0,
+                last_accrual_time: current_time,
+            }
+        };
+        pool.accrue_rewards(&mut staker_data, current_time)?;
 
         // Transfer SOuL tokens to pool
         let transfer_ix = spl_token::instruction::transfer(
@@ -140,11 +245,7 @@ impl Processor {
         )?;
 
         // Calculate LST amount to mint
-        let lst_amount = (amount as u128)
-            .checked_mul(1_000_000_000)
-            .unwrap()
-            .checked_div(pool.exchange_rate as u128)
-            .unwrap() as u64;
+        let lst_amount = pool.lst_amount_for(amount);
 
         // Mint LST tokens to staker
         let mint_ix = spl_token::instruction::mint_to(
@@ -164,19 +265,459 @@ impl Processor {
                 pool_account_info.clone(),
                 token_program_info.clone(),
             ],
-            &[&[&pool_account_info.key.to_bytes(), &[0]]],
+            &[&[LSTPool::SEED, &[pool.pool_type as u8], &[pool.bump]]],
         )?;
 
         // Update pool and staker data
-        pool.total_staked = pool.total_staked.checked_add(amount).unwrap();
-        staker_data.staked_amount = staker_data.staked_amount.checked_add(amount).unwrap();
-        staker_data.last_stake_time = Clock::get()?.unix_timestamp;
+        pool.total_staked = pool
+            .total_staked
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        staker_data.staked_amount = staker_data
+            .staked_amount
+            .checked_add(amount)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        staker_data.last_stake_time = current_time;
 
-        pool.serialize(&mut *pool_account_info.data.borrow_mut())?;
-        staker_data.serialize(&mut *staker_info_account.data.borrow_mut())?;
+        pool.save(pool_account_info)?;
+        staker_data.save(staker_info_account)?;
+
+        SoulEvent::Staked {
+            pool: *pool_account_info.key,
+            staker: *staker_info.key,
+            amount,
+            lst_amount,
+            total_staked: pool.total_staked,
+            timestamp: staker_data.last_stake_time,
+        }
+        .emit()
+    }
+
+    fn process_request_unstake(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let staker_info = next_account_info(account_info_iter)?;
+        let pool_account_info = next_account_info(account_info_iter)?;
+        let staker_info_account = next_account_info(account_info_iter)?;
+        let staker_lst_account = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+
+        if !staker_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if pool_account_info.owner != program_id || staker_info_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let pool = LSTPool::load(pool_account_info, program_id)?;
+        let mut staker_data =
+            Self::load_staker(program_id, pool_account_info, staker_info, staker_info_account)?;
+        if staker_data.unstake_request.is_some() {
+            return Err(ProgramError::from(LSTError::InvalidUnstakeRequest));
+        }
+        if amount == 0 || amount > staker_data.staked_amount {
+            return Err(ProgramError::from(LSTError::InsufficientFunds));
+        }
+
+        let staker_lst = TokenAccount::unpack(&staker_lst_account.data.borrow())?;
+        if staker_lst.owner != *staker_info.key
+            || staker_lst.mint != pool.lst_mint
+            || staker_lst.amount < pool.lst_amount_for(amount)
+        {
+            return Err(ProgramError::from(LSTError::InsufficientFunds));
+        }
+
+        let clock = Clock::from_account_info(clock_info)?;
+        let unlock_time = clock.unix_timestamp + StakerInfo::UNSTAKE_COOLDOWN;
+        staker_data.unstake_request = Some(UnstakeRequest {
+            amount,
+            request_time: clock.unix_timestamp,
+            unlock_time,
+        });
+
+        staker_data.save(staker_info_account)?;
+
+        SoulEvent::UnstakeRequested {
+            pool: *pool_account_info.key,
+            staker: *staker_info.key,
+            amount,
+            unlock_time,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_execute_unstake(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let staker_info = next_account_info(account_info_iter)?;
+        let pool_account_info = next_account_info(account_info_iter)?;
+        let staker_info_account = next_account_info(account_info_iter)?;
+        let staker_lst_account = next_account_info(account_info_iter)?;
+        let staker_soul_account = next_account_info(account_info_iter)?;
+        let pool_soul_account = next_account_info(account_info_iter)?;
+        let lst_mint_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+
+        if !staker_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if pool_account_info.owner != program_id || staker_info_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut pool = LSTPool::load(pool_account_info, program_id)?;
+        let mut staker_data =
+            Self::load_staker(program_id, pool_account_info, staker_info, staker_info_account)?;
+        if pool.lst_mint != *lst_mint_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Self::check_token_program(token_program_info)?;
+        Self::check_vault(&pool, pool_soul_account)?;
+        Self::check_soul_account(&pool, staker_soul_account)?;
+
+        let clock = Clock::from_account_info(clock_info)?;
+        let request = match &staker_data.unstake_request {
+            Some(request) if staker_data.can_unstake(clock.unix_timestamp) => request.clone(),
+            Some(_) => return Err(ProgramError::from(LSTError::UnstakeRequestNotReady)),
+            None => return Err(ProgramError::from(LSTError::InvalidUnstakeRequest)),
+        };
+
+        // Burn the staker's LST
+        let lst_amount = pool.lst_amount_for(request.amount);
+        let burn_ix = spl_token::instruction::burn(
+            token_program_info.key,
+            staker_lst_account.key,
+            lst_mint_info.key,
+            staker_info.key,
+            &[],
+            lst_amount,
+        )?;
+
+        invoke(
+            &burn_ix,
+            &[
+                staker_lst_account.clone(),
+                lst_mint_info.clone(),
+                staker_info.clone(),
+                token_program_info.clone(),
+            ],
+        )?;
+
+        // Return SOuL minus the pool fee
+        let fee = pool.calculate_fee(request.amount);
+        let payout = request
+            .amount
+            .checked_sub(fee)
+            .ok_or(LSTError::ExchangeRateError)?;
+        access::record_outflow(breaker_info, ModuleTag::LST, payout, program_id)?;
+
+        let transfer_ix = spl_token::instruction::transfer(
+            token_program_info.key,
+            pool_soul_account.key,
+            staker_soul_account.key,
+            pool_account_info.key,
+            &[],
+            payout,
+        )?;
+
+        invoke_signed(
+            &transfer_ix,
+            &[
+                pool_soul_account.clone(),
+                staker_soul_account.clone(),
+                pool_account_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[LSTPool::SEED, &[pool.pool_type as u8], &[pool.bump]]],
+        )?;
+
+        pool.accrue_rewards(&mut staker_data, clock.unix_timestamp)?;
+        pool.total_staked = pool.total_staked.saturating_sub(request.amount);
+        staker_data.staked_amount = staker_data.staked_amount.saturating_sub(request.amount);
+        staker_data.unstake_request = None;
+
+        pool.save(pool_account_info)?;
+        staker_data.save(staker_info_account)?;
+
+        SoulEvent::Unstaked {
+            pool: *pool_account_info.key,
+            staker: *staker_info.key,
+            amount: request.amount,
+            lst_amount,
+            fee,
+            total_staked: pool.total_staked,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
 
+    fn process_claim_rewards(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let staker_info = next_account_info(account_info_iter)?;
+        let pool_account_info = next_account_info(account_info_iter)?;
+        let staker_info_account = next_account_info(account_info_iter)?;
+        let staker_soul_account = next_account_info(account_info_iter)?;
+        let pool_reward_account = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let clock_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+
+        if !staker_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if pool_account_info.owner != program_id || staker_info_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let pool = LSTPool::load(pool_account_info, program_id)?;
+        let mut staker_data =
+            Self::load_staker(program_id, pool_account_info, staker_info, staker_info_account)?;
+
+        Self::check_token_program(token_program_info)?;
+        Self::check_vault(&pool, pool_reward_account)?;
+        Self::check_soul_account(&pool, staker_soul_account)?;
+
+        let clock = Clock::from_account_info(clock_info)?;
+        pool.accrue_rewards(&mut staker_data, clock.unix_timestamp)?;
+        let rewards = staker_data.accrued_rewards;
+        if rewards == 0 {
+            return Err(ProgramError::from(LSTError::NoRewardsToHarvest));
+        }
+        // Rewards are paid from what the vault holds beyond the staked SOuL
+        let vault = TokenAccount::unpack(&pool_reward_account.data.borrow())?;
+        if vault.amount.saturating_sub(pool.total_staked) < rewards {
+            return Err(ProgramError::from(LSTError::InsufficientFunds));
+        }
+        access::record_outflow(breaker_info, ModuleTag::LST, rewards, program_id)?;
+
+        let transfer_ix = spl_token::instruction::transfer(
+            token_program_info.key,
+            pool_reward_account.key,
+            staker_soul_account.key,
+            pool_account_info.key,
+            &[],
+            rewards,
+        )?;
+
+        invoke_signed(
+            &transfer_ix,
+            &[
+                pool_reward_account.clone(),
+                staker_soul_account.clone(),
+                pool_account_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[LSTPool::SEED, &[pool.pool_type as u8], &[pool.bump]]],
+        )?;
+
+        staker_data.accrued_rewards = 0;
+        staker_data.rewards_debt = staker_data
+            .rewards_debt
+            .checked_add(rewards)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        staker_data.save(staker_info_account)?;
+
+        SoulEvent::RewardsClaimed {
+            pool: *pool_account_info.key,
+            staker: *staker_info.key,
+            amount: rewards,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_update_pool(
+        accounts: &[AccountInfo],
+        new_fee_rate: Option<u64>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let pool_account_info = next_account_info(account_info_iter)?;
+        let roles_info = account_info_iter.next();
+
+        if pool_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut pool = LSTPool::load(pool_account_info, program_id)?;
+        if !is_authorized(authority_info, &pool.authority, Role::PoolManager, roles_info, program_id)? {
+            return Err(ProgramError::from(LSTError::InvalidAuthority));
+        }
+
+        if let Some(fee_rate) = new_fee_rate {
+            // Validate fee rate (max 10%)
+            if fee_rate > 100_000_000 {
+                return Err(ProgramError::from(LSTError::InvalidFeeRate));
+            }
+            pool.fee_rate = fee_rate;
+        }
+
+        pool.save(pool_account_info)?;
+        Ok(())
+    }
+
+    fn process_emergency_withdraw(
+        accounts: &[AccountInfo],
+        amount: u64,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let pool_account_info = next_account_info(account_info_iter)?;
+        let pool_soul_account = next_account_info(account_info_iter)?;
+        let authority_soul_account = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+        let roles_info = account_info_iter.next();
+
+        if pool_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let pool = LSTPool::load(pool_account_info, program_id)?;
+        if !is_authorized(authority_info, &pool.authority, Role::PoolManager, roles_info, program_id)? {
+            return Err(ProgramError::from(LSTError::InvalidAuthority));
+        }
+        Self::check_token_program(token_program_info)?;
+        Self::check_vault(&pool, pool_soul_account)?;
+        Self::check_soul_account(&pool, authority_soul_account)?;
+
+        if amount == 0 {
+            return Err(ProgramError::from(LSTError::InvalidAmount));
+        }
+        access::record_outflow(breaker_info, ModuleTag::LST, amount, program_id)?;
+
+        let transfer_ix = spl_token::instruction::transfer(
+            token_program_info.key,
+            pool_soul_account.key,
+            authority_soul_account.key,
+            pool_account_info.key,
+            &[],
+            amount,
+        )?;
+
+        invoke_signed(
+            &transfer_ix,
+            &[
+                pool_soul_account.clone(),
+                authority_soul_account.clone(),
+                pool_account_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[LSTPool::SEED, &[pool.pool_type as u8], &[pool.bump]]],
+        )?;
+
+        SoulEvent::EmergencyWithdrawn {
+            pool: *pool_account_info.key,
+            authority: *authority_info.key,
+            destination: *authority_soul_account.key,
+            amount,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_close_staker_info(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let staker_info_account = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+
+        let staker_data = StakerInfo::load(staker_info_account, program_id)?;
+        if !staker_data.is_empty() {
+            return Err(ProgramError::from(LSTError::StakeNotEmpty));
+        }
+
+        let lamports = pda::close_to_payer(staker_info_account, payer_info, &staker_data.payer)?;
+
+        SoulEvent::AccountClosed {
+            module: ModuleTag::LST,
+            account: *staker_info_account.key,
+            payer: *payer_info.key,
+            lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    /// Check `token_program_info` is the SPL Token program, the only one the
+    /// pool PDA signs for
+    fn check_token_program(token_program_info: &AccountInfo) -> ProgramResult {
+        if *token_program_info.key != spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Ok(())
+    }
+
+    /// Check `vault_info` is the pool's SOuL vault
+    fn check_vault(pool: &LSTPool, vault_info: &AccountInfo) -> ProgramResult {
+        if *vault_info.key != pool.soul_vault {
+            return Err(ProgramError::from(LSTError::InvalidVault));
+        }
+        Ok(())
+    }
+
+    /// Check `token_account_info` is a token account of the pool's SOuL mint
+    fn check_soul_account(pool: &LSTPool, token_account_info: &AccountInfo) -> ProgramResult {
+        if token_account_info.owner != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let token_account = TokenAccount::unpack(&token_account_info.data.borrow())?;
+        if token_account.mint != pool.soul_mint {
+            return Err(ProgramError::from(LSTError::InvalidMint));
+        }
         Ok(())
     }
 
-    // Additional processing functions...
+    /// Load a staker's info and check it is the PDA for this pool and owner
+    fn load_staker(
+        program_id: &Pubkey,
+        pool_account_info: &AccountInfo,
+        owner_info: &AccountInfo,
+        staker_info_account: &AccountInfo,
+    ) -> Result<StakerInfo, ProgramError> {
+        if staker_info_account.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let staker_data = StakerInfo::load(staker_info_account, program_id)?;
+        if staker_data.owner != *owner_info.key {
+            return Err(ProgramError::from(LSTError::InvalidAuthority));
+        }
+        if staker_data.pool != *pool_account_info.key {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        let expected = Pubkey::create_program_address(
+            &[
+                StakerInfo::SEED,
+                pool_account_info.key.as_ref(),
+                owner_info.key.as_ref(),
+                &[staker_data.bump],
+            ],
+            program_id,
+        )?;
+        if expected != *staker_info_account.key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        Ok(staker_data)
+    }
 }
@@ -1,14 +1,16 @@
 use borsh::{BorshDeserialize, BorshSerialize};
-use solana_program::{
-    pubkey::Pubkey,
-    program_pack::Pack,
-};
+use solana_program::{entrypoint::ProgramResult, program_error::ProgramError, pubkey::Pubkey};
+
+use crate::account::{AccountState, HEADER_LEN};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct LSTPool {
     pub is_initialized: bool,
     pub authority: Pubkey,
     pub soul_mint: Pubkey,
+    /// Token account of `soul_mint`, owned by the pool PDA, that holds the
+    /// staked SOuL and pays out unstakes and rewards
+    pub soul_vault: Pubkey,
     pub lst_mint: Pubkey,
     pub total_staked: u64,
     pub exchange_rate: u64,  // Scaled by 1e9
@@ -16,9 +18,10 @@ pub struct LSTPool {
     pub last_update_time: i64,
     pub pool_type: LSTType,
     pub fee_rate: u64,  // Scaled by 1e9
+    pub bump: u8,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum LSTType {
     Air,        // airSOuL
     Forest,     // forestSOuL
@@ -37,10 +40,18 @@ pub enum LSTType {
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct StakerInfo {
     pub owner: Pubkey,
+    pub pool: Pubkey,
     pub staked_amount: u64,
+    /// Rewards claimed so far
     pub rewards_debt: u64,
     pub last_stake_time: i64,
+    pub bump: u8,
     pub unstake_request: Option<UnstakeRequest>,
+    /// Wallet that paid the staker info's rent, refunded when it is closed
+    pub payer: Pubkey,
+    /// Rewards earned up to `last_accrual_time` and not yet claimed
+    pub accrued_rewards: u64,
+    pub last_accrual_time: i64,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
@@ -51,16 +62,29 @@ pub struct UnstakeRequest {
 }
 
 impl LSTPool {
-    pub const LEN: usize = 1 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 8;
+    pub const LEN: usize = 1 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 1;
 
-    pub fn calculate_rewards(&self, staker: &StakerInfo, current_time: i64) -> u64 {
-        if staker.staked_amount == 0 {
-            return 0;
-        }
+    /// Seed of the pool PDA (`["pool", lst_type]`), which also signs for the
+    /// pool's token accounts and holds LST mint authority
+    pub const SEED: &'static [u8] = b"pool";
+
+    /// Seed of the SOuL vault PDA (`["pool_vault", pool]`) a new pool creates
+    pub const VAULT_SEED: &'static [u8] = b"pool_vault";
+
+    pub fn find_address(pool_type: LSTType, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &[pool_type as u8]], program_id)
+    }
+
+    pub fn find_vault(pool: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::VAULT_SEED, pool.as_ref()], program_id)
+    }
 
-        let time_delta = current_time - self.last_update_time;
-        if time_delta <= 0 {
-            return 0;
+    /// Rewards `staker` earned since its last accrual, at the pool's yearly
+    /// rate on what it had staked
+    pub fn calculate_rewards(&self, staker: &StakerInfo, current_time: i64) -> Result<u64, ProgramError> {
+        let time_delta = current_time.saturating_sub(staker.last_accrual_time);
+        if staker.staked_amount == 0 || time_delta <= 0 {
+            return Ok(0);
         }
 
         let rewards_rate = match self.pool_type {
@@ -72,13 +96,24 @@ impl LSTPool {
 
         let rewards = (staker.staked_amount as u128)
             .checked_mul(rewards_rate as u128)
-            .unwrap()
-            .checked_mul(time_delta as u128)
-            .unwrap()
-            .checked_div(365 * 24 * 60 * 60 * 100)
-            .unwrap() as u64;
+            .and_then(|rewards| rewards.checked_mul(time_delta as u128))
+            .map(|rewards| rewards / (365 * 24 * 60 * 60 * 100))
+            .ok_or(ProgramError::ArithmeticOverflow)?;
 
-        rewards.saturating_sub(staker.rewards_debt)
+        u64::try_from(rewards).map_err(|_| ProgramError::ArithmeticOverflow)
+    }
+
+    /// Add what `staker` earned up to `current_time` to its accrued rewards.
+    /// Runs before `staked_amount` changes, so every amount earns only for
+    /// the time it was staked.
+    pub fn accrue_rewards(&self, staker: &mut StakerInfo, current_time: i64) -> ProgramResult {
+        let rewards = self.calculate_rewards(staker, current_time)?;
+        staker.accrued_rewards = staker
+            .accrued_rewards
+            .checked_add(rewards)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        staker.last_accrual_time = staker.last_accrual_time.max(current_time);
+        Ok(())
     }
 
     pub fn update_exchange_rate(&mut self, total_rewards: u64) {
@@ -95,6 +130,15 @@ impl LSTPool {
             .unwrap() as u64;
     }
 
+    /// LST minted or burned for `amount` SOuL at the current exchange rate
+    pub fn lst_amount_for(&self, amount: u64) -> u64 {
+        (amount as u128)
+            .checked_mul(1_000_000_000)
+            .unwrap()
+            .checked_div(self.exchange_rate as u128)
+            .unwrap() as u64
+    }
+
     pub fn calculate_fee(&self, amount: u64) -> u64 {
         (amount as u128)
             .checked_mul(self.fee_rate as u128)
@@ -104,8 +148,28 @@ impl LSTPool {
     }
 }
 
+impl AccountState for LSTPool {
+    const DISCRIMINATOR: [u8; 8] = *b"lst_pool";
+    const VERSION: u8 = 2;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
 impl StakerInfo {
-    pub const LEN: usize = 32 + 8 + 8 + 8 + 1 + 24;  // 24 bytes for optional UnstakeRequest
+    pub const LEN: usize = 32 + 32 + 8 + 8 + 8 + 1 + 1 + 24 + 32 + 8 + 8;  // 24 bytes for optional UnstakeRequest
+
+    /// Seed of the staker info PDA (`["staker", pool, owner]`)
+    pub const SEED: &'static [u8] = b"staker";
+
+    pub fn find_address(pool: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, pool.as_ref(), owner.as_ref()], program_id)
+    }
+
+    pub const UNSTAKE_COOLDOWN: i64 = 7 * 24 * 60 * 60;  // 7 days in seconds
+
+    /// Whether nothing is staked, waiting to be unstaked or left to claim
+    pub fn is_empty(&self) -> bool {
+        self.staked_amount == 0 && self.unstake_request.is_none() && self.accrued_rewards == 0
+    }
 
     pub fn can_unstake(&self, current_time: i64) -> bool {
         match &self.unstake_request {
@@ -114,3 +178,9 @@ impl StakerInfo {
         }
     }
 }
+
+impl AccountState for StakerInfo {
+    const DISCRIMINATOR: [u8; 8] = *b"stakinfo";
+    const VERSION: u8 = 3;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
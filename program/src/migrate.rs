@@ -0,0 +1,867 @@
+//! In-place upgrade of accounts written with an older layout.
+//!
+//! Version 0 is the headerless layout written before accounts carried a
+//! discriminator. Because a version 0 account has no type tag, the caller
+//! names the type, and the decoded fields must re-derive the account's own
+//! PDA before it is rewritten.
+//!
+//! A type whose layout changed after version 1 also decodes the body of its
+//! older headered versions, which keep their discriminator. Types with more
+//! than one older layout pick the decoder by the version in the header.
+
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    clock::{Clock, UnixTimestamp},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token::state::Account as TokenAccount;
+
+use crate::{
+    account::{AccountState, AccountType, HEADER_LEN},
+    error::SOuLError,
+    game::{GameState, PlayerState},
+    impact::{DisputeCase, DisputeStatus, GeoLocation, ImpactMetrics, ImpactReport, VerificationStatus, VerifierState},
+    lst::{LSTPool, LSTType, StakerInfo, UnstakeRequest},
+    state::{LSTToken, SOuLToken},
+    vesting::{VestingMilestone, VestingSchedule, VestingTranche, VestingType},
+};
+
+/// `SOuLToken` before version 2 only backed legacy SPL Token mints
+#[derive(BorshDeserialize)]
+struct SOuLTokenV1 {
+    is_initialized: bool,
+    mint_authority: Pubkey,
+    mint: Pubkey,
+    total_supply: u64,
+    decimals: u8,
+    bump: u8,
+    mint_authority_bump: u8,
+}
+
+/// `SOuLToken` before version 3 had no supply cap or emission schedule
+#[derive(BorshDeserialize)]
+struct SOuLTokenV2 {
+    is_initialized: bool,
+    mint_authority: Pubkey,
+    mint: Pubkey,
+    total_supply: u64,
+    decimals: u8,
+    bump: u8,
+    mint_authority_bump: u8,
+    token_program: Pubkey,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+    treasury_bump: u8,
+}
+
+impl From<SOuLTokenV1> for SOuLTokenV2 {
+    fn from(old: SOuLTokenV1) -> Self {
+        Self {
+            is_initialized: old.is_initialized,
+            mint_authority: old.mint_authority,
+            mint: old.mint,
+            total_supply: old.total_supply,
+            decimals: old.decimals,
+            bump: old.bump,
+            mint_authority_bump: old.mint_authority_bump,
+            token_program: spl_token::id(),
+            transfer_fee_basis_points: 0,
+            maximum_fee: 0,
+            treasury_bump: 0,
+        }
+    }
+}
+
+impl From<SOuLTokenV2> for SOuLToken {
+    fn from(old: SOuLTokenV2) -> Self {
+        Self {
+            is_initialized: old.is_initialized,
+            mint_authority: old.mint_authority,
+            mint: old.mint,
+            total_supply: old.total_supply,
+            decimals: old.decimals,
+            bump: old.bump,
+            mint_authority_bump: old.mint_authority_bump,
+            token_program: old.token_program,
+            transfer_fee_basis_points: old.transfer_fee_basis_points,
+            maximum_fee: old.maximum_fee,
+            treasury_bump: old.treasury_bump,
+            max_supply: u64::MAX,
+            emission: None,
+        }
+    }
+}
+
+/// `LSTPool` before version 2 did not record the vault holding its SOuL
+#[derive(BorshDeserialize)]
+struct LSTPoolV1 {
+    is_initialized: bool,
+    authority: Pubkey,
+    soul_mint: Pubkey,
+    lst_mint: Pubkey,
+    total_staked: u64,
+    exchange_rate: u64,
+    rewards_per_token: u64,
+    last_update_time: i64,
+    pool_type: LSTType,
+    fee_rate: u64,
+    bump: u8,
+}
+
+impl LSTPoolV1 {
+    fn with_vault(self, soul_vault: Pubkey) -> LSTPool {
+        LSTPool {
+            is_initialized: self.is_initialized,
+            authority: self.authority,
+            soul_mint: self.soul_mint,
+            soul_vault,
+            lst_mint: self.lst_mint,
+            total_staked: self.total_staked,
+            exchange_rate: self.exchange_rate,
+            rewards_per_token: self.rewards_per_token,
+            last_update_time: self.last_update_time,
+            pool_type: self.pool_type,
+            fee_rate: self.fee_rate,
+            bump: self.bump,
+        }
+    }
+}
+
+/// `StakerInfo` before version 1 stored the pool it belongs to
+#[derive(BorshDeserialize)]
+struct StakerInfoV0 {
+    owner: Pubkey,
+    staked_amount: u64,
+    rewards_debt: u64,
+    last_stake_time: i64,
+    bump: u8,
+    unstake_request: Option<UnstakeRequest>,
+}
+
+/// `StakerInfo` before version 2 did not record who paid its rent
+#[derive(BorshDeserialize)]
+struct StakerInfoV1 {
+    owner: Pubkey,
+    pool: Pubkey,
+    staked_amount: u64,
+    rewards_debt: u64,
+    last_stake_time: i64,
+    bump: u8,
+    unstake_request: Option<UnstakeRequest>,
+}
+
+/// `StakerInfo` before version 3 earned rewards from the pool's creation
+/// rather than from its own last accrual
+#[derive(BorshDeserialize)]
+struct StakerInfoV2 {
+    owner: Pubkey,
+    pool: Pubkey,
+    staked_amount: u64,
+    rewards_debt: u64,
+    last_stake_time: i64,
+    bump: u8,
+    unstake_request: Option<UnstakeRequest>,
+    payer: Pubkey,
+}
+
+impl From<StakerInfoV1> for StakerInfoV2 {
+    fn from(old: StakerInfoV1) -> Self {
+        Self {
+            owner: old.owner,
+            pool: old.pool,
+            staked_amount: old.staked_amount,
+            rewards_debt: old.rewards_debt,
+            last_stake_time: old.last_stake_time,
+            bump: old.bump,
+            unstake_request: old.unstake_request,
+            // The staker paid for its own account
+            payer: old.owner,
+        }
+    }
+}
+
+impl StakerInfoV2 {
+    /// Start accruing rewards at `now`, since what older versions owed cannot
+    /// be told apart from what they overpaid
+    fn accruing_from(self, now: UnixTimestamp) -> StakerInfo {
+        StakerInfo {
+            owner: self.owner,
+            pool: self.pool,
+            staked_amount: self.staked_amount,
+            rewards_debt: self.rewards_debt,
+            last_stake_time: self.last_stake_time,
+            bump: self.bump,
+            unstake_request: self.unstake_request,
+            payer: self.payer,
+            accrued_rewards: 0,
+            last_accrual_time: now,
+        }
+    }
+}
+
+/// `VestingSchedule` before version 2 did not record who paid its rent
+#[derive(BorshDeserialize)]
+struct VestingScheduleV1 {
+    is_initialized: bool,
+    beneficiary: Pubkey,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    total_amount: u64,
+    released_amount: u64,
+    vesting_type: VestingType,
+    original_beneficiary: Pubkey,
+    nonce: u64,
+    bump: u8,
+}
+
+/// `VestingSchedule` before version 3 took its cliff and duration from its
+/// vesting type
+#[derive(BorshDeserialize)]
+struct VestingScheduleV2 {
+    is_initialized: bool,
+    beneficiary: Pubkey,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    total_amount: u64,
+    released_amount: u64,
+    vesting_type: VestingType,
+    original_beneficiary: Pubkey,
+    nonce: u64,
+    bump: u8,
+    payer: Pubkey,
+}
+
+impl From<VestingScheduleV1> for VestingScheduleV2 {
+    fn from(old: VestingScheduleV1) -> Self {
+        Self {
+            is_initialized: old.is_initialized,
+            beneficiary: old.beneficiary,
+            start_timestamp: old.start_timestamp,
+            end_timestamp: old.end_timestamp,
+            total_amount: old.total_amount,
+            released_amount: old.released_amount,
+            vesting_type: old.vesting_type,
+            original_beneficiary: old.original_beneficiary,
+            nonce: old.nonce,
+            bump: old.bump,
+            // The paying authority was never stored; the rent goes to the
+            // beneficiary the schedule was created for
+            payer: old.original_beneficiary,
+        }
+    }
+}
+
+/// `VestingSchedule` before version 4 had no tranches
+#[derive(BorshDeserialize)]
+struct VestingScheduleV3 {
+    is_initialized: bool,
+    beneficiary: Pubkey,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    total_amount: u64,
+    released_amount: u64,
+    vesting_type: VestingType,
+    original_beneficiary: Pubkey,
+    nonce: u64,
+    bump: u8,
+    payer: Pubkey,
+    cliff_seconds: i64,
+    duration_seconds: i64,
+    release_interval_seconds: i64,
+}
+
+impl From<VestingScheduleV2> for VestingScheduleV3 {
+    fn from(old: VestingScheduleV2) -> Self {
+        // The schedule now runs to the end it was created with, keeping the
+        // cliff of its vesting type
+        let duration_seconds = old.end_timestamp.saturating_sub(old.start_timestamp).max(1);
+        Self {
+            is_initialized: old.is_initialized,
+            beneficiary: old.beneficiary,
+            start_timestamp: old.start_timestamp,
+            end_timestamp: old.start_timestamp.saturating_add(duration_seconds),
+            total_amount: old.total_amount,
+            released_amount: old.released_amount,
+            cliff_seconds: old.vesting_type.default_cliff_seconds().min(duration_seconds),
+            vesting_type: old.vesting_type,
+            original_beneficiary: old.original_beneficiary,
+            nonce: old.nonce,
+            bump: old.bump,
+            payer: old.payer,
+            duration_seconds,
+            release_interval_seconds: VestingSchedule::DEFAULT_RELEASE_INTERVAL,
+        }
+    }
+}
+
+/// `VestingSchedule` before version 5 had no impact milestones
+#[derive(BorshDeserialize)]
+struct VestingScheduleV4 {
+    is_initialized: bool,
+    beneficiary: Pubkey,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    total_amount: u64,
+    released_amount: u64,
+    vesting_type: VestingType,
+    original_beneficiary: Pubkey,
+    nonce: u64,
+    bump: u8,
+    payer: Pubkey,
+    cliff_seconds: i64,
+    duration_seconds: i64,
+    release_interval_seconds: i64,
+    tranches: Vec<VestingTranche>,
+}
+
+impl From<VestingScheduleV3> for VestingScheduleV4 {
+    fn from(old: VestingScheduleV3) -> Self {
+        Self {
+            is_initialized: old.is_initialized,
+            beneficiary: old.beneficiary,
+            start_timestamp: old.start_timestamp,
+            end_timestamp: old.end_timestamp,
+            total_amount: old.total_amount,
+            released_amount: old.released_amount,
+            vesting_type: old.vesting_type,
+            original_beneficiary: old.original_beneficiary,
+            nonce: old.nonce,
+            bump: old.bump,
+            payer: old.payer,
+            cliff_seconds: old.cliff_seconds,
+            duration_seconds: old.duration_seconds,
+            release_interval_seconds: old.release_interval_seconds,
+            // Existing schedules keep vesting along their curve
+            tranches: Vec::new(),
+        }
+    }
+}
+
+/// `VestingSchedule` before version 6 did not record who could revoke it
+#[derive(BorshDeserialize)]
+struct VestingScheduleV5 {
+    is_initialized: bool,
+    beneficiary: Pubkey,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    total_amount: u64,
+    released_amount: u64,
+    vesting_type: VestingType,
+    original_beneficiary: Pubkey,
+    nonce: u64,
+    bump: u8,
+    payer: Pubkey,
+    cliff_seconds: i64,
+    duration_seconds: i64,
+    release_interval_seconds: i64,
+    tranches: Vec<VestingTranche>,
+    project: Pubkey,
+    milestones: Vec<VestingMilestone>,
+}
+
+impl From<VestingScheduleV4> for VestingScheduleV5 {
+    fn from(old: VestingScheduleV4) -> Self {
+        Self {
+            is_initialized: old.is_initialized,
+            beneficiary: old.beneficiary,
+            start_timestamp: old.start_timestamp,
+            end_timestamp: old.end_timestamp,
+            total_amount: old.total_amount,
+            released_amount: old.released_amount,
+            vesting_type: old.vesting_type,
+            original_beneficiary: old.original_beneficiary,
+            nonce: old.nonce,
+            bump: old.bump,
+            payer: old.payer,
+            cliff_seconds: old.cliff_seconds,
+            duration_seconds: old.duration_seconds,
+            release_interval_seconds: old.release_interval_seconds,
+            tranches: old.tranches,
+            // Impact milestone schedules did not exist yet
+            project: Pubkey::default(),
+            milestones: Vec::new(),
+        }
+    }
+}
+
+/// `VestingSchedule` before version 7 did not record the mint and vault
+/// backing it
+#[derive(BorshDeserialize)]
+struct VestingScheduleV6 {
+    is_initialized: bool,
+    beneficiary: Pubkey,
+    start_timestamp: UnixTimestamp,
+    end_timestamp: UnixTimestamp,
+    total_amount: u64,
+    released_amount: u64,
+    vesting_type: VestingType,
+    original_beneficiary: Pubkey,
+    nonce: u64,
+    bump: u8,
+    payer: Pubkey,
+    cliff_seconds: i64,
+    duration_seconds: i64,
+    release_interval_seconds: i64,
+    tranches: Vec<VestingTranche>,
+    project: Pubkey,
+    milestones: Vec<VestingMilestone>,
+    revoke_authority: Option<Pubkey>,
+    revoked_at: Option<UnixTimestamp>,
+}
+
+impl From<VestingScheduleV5> for VestingScheduleV6 {
+    fn from(old: VestingScheduleV5) -> Self {
+        // Only Team and Advisor schedules were revocable, by the authority
+        // that created them and paid their rent. Schedules from before the
+        // payer was stored record the beneficiary instead, who must not be
+        // able to revoke their own schedule.
+        let revocable = matches!(old.vesting_type, VestingType::Team | VestingType::Advisor);
+        let revoke_authority = (revocable && old.payer != old.original_beneficiary).then_some(old.payer);
+        Self {
+            is_initialized: old.is_initialized,
+            beneficiary: old.beneficiary,
+            start_timestamp: old.start_timestamp,
+            end_timestamp: old.end_timestamp,
+            total_amount: old.total_amount,
+            released_amount: old.released_amount,
+            vesting_type: old.vesting_type,
+            original_beneficiary: old.original_beneficiary,
+            nonce: old.nonce,
+            bump: old.bump,
+            payer: old.payer,
+            cliff_seconds: old.cliff_seconds,
+            duration_seconds: old.duration_seconds,
+            release_interval_seconds: old.release_interval_seconds,
+            tranches: old.tranches,
+            project: old.project,
+            milestones: old.milestones,
+            revoke_authority,
+            revoked_at: None,
+        }
+    }
+}
+
+impl VestingScheduleV6 {
+    fn with_vault(self, vault: Pubkey, mint: Pubkey) -> VestingSchedule {
+        VestingSchedule {
+            is_initialized: self.is_initialized,
+            beneficiary: self.beneficiary,
+            start_timestamp: self.start_timestamp,
+            end_timestamp: self.end_timestamp,
+            total_amount: self.total_amount,
+            released_amount: self.released_amount,
+            vesting_type: self.vesting_type,
+            original_beneficiary: self.original_beneficiary,
+            nonce: self.nonce,
+            bump: self.bump,
+            payer: self.payer,
+            cliff_seconds: self.cliff_seconds,
+            duration_seconds: self.duration_seconds,
+            release_interval_seconds: self.release_interval_seconds,
+            tranches: self.tranches,
+            project: self.project,
+            milestones: self.milestones,
+            revoke_authority: self.revoke_authority,
+            revoked_at: self.revoked_at,
+            mint,
+            vault,
+        }
+    }
+}
+
+/// `DisputeCase` before version 2 did not record who paid its rent
+#[derive(BorshDeserialize)]
+struct DisputeCaseV1 {
+    report_id: Pubkey,
+    challenger: Pubkey,
+    stake_amount: u64,
+    evidence_hash: [u8; 32],
+    status: DisputeStatus,
+    resolution_timestamp: Option<UnixTimestamp>,
+    bump: u8,
+}
+
+impl From<DisputeCaseV1> for DisputeCase {
+    fn from(old: DisputeCaseV1) -> Self {
+        Self {
+            report_id: old.report_id,
+            challenger: old.challenger,
+            stake_amount: old.stake_amount,
+            evidence_hash: old.evidence_hash,
+            status: old.status,
+            resolution_timestamp: old.resolution_timestamp,
+            bump: old.bump,
+            // The challenger paid for the dispute it opened
+            payer: old.challenger,
+        }
+    }
+}
+
+/// `ImpactReport` before version 1 stored the metrics account it was filed against
+#[derive(BorshDeserialize)]
+struct ImpactReportV0 {
+    timestamp: UnixTimestamp,
+    location: GeoLocation,
+    metrics: ImpactMetrics,
+    verifier: Pubkey,
+    verification_status: VerificationStatus,
+    proof_hash: [u8; 32],
+    bump: u8,
+}
+
+/// `ImpactReport` before version 2 did not record when it was verified
+#[derive(BorshDeserialize)]
+struct ImpactReportV1 {
+    metrics_account: Pubkey,
+    timestamp: UnixTimestamp,
+    location: GeoLocation,
+    metrics: ImpactMetrics,
+    verifier: Pubkey,
+    verification_status: VerificationStatus,
+    proof_hash: [u8; 32],
+    bump: u8,
+}
+
+/// `ImpactReport` before version 3 did not name the category it backs
+#[derive(BorshDeserialize)]
+struct ImpactReportV2 {
+    metrics_account: Pubkey,
+    timestamp: UnixTimestamp,
+    location: GeoLocation,
+    metrics: ImpactMetrics,
+    verifier: Pubkey,
+    verification_status: VerificationStatus,
+    proof_hash: [u8; 32],
+    bump: u8,
+    verified_at: Option<UnixTimestamp>,
+}
+
+impl From<ImpactReportV1> for ImpactReportV2 {
+    fn from(old: ImpactReportV1) -> Self {
+        // The verification time was never stored; a report that was upheld
+        // counts as verified when it was submitted
+        let verified_at = matches!(old.verification_status, VerificationStatus::Verified | VerificationStatus::Disputed)
+            .then_some(old.timestamp);
+        Self {
+            metrics_account: old.metrics_account,
+            timestamp: old.timestamp,
+            location: old.location,
+            metrics: old.metrics,
+            verifier: old.verifier,
+            verification_status: old.verification_status,
+            proof_hash: old.proof_hash,
+            bump: old.bump,
+            verified_at,
+        }
+    }
+}
+
+impl From<ImpactReportV2> for ImpactReport {
+    fn from(old: ImpactReportV2) -> Self {
+        // Nothing says which credits an older report was filed for, so it
+        // backs none
+        Self {
+            metrics_account: old.metrics_account,
+            timestamp: old.timestamp,
+            location: old.location,
+            metrics: old.metrics,
+            verifier: old.verifier,
+            verification_status: old.verification_status,
+            proof_hash: old.proof_hash,
+            bump: old.bump,
+            verified_at: old.verified_at,
+            category: None,
+        }
+    }
+}
+
+pub fn process_migrate(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    account_type: AccountType,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let payer_info = next_account_info(account_info_iter)?;
+    let account_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if !payer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if account_info.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let ctx = Migration {
+        program_id,
+        payer_info,
+        account_info,
+        system_program_info,
+    };
+
+    match account_type {
+        AccountType::SOuLToken => ctx.upgrade_versioned(
+            |version, body| match version {
+                0 | 1 => SOuLTokenV1::deserialize(body).map(|old| SOuLTokenV2::from(old).into()),
+                _ => SOuLTokenV2::deserialize(body).map(SOuLToken::from),
+            },
+            |token: &SOuLToken| {
+                vec![SOuLToken::SEED.to_vec(), token.mint.to_bytes().to_vec(), vec![token.bump]]
+            },
+        ),
+        AccountType::LSTToken => ctx.upgrade(|token: &LSTToken| {
+            vec![LSTToken::SEED.to_vec(), vec![token.token_type as u8], vec![token.bump]]
+        }),
+        AccountType::VestingSchedule => {
+            // Older schedules are bound to the token account holding their
+            // tokens, which only the vesting PDA can move out of
+            let vault_info = next_account_info(account_info_iter)?;
+            if *vault_info.owner != spl_token::id() {
+                return Err(SOuLError::InvalidAccountType.into());
+            }
+            let vault = TokenAccount::unpack(&vault_info.data.borrow())?;
+            if vault.owner != *account_info.key {
+                return Err(SOuLError::InvalidAccountType.into());
+            }
+            ctx.upgrade_versioned(
+                |version, body| {
+                    let old = match version {
+                        0 | 1 => VestingScheduleV1::deserialize(body).map(|old| {
+                            let old = VestingScheduleV3::from(VestingScheduleV2::from(old));
+                            VestingScheduleV6::from(VestingScheduleV5::from(VestingScheduleV4::from(old)))
+                        }),
+                        2 => VestingScheduleV2::deserialize(body).map(|old| {
+                            let old = VestingScheduleV4::from(VestingScheduleV3::from(old));
+                            VestingScheduleV6::from(VestingScheduleV5::from(old))
+                        }),
+                        3 => VestingScheduleV3::deserialize(body).map(|old| {
+                            VestingScheduleV6::from(VestingScheduleV5::from(VestingScheduleV4::from(old)))
+                        }),
+                        4 => VestingScheduleV4::deserialize(body)
+                            .map(|old| VestingScheduleV6::from(VestingScheduleV5::from(old))),
+                        5 => VestingScheduleV5::deserialize(body).map(VestingScheduleV6::from),
+                        _ => VestingScheduleV6::deserialize(body),
+                    }?;
+                    // The vault is picked by whoever could recover the unvested
+                    // tokens, never by a beneficiary who can be revoked, and
+                    // must hold what is left to release
+                    let unreleased = if old.revoked_at.is_some() {
+                        0
+                    } else {
+                        old.total_amount.saturating_sub(old.released_amount)
+                    };
+                    if *payer_info.key != old.revoke_authority.unwrap_or(old.payer) || vault.amount < unreleased {
+                        return Err(std::io::ErrorKind::InvalidData.into());
+                    }
+                    Ok(old.with_vault(*vault_info.key, vault.mint))
+                },
+                |schedule: &VestingSchedule| {
+                    vec![
+                        VestingSchedule::SEED.to_vec(),
+                        schedule.original_beneficiary.to_bytes().to_vec(),
+                        schedule.nonce.to_le_bytes().to_vec(),
+                        vec![schedule.bump],
+                    ]
+                },
+            )
+        }
+        AccountType::LSTPool => {
+            // Older pools kept their SOuL in a token account owned by the
+            // pool PDA, which the pool authority names as its vault
+            let vault_info = next_account_info(account_info_iter)?;
+            if *vault_info.owner != spl_token::id() {
+                return Err(SOuLError::InvalidAccountType.into());
+            }
+            let vault = TokenAccount::unpack(&vault_info.data.borrow())?;
+            if vault.owner != *account_info.key {
+                return Err(SOuLError::InvalidAccountType.into());
+            }
+            ctx.upgrade_versioned(
+                |_, body| {
+                    let old = LSTPoolV1::deserialize(body)?;
+                    if *payer_info.key != old.authority || vault.mint != old.soul_mint {
+                        return Err(std::io::ErrorKind::InvalidData.into());
+                    }
+                    Ok(old.with_vault(*vault_info.key))
+                },
+                |pool: &LSTPool| vec![LSTPool::SEED.to_vec(), vec![pool.pool_type as u8], vec![pool.bump]],
+            )
+        }
+        AccountType::StakerInfo => {
+            let pool_info = next_account_info(account_info_iter)?;
+            let now = Clock::get()?.unix_timestamp;
+            ctx.upgrade_versioned(
+                |version, body| match version {
+                    0 => StakerInfoV0::deserialize(body).map(|old| {
+                        StakerInfoV2::from(StakerInfoV1 {
+                            owner: old.owner,
+                            pool: *pool_info.key,
+                            staked_amount: old.staked_amount,
+                            rewards_debt: old.rewards_debt,
+                            last_stake_time: old.last_stake_time,
+                            bump: old.bump,
+                            unstake_request: old.unstake_request,
+                        })
+                        .accruing_from(now)
+                    }),
+                    1 => StakerInfoV1::deserialize(body).map(|old| StakerInfoV2::from(old).accruing_from(now)),
+                    _ => StakerInfoV2::deserialize(body).map(|old| old.accruing_from(now)),
+                },
+                |staker: &StakerInfo| {
+                    vec![
+                        StakerInfo::SEED.to_vec(),
+                        staker.pool.to_bytes().to_vec(),
+                        staker.owner.to_bytes().to_vec(),
+                        vec![staker.bump],
+                    ]
+                },
+            )
+        }
+        AccountType::ImpactMetrics => ctx.upgrade(|metrics: &ImpactMetrics| {
+            vec![ImpactMetrics::SEED.to_vec(), metrics.authority.to_bytes().to_vec(), vec![metrics.bump]]
+        }),
+        AccountType::ImpactReport => {
+            let metrics_info = next_account_info(account_info_iter)?;
+            ctx.upgrade_versioned(
+                |version, body| match version {
+                    0 => ImpactReportV0::deserialize(body).map(|old| {
+                        ImpactReportV2::from(ImpactReportV1 {
+                            metrics_account: *metrics_info.key,
+                            timestamp: old.timestamp,
+                            location: old.location,
+                            metrics: old.metrics,
+                            verifier: old.verifier,
+                            verification_status: old.verification_status,
+                            proof_hash: old.proof_hash,
+                            bump: old.bump,
+                        })
+                        .into()
+                    }),
+                    1 => ImpactReportV1::deserialize(body).map(|old| ImpactReportV2::from(old).into()),
+                    _ => ImpactReportV2::deserialize(body).map(ImpactReport::from),
+                },
+                |report: &ImpactReport| {
+                    vec![
+                        ImpactReport::SEED.to_vec(),
+                        report.metrics_account.to_bytes().to_vec(),
+                        report.proof_hash.to_vec(),
+                        vec![report.bump],
+                    ]
+                },
+            )
+        }
+        AccountType::VerifierState => ctx.upgrade(|verifier: &VerifierState| {
+            vec![
+                VerifierState::SEED.to_vec(),
+                verifier.verifier_pubkey.to_bytes().to_vec(),
+                vec![verifier.bump],
+            ]
+        }),
+        AccountType::DisputeCase => ctx.upgrade_from(
+            |old: DisputeCaseV1| DisputeCase::from(old),
+            |dispute: &DisputeCase| {
+                vec![
+                    DisputeCase::SEED.to_vec(),
+                    dispute.report_id.to_bytes().to_vec(),
+                    dispute.challenger.to_bytes().to_vec(),
+                    vec![dispute.bump],
+                ]
+            },
+        ),
+        AccountType::GameState => {
+            ctx.upgrade(|game: &GameState| vec![GameState::SEED.to_vec(), vec![game.bump]])
+        }
+        AccountType::PlayerState => ctx.upgrade(|player: &PlayerState| {
+            vec![PlayerState::SEED.to_vec(), player.owner.to_bytes().to_vec(), vec![player.bump]]
+        }),
+    }
+}
+
+struct Migration<'a, 'b> {
+    program_id: &'b Pubkey,
+    payer_info: &'b AccountInfo<'a>,
+    account_info: &'b AccountInfo<'a>,
+    system_program_info: &'b AccountInfo<'a>,
+}
+
+impl<'a, 'b> Migration<'a, 'b> {
+    /// Upgrade a type whose version 0 body matches its current body
+    fn upgrade<T: AccountState>(&self, seeds: impl FnOnce(&T) -> Vec<Vec<u8>>) -> ProgramResult {
+        self.upgrade_from(|old: T| old, seeds)
+    }
+
+    /// Decode the body of an older version as `V`, convert it and rewrite the account
+    fn upgrade_from<V: BorshDeserialize, T: AccountState>(
+        &self,
+        convert: impl FnOnce(V) -> T,
+        seeds: impl FnOnce(&T) -> Vec<Vec<u8>>,
+    ) -> ProgramResult {
+        self.upgrade_versioned(|_, body| V::deserialize(body).map(convert), seeds)
+    }
+
+    /// Decode the body of an older version with `decode`, which is given the
+    /// version in the header (0 for a headerless account), and rewrite the account
+    fn upgrade_versioned<T: AccountState>(
+        &self,
+        decode: impl FnOnce(u8, &mut &[u8]) -> std::io::Result<T>,
+        seeds: impl FnOnce(&T) -> Vec<Vec<u8>>,
+    ) -> ProgramResult {
+        let value = {
+            let data = self.account_info.data.borrow();
+            if T::is_current(&data) {
+                msg!("Account already uses layout version {}", T::VERSION);
+                return Ok(());
+            }
+            let mut body = &data[..];
+            let mut version = 0;
+            if data.len() >= HEADER_LEN && data[..8] == T::DISCRIMINATOR {
+                if data[8] > T::VERSION {
+                    return Err(SOuLError::AccountVersionMismatch.into());
+                }
+                version = data[8];
+                body = &data[HEADER_LEN..];
+            }
+            decode(version, &mut body).map_err(|_| SOuLError::InvalidAccountType)?
+        };
+
+        // The decoded fields must derive this very account, which rules out
+        // reading one account type as another
+        let seeds = seeds(&value);
+        let seeds = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        match Pubkey::create_program_address(&seeds, self.program_id) {
+            Ok(expected) if expected == *self.account_info.key => {}
+            _ => return Err(SOuLError::InvalidAccountType.into()),
+        }
+
+        let space = value.space_for().max(self.account_info.data_len());
+        let required_lamports = Rent::get()?.minimum_balance(space);
+        let top_up = required_lamports.saturating_sub(self.account_info.lamports());
+        if top_up > 0 {
+            invoke(
+                &system_instruction::transfer(self.payer_info.key, self.account_info.key, top_up),
+                &[
+                    self.payer_info.clone(),
+                    self.account_info.clone(),
+                    self.system_program_info.clone(),
+                ],
+            )?;
+        }
+
+        self.account_info.realloc(space, true)?;
+        self.account_info.data.borrow_mut().fill(0);
+        value.save(self.account_info)?;
+
+        msg!("Account migrated to layout version {}", T::VERSION);
+        Ok(())
+    }
+}
@@ -0,0 +1,231 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    system_program,
+};
+use crate::{error::module_error, instruction::ModuleTag};
+use super::state::{Multisig, Proposal, ProposedAccount};
+
+#[derive(BorshSerialize, BorshDeserialize, Debug)]
+pub enum MultisigInstruction {
+    /// Create an M-of-N multisig
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` The creator, pays rent
+    /// 1. `[writable]` The multisig PDA to create (`["multisig", creator, nonce]`)
+    /// 2. `[]` The system program
+    Create {
+        nonce: u64,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+
+    /// Propose a program instruction to be signed by the multisig signer PDA.
+    /// The proposer's approval is recorded with the proposal.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` A signer of the multisig, pays rent
+    /// 1. `[writable]` The multisig
+    /// 2. `[writable]` The proposal PDA to create (`["proposal", multisig, index]`)
+    /// 3. `[]` The system program
+    Propose {
+        accounts: Vec<ProposedAccount>,
+        data: Vec<u8>,
+    },
+
+    /// Approve a pending proposal
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` A signer of the multisig
+    /// 1. `[]` The multisig
+    /// 2. `[writable]` The proposal
+    Approve,
+
+    /// Execute a proposal that reached the threshold. Anyone may execute.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The multisig
+    /// 1. `[writable]` The proposal
+    /// 2. `[]` This program
+    /// 3. `[]` The accounts of the proposed instruction, in order
+    Execute,
+
+    /// Replace the signers and threshold, voiding pending proposals. Only
+    /// callable through an executed proposal.
+    ///
+    /// Accounts expected:
+    /// 0. `[signer]` The multisig signer PDA (`["multisig_signer", multisig]`)
+    /// 1. `[writable]` The multisig
+    SetSigners {
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    },
+
+    /// Close an executed proposal, or one voided by a change of signers,
+    /// returning its rent to the proposer. Anyone may close.
+    ///
+    /// Accounts expected:
+    /// 0. `[]` The multisig
+    /// 1. `[writable]` The proposal
+    /// 2. `[writable]` The proposer
+    CloseProposal,
+}
+
+impl MultisigInstruction {
+    pub fn create(
+        program_id: &Pubkey,
+        creator: &Pubkey,
+        nonce: u64,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<Instruction, ProgramError> {
+        let (multisig, _) = Multisig::find_address(creator, nonce, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*creator, true),
+                AccountMeta::new(multisig, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Multisig.pack(&MultisigInstruction::Create {
+                nonce,
+                signers,
+                threshold,
+            })?,
+        })
+    }
+
+    /// Propose `instruction`, which must target this program. `index` is the
+    /// multisig's current `proposal_count`.
+    pub fn propose(
+        program_id: &Pubkey,
+        proposer: &Pubkey,
+        multisig: &Pubkey,
+        index: u64,
+        instruction: &Instruction,
+    ) -> Result<Instruction, ProgramError> {
+        if instruction.program_id != *program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        let (proposal, _) = Proposal::find_address(multisig, index, program_id);
+        let accounts = instruction
+            .accounts
+            .iter()
+            .map(|meta| ProposedAccount {
+                pubkey: meta.pubkey,
+                is_signer: meta.is_signer,
+                is_writable: meta.is_writable,
+            })
+            .collect();
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*proposer, true),
+                AccountMeta::new(*multisig, false),
+                AccountMeta::new(proposal, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+            data: ModuleTag::Multisig.pack(&MultisigInstruction::Propose {
+                accounts,
+                data: instruction.data.clone(),
+            })?,
+        })
+    }
+
+    pub fn approve(
+        program_id: &Pubkey,
+        signer: &Pubkey,
+        multisig: &Pubkey,
+        proposal: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*signer, true),
+                AccountMeta::new_readonly(*multisig, false),
+                AccountMeta::new(*proposal, false),
+            ],
+            data: ModuleTag::Multisig.pack(&MultisigInstruction::Approve)?,
+        })
+    }
+
+    /// Execute `proposal`, which holds `instruction`. The signer PDA is
+    /// passed as a plain account; the program signs for it.
+    pub fn execute(
+        program_id: &Pubkey,
+        multisig: &Pubkey,
+        proposal: &Pubkey,
+        instruction: &Instruction,
+    ) -> Result<Instruction, ProgramError> {
+        let mut accounts = vec![
+            AccountMeta::new(*multisig, false),
+            AccountMeta::new(*proposal, false),
+            AccountMeta::new_readonly(*program_id, false),
+        ];
+        accounts.extend(instruction.accounts.iter().map(|meta| AccountMeta {
+            pubkey: meta.pubkey,
+            is_signer: false,
+            is_writable: meta.is_writable,
+        }));
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data: ModuleTag::Multisig.pack(&MultisigInstruction::Execute)?,
+        })
+    }
+
+    /// The instruction to propose for changing the signers of `multisig`
+    pub fn set_signers(
+        program_id: &Pubkey,
+        multisig: &Pubkey,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+    ) -> Result<Instruction, ProgramError> {
+        let (multisig_signer, _) = Multisig::find_signer(multisig, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(multisig_signer, true),
+                AccountMeta::new(*multisig, false),
+            ],
+            data: ModuleTag::Multisig.pack(&MultisigInstruction::SetSigners { signers, threshold })?,
+        })
+    }
+
+    pub fn close_proposal(
+        program_id: &Pubkey,
+        multisig: &Pubkey,
+        proposal: &Pubkey,
+        proposer: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*multisig, false),
+                AccountMeta::new(*proposal, false),
+                AccountMeta::new(*proposer, false),
+            ],
+            data: ModuleTag::Multisig.pack(&MultisigInstruction::CloseProposal)?,
+        })
+    }
+}
+
+module_error! {
+    pub enum MultisigError in Multisig {
+        InvalidInstruction => "Invalid Instruction",
+        InvalidSigners => "Invalid Signers Or Threshold",
+        NotASigner => "Not A Multisig Signer",
+        InvalidProposal => "Invalid Proposal",
+        AlreadyApproved => "Proposal Already Approved",
+        AlreadyExecuted => "Proposal Already Executed",
+        NotEnoughApprovals => "Not Enough Approvals",
+        StaleProposal => "Signers Changed Since Proposal",
+        ProposalPending => "Proposal Still Pending",
+    }
+}
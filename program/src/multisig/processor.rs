@@ -0,0 +1,347 @@
+use borsh::BorshDeserialize;
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    sysvar::{clock::Clock, Sysvar},
+};
+
+use crate::{account::AccountState, event::SoulEvent, instruction::ModuleTag, pda};
+
+use super::{
+    instruction::{MultisigError, MultisigInstruction},
+    state::{Multisig, Proposal, ProposedAccount},
+};
+
+pub struct Processor;
+
+impl Processor {
+    pub fn process(
+        program_id: &Pubkey,
+        accounts: &[AccountInfo],
+        instruction_data: &[u8],
+    ) -> ProgramResult {
+        let instruction = MultisigInstruction::try_from_slice(instruction_data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+        match instruction {
+            MultisigInstruction::Create { nonce, signers, threshold } => {
+                Self::process_create(accounts, nonce, signers, threshold, program_id)
+            }
+            MultisigInstruction::Propose { accounts: proposed_accounts, data } => {
+                Self::process_propose(accounts, proposed_accounts, data, program_id)
+            }
+            MultisigInstruction::Approve => Self::process_approve(accounts, program_id),
+            MultisigInstruction::Execute => Self::process_execute(accounts, program_id),
+            MultisigInstruction::SetSigners { signers, threshold } => {
+                Self::process_set_signers(accounts, signers, threshold, program_id)
+            }
+            MultisigInstruction::CloseProposal => Self::process_close_proposal(accounts, program_id),
+        }
+    }
+
+    fn process_create(
+        accounts: &[AccountInfo],
+        nonce: u64,
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let creator_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !creator_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !Multisig::is_valid_config(&signers, threshold) {
+            return Err(MultisigError::InvalidSigners.into());
+        }
+
+        let nonce_bytes = nonce.to_le_bytes();
+        let bump = pda::expect_pda(
+            multisig_info.key,
+            &[Multisig::SEED, creator_info.key.as_ref(), &nonce_bytes],
+            program_id,
+        )?;
+        pda::create_pda_account(
+            creator_info,
+            multisig_info,
+            system_program_info,
+            program_id,
+            Multisig::SPACE,
+            &[Multisig::SEED, creator_info.key.as_ref(), &nonce_bytes, &[bump]],
+        )?;
+
+        let (_, signer_bump) = Multisig::find_signer(multisig_info.key, program_id);
+        let multisig = Multisig {
+            is_initialized: true,
+            creator: *creator_info.key,
+            nonce,
+            signers,
+            threshold,
+            signers_version: 0,
+            proposal_count: 0,
+            bump,
+            signer_bump,
+        };
+
+        multisig.save(multisig_info)
+    }
+
+    fn process_propose(
+        accounts: &[AccountInfo],
+        proposed_accounts: Vec<ProposedAccount>,
+        data: Vec<u8>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let proposer_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !proposer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut multisig = Multisig::load(multisig_info, program_id)?;
+        let signer_index = multisig
+            .signer_index(proposer_info.key)
+            .ok_or(MultisigError::NotASigner)?;
+
+        // The program can only sign for the multisig's own signer PDA
+        let multisig_signer = signer_address(multisig_info.key, &multisig, program_id)?;
+        if proposed_accounts.len() > Proposal::MAX_ACCOUNTS
+            || data.len() > Proposal::MAX_DATA_LEN
+            || proposed_accounts
+                .iter()
+                .any(|account| account.is_signer && account.pubkey != multisig_signer)
+        {
+            return Err(MultisigError::InvalidProposal.into());
+        }
+
+        let index = multisig.proposal_count;
+        let index_bytes = index.to_le_bytes();
+        let bump = pda::expect_pda(
+            proposal_info.key,
+            &[Proposal::SEED, multisig_info.key.as_ref(), &index_bytes],
+            program_id,
+        )?;
+        pda::create_pda_account(
+            proposer_info,
+            proposal_info,
+            system_program_info,
+            program_id,
+            Proposal::SPACE,
+            &[Proposal::SEED, multisig_info.key.as_ref(), &index_bytes, &[bump]],
+        )?;
+
+        let proposal = Proposal {
+            multisig: *multisig_info.key,
+            index,
+            proposer: *proposer_info.key,
+            signers_version: multisig.signers_version,
+            accounts: proposed_accounts,
+            data,
+            approvals: 1 << signer_index,
+            executed: false,
+            bump,
+        };
+        proposal.save(proposal_info)?;
+
+        multisig.proposal_count += 1;
+        multisig.save(multisig_info)?;
+
+        SoulEvent::ProposalCreated {
+            multisig: *multisig_info.key,
+            proposal: *proposal_info.key,
+            proposer: *proposer_info.key,
+            index,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_approve(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let signer_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+
+        if !signer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let multisig = Multisig::load(multisig_info, program_id)?;
+        let mut proposal = load_pending_proposal(proposal_info, multisig_info.key, &multisig, program_id)?;
+        let signer_index = multisig
+            .signer_index(signer_info.key)
+            .ok_or(MultisigError::NotASigner)?;
+
+        let bit = 1 << signer_index;
+        if proposal.approvals & bit != 0 {
+            return Err(MultisigError::AlreadyApproved.into());
+        }
+        proposal.approvals |= bit;
+        proposal.save(proposal_info)?;
+
+        SoulEvent::ProposalApproved {
+            multisig: *multisig_info.key,
+            proposal: *proposal_info.key,
+            signer: *signer_info.key,
+            approvals: proposal.approval_count(),
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_execute(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+        let program_info = next_account_info(account_info_iter)?;
+        let instruction_accounts = account_info_iter.as_slice();
+
+        if program_info.key != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let multisig = Multisig::load(multisig_info, program_id)?;
+        let mut proposal = load_pending_proposal(proposal_info, multisig_info.key, &multisig, program_id)?;
+
+        if proposal.approval_count() < multisig.threshold as u32 {
+            return Err(MultisigError::NotEnoughApprovals.into());
+        }
+
+        if instruction_accounts.len() < proposal.accounts.len()
+            || proposal
+                .accounts
+                .iter()
+                .zip(instruction_accounts)
+                .any(|(account, info)| account.pubkey != *info.key)
+        {
+            return Err(MultisigError::InvalidProposal.into());
+        }
+
+        // Mark the proposal executed before the call so it cannot be replayed
+        // from inside it
+        proposal.executed = true;
+        proposal.save(proposal_info)?;
+
+        invoke_signed(
+            &proposal.instruction(program_id),
+            accounts,
+            &[&[Multisig::SIGNER_SEED, multisig_info.key.as_ref(), &[multisig.signer_bump]]],
+        )?;
+
+        SoulEvent::ProposalExecuted {
+            multisig: *multisig_info.key,
+            proposal: *proposal_info.key,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+
+    fn process_set_signers(
+        accounts: &[AccountInfo],
+        signers: Vec<Pubkey>,
+        threshold: u8,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_signer_info = next_account_info(account_info_iter)?;
+        let multisig_info = next_account_info(account_info_iter)?;
+
+        if !multisig_signer_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        let mut multisig = Multisig::load(multisig_info, program_id)?;
+        if *multisig_signer_info.key != signer_address(multisig_info.key, &multisig, program_id)? {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if !Multisig::is_valid_config(&signers, threshold) {
+            return Err(MultisigError::InvalidSigners.into());
+        }
+
+        multisig.signers = signers;
+        multisig.threshold = threshold;
+        multisig.signers_version += 1;
+        multisig.save(multisig_info)
+    }
+
+    fn process_close_proposal(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let multisig_info = next_account_info(account_info_iter)?;
+        let proposal_info = next_account_info(account_info_iter)?;
+        let proposer_info = next_account_info(account_info_iter)?;
+
+        let multisig = Multisig::load(multisig_info, program_id)?;
+        let proposal = Proposal::load(proposal_info, program_id)?;
+        if proposal.multisig != *multisig_info.key {
+            return Err(MultisigError::InvalidProposal.into());
+        }
+        if !proposal.executed && proposal.signers_version == multisig.signers_version {
+            return Err(MultisigError::ProposalPending.into());
+        }
+
+        let lamports = pda::close_to_payer(proposal_info, proposer_info, &proposal.proposer)?;
+
+        SoulEvent::AccountClosed {
+            module: ModuleTag::Multisig,
+            account: *proposal_info.key,
+            payer: *proposer_info.key,
+            lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+}
+
+/// Address of the PDA that signs for `multisig`
+fn signer_address(
+    multisig_key: &Pubkey,
+    multisig: &Multisig,
+    program_id: &Pubkey,
+) -> Result<Pubkey, ProgramError> {
+    Pubkey::create_program_address(
+        &[Multisig::SIGNER_SEED, multisig_key.as_ref(), &[multisig.signer_bump]],
+        program_id,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)
+}
+
+/// Load a proposal of `multisig` that can still be approved or executed
+fn load_pending_proposal(
+    proposal_info: &AccountInfo,
+    multisig_key: &Pubkey,
+    multisig: &Multisig,
+    program_id: &Pubkey,
+) -> Result<Proposal, ProgramError> {
+    let proposal = Proposal::load(proposal_info, program_id)?;
+    if proposal.multisig != *multisig_key {
+        return Err(MultisigError::InvalidProposal.into());
+    }
+    if proposal.executed {
+        return Err(MultisigError::AlreadyExecuted.into());
+    }
+    if proposal.signers_version != multisig.signers_version {
+        return Err(MultisigError::StaleProposal.into());
+    }
+    Ok(proposal)
+}
@@ -0,0 +1,147 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_program::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+
+use crate::account::{AccountState, HEADER_LEN};
+
+/// An M-of-N set of keys acting as one authority.
+///
+/// The multisig acts through its signer PDA (`["multisig_signer", multisig]`),
+/// which is what gets installed as a mint, pool or metrics authority and
+/// signs every executed proposal.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Multisig {
+    pub is_initialized: bool,
+    pub creator: Pubkey,
+    pub nonce: u64,
+    pub signers: Vec<Pubkey>,
+    /// Approvals needed to execute a proposal
+    pub threshold: u8,
+    /// Bumped whenever the signers change, which voids pending proposals
+    pub signers_version: u32,
+    /// Index of the next proposal
+    pub proposal_count: u64,
+    pub bump: u8,
+    pub signer_bump: u8,
+}
+
+/// An account of a proposed instruction
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ProposedAccount {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// A program instruction awaiting approval by a multisig
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub struct Proposal {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    /// `Multisig::signers_version` when the proposal was made
+    pub signers_version: u32,
+    pub accounts: Vec<ProposedAccount>,
+    pub data: Vec<u8>,
+    /// Bit `i` is set once `signers[i]` has approved
+    pub approvals: u16,
+    pub executed: bool,
+    pub bump: u8,
+}
+
+impl Multisig {
+    pub const MAX_SIGNERS: usize = 10;
+
+    pub const LEN: usize = 1 + 32 + 8 + 4 + 32 * Self::MAX_SIGNERS + 1 + 4 + 8 + 1 + 1;
+
+    /// Seed of the multisig PDA (`["multisig", creator, nonce]`)
+    pub const SEED: &'static [u8] = b"multisig";
+
+    /// Seed of the data-less PDA that signs for the multisig
+    /// (`["multisig_signer", multisig]`)
+    pub const SIGNER_SEED: &'static [u8] = b"multisig_signer";
+
+    pub fn find_address(creator: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, creator.as_ref(), &nonce.to_le_bytes()], program_id)
+    }
+
+    pub fn find_signer(multisig: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SIGNER_SEED, multisig.as_ref()], program_id)
+    }
+
+    /// Position of `key` among the signers
+    pub fn signer_index(&self, key: &Pubkey) -> Option<usize> {
+        self.signers.iter().position(|signer| signer == key)
+    }
+
+    /// Whether `signers` and `threshold` form a usable M-of-N set
+    pub fn is_valid_config(signers: &[Pubkey], threshold: u8) -> bool {
+        let unique = signers
+            .iter()
+            .enumerate()
+            .all(|(i, signer)| !signers[..i].contains(signer));
+        unique
+            && signers.len() <= Self::MAX_SIGNERS
+            && threshold > 0
+            && threshold as usize <= signers.len()
+    }
+}
+
+impl Proposal {
+    pub const MAX_ACCOUNTS: usize = 24;
+    pub const MAX_DATA_LEN: usize = 512;
+
+    pub const LEN: usize = 32
+        + 8
+        + 32
+        + 4
+        + 4
+        + (32 + 1 + 1) * Self::MAX_ACCOUNTS
+        + 4
+        + Self::MAX_DATA_LEN
+        + 2
+        + 1
+        + 1;
+
+    /// Seed of the proposal PDA (`["proposal", multisig, index]`)
+    pub const SEED: &'static [u8] = b"proposal";
+
+    pub fn find_address(multisig: &Pubkey, index: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, multisig.as_ref(), &index.to_le_bytes()], program_id)
+    }
+
+    pub fn approval_count(&self) -> u32 {
+        self.approvals.count_ones()
+    }
+
+    /// The proposed instruction, sent to `program_id`
+    pub fn instruction(&self, program_id: &Pubkey) -> Instruction {
+        Instruction {
+            program_id: *program_id,
+            accounts: self
+                .accounts
+                .iter()
+                .map(|account| AccountMeta {
+                    pubkey: account.pubkey,
+                    is_signer: account.is_signer,
+                    is_writable: account.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+impl AccountState for Multisig {
+    const DISCRIMINATOR: [u8; 8] = *b"multisig";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+impl AccountState for Proposal {
+    const DISCRIMINATOR: [u8; 8] = *b"msigprop";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
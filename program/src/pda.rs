@@ -0,0 +1,176 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use spl_token_2022::{
+    extension::{transfer_fee::TransferFeeAmount, BaseStateWithExtensions, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
+
+use crate::error::SOuLError;
+
+/// Create a program-owned account at a PDA, paid for by `payer`.
+///
+/// `signer_seeds` must include the bump seed. An address that was already
+/// sent lamports is topped up to rent exemption, then allocated and assigned.
+pub fn create_pda_account<'a>(
+    payer: &AccountInfo<'a>,
+    new_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    program_id: &Pubkey,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    if !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *system_program.key != solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if new_account.owner == program_id {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+
+    let required_lamports = Rent::get()?.minimum_balance(space);
+
+    if new_account.lamports() == 0 {
+        invoke_signed(
+            &system_instruction::create_account(
+                payer.key,
+                new_account.key,
+                required_lamports,
+                space as u64,
+                program_id,
+            ),
+            &[payer.clone(), new_account.clone(), system_program.clone()],
+            &[signer_seeds],
+        )?;
+        return Ok(());
+    }
+
+    let top_up = required_lamports.saturating_sub(new_account.lamports());
+    if top_up > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, new_account.key, top_up),
+            &[payer.clone(), new_account.clone(), system_program.clone()],
+        )?;
+    }
+    invoke_signed(
+        &system_instruction::allocate(new_account.key, space as u64),
+        &[new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(new_account.key, program_id),
+        &[new_account.clone(), system_program.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Check that `key` is the canonical PDA for `seeds` and return its bump
+pub fn expect_pda(key: &Pubkey, seeds: &[&[u8]], program_id: &Pubkey) -> Result<u8, ProgramError> {
+    let (expected, bump) = Pubkey::find_program_address(seeds, program_id);
+    if expected != *key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(bump)
+}
+
+/// Close a program-owned account, moving its lamports to `destination`
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    account.try_borrow_mut_data()?.fill(0);
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.realloc(0, false)?;
+    account.assign(&solana_program::system_program::id());
+    Ok(())
+}
+
+/// Close a program-owned account, refunding the payer recorded at its
+/// creation. Returns the lamports refunded.
+pub fn close_to_payer(account: &AccountInfo, payer_info: &AccountInfo, payer: &Pubkey) -> Result<u64, ProgramError> {
+    if payer_info.key != payer {
+        return Err(SOuLError::RentPayerMismatch.into());
+    }
+    let lamports = account.lamports();
+    close_account(account, payer_info)?;
+    Ok(lamports)
+}
+
+/// Close a token vault owned by the PDA `authority`, which signs with
+/// `signer_seeds`, sending its rent to `rent_destination`.
+///
+/// Anyone can send tokens to the vault, and a transfer fee mint withholds
+/// fees in it when it is funded; either would keep it from closing. Tokens
+/// left in it go to `leftover_destination` and withheld fees to the mint.
+#[allow(clippy::too_many_arguments)]
+pub fn close_token_vault<'a>(
+    vault: &AccountInfo<'a>,
+    mint: &AccountInfo<'a>,
+    authority: &AccountInfo<'a>,
+    leftover_destination: &AccountInfo<'a>,
+    rent_destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    let (remaining, withheld) = {
+        let data = vault.data.borrow();
+        let vault_state = StateWithExtensions::<TokenAccount>::unpack(&data)?;
+        let withheld = vault_state
+            .get_extension::<TransferFeeAmount>()
+            .map_or(0, |fee| u64::from(fee.withheld_amount));
+        (vault_state.base.amount, withheld)
+    };
+
+    if remaining > 0 {
+        let decimals = StateWithExtensions::<Mint>::unpack(&mint.data.borrow())?.base.decimals;
+        let transfer_ix = spl_token_2022::instruction::transfer_checked(
+            token_program.key,
+            vault.key,
+            mint.key,
+            leftover_destination.key,
+            authority.key,
+            &[],
+            remaining,
+            decimals,
+        )?;
+        invoke_signed(
+            &transfer_ix,
+            &[
+                vault.clone(),
+                mint.clone(),
+                leftover_destination.clone(),
+                authority.clone(),
+                token_program.clone(),
+            ],
+            &[signer_seeds],
+        )?;
+    }
+
+    if withheld > 0 {
+        let harvest_ix = spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+            token_program.key,
+            mint.key,
+            &[vault.key],
+        )?;
+        invoke(&harvest_ix, &[mint.clone(), vault.clone(), token_program.clone()])?;
+    }
+
+    let close_ix =
+        spl_token_2022::instruction::close_account(token_program.key, vault.key, rent_destination.key, authority.key, &[])?;
+    invoke_signed(
+        &close_ix,
+        &[vault.clone(), rent_destination.clone(), authority.clone(), token_program.clone()],
+        &[signer_seeds],
+    )
+}
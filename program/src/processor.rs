@@ -4,18 +4,70 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
     msg,
-    rent::Rent,
-    sysvar::Sysvar,
+    program::{invoke, invoke_signed},
+    sysvar::{clock::Clock, Sysvar},
 };
-use borsh::{BorshDeserialize, BorshSerialize};
+use spl_token_2022::{
+    extension::{ExtensionType, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
+use borsh::BorshDeserialize;
 
 use crate::{
-    instruction::SOuLInstruction,
+    access::{is_authorized, Role},
+    account::AccountState,
+    instruction::{ModuleTag, SOuLInstruction},
     error::SOuLError,
-    state::{SOuLToken, LSTToken, LSTTokenType},
+    event::SoulEvent,
+    impact::{ImpactReport, VerificationStatus},
+    lst::{LSTPool, LSTType},
+    state::{
+        ComplianceAction, ComplianceEntry, ComplianceHistory, ComplianceRecord, EmissionSchedule, SOuLToken,
+        LSTToken, LSTTokenType, ReportRetirement, RetirementCertificate, TokenMetadata,
+    },
+    access, distributor, game, impact, lst, migrate, multisig, pda, vesting,
 };
 
+/// Route an instruction envelope to the module named by its tag
 pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let (module, payload) = ModuleTag::unpack(instruction_data)?;
+    let token_instruction = match module {
+        ModuleTag::Token => Some(
+            SOuLInstruction::try_from_slice(payload).map_err(|_| ProgramError::InvalidInstructionData)?,
+        ),
+        _ => None,
+    };
+
+    let accounts = if module.is_pausable() {
+        let (config_info, accounts) = accounts
+            .split_last()
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if !token_instruction.as_ref().is_some_and(SOuLInstruction::runs_while_paused) {
+            access::check_not_paused(config_info, module, program_id)?;
+        }
+        accounts
+    } else {
+        accounts
+    };
+
+    match (module, token_instruction) {
+        (ModuleTag::Token, Some(instruction)) => process_soul_instruction(program_id, accounts, instruction),
+        (ModuleTag::Token, None) => Err(ProgramError::InvalidInstructionData),
+        (ModuleTag::Vesting, _) => vesting::Processor::process(program_id, accounts, payload),
+        (ModuleTag::Impact, _) => impact::Processor::process(program_id, accounts, payload),
+        (ModuleTag::LST, _) => lst::Processor::process(program_id, accounts, payload),
+        (ModuleTag::Game, _) => game::Processor::process(program_id, accounts, payload),
+        (ModuleTag::Multisig, _) => multisig::Processor::process(program_id, accounts, payload),
+        (ModuleTag::Access, _) => access::Processor::process(program_id, accounts, payload),
+        (ModuleTag::Distributor, _) => distributor::Processor::process(program_id, accounts, payload),
+    }
+}
+
+pub fn process_soul_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     instruction: SOuLInstruction,
@@ -36,6 +88,48 @@ pub fn process_instruction(
         SOuLInstruction::Burn { amount } => {
             process_burn(program_id, accounts, amount)
         }
+        SOuLInstruction::Migrate { account_type } => {
+            migrate::process_migrate(program_id, accounts, account_type)
+        }
+        SOuLInstruction::InitializeSOuLWithTransferFee {
+            total_supply,
+            transfer_fee_basis_points,
+            maximum_fee,
+        } => {
+            if transfer_fee_basis_points > SOuLToken::MAX_FEE_BASIS_POINTS {
+                return Err(SOuLError::InvalidTransferFee.into());
+            }
+            initialize_soul_token(
+                program_id,
+                accounts,
+                total_supply,
+                Some((transfer_fee_basis_points, maximum_fee)),
+            )
+        }
+        SOuLInstruction::HarvestFees => {
+            process_harvest_fees(program_id, accounts)
+        }
+        SOuLInstruction::SetTransferFee { transfer_fee_basis_points, maximum_fee } => {
+            process_set_transfer_fee(program_id, accounts, transfer_fee_basis_points, maximum_fee)
+        }
+        SOuLInstruction::UpdateMetadataUri { uri } => {
+            process_update_metadata_uri(program_id, accounts, uri)
+        }
+        SOuLInstruction::ConfigureEmission { max_supply, schedule } => {
+            process_configure_emission(program_id, accounts, max_supply, schedule)
+        }
+        SOuLInstruction::Emit => {
+            process_emit(program_id, accounts)
+        }
+        SOuLInstruction::Freeze { reason_hash } => {
+            process_compliance_hold(program_id, accounts, ComplianceAction::Freeze, reason_hash)
+        }
+        SOuLInstruction::Thaw { reason_hash } => {
+            process_compliance_hold(program_id, accounts, ComplianceAction::Thaw, reason_hash)
+        }
+        SOuLInstruction::Retire { nonce, amount, category, beneficiary_hash } => {
+            process_retire(program_id, accounts, nonce, amount, category, beneficiary_hash)
+        }
     }
 }
 
@@ -43,32 +137,205 @@ fn process_initialize_soul(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     total_supply: u64,
+) -> ProgramResult {
+    initialize_soul_token(program_id, accounts, total_supply, None)
+}
+
+/// Create the SOuL token config and its mint. With a `transfer_fee` of basis
+/// points and maximum fee the mint is a Token-2022 mint that withholds the
+/// fee on transfers, and the treasury account the fees are harvested into is
+/// created alongside it.
+fn initialize_soul_token(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    total_supply: u64,
+    transfer_fee: Option<(u16, u64)>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let initializer = next_account_info(account_info_iter)?;
     let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let mint_authority_info = next_account_info(account_info_iter)?;
+    let treasury_info = match transfer_fee {
+        Some(_) => Some(next_account_info(account_info_iter)?),
+        None => None,
+    };
+    let token_program_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
 
     if !initializer.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    let rent = Rent::get()?;
-    if !rent.is_exempt(token_account.lamports(), token_account.data_len()) {
-        return Err(SOuLError::NotRentExempt.into());
+    let token_program = match transfer_fee {
+        Some(_) => spl_token_2022::id(),
+        None => spl_token::id(),
+    };
+    if *token_program_info.key != token_program {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    if token_account.owner == program_id {
+        return Err(SOuLError::AlreadyInitialized.into());
+    }
+
+    let bump = pda::expect_pda(
+        token_account.key,
+        &[SOuLToken::SEED, mint_info.key.as_ref()],
+        program_id,
+    )?;
+
+    let (mint_authority, mint_authority_bump) =
+        SOuLToken::find_mint_authority(token_account.key, program_id);
+    if mint_authority != *mint_authority_info.key {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+
+    pda::create_pda_account(
+        initializer,
+        token_account,
+        system_program_info,
+        program_id,
+        SOuLToken::SPACE,
+        &[SOuLToken::SEED, mint_info.key.as_ref(), &[bump]],
+    )?;
+
+    // Extensions are initialized before the mint itself. The mint authority
+    // PDA controls the fee and is the only account that can withdraw it.
+    let (transfer_fee_basis_points, maximum_fee) = transfer_fee.unwrap_or_default();
+    if transfer_fee.is_some() {
+        let fee_config_ix = spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config(
+            token_program_info.key,
+            mint_info.key,
+            Some(&mint_authority),
+            Some(&mint_authority),
+            transfer_fee_basis_points,
+            maximum_fee,
+        )?;
+        invoke(&fee_config_ix, &[mint_info.clone(), token_program_info.clone()])?;
+    }
+
+    let decimals = 9;
+    let init_mint_ix = spl_token_2022::instruction::initialize_mint2(
+        token_program_info.key,
+        mint_info.key,
+        &mint_authority,
+        Some(&mint_authority),
+        decimals,
+    )?;
+    invoke(&init_mint_ix, &[mint_info.clone(), token_program_info.clone()])?;
+
+    // The treasury is a token account that owns itself, so only this program
+    // can move the fees swept into it
+    let mut treasury_bump = 0;
+    if let Some(treasury_info) = treasury_info {
+        treasury_bump = pda::expect_pda(
+            treasury_info.key,
+            &[SOuLToken::TREASURY_SEED, token_account.key.as_ref()],
+            program_id,
+        )?;
+        let space = ExtensionType::try_calculate_account_len::<TokenAccount>(&[
+            ExtensionType::TransferFeeAmount,
+        ])?;
+        pda::create_pda_account(
+            initializer,
+            treasury_info,
+            system_program_info,
+            token_program_info.key,
+            space,
+            &[SOuLToken::TREASURY_SEED, token_account.key.as_ref(), &[treasury_bump]],
+        )?;
+        let init_treasury_ix = spl_token_2022::instruction::initialize_account3(
+            token_program_info.key,
+            treasury_info.key,
+            mint_info.key,
+            treasury_info.key,
+        )?;
+        invoke(
+            &init_treasury_ix,
+            &[treasury_info.clone(), mint_info.clone(), token_program_info.clone()],
+        )?;
+    }
+
+    // The initial supply goes to the initializer's destination account
+    if total_supply > 0 {
+        let mint_ix = spl_token_2022::instruction::mint_to(
+            token_program_info.key,
+            mint_info.key,
+            destination_info.key,
+            &mint_authority,
+            &[],
+            total_supply,
+        )?;
+        invoke_signed(
+            &mint_ix,
+            &[
+                mint_info.clone(),
+                destination_info.clone(),
+                mint_authority_info.clone(),
+                token_program_info.clone(),
+            ],
+            &[&[
+                SOuLToken::MINT_AUTHORITY_SEED,
+                token_account.key.as_ref(),
+                &[mint_authority_bump],
+            ]],
+        )?;
     }
 
     let token = SOuLToken {
         is_initialized: true,
         mint_authority: *initializer.key,
+        mint: *mint_info.key,
         total_supply,
-        decimals: 9,
+        decimals,
+        bump,
+        mint_authority_bump,
+        token_program,
+        transfer_fee_basis_points,
+        maximum_fee,
+        treasury_bump,
+        max_supply: u64::MAX,
+        emission: None,
     };
 
-    token.serialize(&mut *token_account.data.borrow_mut())?;
+    token.save(token_account)?;
+    create_metadata(
+        program_id,
+        initializer,
+        metadata_info,
+        system_program_info,
+        token_account.key,
+        TokenMetadata::SOUL_NAME,
+        TokenMetadata::SOUL_SYMBOL,
+    )?;
     msg!("SOuL token initialized successfully");
     Ok(())
 }
 
+/// Load an initialized SOuL token config and check it backs `mint_info`
+fn load_soul_token(
+    program_id: &Pubkey,
+    token_account: &AccountInfo,
+    mint_info: &AccountInfo,
+) -> Result<SOuLToken, ProgramError> {
+    if token_account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let token = SOuLToken::load(token_account, program_id)?;
+    if !token.is_initialized {
+        return Err(ProgramError::UninitializedAccount);
+    }
+    if token.mint != *mint_info.key {
+        return Err(SOuLError::InvalidTokenType.into());
+    }
+
+    Ok(token)
+}
+
 fn process_initialize_lst(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -78,10 +345,11 @@ fn process_initialize_lst(
     let account_info_iter = &mut accounts.iter();
     let initializer = next_account_info(account_info_iter)?;
     let token_account = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    let roles_info = next_account_info(account_info_iter)?;
 
-    if !initializer.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    access::load_roles_as_any(initializer, roles_info, &[Role::Admin, Role::PoolManager], program_id)?;
 
     let lst_type = match token_type {
         0 => LSTTokenType::AirSOuL,
@@ -99,19 +367,97 @@ fn process_initialize_lst(
         _ => return Err(SOuLError::InvalidTokenType.into()),
     };
 
+    let bump = pda::expect_pda(token_account.key, &[LSTToken::SEED, &[token_type]], program_id)?;
+    pda::create_pda_account(
+        initializer,
+        token_account,
+        system_program_info,
+        program_id,
+        LSTToken::SPACE,
+        &[LSTToken::SEED, &[token_type], &[bump]],
+    )?;
+
     let token = LSTToken {
         is_initialized: true,
         mint_authority: *initializer.key,
         total_supply,
         decimals: 9,
         token_type: lst_type,
+        bump,
     };
 
-    token.serialize(&mut *token_account.data.borrow_mut())?;
+    token.save(token_account)?;
+    create_metadata(
+        program_id,
+        initializer,
+        metadata_info,
+        system_program_info,
+        token_account.key,
+        lst_type.name(),
+        lst_type.symbol(),
+    )?;
     msg!("LST token initialized successfully");
     Ok(())
 }
 
+/// Create the metadata PDA of a token config with its default name and
+/// symbol. The URI is left empty until the update authority sets it.
+fn create_metadata<'a>(
+    program_id: &Pubkey,
+    initializer: &AccountInfo<'a>,
+    metadata_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    token: &Pubkey,
+    name: &str,
+    symbol: &str,
+) -> ProgramResult {
+    let bump = pda::expect_pda(metadata_info.key, &[TokenMetadata::SEED, token.as_ref()], program_id)?;
+    pda::create_pda_account(
+        initializer,
+        metadata_info,
+        system_program_info,
+        program_id,
+        TokenMetadata::SPACE,
+        &[TokenMetadata::SEED, token.as_ref(), &[bump]],
+    )?;
+
+    TokenMetadata {
+        is_initialized: true,
+        token: *token,
+        update_authority: *initializer.key,
+        name: name.to_string(),
+        symbol: symbol.to_string(),
+        uri: String::new(),
+        bump,
+    }
+    .save(metadata_info)
+}
+
+fn process_update_metadata_uri(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    uri: String,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let update_authority = next_account_info(account_info_iter)?;
+    let metadata_info = next_account_info(account_info_iter)?;
+    let roles_info = account_info_iter.next();
+
+    let mut metadata = TokenMetadata::load(metadata_info, program_id)?;
+    if !is_authorized(update_authority, &metadata.update_authority, Role::Admin, roles_info, program_id)? {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+    if uri.len() > TokenMetadata::MAX_URI_LEN {
+        return Err(SOuLError::InvalidMetadata.into());
+    }
+
+    metadata.uri = uri;
+    metadata.save(metadata_info)?;
+
+    msg!("Token metadata URI updated successfully");
+    Ok(())
+}
+
 fn process_transfer(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -121,12 +467,58 @@ fn process_transfer(
     let source_account = next_account_info(account_info_iter)?;
     let destination_account = next_account_info(account_info_iter)?;
     let authority = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
 
     if !authority.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Transfer implementation
+    let token = load_soul_token(program_id, token_account, mint_info)?;
+    if *token_program_info.key != token.token_program {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let source = StateWithExtensions::<TokenAccount>::unpack(&source_account.data.borrow())?.base;
+    if source.mint != token.mint {
+        return Err(SOuLError::InvalidTokenType.into());
+    }
+    if source.amount < amount {
+        return Err(SOuLError::InsufficientFunds.into());
+    }
+
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        token_program_info.key,
+        source_account.key,
+        mint_info.key,
+        destination_account.key,
+        authority.key,
+        &[],
+        amount,
+        token.decimals,
+    )?;
+    invoke(
+        &transfer_ix,
+        &[
+            source_account.clone(),
+            mint_info.clone(),
+            destination_account.clone(),
+            authority.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    SoulEvent::Transferred {
+        mint: *mint_info.key,
+        source: *source_account.key,
+        destination: *destination_account.key,
+        authority: *authority.key,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    }
+    .emit()?;
+
     msg!("Transfer processed successfully");
     Ok(())
 }
@@ -139,12 +531,73 @@ fn process_mint(
     let account_info_iter = &mut accounts.iter();
     let mint_authority = next_account_info(account_info_iter)?;
     let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let destination_account = next_account_info(account_info_iter)?;
+    let mint_authority_pda = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let breaker_info = next_account_info(account_info_iter)?;
+    let roles_info = account_info_iter.next();
 
-    if !mint_authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    let mut token = load_soul_token(program_id, token_account, mint_info)?;
+    if !is_authorized(mint_authority, &token.mint_authority, Role::Minter, roles_info, program_id)? {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+    if *token_program_info.key != token.token_program {
+        return Err(ProgramError::IncorrectProgramId);
     }
 
-    // Minting implementation
+    let mint_authority_seeds: &[&[u8]] = &[
+        SOuLToken::MINT_AUTHORITY_SEED,
+        token_account.key.as_ref(),
+        &[token.mint_authority_bump],
+    ];
+    let expected_pda = Pubkey::create_program_address(mint_authority_seeds, program_id)?;
+    if expected_pda != *mint_authority_pda.key {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+
+    let supply = StateWithExtensions::<Mint>::unpack(&mint_info.data.borrow())?.base.supply;
+    match supply.checked_add(amount) {
+        None => return Err(SOuLError::AmountOverflow.into()),
+        Some(new_supply) if new_supply > token.max_supply => {
+            return Err(SOuLError::SupplyCapExceeded.into())
+        }
+        Some(_) => {}
+    }
+    access::record_outflow(breaker_info, ModuleTag::Token, amount, program_id)?;
+
+    let mint_ix = spl_token_2022::instruction::mint_to(
+        token_program_info.key,
+        mint_info.key,
+        destination_account.key,
+        mint_authority_pda.key,
+        &[],
+        amount,
+    )?;
+    invoke_signed(
+        &mint_ix,
+        &[
+            mint_info.clone(),
+            destination_account.clone(),
+            mint_authority_pda.clone(),
+            token_program_info.clone(),
+        ],
+        &[mint_authority_seeds],
+    )?;
+
+    sync_total_supply(&mut token, mint_info)?;
+    token.save(token_account)?;
+
+    SoulEvent::Minted {
+        mint: *mint_info.key,
+        destination: *destination_account.key,
+        authority: *mint_authority.key,
+        amount,
+        total_supply: token.total_supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    }
+    .emit()?;
+
     msg!("Tokens minted successfully");
     Ok(())
 }
@@ -155,14 +608,671 @@ fn process_burn(
     amount: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
-    let token_account = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
     let authority = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
 
     if !authority.is_signer {
         return Err(ProgramError::MissingRequiredSignature);
     }
 
-    // Burning implementation
+    let mut token = load_soul_token(program_id, token_account, mint_info)?;
+    if *token_program_info.key != token.token_program {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let source = StateWithExtensions::<TokenAccount>::unpack(&source_account.data.borrow())?.base;
+    if source.mint != token.mint {
+        return Err(SOuLError::InvalidTokenType.into());
+    }
+    if source.amount < amount {
+        return Err(SOuLError::InsufficientFunds.into());
+    }
+
+    let burn_ix = spl_token_2022::instruction::burn(
+        token_program_info.key,
+        source_account.key,
+        mint_info.key,
+        authority.key,
+        &[],
+        amount,
+    )?;
+    invoke(
+        &burn_ix,
+        &[
+            source_account.clone(),
+            mint_info.clone(),
+            authority.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    sync_total_supply(&mut token, mint_info)?;
+    token.save(token_account)?;
+
+    SoulEvent::Burned {
+        mint: *mint_info.key,
+        source: *source_account.key,
+        authority: *authority.key,
+        amount,
+        total_supply: token.total_supply,
+        timestamp: Clock::get()?.unix_timestamp,
+    }
+    .emit()?;
+
     msg!("Tokens burned successfully");
     Ok(())
 }
+
+fn process_harvest_fees(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let treasury_info = next_account_info(account_info_iter)?;
+    let mint_authority_pda = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let sources = account_info_iter.as_slice();
+
+    let token = load_soul_token(program_id, token_account, mint_info)?;
+    if token.token_program != spl_token_2022::id() {
+        return Err(SOuLError::TransferFeeNotEnabled.into());
+    }
+    if *token_program_info.key != token.token_program {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let treasury = Pubkey::create_program_address(
+        &[SOuLToken::TREASURY_SEED, token_account.key.as_ref(), &[token.treasury_bump]],
+        program_id,
+    )?;
+    if treasury != *treasury_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let mint_authority_seeds: &[&[u8]] = &[
+        SOuLToken::MINT_AUTHORITY_SEED,
+        token_account.key.as_ref(),
+        &[token.mint_authority_bump],
+    ];
+    let expected_pda = Pubkey::create_program_address(mint_authority_seeds, program_id)?;
+    if expected_pda != *mint_authority_pda.key {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+
+    let balance_before = token_balance(treasury_info)?;
+
+    // Fees withheld in token accounts are first moved to the mint, from
+    // where the withdraw authority can sweep them in one go
+    if !sources.is_empty() {
+        let source_keys = sources.iter().map(|source| source.key).collect::<Vec<_>>();
+        let harvest_ix = spl_token_2022::extension::transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+            token_program_info.key,
+            mint_info.key,
+            &source_keys,
+        )?;
+        let mut harvest_accounts = vec![mint_info.clone()];
+        harvest_accounts.extend(sources.iter().cloned());
+        harvest_accounts.push(token_program_info.clone());
+        invoke(&harvest_ix, &harvest_accounts)?;
+    }
+
+    let withdraw_ix = spl_token_2022::extension::transfer_fee::instruction::withdraw_withheld_tokens_from_mint(
+        token_program_info.key,
+        mint_info.key,
+        treasury_info.key,
+        mint_authority_pda.key,
+        &[],
+    )?;
+    invoke_signed(
+        &withdraw_ix,
+        &[
+            mint_info.clone(),
+            treasury_info.clone(),
+            mint_authority_pda.clone(),
+            token_program_info.clone(),
+        ],
+        &[mint_authority_seeds],
+    )?;
+
+    let amount = token_balance(treasury_info)?.saturating_sub(balance_before);
+    SoulEvent::FeesHarvested {
+        mint: *mint_info.key,
+        treasury: *treasury_info.key,
+        amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    }
+    .emit()?;
+
+    msg!("Transfer fees harvested successfully");
+    Ok(())
+}
+
+fn process_set_transfer_fee(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let mint_authority_pda = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let roles_info = account_info_iter.next();
+
+    let mut token = load_soul_token(program_id, token_account, mint_info)?;
+    if !is_authorized(mint_authority, &token.mint_authority, Role::Admin, roles_info, program_id)? {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+    if token.token_program != spl_token_2022::id() {
+        return Err(SOuLError::TransferFeeNotEnabled.into());
+    }
+    if *token_program_info.key != token.token_program {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if transfer_fee_basis_points > SOuLToken::MAX_FEE_BASIS_POINTS {
+        return Err(SOuLError::InvalidTransferFee.into());
+    }
+
+    let mint_authority_seeds: &[&[u8]] = &[
+        SOuLToken::MINT_AUTHORITY_SEED,
+        token_account.key.as_ref(),
+        &[token.mint_authority_bump],
+    ];
+    let expected_pda = Pubkey::create_program_address(mint_authority_seeds, program_id)?;
+    if expected_pda != *mint_authority_pda.key {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+
+    let set_fee_ix = spl_token_2022::extension::transfer_fee::instruction::set_transfer_fee(
+        token_program_info.key,
+        mint_info.key,
+        mint_authority_pda.key,
+        &[],
+        transfer_fee_basis_points,
+        maximum_fee,
+    )?;
+    invoke_signed(
+        &set_fee_ix,
+        &[mint_info.clone(), mint_authority_pda.clone(), token_program_info.clone()],
+        &[mint_authority_seeds],
+    )?;
+
+    token.transfer_fee_basis_points = transfer_fee_basis_points;
+    token.maximum_fee = maximum_fee;
+    token.save(token_account)?;
+
+    msg!("Transfer fee updated successfully");
+    Ok(())
+}
+
+fn process_configure_emission(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    max_supply: u64,
+    schedule: EmissionSchedule,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_authority = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let roles_info = account_info_iter.next();
+
+    let mut token = load_soul_token(program_id, token_account, mint_info)?;
+    if !is_authorized(mint_authority, &token.mint_authority, Role::Admin, roles_info, program_id)? {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+    // The cap and schedule are published once and never loosened
+    if token.emission.is_some() {
+        return Err(SOuLError::EmissionAlreadyConfigured.into());
+    }
+    if !schedule.is_valid() || schedule.emitted != 0 {
+        return Err(SOuLError::InvalidEmissionSchedule.into());
+    }
+
+    sync_total_supply(&mut token, mint_info)?;
+    if max_supply < token.total_supply {
+        return Err(SOuLError::SupplyCapExceeded.into());
+    }
+
+    token.max_supply = max_supply;
+    token.emission = Some(schedule);
+    token.save(token_account)?;
+
+    msg!("Emission schedule configured successfully");
+    Ok(())
+}
+
+fn process_emit(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let mint_authority_pda = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let staking_info = next_account_info(account_info_iter)?;
+    let game_rewards_info = next_account_info(account_info_iter)?;
+    let verifier_fund_info = next_account_info(account_info_iter)?;
+
+    let mut token = load_soul_token(program_id, token_account, mint_info)?;
+    let mut schedule = token.emission.clone().ok_or(SOuLError::EmissionNotConfigured)?;
+    if *token_program_info.key != token.token_program {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let buckets = [staking_info, game_rewards_info, verifier_fund_info];
+    let destinations = [
+        &schedule.staking.destination,
+        &schedule.game_rewards.destination,
+        &schedule.verifier_fund.destination,
+    ];
+    if buckets.iter().zip(destinations).any(|(info, destination)| info.key != destination) {
+        return Err(SOuLError::InvalidEmissionSchedule.into());
+    }
+
+    let mint_authority_seeds: &[&[u8]] = &[
+        SOuLToken::MINT_AUTHORITY_SEED,
+        token_account.key.as_ref(),
+        &[token.mint_authority_bump],
+    ];
+    let expected_pda = Pubkey::create_program_address(mint_authority_seeds, program_id)?;
+    if expected_pda != *mint_authority_pda.key {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+
+    // Only what the schedule has released so far, and never past the cap
+    let clock = Clock::get()?;
+    sync_total_supply(&mut token, mint_info)?;
+    let due = schedule.released_through(clock.epoch).saturating_sub(schedule.emitted);
+    let amount = due.min(token.max_supply.saturating_sub(token.total_supply));
+    if amount == 0 {
+        return Err(SOuLError::NothingToEmit.into());
+    }
+
+    let shares = schedule.split(amount);
+    for (bucket, share) in buckets.iter().zip(shares) {
+        if share == 0 {
+            continue;
+        }
+        let mint_ix = spl_token_2022::instruction::mint_to(
+            token_program_info.key,
+            mint_info.key,
+            bucket.key,
+            mint_authority_pda.key,
+            &[],
+            share,
+        )?;
+        invoke_signed(
+            &mint_ix,
+            &[
+                mint_info.clone(),
+                (*bucket).clone(),
+                mint_authority_pda.clone(),
+                token_program_info.clone(),
+            ],
+            &[mint_authority_seeds],
+        )?;
+    }
+
+    schedule.emitted += amount;
+    sync_total_supply(&mut token, mint_info)?;
+    let emitted = schedule.emitted;
+    token.emission = Some(schedule);
+    token.save(token_account)?;
+
+    SoulEvent::Emitted {
+        mint: *mint_info.key,
+        epoch: clock.epoch,
+        staking: shares[0],
+        game_rewards: shares[1],
+        verifier_fund: shares[2],
+        emitted,
+        total_supply: token.total_supply,
+        timestamp: clock.unix_timestamp,
+    }
+    .emit()?;
+
+    msg!("Scheduled emission minted successfully");
+    Ok(())
+}
+
+/// Freeze or thaw a SOuL token account as a Compliance role holder, with
+/// the mint authority PDA signing as the mint's freeze authority
+fn process_compliance_hold(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    action: ComplianceAction,
+    reason_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let officer_info = next_account_info(account_info_iter)?;
+    let token_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let holder_info = next_account_info(account_info_iter)?;
+    let mint_authority_pda = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let record_info = next_account_info(account_info_iter)?;
+    let roles_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    access::load_roles_as(officer_info, roles_info, Role::Compliance, program_id)?;
+    let token = load_soul_token(program_id, token_account, mint_info)?;
+    if *token_program_info.key != token.token_program || holder_info.owner != token_program_info.key {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let holder = StateWithExtensions::<TokenAccount>::unpack(&holder_info.data.borrow())?.base;
+    if holder.mint != *mint_info.key {
+        return Err(SOuLError::InvalidTokenType.into());
+    }
+    match (action, holder.is_frozen()) {
+        (ComplianceAction::Freeze, true) => return Err(SOuLError::AccountFrozen.into()),
+        (ComplianceAction::Thaw, false) => return Err(SOuLError::AccountNotFrozen.into()),
+        _ => {}
+    }
+
+    let mint_authority_seeds: &[&[u8]] = &[
+        SOuLToken::MINT_AUTHORITY_SEED,
+        token_account.key.as_ref(),
+        &[token.mint_authority_bump],
+    ];
+    let expected_pda = Pubkey::create_program_address(mint_authority_seeds, program_id)?;
+    if expected_pda != *mint_authority_pda.key {
+        return Err(SOuLError::InvalidAuthority.into());
+    }
+
+    let hold_ix = match action {
+        ComplianceAction::Freeze => spl_token_2022::instruction::freeze_account(
+            token_program_info.key,
+            holder_info.key,
+            mint_info.key,
+            mint_authority_pda.key,
+            &[],
+        )?,
+        ComplianceAction::Thaw => spl_token_2022::instruction::thaw_account(
+            token_program_info.key,
+            holder_info.key,
+            mint_info.key,
+            mint_authority_pda.key,
+            &[],
+        )?,
+    };
+    invoke_signed(
+        &hold_ix,
+        &[
+            holder_info.clone(),
+            mint_info.clone(),
+            mint_authority_pda.clone(),
+            token_program_info.clone(),
+        ],
+        &[mint_authority_seeds],
+    )?;
+
+    let bump = pda::expect_pda(record_info.key, &[ComplianceRecord::SEED, holder_info.key.as_ref()], program_id)?;
+    let mut record = if record_info.owner == program_id {
+        let record = ComplianceRecord::load(record_info, program_id)?;
+        if record.token_account != *holder_info.key || record.mint != *mint_info.key {
+            return Err(SOuLError::InvalidAccountType.into());
+        }
+        record
+    } else {
+        pda::create_pda_account(
+            officer_info,
+            record_info,
+            system_program_info,
+            program_id,
+            ComplianceRecord::SPACE,
+            &[ComplianceRecord::SEED, holder_info.key.as_ref(), &[bump]],
+        )?;
+        ComplianceRecord {
+            token_account: *holder_info.key,
+            mint: *mint_info.key,
+            frozen: false,
+            action_count: 0,
+            entries: Vec::new(),
+            payer: *officer_info.key,
+            bump,
+        }
+    };
+
+    // A full record moves its entries into the next history page, so the
+    // audit trail never loses an action
+    if let Some(page) = record.archive_page() {
+        let history_info = next_account_info(account_info_iter)?;
+        let page_bytes = page.to_le_bytes();
+        let seeds: &[&[u8]] = &[ComplianceHistory::SEED, holder_info.key.as_ref(), &page_bytes];
+        let history_bump = pda::expect_pda(history_info.key, seeds, program_id)?;
+        pda::create_pda_account(
+            officer_info,
+            history_info,
+            system_program_info,
+            program_id,
+            ComplianceHistory::SPACE,
+            &[seeds[0], seeds[1], seeds[2], &[history_bump]],
+        )?;
+        ComplianceHistory {
+            token_account: *holder_info.key,
+            page,
+            entries: record.archive(),
+            payer: *officer_info.key,
+            bump: history_bump,
+        }
+        .save(history_info)?;
+    }
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    record.record(ComplianceEntry {
+        action,
+        reason_hash,
+        officer: *officer_info.key,
+        timestamp,
+    });
+    record.save(record_info)?;
+
+    SoulEvent::ComplianceHold {
+        token_account: *holder_info.key,
+        mint: *mint_info.key,
+        action,
+        reason_hash,
+        officer: *officer_info.key,
+        timestamp,
+    }
+    .emit()
+}
+
+/// Burn SOuL or a category LST and record the retirement of the credits it
+/// stands for in a new certificate
+fn process_retire(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    nonce: u64,
+    amount: u64,
+    category: LSTType,
+    beneficiary_hash: [u8; 32],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let owner_info = next_account_info(account_info_iter)?;
+    let source_account = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let token_config_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+    let certificate_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let canonical_mint_info = next_account_info(account_info_iter)?;
+    // Each report is followed by its retirement tally
+    let report_pairs = account_info_iter.as_slice().chunks_exact(2);
+
+    if !owner_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if amount == 0
+        || report_pairs.len() == 0
+        || report_pairs.len() > RetirementCertificate::MAX_REPORTS
+        || !report_pairs.remainder().is_empty()
+    {
+        return Err(SOuLError::InvalidRetirement.into());
+    }
+
+    // SOuL is retired under any category and keeps its config's supply in
+    // step; an LST only under the category of its pool. Only the canonical
+    // SOuL mint stands for credits, not a config anyone made for their own.
+    let mut soul_token = if SOuLToken::is_current(&token_config_info.data.borrow()) {
+        if access::load_canonical_mint(canonical_mint_info, program_id)? != *mint_info.key {
+            return Err(SOuLError::InvalidRetirement.into());
+        }
+        let token = load_soul_token(program_id, token_config_info, mint_info)?;
+        if *token_program_info.key != token.token_program {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        Some(token)
+    } else {
+        let pool = LSTPool::load(token_config_info, program_id)?;
+        if pool.lst_mint != *mint_info.key || pool.pool_type != category {
+            return Err(SOuLError::InvalidRetirement.into());
+        }
+        if mint_info.owner != token_program_info.key {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+        None
+    };
+
+    let source = StateWithExtensions::<TokenAccount>::unpack(&source_account.data.borrow())?.base;
+    if source.mint != *mint_info.key {
+        return Err(SOuLError::InvalidTokenType.into());
+    }
+    if source.amount < amount {
+        return Err(SOuLError::InsufficientFunds.into());
+    }
+
+    // The credits are counted against the carbon the reports offset, in
+    // order, and every report must be for the category and back some of them
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint_info.data.borrow())?.base.decimals;
+    let timestamp = Clock::get()?.unix_timestamp;
+    let mut reports = Vec::with_capacity(report_pairs.len());
+    let mut unbacked_amount = amount;
+    for pair in report_pairs {
+        let (report_info, tally_info) = (&pair[0], &pair[1]);
+        let report = ImpactReport::load(report_info, program_id)?;
+        if report.verification_status != VerificationStatus::Verified {
+            return Err(SOuLError::ReportNotVerified.into());
+        }
+        if !report.is_final(timestamp) {
+            return Err(SOuLError::ReportNotFinal.into());
+        }
+        if report.category != Some(category) || reports.contains(report_info.key) {
+            return Err(SOuLError::InvalidRetirement.into());
+        }
+        reports.push(*report_info.key);
+
+        let bump = pda::expect_pda(tally_info.key, &[ReportRetirement::SEED, report_info.key.as_ref()], program_id)?;
+        let mut tally = if tally_info.owner == program_id {
+            ReportRetirement::load(tally_info, program_id)?
+        } else {
+            pda::create_pda_account(
+                owner_info,
+                tally_info,
+                system_program_info,
+                program_id,
+                ReportRetirement::SPACE,
+                &[ReportRetirement::SEED, report_info.key.as_ref(), &[bump]],
+            )?;
+            ReportRetirement {
+                report: *report_info.key,
+                category,
+                retired_amount: 0,
+                bump,
+            }
+        };
+        let capacity = ReportRetirement::capacity(report.metrics.carbon_offset, decimals)
+            .ok_or(SOuLError::AmountOverflow)?;
+        let backed_amount = capacity.saturating_sub(tally.retired_amount).min(unbacked_amount);
+        if backed_amount == 0 {
+            return Err(SOuLError::RetirementExceedsOffset.into());
+        }
+        tally.retired_amount += backed_amount;
+        unbacked_amount -= backed_amount;
+        tally.save(tally_info)?;
+    }
+    if unbacked_amount > 0 {
+        return Err(SOuLError::RetirementExceedsOffset.into());
+    }
+
+    let burn_ix = spl_token_2022::instruction::burn(
+        token_program_info.key,
+        source_account.key,
+        mint_info.key,
+        owner_info.key,
+        &[],
+        amount,
+    )?;
+    invoke(
+        &burn_ix,
+        &[
+            source_account.clone(),
+            mint_info.clone(),
+            owner_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+    if let Some(token) = soul_token.as_mut() {
+        sync_total_supply(token, mint_info)?;
+        token.save(token_config_info)?;
+    }
+
+    let nonce_bytes = nonce.to_le_bytes();
+    let bump = pda::expect_pda(
+        certificate_info.key,
+        &[RetirementCertificate::SEED, owner_info.key.as_ref(), &nonce_bytes],
+        program_id,
+    )?;
+    pda::create_pda_account(
+        owner_info,
+        certificate_info,
+        system_program_info,
+        program_id,
+        RetirementCertificate::SPACE,
+        &[RetirementCertificate::SEED, owner_info.key.as_ref(), &nonce_bytes, &[bump]],
+    )?;
+
+    RetirementCertificate {
+        owner: *owner_info.key,
+        nonce,
+        mint: *mint_info.key,
+        amount,
+        category,
+        beneficiary_hash,
+        reports: reports.clone(),
+        timestamp,
+        bump,
+    }
+    .save(certificate_info)?;
+
+    SoulEvent::Retired {
+        certificate: *certificate_info.key,
+        owner: *owner_info.key,
+        source: *source_account.key,
+        mint: *mint_info.key,
+        category,
+        amount,
+        beneficiary_hash,
+        reports,
+        timestamp,
+    }
+    .emit()?;
+
+    msg!("Credits retired successfully");
+    Ok(())
+}
+
+/// Take the recorded supply from the SPL mint after a mint or burn
+fn sync_total_supply(token: &mut SOuLToken, mint_info: &AccountInfo) -> ProgramResult {
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_info.data.borrow())?.base;
+    token.total_supply = mint.supply;
+    Ok(())
+}
+
+fn token_balance(account_info: &AccountInfo) -> Result<u64, ProgramError> {
+    Ok(StateWithExtensions::<TokenAccount>::unpack(&account_info.data.borrow())?.base.amount)
+}
@@ -1,19 +1,146 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    program_pack::{IsInitialized, Pack, Sealed},
+    program_pack::{IsInitialized, Sealed},
     pubkey::Pubkey,
 };
 
+use crate::{
+    account::{AccountState, HEADER_LEN},
+    lst::LSTType,
+};
+
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub struct SOuLToken {
     pub is_initialized: bool,
     pub mint_authority: Pubkey,
+    pub mint: Pubkey,
     pub total_supply: u64,
     pub decimals: u8,
+    pub bump: u8,
+    pub mint_authority_bump: u8,
+    /// Token program that owns the mint, legacy SPL Token or Token-2022
+    pub token_program: Pubkey,
+    /// Transfer fee of a Token-2022 mint, zero for a legacy mint
+    pub transfer_fee_basis_points: u16,
+    /// Largest fee withheld from a single transfer
+    pub maximum_fee: u64,
+    /// Bump of the treasury token account, zero when the mint has no transfer fee
+    pub treasury_bump: u8,
+    /// Supply that minting can never exceed, `u64::MAX` until emission is configured
+    pub max_supply: u64,
+    pub emission: Option<EmissionSchedule>,
+}
+
+/// Per-epoch SOuL emission, minted by the permissionless `Emit` crank
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmissionSchedule {
+    /// First epoch that emits
+    pub start_epoch: u64,
+    /// Tokens emitted in each epoch of the first reduction interval
+    pub epoch_emission: u64,
+    /// Epochs between reductions of the per-epoch emission
+    pub reduction_interval_epochs: u64,
+    /// Cut applied at each reduction, 5_000 halves the emission and 0 keeps it flat
+    pub reduction_basis_points: u16,
+    pub staking: EmissionBucket,
+    pub game_rewards: EmissionBucket,
+    pub verifier_fund: EmissionBucket,
+    /// Tokens minted by `Emit` so far
+    pub emitted: u64,
+}
+
+/// Token account receiving a fixed share of every emission
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EmissionBucket {
+    pub destination: Pubkey,
+    pub basis_points: u16,
 }
 
 impl SOuLToken {
-    pub const LEN: usize = 1 + 32 + 8 + 1;
+    pub const LEN: usize =
+        1 + 32 + 32 + 8 + 1 + 1 + 1 + 32 + 2 + 8 + 1 + 8 + 1 + EmissionSchedule::LEN;
+
+    /// Largest transfer fee, 100% in basis points
+    pub const MAX_FEE_BASIS_POINTS: u16 = 10_000;
+
+    /// Seed of the token config PDA (`["soul_token", mint]`)
+    pub const SEED: &'static [u8] = b"soul_token";
+
+    /// Seed of the PDA that holds mint authority over the SPL mint
+    pub const MINT_AUTHORITY_SEED: &'static [u8] = b"mint_authority";
+
+    /// Seed of the token account that harvested transfer fees are swept into
+    pub const TREASURY_SEED: &'static [u8] = b"treasury";
+
+    pub fn find_address(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, mint.as_ref()], program_id)
+    }
+
+    pub fn find_mint_authority(token: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::MINT_AUTHORITY_SEED, token.as_ref()], program_id)
+    }
+
+    pub fn find_treasury(token: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::TREASURY_SEED, token.as_ref()], program_id)
+    }
+}
+
+impl EmissionSchedule {
+    pub const LEN: usize = 8 + 8 + 8 + 2 + 3 * EmissionBucket::LEN + 8;
+
+    /// Basis points the bucket shares must add up to
+    pub const TOTAL_BASIS_POINTS: u16 = 10_000;
+
+    pub fn is_valid(&self) -> bool {
+        let shares = [&self.staking, &self.game_rewards, &self.verifier_fund]
+            .iter()
+            .map(|bucket| bucket.basis_points as u32)
+            .sum::<u32>();
+        self.reduction_interval_epochs > 0
+            && self.reduction_basis_points <= Self::TOTAL_BASIS_POINTS
+            && shares == Self::TOTAL_BASIS_POINTS as u32
+    }
+
+    /// Tokens the schedule releases from `start_epoch` through `epoch`
+    pub fn released_through(&self, epoch: u64) -> u64 {
+        let mut remaining = (epoch as u128 + 1).saturating_sub(self.start_epoch as u128);
+        let mut rate = self.epoch_emission as u128;
+        if self.reduction_basis_points == 0 {
+            return (rate * remaining).min(u64::MAX as u128) as u64;
+        }
+
+        let interval = self.reduction_interval_epochs as u128;
+        let keep = (Self::TOTAL_BASIS_POINTS - self.reduction_basis_points) as u128;
+        let mut released = 0u128;
+        while remaining > 0 && rate > 0 && released < u64::MAX as u128 {
+            let epochs = remaining.min(interval);
+            released += rate * epochs;
+            remaining -= epochs;
+            rate = rate * keep / Self::TOTAL_BASIS_POINTS as u128;
+        }
+        released.min(u64::MAX as u128) as u64
+    }
+
+    /// Shares of `amount` for staking, game rewards and the verifier fund.
+    /// Rounding dust goes to the verifier fund.
+    pub fn split(&self, amount: u64) -> [u64; 3] {
+        let share = |bucket: &EmissionBucket| {
+            (amount as u128 * bucket.basis_points as u128 / Self::TOTAL_BASIS_POINTS as u128) as u64
+        };
+        let staking = share(&self.staking);
+        let game_rewards = share(&self.game_rewards);
+        [staking, game_rewards, amount - staking - game_rewards]
+    }
+}
+
+impl EmissionBucket {
+    pub const LEN: usize = 32 + 2;
+}
+
+impl AccountState for SOuLToken {
+    const DISCRIMINATOR: [u8; 8] = *b"soultokn";
+    const VERSION: u8 = 3;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
 }
 
 impl Sealed for SOuLToken {}
@@ -31,9 +158,268 @@ pub struct LSTToken {
     pub total_supply: u64,
     pub decimals: u8,
     pub token_type: LSTTokenType,
+    pub bump: u8,
 }
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+impl LSTToken {
+    pub const LEN: usize = 1 + 32 + 8 + 1 + 1 + 1;
+
+    /// Seed of the LST token config PDA (`["lst_token", token_type]`)
+    pub const SEED: &'static [u8] = b"lst_token";
+
+    pub fn find_address(token_type: LSTTokenType, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, &[token_type as u8]], program_id)
+    }
+}
+
+impl AccountState for LSTToken {
+    const DISCRIMINATOR: [u8; 8] = *b"lsttoken";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+/// Name, symbol and URI that wallets show for a mint.
+///
+/// Kept in a program account next to the token config, so legacy SPL mints
+/// and LST token types without a mint are described the same way.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct TokenMetadata {
+    pub is_initialized: bool,
+    /// The SOuL or LST token config this metadata describes
+    pub token: Pubkey,
+    pub update_authority: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+    pub bump: u8,
+}
+
+impl TokenMetadata {
+    pub const MAX_NAME_LEN: usize = 32;
+    pub const MAX_SYMBOL_LEN: usize = 16;
+    pub const MAX_URI_LEN: usize = 200;
+
+    pub const LEN: usize = 1 + 32 + 32
+        + 4 + Self::MAX_NAME_LEN
+        + 4 + Self::MAX_SYMBOL_LEN
+        + 4 + Self::MAX_URI_LEN
+        + 1;
+
+    /// Seed of the metadata PDA (`["metadata", token]`)
+    pub const SEED: &'static [u8] = b"metadata";
+
+    /// Name and symbol given to the SOuL mint at initialization
+    pub const SOUL_NAME: &'static str = "SOuL Sanctuary";
+    pub const SOUL_SYMBOL: &'static str = "SOuL";
+
+    pub fn find_address(token: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, token.as_ref()], program_id)
+    }
+}
+
+impl AccountState for TokenMetadata {
+    const DISCRIMINATOR: [u8; 8] = *b"tokenmta";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+/// A compliance hold placed on or lifted from a SOuL token account
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComplianceAction {
+    Freeze,
+    Thaw,
+}
+
+/// One freeze or thaw in a compliance record
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComplianceEntry {
+    pub action: ComplianceAction,
+    /// Hash of the off-chain reason, such as the case file behind the hold
+    pub reason_hash: [u8; 32],
+    /// The compliance officer who signed
+    pub officer: Pubkey,
+    pub timestamp: i64,
+}
+
+/// Audit trail of the compliance holds on one SOuL token account
+/// (`["compliance", token_account]`).
+///
+/// Keeps the latest actions, oldest first. Once `MAX_ENTRIES` are recorded,
+/// the next action moves them into a new `ComplianceHistory` page, so no
+/// action is ever dropped. `action_count` counts every action, and each one
+/// is also logged as an event.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ComplianceRecord {
+    pub token_account: Pubkey,
+    pub mint: Pubkey,
+    pub frozen: bool,
+    pub action_count: u64,
+    pub entries: Vec<ComplianceEntry>,
+    /// Paid the rent of the record
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl ComplianceRecord {
+    pub const MAX_ENTRIES: usize = 16;
+
+    pub const LEN: usize = 32 + 32 + 1 + 8
+        + 4 + (1 + 32 + 32 + 8) * Self::MAX_ENTRIES
+        + 32 + 1;
+
+    /// Seed of the compliance record PDA (`["compliance", token_account]`)
+    pub const SEED: &'static [u8] = b"compliance";
+
+    pub fn find_address(token_account: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, token_account.as_ref()], program_id)
+    }
+
+    /// The history page a full record moves its entries into before the
+    /// next action, or `None` while there is room left
+    pub fn archive_page(&self) -> Option<u64> {
+        (self.entries.len() >= Self::MAX_ENTRIES)
+            .then(|| (self.action_count - self.entries.len() as u64) / Self::MAX_ENTRIES as u64)
+    }
+
+    /// Take the entries of a full record out to be written to its history page
+    pub fn archive(&mut self) -> Vec<ComplianceEntry> {
+        std::mem::take(&mut self.entries)
+    }
+
+    /// Append `entry`; a full record must be archived first
+    pub fn record(&mut self, entry: ComplianceEntry) {
+        self.frozen = entry.action == ComplianceAction::Freeze;
+        self.action_count += 1;
+        self.entries.push(entry);
+    }
+}
+
+impl AccountState for ComplianceRecord {
+    const DISCRIMINATOR: [u8; 8] = *b"complrec";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+/// `MAX_ENTRIES` consecutive actions moved out of a full compliance record
+/// (`["compliance_history", token_account, page]`). Page `n` holds actions
+/// `n * MAX_ENTRIES` up to `(n + 1) * MAX_ENTRIES`, counting from zero.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ComplianceHistory {
+    pub token_account: Pubkey,
+    pub page: u64,
+    pub entries: Vec<ComplianceEntry>,
+    /// Paid the rent of the page
+    pub payer: Pubkey,
+    pub bump: u8,
+}
+
+impl ComplianceHistory {
+    pub const LEN: usize = 32 + 8
+        + 4 + (1 + 32 + 32 + 8) * ComplianceRecord::MAX_ENTRIES
+        + 32 + 1;
+
+    /// Seed of the history page PDA (`["compliance_history", token_account, page]`)
+    pub const SEED: &'static [u8] = b"compliance_history";
+
+    pub fn find_address(token_account: &Pubkey, page: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, token_account.as_ref(), &page.to_le_bytes()], program_id)
+    }
+}
+
+impl AccountState for ComplianceHistory {
+    const DISCRIMINATOR: [u8; 8] = *b"complhis";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+/// Proof that `amount` SOuL or category LST was burned to retire the carbon
+/// credits it stood for (`["retirement", owner, nonce]`).
+///
+/// A certificate is created by the burn itself and can never be created
+/// again for the same owner and nonce. The final impact reports behind the
+/// credits are listed by key, and the credits are counted against their
+/// `ReportRetirement` tallies, so each credit is retired exactly once.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct RetirementCertificate {
+    /// The wallet whose tokens were burned
+    pub owner: Pubkey,
+    pub nonce: u64,
+    /// The SOuL or LST mint burned from
+    pub mint: Pubkey,
+    pub amount: u64,
+    /// The impact category the credits are retired under. An LST is always
+    /// retired under its pool's category.
+    pub category: LSTType,
+    /// Hash of the name of the company or person the credits are retired for
+    pub beneficiary_hash: [u8; 32],
+    /// Verified impact reports backing the retired credits
+    pub reports: Vec<Pubkey>,
+    pub timestamp: i64,
+    pub bump: u8,
+}
+
+impl RetirementCertificate {
+    /// Most impact reports one certificate can link
+    pub const MAX_REPORTS: usize = 8;
+
+    pub const LEN: usize = 32 + 8 + 32 + 8 + 1 + 32 + 4 + 32 * Self::MAX_REPORTS + 8 + 1;
+
+    /// Seed of the certificate PDA (`["retirement", owner, nonce]`)
+    pub const SEED: &'static [u8] = b"retirement";
+
+    pub fn find_address(owner: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, owner.as_ref(), &nonce.to_le_bytes()], program_id)
+    }
+}
+
+impl AccountState for RetirementCertificate {
+    const DISCRIMINATOR: [u8; 8] = *b"retirecr";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+/// How much has been retired against an impact report
+/// (`["report_retirement", report]`).
+///
+/// A report backs credits of the category it was submitted for, up to one
+/// whole token per tonne of carbon it offsets.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq)]
+pub struct ReportRetirement {
+    pub report: Pubkey,
+    pub category: LSTType,
+    /// Base units of SOuL or LST retired against the report
+    pub retired_amount: u64,
+    pub bump: u8,
+}
+
+impl ReportRetirement {
+    pub const LEN: usize = 32 + 1 + 8 + 1;
+
+    /// Kilograms of offset carbon behind one whole retired token
+    pub const KG_PER_TOKEN: u64 = 1_000;
+
+    /// Seed of the tally PDA (`["report_retirement", report]`)
+    pub const SEED: &'static [u8] = b"report_retirement";
+
+    pub fn find_address(report: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::SEED, report.as_ref()], program_id)
+    }
+
+    /// Base units of a mint with `decimals` that `carbon_offset` kilograms
+    /// can back, or `None` if that does not fit a `u64`
+    pub fn capacity(carbon_offset: u64, decimals: u8) -> Option<u64> {
+        let base_units = u128::from(carbon_offset).checked_mul(10u128.checked_pow(decimals.into())?)?;
+        u64::try_from(base_units / u128::from(Self::KG_PER_TOKEN)).ok()
+    }
+}
+
+impl AccountState for ReportRetirement {
+    const DISCRIMINATOR: [u8; 8] = *b"rptretir";
+    const VERSION: u8 = 1;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq)]
 pub enum LSTTokenType {
     AirSOuL,
     ForestSOuL,
@@ -48,3 +434,41 @@ pub enum LSTTokenType {
     PPPSOuL,
     SSSSOuL,
 }
+
+impl LSTTokenType {
+    /// Default name written to the metadata of this token type
+    pub fn name(self) -> &'static str {
+        match self {
+            LSTTokenType::AirSOuL => "Air SOuL",
+            LSTTokenType::ForestSOuL => "Forest SOuL",
+            LSTTokenType::LandSOuL => "Land SOuL",
+            LSTTokenType::WaterSOuL => "Water SOuL",
+            LSTTokenType::OceanSOuL => "Ocean SOuL",
+            LSTTokenType::WildlifeSOuL => "Wildlife SOuL",
+            LSTTokenType::EnergySOuL => "Energy SOuL",
+            LSTTokenType::LifeSOuL => "Life SOuL",
+            LSTTokenType::SanctuarySOuL => "Sanctuary SOuL",
+            LSTTokenType::PESSOuL => "Ecosystem Services SOuL",
+            LSTTokenType::PPPSOuL => "Partnership SOuL",
+            LSTTokenType::SSSSOuL => "Sustainable Systems SOuL",
+        }
+    }
+
+    /// Default symbol written to the metadata of this token type
+    pub fn symbol(self) -> &'static str {
+        match self {
+            LSTTokenType::AirSOuL => "airSOuL",
+            LSTTokenType::ForestSOuL => "forestSOuL",
+            LSTTokenType::LandSOuL => "landSOuL",
+            LSTTokenType::WaterSOuL => "waterSOuL",
+            LSTTokenType::OceanSOuL => "oceanSOuL",
+            LSTTokenType::WildlifeSOuL => "wildlifeSOuL",
+            LSTTokenType::EnergySOuL => "energySOuL",
+            LSTTokenType::LifeSOuL => "lifeSOuL",
+            LSTTokenType::SanctuarySOuL => "sanctuarySOuL",
+            LSTTokenType::PESSOuL => "pesSOuL",
+            LSTTokenType::PPPSOuL => "pppSOuL",
+            LSTTokenType::SSSSOuL => "sssSOuL",
+        }
+    }
+}
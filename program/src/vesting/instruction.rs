@@ -1,44 +1,76 @@
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
     clock::UnixTimestamp,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
     pubkey::Pubkey,
+    system_program,
 };
-use super::state::VestingType;
+use crate::{access::{CircuitBreaker, ProgramConfig}, error::module_error, instruction::ModuleTag};
+use super::state::{VestingMilestone, VestingSchedule, VestingTranche, VestingType};
 
 #[derive(BorshSerialize, BorshDeserialize, Debug)]
 pub enum VestingInstruction {
-    /// Initialize a new vesting schedule
+    /// Initialize a new vesting schedule and fund its vault with the total
+    /// amount. A cliff, duration or release interval left out takes the
+    /// preset of the vesting type; Ecosystem schedules have no preset
+    /// duration and must give one.
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority creating the vesting schedule
-    /// 1. `[writable]` The vesting account to initialize
+    /// 0. `[writable, signer]` The authority creating the vesting schedule, pays rent
+    /// 1. `[writable]` The vesting PDA to create (`["vesting", beneficiary, nonce]`)
     /// 2. `[]` The beneficiary who will receive the tokens
-    /// 3. `[]` The system program
+    /// 3. `[writable]` The vault PDA to create (`["vesting_vault", vesting]`), a token account owned by the vesting PDA
+    /// 4. `[]` The mint of the vested tokens
+    /// 5. `[writable]` The authority's token account the total amount is moved from
+    /// 6. `[]` The token program that owns the mint, SPL Token or Token-2022
+    /// 7. `[]` The system program
+    /// 8. `[]` The program config (`["config"]`)
     Initialize {
+        nonce: u64,
         start_timestamp: UnixTimestamp,
-        end_timestamp: UnixTimestamp,
         total_amount: u64,
         vesting_type: VestingType,
+        cliff_seconds: Option<i64>,
+        duration_seconds: Option<i64>,
+        release_interval_seconds: Option<i64>,
+        /// See [`VestingSchedule::revoke_authority`]
+        revoke_authority: Option<Pubkey>,
     },
 
-    /// Release vested tokens to the beneficiary
+    /// Release vested tokens to the beneficiary. An impact milestone
+    /// schedule first reaches the milestones met by the given reports.
     /// 
     /// Accounts expected:
     /// 0. `[writable]` The vesting account
-    /// 1. `[writable]` The token account holding vested tokens
-    /// 2. `[writable]` The beneficiary's token account
-    /// 3. `[]` The token program
-    /// 4. `[signer]` The beneficiary
+    /// 1. `[writable]` The vault recorded in the vesting account
+    /// 2. `[]` The mint recorded in the vesting account
+    /// 3. `[writable]` The beneficiary's token account
+    /// 4. `[]` The token program that owns the vault
+    /// 5. `[signer]` The beneficiary
+    /// 6. `[writable]` The vesting circuit breaker (`["circuit_breaker", module]`)
+    /// 7. `[]` Zero or more verified impact reports of the schedule's project, past their dispute window
+    /// 8. `[]` The program config (`["config"]`)
     Release,
 
-    /// Revoke a vesting schedule (only for team and advisor tokens)
+    /// Revoke a vesting schedule created with a revoke authority. What has
+    /// vested but not been released is paid to the beneficiary, the unvested
+    /// rest returns to the recovery account, and the schedule releases
+    /// nothing more.
+    /// An impact milestone schedule first reaches the milestones met by the
+    /// given reports, as in Release.
     /// 
     /// Accounts expected:
-    /// 0. `[signer]` The authority who can revoke
+    /// 0. `[signer]` The revoke authority recorded in the vesting account
     /// 1. `[writable]` The vesting account
-    /// 2. `[writable]` The token account holding vested tokens
-    /// 3. `[writable]` The recovery token account
-    /// 4. `[]` The token program
+    /// 2. `[writable]` The vault recorded in the vesting account
+    /// 3. `[]` The mint recorded in the vesting account
+    /// 4. `[writable]` The beneficiary's token account
+    /// 5. `[writable]` The recovery token account
+    /// 6. `[]` The token program that owns the vault
+    /// 7. `[writable]` The vesting circuit breaker (`["circuit_breaker", module]`)
+    /// 8. `[]` Zero or more verified impact reports of the schedule's project, past their dispute window
+    /// 9. `[]` The program config (`["config"]`)
     Revoke,
 
     /// Update the beneficiary of a vesting schedule
@@ -47,21 +79,330 @@ pub enum VestingInstruction {
     /// 0. `[signer]` Current beneficiary
     /// 1. `[writable]` The vesting account
     /// 2. `[]` The new beneficiary
+    /// 3. `[]` The program config (`["config"]`)
     UpdateBeneficiary {
         new_beneficiary: Pubkey,
     },
+
+    /// Close a fully released or revoked vesting schedule and its vault,
+    /// returning their rent to the payer of the schedule. Tokens sent to the
+    /// vault after it was emptied go to the beneficiary first.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable]` The vesting account
+    /// 1. `[writable]` The vault recorded in the vesting account
+    /// 2. `[writable]` The mint recorded in the vesting account, which takes any transfer fees withheld in the vault
+    /// 3. `[writable]` The beneficiary's token account, which takes anything left in the vault
+    /// 4. `[writable]` The payer recorded in the vesting account
+    /// 5. `[]` The token program that owns the vault
+    /// 6. `[]` The program config (`["config"]`)
+    CloseSchedule,
+
+    /// Initialize a vesting schedule made of ordered tranches, each unlocking
+    /// its amount at once or linearly up to its unlock time. The schedule's
+    /// total is the sum of the tranches, moved into its vault, and its
+    /// account is sized to them.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` The authority creating the vesting schedule, pays rent
+    /// 1. `[writable]` The vesting PDA to create (`["vesting", beneficiary, nonce]`)
+    /// 2. `[]` The beneficiary who will receive the tokens
+    /// 3. `[writable]` The vault PDA to create (`["vesting_vault", vesting]`), a token account owned by the vesting PDA
+    /// 4. `[]` The mint of the vested tokens
+    /// 5. `[writable]` The authority's token account the total amount is moved from
+    /// 6. `[]` The token program that owns the mint, SPL Token or Token-2022
+    /// 7. `[]` The system program
+    /// 8. `[]` The program config (`["config"]`)
+    InitializeTranched {
+        nonce: u64,
+        start_timestamp: UnixTimestamp,
+        vesting_type: VestingType,
+        tranches: Vec<VestingTranche>,
+        /// See [`VestingSchedule::revoke_authority`]
+        revoke_authority: Option<Pubkey>,
+    },
+
+    /// Initialize an impact milestone schedule for a project, starting now.
+    /// Each milestone unlocks its amount once a verified report of the
+    /// project meets its target; the schedule's total is their sum, moved
+    /// into its vault.
+    ///
+    /// Accounts expected:
+    /// 0. `[writable, signer]` The authority creating the vesting schedule, pays rent
+    /// 1. `[writable]` The vesting PDA to create (`["vesting", beneficiary, nonce]`)
+    /// 2. `[]` The beneficiary who will receive the tokens
+    /// 3. `[]` The impact metrics account of the project
+    /// 4. `[writable]` The vault PDA to create (`["vesting_vault", vesting]`), a token account owned by the vesting PDA
+    /// 5. `[]` The mint of the vested tokens
+    /// 6. `[writable]` The authority's token account the total amount is moved from
+    /// 7. `[]` The token program that owns the mint, SPL Token or Token-2022
+    /// 8. `[]` The system program
+    /// 9. `[]` The program config (`["config"]`)
+    InitializeMilestones {
+        nonce: u64,
+        milestones: Vec<VestingMilestone>,
+        /// See [`VestingSchedule::revoke_authority`]
+        revoke_authority: Option<Pubkey>,
+    },
+}
+
+impl VestingInstruction {
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        authority: &Pubkey,
+        beneficiary: &Pubkey,
+        mint: &Pubkey,
+        source: &Pubkey,
+        nonce: u64,
+        start_timestamp: UnixTimestamp,
+        total_amount: u64,
+        vesting_type: VestingType,
+        cliff_seconds: Option<i64>,
+        duration_seconds: Option<i64>,
+        release_interval_seconds: Option<i64>,
+        revoke_authority: Option<Pubkey>,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Vesting.pack(&VestingInstruction::Initialize {
+            nonce,
+            start_timestamp,
+            total_amount,
+            vesting_type,
+            cliff_seconds,
+            duration_seconds,
+            release_interval_seconds,
+            revoke_authority,
+        })?;
+        let (vesting_account, _) = VestingSchedule::find_address(beneficiary, nonce, program_id);
+        let (vault, _) = VestingSchedule::find_vault(&vesting_account, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(vesting_account, false),
+                AccountMeta::new_readonly(*beneficiary, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*source, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_tranched(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        authority: &Pubkey,
+        beneficiary: &Pubkey,
+        mint: &Pubkey,
+        source: &Pubkey,
+        nonce: u64,
+        start_timestamp: UnixTimestamp,
+        vesting_type: VestingType,
+        tranches: Vec<VestingTranche>,
+        revoke_authority: Option<Pubkey>,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Vesting.pack(&VestingInstruction::InitializeTranched {
+            nonce,
+            start_timestamp,
+            vesting_type,
+            tranches,
+            revoke_authority,
+        })?;
+        let (vesting_account, _) = VestingSchedule::find_address(beneficiary, nonce, program_id);
+        let (vault, _) = VestingSchedule::find_vault(&vesting_account, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(vesting_account, false),
+                AccountMeta::new_readonly(*beneficiary, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*source, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_milestones(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        authority: &Pubkey,
+        beneficiary: &Pubkey,
+        mint: &Pubkey,
+        source: &Pubkey,
+        nonce: u64,
+        project: &Pubkey,
+        milestones: Vec<VestingMilestone>,
+        revoke_authority: Option<Pubkey>,
+    ) -> Result<Instruction, ProgramError> {
+        let data = ModuleTag::Vesting.pack(&VestingInstruction::InitializeMilestones {
+            nonce,
+            milestones,
+            revoke_authority,
+        })?;
+        let (vesting_account, _) = VestingSchedule::find_address(beneficiary, nonce, program_id);
+        let (vault, _) = VestingSchedule::find_vault(&vesting_account, program_id);
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*authority, true),
+                AccountMeta::new(vesting_account, false),
+                AccountMeta::new_readonly(*beneficiary, false),
+                AccountMeta::new_readonly(*project, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(*mint, false),
+                AccountMeta::new(*source, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data,
+        })
+    }
+
+    /// `reports` are the evidence for an impact milestone schedule, and
+    /// empty for any other
+    #[allow(clippy::too_many_arguments)]
+    pub fn release(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        vesting_account: &Pubkey,
+        vesting_token_account: &Pubkey,
+        mint: &Pubkey,
+        beneficiary_token_account: &Pubkey,
+        beneficiary: &Pubkey,
+        reports: &[Pubkey],
+    ) -> Result<Instruction, ProgramError> {
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::Vesting, program_id);
+
+        let mut accounts = vec![
+            AccountMeta::new(*vesting_account, false),
+            AccountMeta::new(*vesting_token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*beneficiary_token_account, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new_readonly(*beneficiary, true),
+            AccountMeta::new(breaker, false),
+        ];
+        accounts.extend(reports.iter().map(|report| AccountMeta::new_readonly(*report, false)));
+        accounts.push(ProgramConfig::account_meta(program_id));
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data: ModuleTag::Vesting.pack(&VestingInstruction::Release)?,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn revoke(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        authority: &Pubkey,
+        vesting_account: &Pubkey,
+        vesting_token_account: &Pubkey,
+        mint: &Pubkey,
+        beneficiary_token_account: &Pubkey,
+        recovery_token_account: &Pubkey,
+        reports: &[Pubkey],
+    ) -> Result<Instruction, ProgramError> {
+        let (breaker, _) = CircuitBreaker::find_address(ModuleTag::Vesting, program_id);
+
+        let mut accounts = vec![
+            AccountMeta::new_readonly(*authority, true),
+            AccountMeta::new(*vesting_account, false),
+            AccountMeta::new(*vesting_token_account, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(*beneficiary_token_account, false),
+            AccountMeta::new(*recovery_token_account, false),
+            AccountMeta::new_readonly(*token_program_id, false),
+            AccountMeta::new(breaker, false),
+        ];
+        accounts.extend(reports.iter().map(|report| AccountMeta::new_readonly(*report, false)));
+        accounts.push(ProgramConfig::account_meta(program_id));
+
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts,
+            data: ModuleTag::Vesting.pack(&VestingInstruction::Revoke)?,
+        })
+    }
+
+    pub fn update_beneficiary(
+        program_id: &Pubkey,
+        current_beneficiary: &Pubkey,
+        vesting_account: &Pubkey,
+        new_beneficiary: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new_readonly(*current_beneficiary, true),
+                AccountMeta::new(*vesting_account, false),
+                AccountMeta::new_readonly(*new_beneficiary, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Vesting.pack(&VestingInstruction::UpdateBeneficiary {
+                new_beneficiary: *new_beneficiary,
+            })?,
+        })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn close_schedule(
+        program_id: &Pubkey,
+        token_program_id: &Pubkey,
+        vesting_account: &Pubkey,
+        vesting_token_account: &Pubkey,
+        mint: &Pubkey,
+        beneficiary_token_account: &Pubkey,
+        payer: &Pubkey,
+    ) -> Result<Instruction, ProgramError> {
+        Ok(Instruction {
+            program_id: *program_id,
+            accounts: vec![
+                AccountMeta::new(*vesting_account, false),
+                AccountMeta::new(*vesting_token_account, false),
+                AccountMeta::new(*mint, false),
+                AccountMeta::new(*beneficiary_token_account, false),
+                AccountMeta::new(*payer, false),
+                AccountMeta::new_readonly(*token_program_id, false),
+                ProgramConfig::account_meta(program_id),
+            ],
+            data: ModuleTag::Vesting.pack(&VestingInstruction::CloseSchedule)?,
+        })
+    }
 }
 
-#[derive(Debug)]
-pub enum VestingError {
-    InvalidInstruction,
-    NotRentExempt,
-    AlreadyInitialized,
-    NotInitialized,
-    InvalidBeneficiary,
-    InvalidAuthority,
-    InvalidVestingSchedule,
-    NoTokensToRelease,
-    InvalidTokenAccount,
-    InvalidRevocation,
+module_error! {
+    pub enum VestingError in Vesting {
+        InvalidInstruction => "Invalid Instruction",
+        NotRentExempt => "Not Rent Exempt",
+        AlreadyInitialized => "Already Initialized",
+        NotInitialized => "Not Initialized",
+        InvalidBeneficiary => "Invalid Beneficiary",
+        InvalidAuthority => "Invalid Authority",
+        InvalidVestingSchedule => "Invalid Vesting Schedule",
+        NoTokensToRelease => "No Tokens To Release",
+        InvalidTokenAccount => "Invalid Token Account",
+        InvalidRevocation => "Invalid Revocation",
+        ScheduleNotComplete => "Vesting Schedule Not Fully Released",
+        InvalidImpactEvidence => "Invalid Impact Evidence",
+        ScheduleRevoked => "Vesting Schedule Revoked",
+        VaultUnderfunded => "Vault Holds Less Than The Schedule Total",
+    }
 }
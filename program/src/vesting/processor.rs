@@ -1,20 +1,34 @@
+use borsh::BorshDeserialize;
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
-    msg,
     program::{invoke, invoke_signed},
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
-    rent::Rent,
     sysvar::Sysvar,
     clock::Clock,
 };
-use spl_token::state::Account as TokenAccount;
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+
+use crate::{
+    access,
+    account::AccountState,
+    error::SOuLError,
+    event::SoulEvent,
+    impact::{ImpactMetrics, ImpactReport},
+    instruction::ModuleTag,
+    pda,
+};
 
 use super::{
     instruction::{VestingInstruction, VestingError},
-    state::{VestingSchedule, VestingType},
+    state::{VestingMilestone, VestingSchedule, VestingTranche, VestingType},
 };
 
 pub struct Processor;
@@ -30,17 +44,25 @@ impl Processor {
 
         match instruction {
             VestingInstruction::Initialize {
+                nonce,
                 start_timestamp,
-                end_timestamp,
                 total_amount,
                 vesting_type,
+                cliff_seconds,
+                duration_seconds,
+                release_interval_seconds,
+                revoke_authority,
             } => {
                 Self::process_initialize(
                     accounts,
+                    nonce,
                     start_timestamp,
-                    end_timestamp,
                     total_amount,
                     vesting_type,
+                    cliff_seconds,
+                    duration_seconds,
+                    release_interval_seconds,
+                    revoke_authority,
                     program_id,
                 )
             }
@@ -49,33 +71,177 @@ impl Processor {
             VestingInstruction::UpdateBeneficiary { new_beneficiary } => {
                 Self::process_update_beneficiary(accounts, new_beneficiary, program_id)
             }
+            VestingInstruction::CloseSchedule => Self::process_close_schedule(accounts, program_id),
+            VestingInstruction::InitializeTranched {
+                nonce,
+                start_timestamp,
+                vesting_type,
+                tranches,
+                revoke_authority,
+            } => Self::process_initialize_tranched(
+                accounts,
+                nonce,
+                start_timestamp,
+                vesting_type,
+                tranches,
+                revoke_authority,
+                program_id,
+            ),
+            VestingInstruction::InitializeMilestones {
+                nonce,
+                milestones,
+                revoke_authority,
+            } => Self::process_initialize_milestones(accounts, nonce, milestones, revoke_authority, program_id),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_initialize(
         accounts: &[AccountInfo],
+        nonce: u64,
         start_timestamp: i64,
-        end_timestamp: i64,
         total_amount: u64,
         vesting_type: VestingType,
+        cliff_seconds: Option<i64>,
+        duration_seconds: Option<i64>,
+        release_interval_seconds: Option<i64>,
+        revoke_authority: Option<Pubkey>,
         program_id: &Pubkey,
     ) -> ProgramResult {
         let account_info_iter = &mut accounts.iter();
         let authority_info = next_account_info(account_info_iter)?;
         let vesting_account_info = next_account_info(account_info_iter)?;
         let beneficiary_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
         let system_program_info = next_account_info(account_info_iter)?;
 
         if !authority_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
+        if revoke_authority.is_some() && !vesting_type.is_revocable() {
+            return Err(VestingError::InvalidRevocation.into());
+        }
+
+        let (cliff_seconds, duration_seconds, release_interval_seconds) = VestingSchedule::resolve_terms(
+            &vesting_type,
+            cliff_seconds,
+            duration_seconds,
+            release_interval_seconds,
+        )
+        .ok_or(VestingError::InvalidVestingSchedule)?;
+        let end_timestamp = start_timestamp
+            .checked_add(duration_seconds)
+            .ok_or(VestingError::InvalidVestingSchedule)?;
+
+        let bump = create_schedule_account(
+            authority_info,
+            vesting_account_info,
+            beneficiary_info,
+            system_program_info,
+            nonce,
+            VestingSchedule::SPACE,
+            program_id,
+        )?;
+        fund_vault(
+            authority_info,
+            vesting_account_info,
+            vault_info,
+            mint_info,
+            source_info,
+            token_program_info,
+            system_program_info,
+            total_amount,
+            program_id,
+        )?;
+
+        let vesting_schedule = VestingSchedule {
+            is_initialized: true,
+            beneficiary: *beneficiary_info.key,
+            start_timestamp,
+            end_timestamp,
+            total_amount,
+            released_amount: 0,
+            vesting_type,
+            original_beneficiary: *beneficiary_info.key,
+            nonce,
+            bump,
+            payer: *authority_info.key,
+            cliff_seconds,
+            duration_seconds,
+            release_interval_seconds,
+            tranches: Vec::new(),
+            project: Pubkey::default(),
+            milestones: Vec::new(),
+            revoke_authority,
+            revoked_at: None,
+            mint: *mint_info.key,
+            vault: *vault_info.key,
+        };
+
+        vesting_schedule.save(vesting_account_info)?;
+        Ok(())
+    }
+
+    fn process_initialize_tranched(
+        accounts: &[AccountInfo],
+        nonce: u64,
+        start_timestamp: i64,
+        vesting_type: VestingType,
+        tranches: Vec<VestingTranche>,
+        revoke_authority: Option<Pubkey>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let vesting_account_info = next_account_info(account_info_iter)?;
+        let beneficiary_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
 
-        let rent = Rent::get()?;
-        if !rent.is_exempt(vesting_account_info.lamports(), VestingSchedule::LEN) {
-            return Err(ProgramError::AccountNotRentExempt);
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        if !vesting_type.is_timed() {
+            return Err(VestingError::InvalidVestingSchedule.into());
+        }
+        if revoke_authority.is_some() && !vesting_type.is_revocable() {
+            return Err(VestingError::InvalidRevocation.into());
         }
+        let total_amount = VestingSchedule::tranches_total(start_timestamp, &tranches)
+            .ok_or(VestingError::InvalidVestingSchedule)?;
+        // The schedule's terms describe the span of its tranches
+        let end_timestamp = tranches.last().map_or(start_timestamp, |tranche| tranche.unlock_timestamp);
+        let duration_seconds = end_timestamp - start_timestamp;
 
-        let mut vesting_schedule = VestingSchedule {
+        let bump = create_schedule_account(
+            authority_info,
+            vesting_account_info,
+            beneficiary_info,
+            system_program_info,
+            nonce,
+            VestingSchedule::space(tranches.len(), 0),
+            program_id,
+        )?;
+        fund_vault(
+            authority_info,
+            vesting_account_info,
+            vault_info,
+            mint_info,
+            source_info,
+            token_program_info,
+            system_program_info,
+            total_amount,
+            program_id,
+        )?;
+
+        let vesting_schedule = VestingSchedule {
             is_initialized: true,
             beneficiary: *beneficiary_info.key,
             start_timestamp,
@@ -83,9 +249,100 @@ impl Processor {
             total_amount,
             released_amount: 0,
             vesting_type,
+            original_beneficiary: *beneficiary_info.key,
+            nonce,
+            bump,
+            payer: *authority_info.key,
+            cliff_seconds: 0,
+            duration_seconds,
+            release_interval_seconds: VestingSchedule::DEFAULT_RELEASE_INTERVAL,
+            tranches,
+            project: Pubkey::default(),
+            milestones: Vec::new(),
+            revoke_authority,
+            revoked_at: None,
+            mint: *mint_info.key,
+            vault: *vault_info.key,
+        };
+
+        vesting_schedule.save(vesting_account_info)?;
+        Ok(())
+    }
+
+    fn process_initialize_milestones(
+        accounts: &[AccountInfo],
+        nonce: u64,
+        milestones: Vec<VestingMilestone>,
+        revoke_authority: Option<Pubkey>,
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let authority_info = next_account_info(account_info_iter)?;
+        let vesting_account_info = next_account_info(account_info_iter)?;
+        let beneficiary_info = next_account_info(account_info_iter)?;
+        let project_info = next_account_info(account_info_iter)?;
+        let vault_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let source_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+        let system_program_info = next_account_info(account_info_iter)?;
+
+        if !authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+
+        // The project must be an impact metrics account reports are filed against
+        ImpactMetrics::load(project_info, program_id)?;
+        let total_amount =
+            VestingSchedule::milestones_total(&milestones).ok_or(VestingError::InvalidVestingSchedule)?;
+
+        let bump = create_schedule_account(
+            authority_info,
+            vesting_account_info,
+            beneficiary_info,
+            system_program_info,
+            nonce,
+            VestingSchedule::space(0, milestones.len()),
+            program_id,
+        )?;
+        fund_vault(
+            authority_info,
+            vesting_account_info,
+            vault_info,
+            mint_info,
+            source_info,
+            token_program_info,
+            system_program_info,
+            total_amount,
+            program_id,
+        )?;
+
+        let now = Clock::get()?.unix_timestamp;
+        let vesting_schedule = VestingSchedule {
+            is_initialized: true,
+            beneficiary: *beneficiary_info.key,
+            start_timestamp: now,
+            end_timestamp: now,
+            total_amount,
+            released_amount: 0,
+            vesting_type: VestingType::ImpactMilestone,
+            original_beneficiary: *beneficiary_info.key,
+            nonce,
+            bump,
+            payer: *authority_info.key,
+            cliff_seconds: 0,
+            duration_seconds: 0,
+            release_interval_seconds: VestingSchedule::DEFAULT_RELEASE_INTERVAL,
+            tranches: Vec::new(),
+            project: *project_info.key,
+            milestones,
+            revoke_authority,
+            revoked_at: None,
+            mint: *mint_info.key,
+            vault: *vault_info.key,
         };
 
-        vesting_schedule.serialize(&mut *vesting_account_info.data.borrow_mut())?;
+        vesting_schedule.save(vesting_account_info)?;
         Ok(())
     }
 
@@ -96,50 +353,63 @@ impl Processor {
         let account_info_iter = &mut accounts.iter();
         let vesting_account_info = next_account_info(account_info_iter)?;
         let vesting_token_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
         let beneficiary_token_info = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
         let beneficiary_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+        let report_infos = account_info_iter.as_slice();
 
         if !beneficiary_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let mut vesting_schedule = VestingSchedule::try_from_slice(&vesting_account_info.data.borrow())?;
+        if vesting_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut vesting_schedule = VestingSchedule::load(vesting_account_info, program_id)?;
         
         if vesting_schedule.beneficiary != *beneficiary_info.key {
             return Err(ProgramError::InvalidAccountData);
         }
 
+        if vesting_schedule.revoked_at.is_some() {
+            return Err(ProgramError::from(VestingError::ScheduleRevoked));
+        }
+        check_vault(&vesting_schedule, vesting_token_info, mint_info, token_program_info)?;
+
         let clock = Clock::get()?;
+
+        reach_reported_milestones(&mut vesting_schedule, report_infos, clock.unix_timestamp, program_id)?;
         let releasable_amount = vesting_schedule.calculate_releasable_amount(clock.unix_timestamp);
 
         if releasable_amount == 0 {
             return Err(ProgramError::from(VestingError::NoTokensToRelease));
         }
+        access::record_outflow(breaker_info, ModuleTag::Vesting, releasable_amount, program_id)?;
 
-        // Transfer tokens
-        let transfer_ix = spl_token::instruction::transfer(
-            token_program_info.key,
-            vesting_token_info.key,
-            beneficiary_token_info.key,
-            vesting_account_info.key,
-            &[],
+        pay_from_vault(
+            &vesting_schedule,
+            vesting_account_info,
+            vesting_token_info,
+            mint_info,
+            beneficiary_token_info,
+            token_program_info,
             releasable_amount,
         )?;
 
-        invoke_signed(
-            &transfer_ix,
-            &[
-                vesting_token_info.clone(),
-                beneficiary_token_info.clone(),
-                vesting_account_info.clone(),
-                token_program_info.clone(),
-            ],
-            &[&[&vesting_account_info.key.to_bytes(), &[0]]],
-        )?;
-
         vesting_schedule.released_amount += releasable_amount;
-        vesting_schedule.serialize(&mut *vesting_account_info.data.borrow_mut())?;
+        vesting_schedule.save(vesting_account_info)?;
+
+        SoulEvent::VestingReleased {
+            vesting_account: *vesting_account_info.key,
+            beneficiary: *beneficiary_info.key,
+            amount: releasable_amount,
+            released_amount: vesting_schedule.released_amount,
+            timestamp: clock.unix_timestamp,
+        }
+        .emit()?;
 
         Ok(())
     }
@@ -152,45 +422,90 @@ impl Processor {
         let authority_info = next_account_info(account_info_iter)?;
         let vesting_account_info = next_account_info(account_info_iter)?;
         let vesting_token_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let beneficiary_token_info = next_account_info(account_info_iter)?;
         let recovery_token_info = next_account_info(account_info_iter)?;
         let token_program_info = next_account_info(account_info_iter)?;
+        let breaker_info = next_account_info(account_info_iter)?;
+        let report_infos = account_info_iter.as_slice();
 
         if !authority_info.is_signer {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let vesting_schedule = VestingSchedule::try_from_slice(&vesting_account_info.data.borrow())?;
-        
-        match vesting_schedule.vesting_type {
-            VestingType::Team | VestingType::Advisor => {
-                let vesting_token = TokenAccount::unpack(&vesting_token_info.data.borrow())?;
-                let remaining_amount = vesting_token.amount;
-
-                if remaining_amount > 0 {
-                    let transfer_ix = spl_token::instruction::transfer(
-                        token_program_info.key,
-                        vesting_token_info.key,
-                        recovery_token_info.key,
-                        vesting_account_info.key,
-                        &[],
-                        remaining_amount,
-                    )?;
-
-                    invoke_signed(
-                        &transfer_ix,
-                        &[
-                            vesting_token_info.clone(),
-                            recovery_token_info.clone(),
-                            vesting_account_info.clone(),
-                            token_program_info.clone(),
-                        ],
-                        &[&[&vesting_account_info.key.to_bytes(), &[0]]],
-                    )?;
-                }
-                Ok(())
+        if vesting_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut vesting_schedule = VestingSchedule::load(vesting_account_info, program_id)?;
+        let Some(revoke_authority) = vesting_schedule.revoke_authority else {
+            return Err(ProgramError::from(VestingError::InvalidRevocation));
+        };
+        if revoke_authority != *authority_info.key {
+            return Err(ProgramError::from(VestingError::InvalidAuthority));
+        }
+        if vesting_schedule.revoked_at.is_some() {
+            return Err(ProgramError::from(VestingError::ScheduleRevoked));
+        }
+
+        check_vault(&vesting_schedule, vesting_token_info, mint_info, token_program_info)?;
+        let vesting_token = StateWithExtensions::<TokenAccount>::unpack(&vesting_token_info.data.borrow())?.base;
+        let beneficiary_token =
+            StateWithExtensions::<TokenAccount>::unpack(&beneficiary_token_info.data.borrow())?.base;
+        if beneficiary_token.owner != vesting_schedule.beneficiary || beneficiary_token.mint != vesting_schedule.mint {
+            return Err(ProgramError::from(VestingError::InvalidTokenAccount));
+        }
+
+        // What has vested stays the beneficiary's, including milestones met by
+        // final reports that were never released; only the rest is returned
+        let now = Clock::get()?.unix_timestamp;
+        reach_reported_milestones(&mut vesting_schedule, report_infos, now, program_id)?;
+        let vested_amount = vesting_schedule.calculate_releasable_amount(now);
+        let unvested_amount = vesting_token.amount.saturating_sub(vested_amount);
+        // Paying out what has vested counts against the breaker like a release
+        access::record_outflow(breaker_info, ModuleTag::Vesting, vested_amount, program_id)?;
+
+        for (destination_info, amount) in [
+            (beneficiary_token_info, vested_amount),
+            (recovery_token_info, unvested_amount),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            pay_from_vault(
+                &vesting_schedule,
+                vesting_account_info,
+                vesting_token_info,
+                mint_info,
+                destination_info,
+                token_program_info,
+                amount,
+            )?;
+        }
+
+        vesting_schedule.released_amount += vested_amount;
+        vesting_schedule.revoked_at = Some(now);
+        vesting_schedule.save(vesting_account_info)?;
+
+        if vested_amount > 0 {
+            SoulEvent::VestingReleased {
+                vesting_account: *vesting_account_info.key,
+                beneficiary: vesting_schedule.beneficiary,
+                amount: vested_amount,
+                released_amount: vesting_schedule.released_amount,
+                timestamp: now,
             }
-            _ => Err(ProgramError::from(VestingError::InvalidRevocation)),
+            .emit()?;
+        }
+
+        SoulEvent::VestingRevoked {
+            vesting_account: *vesting_account_info.key,
+            authority: *authority_info.key,
+            recovery_account: *recovery_token_info.key,
+            amount: unvested_amount,
+            timestamp: now,
         }
+        .emit()
     }
 
     fn process_update_beneficiary(
@@ -207,7 +522,11 @@ impl Processor {
             return Err(ProgramError::MissingRequiredSignature);
         }
 
-        let mut vesting_schedule = VestingSchedule::try_from_slice(&vesting_account_info.data.borrow())?;
+        if vesting_account_info.owner != program_id {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        let mut vesting_schedule = VestingSchedule::load(vesting_account_info, program_id)?;
         
         if vesting_schedule.beneficiary != *current_beneficiary_info.key {
             return Err(ProgramError::InvalidAccountData);
@@ -218,8 +537,258 @@ impl Processor {
         }
 
         vesting_schedule.beneficiary = new_beneficiary;
-        vesting_schedule.serialize(&mut *vesting_account_info.data.borrow_mut())?;
+        vesting_schedule.save(vesting_account_info)?;
 
         Ok(())
     }
+
+    fn process_close_schedule(
+        accounts: &[AccountInfo],
+        program_id: &Pubkey,
+    ) -> ProgramResult {
+        let account_info_iter = &mut accounts.iter();
+        let vesting_account_info = next_account_info(account_info_iter)?;
+        let vesting_token_info = next_account_info(account_info_iter)?;
+        let mint_info = next_account_info(account_info_iter)?;
+        let beneficiary_token_info = next_account_info(account_info_iter)?;
+        let payer_info = next_account_info(account_info_iter)?;
+        let token_program_info = next_account_info(account_info_iter)?;
+
+        let vesting_schedule = VestingSchedule::load(vesting_account_info, program_id)?;
+        if !vesting_schedule.is_complete() {
+            return Err(ProgramError::from(VestingError::ScheduleNotComplete));
+        }
+        if *payer_info.key != vesting_schedule.payer {
+            return Err(SOuLError::RentPayerMismatch.into());
+        }
+
+        check_vault(&vesting_schedule, vesting_token_info, mint_info, token_program_info)?;
+        let beneficiary_token =
+            StateWithExtensions::<TokenAccount>::unpack(&beneficiary_token_info.data.borrow())?.base;
+        if beneficiary_token.owner != vesting_schedule.beneficiary || beneficiary_token.mint != vesting_schedule.mint {
+            return Err(ProgramError::from(VestingError::InvalidTokenAccount));
+        }
+
+        let nonce_bytes = vesting_schedule.nonce.to_le_bytes();
+        pda::close_token_vault(
+            vesting_token_info,
+            mint_info,
+            vesting_account_info,
+            beneficiary_token_info,
+            payer_info,
+            token_program_info,
+            &[
+                VestingSchedule::SEED,
+                vesting_schedule.original_beneficiary.as_ref(),
+                &nonce_bytes,
+                &[vesting_schedule.bump],
+            ],
+        )?;
+
+        let lamports = pda::close_to_payer(vesting_account_info, payer_info, &vesting_schedule.payer)?;
+
+        SoulEvent::AccountClosed {
+            module: ModuleTag::Vesting,
+            account: *vesting_account_info.key,
+            payer: *payer_info.key,
+            lamports,
+            timestamp: Clock::get()?.unix_timestamp,
+        }
+        .emit()
+    }
+}
+
+/// Create the vesting PDA (`["vesting", beneficiary, nonce]`) with `space`
+/// bytes, paid for by the authority, and return its bump
+fn create_schedule_account<'a>(
+    authority_info: &AccountInfo<'a>,
+    vesting_account_info: &AccountInfo<'a>,
+    beneficiary_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    nonce: u64,
+    space: usize,
+    program_id: &Pubkey,
+) -> Result<u8, ProgramError> {
+    let nonce_bytes = nonce.to_le_bytes();
+    let bump = pda::expect_pda(
+        vesting_account_info.key,
+        &[VestingSchedule::SEED, beneficiary_info.key.as_ref(), &nonce_bytes],
+        program_id,
+    )?;
+    pda::create_pda_account(
+        authority_info,
+        vesting_account_info,
+        system_program_info,
+        program_id,
+        space,
+        &[VestingSchedule::SEED, beneficiary_info.key.as_ref(), &nonce_bytes, &[bump]],
+    )?;
+    Ok(bump)
+}
+
+/// Reach the milestones met by `report_infos`, which are evidence for impact
+/// milestones only and must be final reports of the schedule's project
+fn reach_reported_milestones(
+    vesting_schedule: &mut VestingSchedule,
+    report_infos: &[AccountInfo],
+    now: i64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if vesting_schedule.vesting_type.is_timed() && !report_infos.is_empty() {
+        return Err(VestingError::InvalidImpactEvidence.into());
+    }
+    for report_info in report_infos {
+        let report = ImpactReport::load(report_info, program_id)?;
+        if report.metrics_account != vesting_schedule.project || !report.is_final(now) {
+            return Err(VestingError::InvalidImpactEvidence.into());
+        }
+        vesting_schedule.reach_milestones(report_info.key, &report);
+    }
+    Ok(())
+}
+
+/// Check the vault, mint and token program passed alongside a schedule
+fn check_vault(
+    vesting_schedule: &VestingSchedule,
+    vault_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+) -> ProgramResult {
+    if *vault_info.key != vesting_schedule.vault || *mint_info.key != vesting_schedule.mint {
+        return Err(ProgramError::from(VestingError::InvalidTokenAccount));
+    }
+    if token_program_info.key != vault_info.owner {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Move `amount` out of the schedule's vault, signed by the vesting PDA
+fn pay_from_vault<'a>(
+    vesting_schedule: &VestingSchedule,
+    vesting_account_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    destination_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    let decimals = StateWithExtensions::<Mint>::unpack(&mint_info.data.borrow())?.base.decimals;
+    let transfer_ix = spl_token_2022::instruction::transfer_checked(
+        token_program_info.key,
+        vault_info.key,
+        mint_info.key,
+        destination_info.key,
+        vesting_account_info.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    let nonce_bytes = vesting_schedule.nonce.to_le_bytes();
+    invoke_signed(
+        &transfer_ix,
+        &[
+            vault_info.clone(),
+            mint_info.clone(),
+            destination_info.clone(),
+            vesting_account_info.clone(),
+            token_program_info.clone(),
+        ],
+        &[&[
+            VestingSchedule::SEED,
+            vesting_schedule.original_beneficiary.as_ref(),
+            &nonce_bytes,
+            &[vesting_schedule.bump],
+        ]],
+    )
+}
+
+/// Create the vault PDA (`["vesting_vault", vesting]`) as a token account of
+/// `mint` owned by the vesting PDA, and move `amount` into it from the
+/// authority's `source` account. A transfer fee mint is sent enough to cover
+/// its fee, so the vault always ends up holding `amount`.
+#[allow(clippy::too_many_arguments)]
+fn fund_vault<'a>(
+    authority_info: &AccountInfo<'a>,
+    vesting_account_info: &AccountInfo<'a>,
+    vault_info: &AccountInfo<'a>,
+    mint_info: &AccountInfo<'a>,
+    source_info: &AccountInfo<'a>,
+    token_program_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    amount: u64,
+    program_id: &Pubkey,
+) -> ProgramResult {
+    if mint_info.owner != token_program_info.key
+        || (*token_program_info.key != spl_token::id() && *token_program_info.key != spl_token_2022::id())
+    {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // The vault carries whatever account extensions the mint requires
+    let (decimals, space, funding) = {
+        let mint_data = mint_info.data.borrow();
+        let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        let required = ExtensionType::get_required_init_account_extensions(&mint.get_extension_types()?);
+        let funding = match mint.get_extension::<TransferFeeConfig>() {
+            Ok(fee_config) => fee_config
+                .get_epoch_fee(Clock::get()?.epoch)
+                .calculate_pre_fee_amount(amount)
+                .ok_or(VestingError::InvalidVestingSchedule)?,
+            Err(_) => amount,
+        };
+        (mint.base.decimals, ExtensionType::try_calculate_account_len::<TokenAccount>(&required)?, funding)
+    };
+
+    let vault_bump = pda::expect_pda(
+        vault_info.key,
+        &[VestingSchedule::VAULT_SEED, vesting_account_info.key.as_ref()],
+        program_id,
+    )?;
+    pda::create_pda_account(
+        authority_info,
+        vault_info,
+        system_program_info,
+        token_program_info.key,
+        space,
+        &[VestingSchedule::VAULT_SEED, vesting_account_info.key.as_ref(), &[vault_bump]],
+    )?;
+    let init_vault_ix = spl_token_2022::instruction::initialize_account3(
+        token_program_info.key,
+        vault_info.key,
+        mint_info.key,
+        vesting_account_info.key,
+    )?;
+    invoke(
+        &init_vault_ix,
+        &[vault_info.clone(), mint_info.clone(), token_program_info.clone()],
+    )?;
+
+    let fund_ix = spl_token_2022::instruction::transfer_checked(
+        token_program_info.key,
+        source_info.key,
+        mint_info.key,
+        vault_info.key,
+        authority_info.key,
+        &[],
+        funding,
+        decimals,
+    )?;
+    invoke(
+        &fund_ix,
+        &[
+            source_info.clone(),
+            mint_info.clone(),
+            vault_info.clone(),
+            authority_info.clone(),
+            token_program_info.clone(),
+        ],
+    )?;
+
+    // Whatever the mint takes in transit, the schedule must be fully backed
+    let funded = StateWithExtensions::<TokenAccount>::unpack(&vault_info.data.borrow())?.base.amount;
+    if funded < amount {
+        return Err(ProgramError::from(VestingError::VaultUnderfunded));
+    }
+    Ok(())
 }
@@ -4,6 +4,11 @@ use solana_program::{
     pubkey::Pubkey,
 };
 
+use crate::{
+    account::{AccountState, HEADER_LEN},
+    impact::{ImpactMetric, ImpactReport},
+};
+
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct VestingSchedule {
     pub is_initialized: bool,
@@ -13,8 +18,70 @@ pub struct VestingSchedule {
     pub total_amount: u64,
     pub released_amount: u64,
     pub vesting_type: VestingType,
+    /// Beneficiary at creation, kept because it is part of the PDA seeds
+    pub original_beneficiary: Pubkey,
+    pub nonce: u64,
+    pub bump: u8,
+    /// Authority that paid the schedule's rent, refunded when it is closed
+    pub payer: Pubkey,
+    /// Nothing vests until this long after the start
+    pub cliff_seconds: i64,
+    /// Everything has vested this long after the start, at `end_timestamp`
+    pub duration_seconds: i64,
+    /// Vested tokens unlock in steps of this many seconds
+    pub release_interval_seconds: i64,
+    /// Unlocks of a tranched schedule, in order. A schedule without tranches
+    /// vests on its cliff, duration and release interval.
+    pub tranches: Vec<VestingTranche>,
+    /// Impact metrics account of the project an impact milestone schedule
+    /// funds, default otherwise
+    pub project: Pubkey,
+    /// Targets of an impact milestone schedule, each unlocking its amount
+    /// once a final report of the project meets it
+    pub milestones: Vec<VestingMilestone>,
+    /// Key that may revoke the schedule, `None` if it cannot be revoked.
+    /// Its creator names it, or none, when initializing the schedule; only
+    /// Team, Advisor and impact milestone schedules may have one.
+    pub revoke_authority: Option<Pubkey>,
+    /// When the schedule was revoked; nothing more vests or releases after
+    pub revoked_at: Option<UnixTimestamp>,
+    /// Mint of the vested tokens
+    pub mint: Pubkey,
+    /// Token account holding the schedule's tokens, owned by the vesting PDA
+    pub vault: Pubkey,
+}
+
+/// One unlock of a tranched schedule
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VestingTranche {
+    pub unlock_timestamp: UnixTimestamp,
+    pub amount: u64,
+    pub mode: TrancheMode,
+}
+
+/// How the amount of a tranche unlocks
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrancheMode {
+    /// All at once at the tranche's unlock time
+    Instant,
+    /// Linearly from the previous tranche's unlock time, or the schedule's
+    /// start for the first tranche, until the tranche's own
+    Linear,
+}
+
+/// One target of an impact milestone schedule
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct VestingMilestone {
+    pub metric: ImpactMetric,
+    /// Reached by a report whose metric is at least this
+    pub target: u64,
+    pub amount: u64,
+    /// Report that reached the milestone, `None` while it is pending
+    pub report: Option<Pubkey>,
 }
 
+/// Kind of a schedule, whose preset cliff and duration apply when
+/// `Initialize` leaves them out
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum VestingType {
     Team,           // 3-year vesting, 1-year cliff
@@ -22,43 +89,228 @@ pub enum VestingType {
     PrivateSale,    // 18-month vesting, 6-month cliff
     PublicSale,     // 12-month vesting, 3-month cliff
     Ecosystem,      // Custom vesting for ecosystem development
+    ImpactMilestone, // Unlocks on verified impact reports of a project
+}
+
+impl VestingType {
+    pub fn default_cliff_seconds(&self) -> i64 {
+        match self {
+            VestingType::Team | VestingType::Advisor => 31_536_000, // 1 year in seconds
+            VestingType::PrivateSale => 15_768_000,                  // 6 months in seconds
+            VestingType::PublicSale => 7_884_000,                    // 3 months in seconds
+            VestingType::Ecosystem | VestingType::ImpactMilestone => 0,
+        }
+    }
+
+    /// Ecosystem schedules have no preset and must give their duration;
+    /// impact milestone schedules do not vest over time
+    pub fn default_duration_seconds(&self) -> Option<i64> {
+        match self {
+            VestingType::Team | VestingType::Advisor => Some(94_608_000), // 3 years in seconds
+            VestingType::PrivateSale => Some(47_304_000),                  // 18 months in seconds
+            VestingType::PublicSale => Some(31_536_000),                   // 12 months in seconds
+            VestingType::Ecosystem | VestingType::ImpactMilestone => None,
+        }
+    }
+
+    /// Whether schedules of this type may have a revoke authority
+    pub fn is_revocable(&self) -> bool {
+        matches!(self, VestingType::Team | VestingType::Advisor | VestingType::ImpactMilestone)
+    }
+
+    /// Whether schedules of this type vest over time rather than on
+    /// impact milestones
+    pub fn is_timed(&self) -> bool {
+        !matches!(self, VestingType::ImpactMilestone)
+    }
 }
 
 impl VestingSchedule {
-    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 8 + 1;
+    /// Fixed fields, and the length prefixes of empty tranche and milestone lists
+    pub const LEN: usize = 1 + 32 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 1 + 32 + 8 + 8 + 8 + 4 + 32 + 4 + 33 + 9 + 32 + 32;
+
+    /// Most tranches a single schedule can hold
+    pub const MAX_TRANCHES: usize = 64;
+
+    /// Most milestones a single schedule can hold
+    pub const MAX_MILESTONES: usize = 32;
+
+    /// Release interval of schedules that vest continuously
+    pub const DEFAULT_RELEASE_INTERVAL: i64 = 1;
+
+    /// Seed of the vesting schedule PDA (`["vesting", beneficiary, nonce]`)
+    pub const SEED: &'static [u8] = b"vesting";
+
+    /// Seed of the vault PDA (`["vesting_vault", vesting]`) a new schedule
+    /// is funded into
+    pub const VAULT_SEED: &'static [u8] = b"vesting_vault";
+
+    pub fn find_address(beneficiary: &Pubkey, nonce: u64, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[Self::SEED, beneficiary.as_ref(), &nonce.to_le_bytes()],
+            program_id,
+        )
+    }
+
+    pub fn find_vault(vesting: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[Self::VAULT_SEED, vesting.as_ref()], program_id)
+    }
+
+    /// Space of a schedule with `num_tranches` tranches and `num_milestones`
+    /// milestones, header included
+    pub fn space(num_tranches: usize, num_milestones: usize) -> usize {
+        Self::SPACE + num_tranches * VestingTranche::LEN + num_milestones * VestingMilestone::LEN
+    }
+
+    /// Total amount of tranches starting at `start_timestamp`, or `None` if
+    /// they are empty, out of order or cannot vest
+    pub fn tranches_total(start_timestamp: UnixTimestamp, tranches: &[VestingTranche]) -> Option<u64> {
+        if tranches.is_empty() || tranches.len() > Self::MAX_TRANCHES {
+            return None;
+        }
+        let mut previous_unlock = start_timestamp;
+        let mut total: u64 = 0;
+        for tranche in tranches {
+            let in_order = match tranche.mode {
+                TrancheMode::Instant => tranche.unlock_timestamp >= previous_unlock,
+                TrancheMode::Linear => tranche.unlock_timestamp > previous_unlock,
+            };
+            if !in_order || tranche.amount == 0 {
+                return None;
+            }
+            total = total.checked_add(tranche.amount)?;
+            previous_unlock = tranche.unlock_timestamp;
+        }
+        Some(total)
+    }
+
+    /// Total amount of new milestones, or `None` if they are empty, already
+    /// reached or cannot vest
+    pub fn milestones_total(milestones: &[VestingMilestone]) -> Option<u64> {
+        if milestones.is_empty() || milestones.len() > Self::MAX_MILESTONES {
+            return None;
+        }
+        milestones.iter().try_fold(0u64, |total, milestone| {
+            if milestone.amount == 0 || milestone.report.is_some() {
+                return None;
+            }
+            total.checked_add(milestone.amount)
+        })
+    }
+
+    /// Mark every pending milestone that `report` meets as reached by it,
+    /// returning how many were
+    pub fn reach_milestones(&mut self, report_key: &Pubkey, report: &ImpactReport) -> usize {
+        let mut reached = 0;
+        for milestone in self.milestones.iter_mut().filter(|milestone| milestone.report.is_none()) {
+            if report.metrics.value(milestone.metric) >= milestone.target {
+                milestone.report = Some(*report_key);
+                reached += 1;
+            }
+        }
+        reached
+    }
+
+    /// Resolve the cliff, duration and release interval of a new schedule,
+    /// taking the preset of `vesting_type` for any left out. Returns `None`
+    /// for terms that cannot vest, or a type that does not vest over time.
+    pub fn resolve_terms(
+        vesting_type: &VestingType,
+        cliff_seconds: Option<i64>,
+        duration_seconds: Option<i64>,
+        release_interval_seconds: Option<i64>,
+    ) -> Option<(i64, i64, i64)> {
+        if !vesting_type.is_timed() {
+            return None;
+        }
+        let duration_seconds = duration_seconds.or(vesting_type.default_duration_seconds())?;
+        let cliff_seconds = cliff_seconds.unwrap_or(vesting_type.default_cliff_seconds());
+        let release_interval_seconds = release_interval_seconds.unwrap_or(Self::DEFAULT_RELEASE_INTERVAL);
+
+        let valid = duration_seconds > 0
+            && (0..=duration_seconds).contains(&cliff_seconds)
+            && (1..=duration_seconds).contains(&release_interval_seconds);
+        valid.then_some((cliff_seconds, duration_seconds, release_interval_seconds))
+    }
 
     pub fn calculate_releasable_amount(&self, current_timestamp: UnixTimestamp) -> u64 {
-        if current_timestamp < self.start_timestamp {
+        if self.revoked_at.is_some() {
             return 0;
         }
-
-        let (cliff_duration, total_duration) = match self.vesting_type {
-            VestingType::Team | VestingType::Advisor => (
-                31_536_000, // 1 year in seconds
-                94_608_000, // 3 years in seconds
-            ),
-            VestingType::PrivateSale => (
-                15_768_000, // 6 months in seconds
-                47_304_000, // 18 months in seconds
-            ),
-            VestingType::PublicSale => (
-                7_884_000,  // 3 months in seconds
-                31_536_000, // 12 months in seconds
-            ),
-            VestingType::Ecosystem => (0, self.end_timestamp - self.start_timestamp),
+        let vested_amount = if !self.vesting_type.is_timed() {
+            self.milestone_vested_amount()
+        } else if self.tranches.is_empty() {
+            self.curve_vested_amount(current_timestamp)
+        } else {
+            self.tranche_vested_amount(current_timestamp)
         };
 
-        if current_timestamp < self.start_timestamp + cliff_duration {
+        vested_amount.saturating_sub(self.released_amount)
+    }
+
+    fn curve_vested_amount(&self, current_timestamp: UnixTimestamp) -> u64 {
+        if current_timestamp < self.start_timestamp.saturating_add(self.cliff_seconds) {
             return 0;
         }
+        if current_timestamp >= self.end_timestamp {
+            return self.total_amount;
+        }
+
+        // Tokens unlock at whole release intervals from the start
+        let elapsed = current_timestamp - self.start_timestamp;
+        let elapsed = elapsed - elapsed % self.release_interval_seconds;
+        ((self.total_amount as u128) * (elapsed as u128) / (self.duration_seconds as u128)) as u64
+    }
+
+    /// Sum of what every tranche has unlocked by `current_timestamp`
+    fn tranche_vested_amount(&self, current_timestamp: UnixTimestamp) -> u64 {
+        let mut previous_unlock = self.start_timestamp;
+        let mut vested_amount: u64 = 0;
+        for tranche in &self.tranches {
+            let unlocked = if current_timestamp >= tranche.unlock_timestamp {
+                tranche.amount
+            } else if tranche.mode == TrancheMode::Linear && current_timestamp > previous_unlock {
+                let elapsed = current_timestamp - previous_unlock;
+                let period = tranche.unlock_timestamp - previous_unlock;
+                ((tranche.amount as u128) * (elapsed as u128) / (period as u128)) as u64
+            } else {
+                0
+            };
+            vested_amount = vested_amount.saturating_add(unlocked);
+            previous_unlock = tranche.unlock_timestamp;
+        }
+        vested_amount
+    }
+
+    /// Sum of the milestones reached so far
+    fn milestone_vested_amount(&self) -> u64 {
+        self.milestones
+            .iter()
+            .filter(|milestone| milestone.report.is_some())
+            .fold(0u64, |vested_amount, milestone| vested_amount.saturating_add(milestone.amount))
+    }
+
+    /// Whether every token of the schedule has been released, or it was
+    /// revoked and the rest returned
+    pub fn is_complete(&self) -> bool {
+        self.released_amount >= self.total_amount || self.revoked_at.is_some()
+    }
+}
+
+impl VestingTranche {
+    pub const LEN: usize = 8 + 8 + 1;
+}
+
+impl VestingMilestone {
+    pub const LEN: usize = 1 + 8 + 8 + 33;
+}
 
-        let elapsed = current_timestamp.min(self.end_timestamp) - self.start_timestamp;
-        let vested_amount = (self.total_amount as u128)
-            .checked_mul(elapsed as u128)
-            .unwrap()
-            .checked_div(total_duration as u128)
-            .unwrap() as u64;
+impl AccountState for VestingSchedule {
+    const DISCRIMINATOR: [u8; 8] = *b"vestschd";
+    const VERSION: u8 = 7;
+    const SPACE: usize = HEADER_LEN + Self::LEN;
 
-        vested_amount.checked_sub(self.released_amount).unwrap_or(0)
+    fn space_for(&self) -> usize {
+        Self::space(self.tranches.len(), self.milestones.len())
     }
 }
@@ -0,0 +1,535 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        access::{
+            AccessError, AccessInstruction, AuthorityTransfer, CanonicalMint, CircuitBreaker, ProgramConfig, Role,
+            RoleGrant, Roles,
+        },
+        error::SOuLError,
+        event::SoulEvent,
+        game::{GameError, GameInstruction, GameState, PlayerState},
+        instruction::{ModuleTag, SOuLInstruction},
+        lst::{LSTError, LSTInstruction, LSTPool, LSTType},
+        processor::process_instruction,
+        state::SOuLToken,
+    },
+    solana_program::{instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey},
+    spl_token::state::Mint,
+};
+
+struct TokenSetup {
+    token_account: Pubkey,
+    mint: Pubkey,
+    authority: Pubkey,
+    holder: Pubkey,
+}
+
+fn initialize_soul(harness: &mut ProgramHarness) -> TokenSetup {
+    let program_id = harness.program_id;
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let holder = harness.add_token_account(&mint, &authority, 0);
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(&program_id, &authority, &mint, &holder, 1_000).unwrap(),
+        )
+        .unwrap();
+
+    TokenSetup {
+        token_account: SOuLToken::find_address(&mint, &program_id).0,
+        mint,
+        authority,
+        holder,
+    }
+}
+
+fn grant(harness: &mut ProgramHarness, admin: &Pubkey, member: &Pubkey, role: Role) -> Result<(), ProgramError> {
+    let program_id = harness.program_id;
+    harness.process_instruction(&AccessInstruction::grant_role(&program_id, admin, member, role).unwrap())
+}
+
+fn mint_as(program_id: &Pubkey, setup: &TokenSetup, authority: &Pubkey) -> Instruction {
+    SOuLInstruction::mint(program_id, &spl_token::id(), authority, &setup.mint, &setup.holder, 100).unwrap()
+}
+
+fn events(harness: &ProgramHarness) -> Vec<SoulEvent> {
+    harness
+        .take_logged_data()
+        .iter()
+        .filter_map(|fields| SoulEvent::from_log_data(fields))
+        .collect()
+}
+
+#[test]
+fn test_only_the_upgrade_authority_initializes_roles() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let [upgrade_authority, stranger] = [harness.add_wallet(), harness.add_wallet()];
+
+    // Without the program's ProgramData account no one can claim the admin
+    assert_eq!(
+        harness.process_instruction(&AccessInstruction::initialize_roles(&program_id, &stranger).unwrap()),
+        Err(AccessError::InvalidProgramData.into())
+    );
+
+    harness.add_program_data(&upgrade_authority);
+    assert_eq!(
+        harness.process_instruction(&AccessInstruction::initialize_roles(&program_id, &stranger).unwrap()),
+        Err(AccessError::NotAuthorized.into())
+    );
+
+    harness
+        .process_instruction(&AccessInstruction::initialize_roles(&program_id, &upgrade_authority).unwrap())
+        .unwrap();
+    let (roles, _) = Roles::find_address(&program_id);
+    assert_eq!(harness.get_state::<Roles>(&roles).admin, upgrade_authority);
+}
+
+#[test]
+fn test_admin_grants_and_revokes_roles() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let (roles, _) = Roles::find_address(&program_id);
+    let [minter, deputy, outsider] = [harness.add_wallet(), harness.add_wallet(), harness.add_wallet()];
+
+    assert_eq!(
+        harness.process_instruction(&AccessInstruction::initialize_roles(&program_id, &outsider).unwrap()),
+        Err(AccessError::AlreadyInitialized.into())
+    );
+
+    grant(&mut harness, &admin, &minter, Role::Minter).unwrap();
+    let timestamp = harness.clock().unix_timestamp;
+    assert_eq!(
+        events(&harness),
+        vec![SoulEvent::RoleGranted {
+            member: minter,
+            role: Role::Minter,
+            admin,
+            timestamp,
+        }]
+    );
+    assert_eq!(
+        grant(&mut harness, &admin, &minter, Role::Minter),
+        Err(AccessError::RoleAlreadyGranted.into())
+    );
+    assert_eq!(
+        grant(&mut harness, &minter, &outsider, Role::Minter),
+        Err(AccessError::NotAuthorized.into())
+    );
+
+    // A holder of the Admin role manages roles alongside the admin
+    grant(&mut harness, &admin, &deputy, Role::Admin).unwrap();
+    grant(&mut harness, &deputy, &outsider, Role::Guardian).unwrap();
+    let state = harness.get_state::<Roles>(&roles);
+    assert_eq!(state.admin, admin);
+    assert_eq!(
+        state.grants,
+        vec![
+            RoleGrant { member: minter, role: Role::Minter },
+            RoleGrant { member: deputy, role: Role::Admin },
+            RoleGrant { member: outsider, role: Role::Guardian },
+        ]
+    );
+    assert!(state.has_role(&admin, Role::DisputeResolver));
+    assert!(!state.has_role(&minter, Role::Guardian));
+
+    harness
+        .process_instruction(&AccessInstruction::revoke_role(&program_id, &deputy, &minter, Role::Minter).unwrap())
+        .unwrap();
+    assert!(!harness.get_state::<Roles>(&roles).has_role(&minter, Role::Minter));
+    assert_eq!(
+        harness.process_instruction(
+            &AccessInstruction::revoke_role(&program_id, &admin, &minter, Role::Minter).unwrap()
+        ),
+        Err(AccessError::RoleNotGranted.into())
+    );
+}
+
+#[test]
+fn test_role_holders_sign_privileged_instructions() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let setup = initialize_soul(&mut harness);
+    let [minter, pool_manager, game_master] = [harness.add_wallet(), harness.add_wallet(), harness.add_wallet()];
+    grant(&mut harness, &admin, &minter, Role::Minter).unwrap();
+    grant(&mut harness, &admin, &pool_manager, Role::PoolManager).unwrap();
+    grant(&mut harness, &admin, &game_master, Role::GameMaster).unwrap();
+
+    // The role only counts when the roles account is passed
+    assert_eq!(
+        harness.process_instruction(&mint_as(&program_id, &setup, &minter)),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+    harness
+        .process_instruction(&AccessInstruction::with_roles(&program_id, mint_as(&program_id, &setup, &minter)))
+        .unwrap();
+    assert_eq!(harness.token_balance(&setup.holder), 1_100);
+    assert_eq!(
+        harness.process_instruction(&AccessInstruction::with_roles(
+            &program_id,
+            mint_as(&program_id, &setup, &pool_manager)
+        )),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+    // The recorded authority still signs with or without the roles account
+    harness
+        .process_instruction(&AccessInstruction::with_roles(
+            &program_id,
+            mint_as(&program_id, &setup, &setup.authority),
+        ))
+        .unwrap();
+
+    let pool_authority = harness.add_wallet();
+    grant(&mut harness, &admin, &pool_authority, Role::PoolManager).unwrap();
+    let soul_mint = harness.add_mint(&pool_authority, 0, 9);
+    let lst_mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    harness
+        .process_instruction(
+            &LSTInstruction::initialize_pool(&program_id, &pool_authority, &soul_mint, &lst_mint, LSTType::Air, 0)
+                .unwrap(),
+        )
+        .unwrap();
+    let update_pool = |manager: &Pubkey| {
+        AccessInstruction::with_roles(
+            &program_id,
+            LSTInstruction::update_pool(&program_id, manager, LSTType::Air, Some(1_000)).unwrap(),
+        )
+    };
+    assert_eq!(
+        harness.process_instruction(&update_pool(&game_master)),
+        Err(LSTError::InvalidAuthority.into())
+    );
+    harness.process_instruction(&update_pool(&pool_manager)).unwrap();
+    let (pool, _) = LSTPool::find_address(LSTType::Air, &program_id);
+    assert_eq!(harness.get_state::<LSTPool>(&pool).fee_rate, 1_000);
+
+    let game_authority = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_game(&program_id, &game_authority).unwrap())
+        .unwrap();
+    let player = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_player(&program_id, &player).unwrap())
+        .unwrap();
+    let update_stats = |master: &Pubkey| {
+        AccessInstruction::with_roles(
+            &program_id,
+            GameInstruction::update_player_stats(&program_id, master, &player, 50, false).unwrap(),
+        )
+    };
+    assert_eq!(
+        harness.process_instruction(&update_stats(&minter)),
+        Err(GameError::InvalidPlayer.into())
+    );
+    harness.process_instruction(&update_stats(&game_master)).unwrap();
+    let (player_state, _) = PlayerState::find_address(&player, &program_id);
+    assert_eq!(harness.get_state::<PlayerState>(&player_state).experience, 50);
+
+    // Revoking the role takes the privilege away
+    harness
+        .process_instruction(&AccessInstruction::revoke_role(&program_id, &admin, &minter, Role::Minter).unwrap())
+        .unwrap();
+    assert_eq!(
+        harness.process_instruction(&AccessInstruction::with_roles(
+            &program_id,
+            mint_as(&program_id, &setup, &minter)
+        )),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+}
+
+#[test]
+fn test_authority_is_rotated_in_two_steps() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness);
+    let [first_choice, new_authority, outsider] = [harness.add_wallet(), harness.add_wallet(), harness.add_wallet()];
+    let (transfer, _) = AuthorityTransfer::find_address(&setup.token_account, &program_id);
+    let propose = |authority: &Pubkey, new_authority: &Pubkey| {
+        AccessInstruction::propose_authority(&program_id, authority, &setup.token_account, new_authority).unwrap()
+    };
+    let accept = |new_authority: &Pubkey| {
+        AccessInstruction::accept_authority(&program_id, new_authority, &setup.token_account, &setup.authority)
+            .unwrap()
+    };
+
+    assert_eq!(
+        harness.process_instruction(&propose(&outsider, &outsider)),
+        Err(AccessError::NotAuthorized.into())
+    );
+    harness.process_instruction(&propose(&setup.authority, &first_choice)).unwrap();
+
+    // Proposing again replaces the pending authority
+    harness.process_instruction(&propose(&setup.authority, &new_authority)).unwrap();
+    assert_eq!(harness.get_state::<AuthorityTransfer>(&transfer).pending_authority, new_authority);
+    assert_eq!(
+        harness.process_instruction(&accept(&first_choice)),
+        Err(AccessError::NotAuthorized.into())
+    );
+    let mut wrong_payer = accept(&new_authority);
+    wrong_payer.accounts[3].pubkey = outsider;
+    assert_eq!(harness.process_instruction(&wrong_payer), Err(AccessError::InvalidTransfer.into()));
+
+    let payer_lamports = harness.get_account(&setup.authority).unwrap().lamports;
+    let rent = harness.get_account(&transfer).unwrap().lamports;
+    harness.process_instruction(&accept(&new_authority)).unwrap();
+    let timestamp = harness.clock().unix_timestamp;
+    assert_eq!(
+        events(&harness),
+        vec![SoulEvent::AuthorityTransferred {
+            account: setup.token_account,
+            previous_authority: setup.authority,
+            new_authority,
+            timestamp,
+        }]
+    );
+    assert_eq!(harness.get_state::<SOuLToken>(&setup.token_account).mint_authority, new_authority);
+
+    // The transfer account is closed and its rent refunded
+    let closed = harness.get_account(&transfer).unwrap();
+    assert_eq!(closed.lamports, 0);
+    assert!(closed.data.is_empty());
+    assert_eq!(harness.get_account(&setup.authority).unwrap().lamports, payer_lamports + rent);
+    assert_eq!(
+        harness.process_instruction(&accept(&new_authority)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    assert_eq!(
+        harness.process_instruction(&mint_as(&program_id, &setup, &setup.authority)),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+    harness.process_instruction(&mint_as(&program_id, &setup, &new_authority)).unwrap();
+    assert_eq!(harness.token_balance(&setup.holder), 1_100);
+}
+
+#[test]
+fn test_admin_rotates_roles_and_stranded_authorities() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let (roles, _) = Roles::find_address(&program_id);
+    let new_admin = harness.add_wallet();
+
+    harness
+        .process_instruction(&AccessInstruction::propose_authority(&program_id, &admin, &roles, &new_admin).unwrap())
+        .unwrap();
+    harness
+        .process_instruction(&AccessInstruction::accept_authority(&program_id, &new_admin, &roles, &admin).unwrap())
+        .unwrap();
+    assert_eq!(harness.get_state::<Roles>(&roles).admin, new_admin);
+    let deputy = harness.add_wallet();
+    assert_eq!(
+        grant(&mut harness, &admin, &deputy, Role::Admin),
+        Err(AccessError::NotAuthorized.into())
+    );
+    grant(&mut harness, &new_admin, &deputy, Role::Admin).unwrap();
+
+    // An Admin can hand over an authority whose key was lost
+    let game_authority = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_game(&program_id, &game_authority).unwrap())
+        .unwrap();
+    let (game_state, _) = GameState::find_address(&program_id);
+    let replacement = harness.add_wallet();
+    let propose = AccessInstruction::propose_authority(&program_id, &deputy, &game_state, &replacement).unwrap();
+    assert_eq!(harness.process_instruction(&propose), Err(AccessError::NotAuthorized.into()));
+    harness
+        .process_instruction(&AccessInstruction::with_roles(&program_id, propose))
+        .unwrap();
+    harness
+        .process_instruction(
+            &AccessInstruction::accept_authority(&program_id, &replacement, &game_state, &deputy).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.get_state::<GameState>(&game_state).authority, replacement);
+
+    // Only accounts that record an authority can change hands
+    let player = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_player(&program_id, &player).unwrap())
+        .unwrap();
+    let (player_state, _) = PlayerState::find_address(&player, &program_id);
+    assert_eq!(
+        harness.process_instruction(
+            &AccessInstruction::propose_authority(&program_id, &player, &player_state, &replacement).unwrap()
+        ),
+        Err(AccessError::UnsupportedAccount.into())
+    );
+}
+
+#[test]
+fn test_guardian_pauses_and_resumes_modules() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let setup = initialize_soul(&mut harness);
+    let [guardian, outsider] = [harness.add_wallet(), harness.add_wallet()];
+    grant(&mut harness, &admin, &guardian, Role::Guardian).unwrap();
+    let set_paused = |signer: &Pubkey, module: ModuleTag, paused: bool| {
+        AccessInstruction::set_paused(&program_id, signer, module, paused).unwrap()
+    };
+
+    assert_eq!(
+        harness.process_instruction(&set_paused(&outsider, ModuleTag::Token, true)),
+        Err(AccessError::NotAuthorized.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&set_paused(&guardian, ModuleTag::Access, true)),
+        Err(AccessError::InvalidModule.into())
+    );
+
+    harness.process_instruction(&set_paused(&guardian, ModuleTag::Token, true)).unwrap();
+    let timestamp = harness.clock().unix_timestamp;
+    assert_eq!(
+        events(&harness),
+        vec![SoulEvent::PauseSet {
+            module: ModuleTag::Token,
+            paused: true,
+            guardian,
+            timestamp,
+        }]
+    );
+    let (config, _) = ProgramConfig::find_address(&program_id);
+    assert!(harness.get_state::<ProgramConfig>(&config).is_paused(ModuleTag::Token));
+    assert_eq!(
+        harness.process_instruction(&mint_as(&program_id, &setup, &setup.authority)),
+        Err(AccessError::ModulePaused.into())
+    );
+
+    // Compliance holds stay available while Token is paused
+    let officer = harness.add_wallet();
+    grant(&mut harness, &admin, &officer, Role::Compliance).unwrap();
+    let frozen = |harness: &ProgramHarness| {
+        spl_token::state::Account::unpack(&harness.get_account(&setup.holder).unwrap().data)
+            .unwrap()
+            .is_frozen()
+    };
+    harness
+        .process_instruction(
+            &SOuLInstruction::freeze(&program_id, &spl_token::id(), &officer, &setup.mint, &setup.holder, [1; 32], None)
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(frozen(&harness));
+    harness
+        .process_instruction(
+            &SOuLInstruction::thaw(&program_id, &spl_token::id(), &officer, &setup.mint, &setup.holder, [2; 32], None)
+                .unwrap(),
+        )
+        .unwrap();
+    assert!(!frozen(&harness));
+
+    // Other modules keep running, and a paused LST reports the pool error
+    harness
+        .process_instruction(&GameInstruction::initialize_game(&program_id, &admin).unwrap())
+        .unwrap();
+    harness.process_instruction(&set_paused(&guardian, ModuleTag::LST, true)).unwrap();
+    assert_eq!(
+        harness.process_instruction(&LSTInstruction::update_pool(&program_id, &admin, LSTType::Air, None).unwrap()),
+        Err(LSTError::PoolPaused.into())
+    );
+
+    // The config must be the program config PDA
+    let mut spoofed = mint_as(&program_id, &setup, &setup.authority);
+    spoofed.accounts.last_mut().unwrap().pubkey = harness.add_wallet();
+    assert_eq!(harness.process_instruction(&spoofed), Err(AccessError::InvalidConfig.into()));
+
+    harness.process_instruction(&set_paused(&guardian, ModuleTag::Token, false)).unwrap();
+    harness
+        .process_instruction(&mint_as(&program_id, &setup, &setup.authority))
+        .unwrap();
+    assert_eq!(harness.token_balance(&setup.holder), 1_100);
+    assert!(harness.get_state::<ProgramConfig>(&config).is_paused(ModuleTag::LST));
+}
+
+#[test]
+fn test_circuit_breaker_caps_outflow_per_slot_window() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let setup = initialize_soul(&mut harness);
+    let outsider = harness.add_wallet();
+    let configure = |signer: &Pubkey, window_slots: u64| {
+        AccessInstruction::configure_circuit_breaker(&program_id, signer, ModuleTag::Token, window_slots, 150)
+            .unwrap()
+    };
+
+    // Without a breaker a module has no limit
+    harness
+        .process_instruction(&mint_as(&program_id, &setup, &setup.authority))
+        .unwrap();
+
+    assert_eq!(
+        harness.process_instruction(&configure(&outsider, 100)),
+        Err(AccessError::NotAuthorized.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&configure(&admin, 0)),
+        Err(AccessError::InvalidCircuitBreaker.into())
+    );
+    harness.process_instruction(&configure(&admin, 100)).unwrap();
+
+    harness
+        .process_instruction(&mint_as(&program_id, &setup, &setup.authority))
+        .unwrap();
+    assert_eq!(
+        harness.process_instruction(&mint_as(&program_id, &setup, &setup.authority)),
+        Err(AccessError::OutflowLimitExceeded.into())
+    );
+    assert_eq!(harness.token_balance(&setup.holder), 1_200);
+
+    // A new window starts once `window_slots` have passed
+    let (breaker, _) = CircuitBreaker::find_address(ModuleTag::Token, &program_id);
+    let window_start = harness.get_state::<CircuitBreaker>(&breaker).window_start;
+    harness.warp_to_slot(window_start + 100);
+    harness
+        .process_instruction(&mint_as(&program_id, &setup, &setup.authority))
+        .unwrap();
+    let state = harness.get_state::<CircuitBreaker>(&breaker);
+    assert_eq!((state.window_start, state.outflow), (window_start + 100, 100));
+
+    // Another module's breaker does not stand in for this one
+    let mut spoofed = mint_as(&program_id, &setup, &setup.authority);
+    let index = spoofed.accounts.len() - 2;
+    spoofed.accounts[index].pubkey = CircuitBreaker::find_address(ModuleTag::LST, &program_id).0;
+    assert_eq!(
+        harness.process_instruction(&spoofed),
+        Err(AccessError::InvalidCircuitBreaker.into())
+    );
+}
+
+#[test]
+fn test_admin_pins_the_canonical_soul_mint_once() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let setup = initialize_soul(&mut harness);
+    let other = initialize_soul(&mut harness);
+    let outsider = harness.add_wallet();
+    let pin = |signer: &Pubkey, mint: &Pubkey| AccessInstruction::pin_soul_mint(&program_id, signer, mint).unwrap();
+
+    assert_eq!(
+        harness.process_instruction(&pin(&outsider, &setup.mint)),
+        Err(AccessError::NotAuthorized.into())
+    );
+
+    // A mint without a SOuL token config cannot be pinned
+    let bare_mint = harness.add_mint(&admin, 1_000, 9);
+    assert!(harness.process_instruction(&pin(&admin, &bare_mint)).is_err());
+
+    harness.process_instruction(&pin(&admin, &setup.mint)).unwrap();
+    let (canonical_mint, bump) = CanonicalMint::find_address(&program_id);
+    let pinned = harness.get_state::<CanonicalMint>(&canonical_mint);
+    assert_eq!((pinned.mint, pinned.bump), (setup.mint, bump));
+
+    assert_eq!(
+        harness.process_instruction(&pin(&admin, &other.mint)),
+        Err(AccessError::SoulMintAlreadyPinned.into())
+    );
+    assert_eq!(harness.get_state::<CanonicalMint>(&canonical_mint).mint, setup.mint);
+}
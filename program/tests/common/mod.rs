@@ -0,0 +1,639 @@
+//! In-process test harness for the SOuL Sanctuary program.
+//!
+//! Accounts are serialized into the same input buffer layout the runtime
+//! hands to an on-chain program, so reallocation and owner changes behave
+//! as they do on-chain. Cross-program invocations into the SPL Token and
+//! Token-2022 programs, the System program and the program itself are
+//! executed in place through the syscall stubs.
+
+#![allow(dead_code)]
+
+use std::{cell::RefCell, collections::HashMap, mem::size_of, sync::Once};
+
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    entrypoint::{self, ProgramResult, MAX_PERMITTED_DATA_INCREASE, NON_DUP_MARKER},
+    instruction::Instruction,
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    program_stubs::{set_syscall_stubs, SyscallStubs},
+    pubkey::Pubkey,
+    rent::Rent,
+    system_program,
+    sysvar::{self, Sysvar},
+};
+
+use soul_sanctuary::{access::AccessInstruction, account::AccountState};
+use spl_token_2022::{
+    extension::{
+        transfer_fee::TransferFeeAmount, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions, StateWithExtensionsMut,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+
+pub type ProcessInstruction = fn(&Pubkey, &[AccountInfo], &[u8]) -> ProgramResult;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Account {
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub owner: Pubkey,
+    pub executable: bool,
+}
+
+impl Account {
+    pub fn new(lamports: u64, space: usize, owner: &Pubkey) -> Self {
+        Self {
+            lamports,
+            data: vec![0; space],
+            owner: *owner,
+            executable: false,
+        }
+    }
+}
+
+thread_local! {
+    static CLOCK: RefCell<Clock> = RefCell::new(Clock::default());
+    static PROGRAMS: RefCell<Vec<(Pubkey, ProcessInstruction)>> = const { RefCell::new(Vec::new()) };
+    static CALL_STACK: RefCell<Vec<Pubkey>> = const { RefCell::new(Vec::new()) };
+    static LOGS: RefCell<Vec<Vec<Vec<u8>>>> = const { RefCell::new(Vec::new()) };
+}
+
+struct HarnessStubs;
+
+impl SyscallStubs for HarnessStubs {
+    fn sol_log(&self, _message: &str) {}
+
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        LOGS.with(|logs| {
+            logs.borrow_mut()
+                .push(fields.iter().map(|field| field.to_vec()).collect())
+        });
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = CLOCK.with(|clock| clock.borrow().clone());
+        unsafe { *(var_addr as *mut Clock) = clock };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = CALL_STACK.with(|stack| *stack.borrow().last().expect("no active program"));
+        let pda_signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let mut callee_infos = Vec::with_capacity(instruction.accounts.len());
+        for meta in &instruction.accounts {
+            let info = account_infos
+                .iter()
+                .find(|info| *info.key == meta.pubkey)
+                .ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if meta.is_signer && !info.is_signer && !pda_signers.contains(info.key) {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if meta.is_writable && !info.is_writable {
+                return Err(ProgramError::InvalidArgument);
+            }
+            let mut callee_info = info.clone();
+            callee_info.is_signer = meta.is_signer;
+            callee_info.is_writable = meta.is_writable;
+            callee_infos.push(callee_info);
+        }
+
+        if instruction.program_id == spl_token::id() {
+            return spl_token::processor::Processor::process(
+                &instruction.program_id,
+                &callee_infos,
+                &instruction.data,
+            );
+        }
+        if instruction.program_id == spl_token_2022::id() {
+            return spl_token_2022::processor::Processor::process(
+                &instruction.program_id,
+                &callee_infos,
+                &instruction.data,
+            );
+        }
+        if instruction.program_id == system_program::id() {
+            return process_system_instruction(&callee_infos, &instruction.data);
+        }
+
+        let entry = PROGRAMS.with(|programs| {
+            programs
+                .borrow()
+                .iter()
+                .find(|(program_id, _)| *program_id == instruction.program_id)
+                .map(|(_, entry)| *entry)
+        });
+        match entry {
+            Some(entry) => invoke_program(&instruction.program_id, entry, &callee_infos, &instruction.data),
+            None => Err(ProgramError::IncorrectProgramId),
+        }
+    }
+}
+
+fn invoke_program(
+    program_id: &Pubkey,
+    entry: ProcessInstruction,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    CALL_STACK.with(|stack| stack.borrow_mut().push(*program_id));
+    let result = entry(program_id, accounts, data);
+    CALL_STACK.with(|stack| stack.borrow_mut().pop());
+    result
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, ProgramError> {
+    data.get(offset..offset + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// The subset of the System program the SOuL program relies on
+fn process_system_instruction(accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let tag = data
+        .get(..4)
+        .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+    match tag {
+        // CreateAccount { lamports, space, owner }
+        0 => {
+            let lamports = read_u64(data, 4)?;
+            let space = read_u64(data, 12)? as usize;
+            let owner = Pubkey::try_from(&data[20..52]).map_err(|_| ProgramError::InvalidInstructionData)?;
+            let (from, to) = (&accounts[0], &accounts[1]);
+            if !from.is_signer || !to.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            if to.lamports() != 0 || to.data_len() != 0 || *to.owner != system_program::id() {
+                return Err(ProgramError::AccountAlreadyInitialized);
+            }
+            transfer_lamports(from, to, lamports)?;
+            to.realloc(space, true)?;
+            to.assign(&owner);
+            Ok(())
+        }
+        // Assign { owner }
+        1 => {
+            let owner = Pubkey::try_from(&data[4..36]).map_err(|_| ProgramError::InvalidInstructionData)?;
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            accounts[0].assign(&owner);
+            Ok(())
+        }
+        // Transfer { lamports }
+        2 => {
+            let lamports = read_u64(data, 4)?;
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            transfer_lamports(&accounts[0], &accounts[1], lamports)
+        }
+        // Allocate { space }
+        8 => {
+            let space = read_u64(data, 4)? as usize;
+            if !accounts[0].is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            accounts[0].realloc(space, true)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if *from.owner != system_program::id() || from.lamports() < lamports {
+        return Err(ProgramError::InsufficientFunds);
+    }
+    **from.try_borrow_mut_lamports()? -= lamports;
+    **to.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+/// Where each account's fields live inside the serialized input buffer
+struct AccountLayout {
+    key: Pubkey,
+    owner: usize,
+    lamports: usize,
+    data_len: usize,
+}
+
+pub struct ProgramHarness {
+    pub program_id: Pubkey,
+    pub payer: Pubkey,
+    process_instruction: ProcessInstruction,
+    accounts: HashMap<Pubkey, Account>,
+    clock: Clock,
+}
+
+impl ProgramHarness {
+    pub fn new(program_id: Pubkey, process_instruction: ProcessInstruction) -> Self {
+        static INSTALL_STUBS: Once = Once::new();
+        INSTALL_STUBS.call_once(|| {
+            set_syscall_stubs(Box::new(HarnessStubs));
+        });
+        PROGRAMS.with(|programs| programs.borrow_mut().push((program_id, process_instruction)));
+
+        let mut harness = Self {
+            program_id,
+            payer: Pubkey::new_unique(),
+            process_instruction,
+            accounts: HashMap::new(),
+            clock: Clock {
+                unix_timestamp: 1_700_000_000,
+                ..Clock::default()
+            },
+        };
+        harness.add_account(harness.payer, Account::new(1_000_000_000_000, 0, &system_program::id()));
+        for program in [program_id, spl_token::id(), spl_token_2022::id(), system_program::id()] {
+            harness.add_account(
+                program,
+                Account {
+                    lamports: 1,
+                    executable: true,
+                    owner: solana_program::bpf_loader::id(),
+                    ..Account::default()
+                },
+            );
+        }
+        harness
+    }
+
+    pub fn add_account(&mut self, key: Pubkey, account: Account) {
+        self.accounts.insert(key, account);
+    }
+
+    pub fn get_account(&self, key: &Pubkey) -> Option<&Account> {
+        self.accounts.get(key)
+    }
+
+    /// Decode a program account, checking its discriminator and version
+    pub fn get_state<T: AccountState>(&self, key: &Pubkey) -> T {
+        T::unpack_account(&self.get_account(key).expect("missing account").data).unwrap()
+    }
+
+    /// Overwrite the body of a program account, keeping its size
+    pub fn set_state<T: AccountState>(&mut self, key: &Pubkey, state: &T) {
+        let account = self.accounts.get_mut(key).expect("missing account");
+        account.data[..8].copy_from_slice(&T::DISCRIMINATOR);
+        account.data[8] = T::VERSION;
+        state.serialize(&mut &mut account.data[9..]).unwrap();
+    }
+
+    pub fn rent_exempt(space: usize) -> u64 {
+        Rent::default().minimum_balance(space)
+    }
+
+    /// Add a rent-exempt account of `space` zeroed bytes owned by `owner`
+    pub fn add_funded_account(&mut self, owner: &Pubkey, space: usize) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.add_account(key, Account::new(Self::rent_exempt(space), space, owner));
+        key
+    }
+
+    /// Add a funded system account to act as a transaction signer and rent payer
+    pub fn add_wallet(&mut self) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.add_account(key, Account::new(10_000_000_000, 0, &system_program::id()));
+        key
+    }
+
+    /// Add the ProgramData account the upgradeable loader keeps for the
+    /// program, recording `upgrade_authority`
+    pub fn add_program_data(&mut self, upgrade_authority: &Pubkey) {
+        let mut data = vec![0; UpgradeableLoaderState::size_of_programdata_metadata()];
+        data[..4].copy_from_slice(&3u32.to_le_bytes());
+        data[12] = 1;
+        data[13..45].copy_from_slice(upgrade_authority.as_ref());
+        self.add_account(
+            Pubkey::find_program_address(&[self.program_id.as_ref()], &bpf_loader_upgradeable::id()).0,
+            Account {
+                lamports: Self::rent_exempt(data.len()),
+                data,
+                owner: bpf_loader_upgradeable::id(),
+                executable: false,
+            },
+        );
+    }
+
+    /// Create the roles account as the program's upgrade authority, who
+    /// becomes the admin
+    pub fn initialize_roles(&mut self) -> Pubkey {
+        let admin = self.add_wallet();
+        self.add_program_data(&admin);
+        self.process_instruction(&AccessInstruction::initialize_roles(&self.program_id, &admin).unwrap())
+            .unwrap();
+        admin
+    }
+
+    pub fn add_mint(&mut self, mint_authority: &Pubkey, supply: u64, decimals: u8) -> Pubkey {
+        let mut data = vec![0; spl_token::state::Mint::LEN];
+        spl_token::state::Mint::pack(
+            spl_token::state::Mint {
+                mint_authority: COption::Some(*mint_authority),
+                supply,
+                decimals,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut data,
+        )
+        .unwrap();
+        let key = Pubkey::new_unique();
+        self.add_account(
+            key,
+            Account {
+                lamports: Self::rent_exempt(data.len()),
+                data,
+                owner: spl_token::id(),
+                executable: false,
+            },
+        );
+        key
+    }
+
+    pub fn add_token_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let key = Pubkey::new_unique();
+        self.set_token_account(key, mint, owner, amount);
+        key
+    }
+
+    pub fn set_token_account(&mut self, key: Pubkey, mint: &Pubkey, owner: &Pubkey, amount: u64) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account::pack(
+            spl_token::state::Account {
+                mint: *mint,
+                owner: *owner,
+                amount,
+                state: spl_token::state::AccountState::Initialized,
+                ..spl_token::state::Account::default()
+            },
+            &mut data,
+        )
+        .unwrap();
+        self.add_account(
+            key,
+            Account {
+                lamports: Self::rent_exempt(data.len()),
+                data,
+                owner: spl_token::id(),
+                executable: false,
+            },
+        );
+    }
+
+    /// Add a Token-2022 account with room for the fees withheld by a
+    /// transfer fee mint
+    pub fn add_token_2022_account(&mut self, mint: &Pubkey, owner: &Pubkey, amount: u64) -> Pubkey {
+        let space =
+            ExtensionType::try_calculate_account_len::<TokenAccount>(&[ExtensionType::TransferFeeAmount]).unwrap();
+        let mut data = vec![0; space];
+        let mut state = StateWithExtensionsMut::<TokenAccount>::unpack_uninitialized(&mut data).unwrap();
+        state.init_extension::<TransferFeeAmount>(true).unwrap();
+        state.base = TokenAccount {
+            mint: *mint,
+            owner: *owner,
+            amount,
+            state: spl_token_2022::state::AccountState::Initialized,
+            ..TokenAccount::default()
+        };
+        state.pack_base();
+        state.init_account_type().unwrap();
+
+        let key = Pubkey::new_unique();
+        self.add_account(
+            key,
+            Account {
+                lamports: Self::rent_exempt(space),
+                data,
+                owner: spl_token_2022::id(),
+                executable: false,
+            },
+        );
+        key
+    }
+
+    pub fn token_balance(&self, key: &Pubkey) -> u64 {
+        StateWithExtensions::<TokenAccount>::unpack(&self.accounts[key].data)
+            .unwrap()
+            .base
+            .amount
+    }
+
+    /// Transfer fees withheld in a Token-2022 account
+    pub fn withheld_fees(&self, key: &Pubkey) -> u64 {
+        StateWithExtensions::<TokenAccount>::unpack(&self.accounts[key].data)
+            .unwrap()
+            .get_extension::<TransferFeeAmount>()
+            .unwrap()
+            .withheld_amount
+            .into()
+    }
+
+    pub fn mint_supply(&self, key: &Pubkey) -> u64 {
+        StateWithExtensions::<Mint>::unpack(&self.accounts[key].data)
+            .unwrap()
+            .base
+            .supply
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    pub fn warp_to_timestamp(&mut self, unix_timestamp: i64) {
+        self.clock.unix_timestamp = unix_timestamp;
+    }
+
+    pub fn warp_to_slot(&mut self, slot: u64) {
+        self.clock.slot = slot;
+    }
+
+    pub fn warp_to_epoch(&mut self, epoch: u64) {
+        self.clock.epoch = epoch;
+    }
+
+    /// Borsh-encoded payloads passed to `sol_log_data` by the last transaction
+    pub fn take_logged_data(&self) -> Vec<Vec<Vec<u8>>> {
+        LOGS.with(|logs| std::mem::take(&mut *logs.borrow_mut()))
+    }
+
+    pub fn process_instruction(&mut self, instruction: &Instruction) -> ProgramResult {
+        self.process_transaction(std::slice::from_ref(instruction))
+    }
+
+    /// Run instructions in order, keeping their changes only if all succeed
+    pub fn process_transaction(&mut self, instructions: &[Instruction]) -> ProgramResult {
+        let snapshot = self.accounts.clone();
+        LOGS.with(|logs| logs.borrow_mut().clear());
+        CLOCK.with(|clock| *clock.borrow_mut() = self.clock.clone());
+
+        for instruction in instructions {
+            if let Err(error) = self.execute(instruction) {
+                self.accounts = snapshot;
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    fn sysvar_account(&self, key: &Pubkey) -> Option<Account> {
+        let data = if *key == sysvar::clock::id() {
+            let mut data = vec![0; Clock::size_of()];
+            self.clock.to_account_info(&mut AccountInfo::new(
+                key,
+                false,
+                true,
+                &mut 0,
+                &mut data,
+                &sysvar::id(),
+                false,
+                0,
+            ))?;
+            data
+        } else if *key == sysvar::rent::id() {
+            let mut data = vec![0; Rent::size_of()];
+            Rent::default().to_account_info(&mut AccountInfo::new(
+                key,
+                false,
+                true,
+                &mut 0,
+                &mut data,
+                &sysvar::id(),
+                false,
+                0,
+            ))?;
+            data
+        } else {
+            return None;
+        };
+        Some(Account {
+            lamports: 1,
+            data,
+            owner: sysvar::id(),
+            executable: false,
+        })
+    }
+
+    fn execute(&mut self, instruction: &Instruction) -> ProgramResult {
+        let (mut buffer, layouts) = self.serialize(instruction);
+
+        let result = {
+            let (program_id, account_infos, data) =
+                unsafe { entrypoint::deserialize(buffer.as_mut_ptr() as *mut u8) };
+            invoke_program(program_id, self.process_instruction, &account_infos, data)
+        };
+        result?;
+
+        let bytes = unsafe {
+            std::slice::from_raw_parts(buffer.as_ptr() as *const u8, buffer.len() * size_of::<u64>())
+        };
+        for layout in layouts {
+            if self.sysvar_account(&layout.key).is_some() {
+                continue;
+            }
+            let data_len = read_u64(bytes, layout.data_len)? as usize;
+            let data_start = layout.data_len + size_of::<u64>();
+            let account = self.accounts.entry(layout.key).or_default();
+            account.owner = Pubkey::try_from(&bytes[layout.owner..layout.owner + 32]).unwrap();
+            account.lamports = read_u64(bytes, layout.lamports)?;
+            account.data = bytes[data_start..data_start + data_len].to_vec();
+        }
+        Ok(())
+    }
+
+    /// Lay out accounts and instruction data the way the loader does
+    fn serialize(&self, instruction: &Instruction) -> (Vec<u64>, Vec<AccountLayout>) {
+        let mut bytes = Vec::new();
+        let mut layouts = Vec::new();
+        let mut seen: Vec<Pubkey> = Vec::new();
+
+        bytes.extend_from_slice(&(instruction.accounts.len() as u64).to_le_bytes());
+        for meta in &instruction.accounts {
+            if let Some(index) = seen.iter().position(|key| *key == meta.pubkey) {
+                bytes.push(index as u8);
+                bytes.extend_from_slice(&[0; 7]);
+                seen.push(meta.pubkey);
+                continue;
+            }
+            seen.push(meta.pubkey);
+
+            let account = self
+                .accounts
+                .get(&meta.pubkey)
+                .cloned()
+                .or_else(|| self.sysvar_account(&meta.pubkey))
+                .unwrap_or_else(|| Account::new(0, 0, &system_program::id()));
+            let is_signer = instruction
+                .accounts
+                .iter()
+                .any(|other| other.pubkey == meta.pubkey && other.is_signer);
+            let is_writable = instruction
+                .accounts
+                .iter()
+                .any(|other| other.pubkey == meta.pubkey && other.is_writable);
+
+            bytes.push(NON_DUP_MARKER);
+            bytes.push(is_signer as u8);
+            bytes.push(is_writable as u8);
+            bytes.push(account.executable as u8);
+            bytes.extend_from_slice(&[0; 4]);
+            bytes.extend_from_slice(meta.pubkey.as_ref());
+            let owner = bytes.len();
+            bytes.extend_from_slice(account.owner.as_ref());
+            let lamports = bytes.len();
+            bytes.extend_from_slice(&account.lamports.to_le_bytes());
+            let data_len = bytes.len();
+            bytes.extend_from_slice(&(account.data.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&account.data);
+            bytes.resize(bytes.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+            bytes.resize(bytes.len().next_multiple_of(8), 0);
+            bytes.extend_from_slice(&0u64.to_le_bytes());
+
+            layouts.push(AccountLayout {
+                key: meta.pubkey,
+                owner,
+                lamports,
+                data_len,
+            });
+        }
+        bytes.extend_from_slice(&(instruction.data.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&instruction.data);
+        bytes.extend_from_slice(instruction.program_id.as_ref());
+
+        let mut buffer = vec![0u64; bytes.len().div_ceil(8)];
+        unsafe {
+            std::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.as_mut_ptr() as *mut u8, bytes.len());
+        }
+        (buffer, layouts)
+    }
+}
+
+/// Swap the SPL Token program of `instruction` for `fake`, as a caller
+/// trying to borrow a PDA's signature would
+pub fn with_token_program(mut instruction: Instruction, fake: &Pubkey) -> Instruction {
+    for meta in instruction.accounts.iter_mut().filter(|meta| meta.pubkey == spl_token::id()) {
+        meta.pubkey = *fake;
+    }
+    instruction
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use {
+    common::{Account, ProgramHarness},
+    soul_sanctuary::{
+        access::ProgramConfig,
+        error::SOuLError,
+        game::{GameInstruction, GameState},
+        instruction::{ModuleTag, SOuLInstruction},
+        processor::process_instruction,
+        vesting::{VestingInstruction, VestingSchedule, VestingType},
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[test]
+fn test_routes_game_instruction() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    let authority = harness.add_wallet();
+    let (game_state, _) = GameState::find_address(&program_id);
+
+    harness
+        .process_instruction(&Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(game_state, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(&program_id),
+            ],
+            data: ModuleTag::Game.pack(&GameInstruction::InitializeGame).unwrap(),
+        })
+        .unwrap();
+
+    let state = harness.get_state::<GameState>(&game_state);
+    assert!(state.is_initialized);
+    assert_eq!(state.authority, authority);
+}
+
+#[test]
+fn test_routes_vesting_instruction() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    let authority = harness.add_wallet();
+    let beneficiary = Pubkey::new_unique();
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let mint = harness.add_mint(&authority, 1_000, 9);
+    let source = harness.add_token_account(&mint, &authority, 1_000);
+
+    harness
+        .process_instruction(&Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(vesting_account, false),
+                AccountMeta::new_readonly(beneficiary, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(source, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(&program_id),
+            ],
+            data: ModuleTag::Vesting
+                .pack(&VestingInstruction::Initialize {
+                    nonce: 0,
+                    start_timestamp: 0,
+                    total_amount: 1_000,
+                    vesting_type: VestingType::Ecosystem,
+                    cliff_seconds: None,
+                    duration_seconds: Some(100),
+                    release_interval_seconds: None,
+                    revoke_authority: None,
+                })
+                .unwrap(),
+        })
+        .unwrap();
+
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.beneficiary, beneficiary);
+    assert_eq!(schedule.total_amount, 1_000);
+}
+
+#[test]
+fn test_module_payload_is_not_decoded_by_another_module() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    let initializer = harness.add_wallet();
+    let token_account = Pubkey::new_unique();
+    harness.add_account(token_account, Account::new(5000000, 64, &program_id));
+
+    // A token payload sent under the vesting tag must not be accepted
+    let mut data = ModuleTag::Token
+        .pack(&SOuLInstruction::InitializeSOuL { total_supply: 1 })
+        .unwrap();
+    data[0] = ModuleTag::Vesting as u8;
+
+    let result = harness.process_instruction(&Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(initializer, true),
+            AccountMeta::new(token_account, false),
+        ],
+        data,
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_rejects_unknown_module_tag() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    for data in [vec![], vec![42, 0, 0]] {
+        let result = harness.process_instruction(&Instruction {
+            program_id,
+            accounts: vec![],
+            data: data.clone(),
+        });
+        let expected = if data.is_empty() {
+            ProgramError::InvalidInstructionData
+        } else {
+            SOuLError::InvalidInstruction.into()
+        };
+        assert_eq!(result, Err(expected));
+    }
+}
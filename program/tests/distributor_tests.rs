@@ -0,0 +1,328 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        access::{AccessError, AccessInstruction},
+        distributor::{merkle, Distributor, DistributorError, DistributorInstruction},
+        error::SOuLError,
+        event::SoulEvent,
+        instruction::ModuleTag,
+        processor::process_instruction,
+    },
+    solana_program::{entrypoint::MAX_PERMITTED_DATA_INCREASE, instruction::Instruction, pubkey::Pubkey},
+};
+
+const DAY: i64 = 24 * 60 * 60;
+
+struct Airdrop {
+    admin: Pubkey,
+    mint: Pubkey,
+    distributor: Pubkey,
+    vault: Pubkey,
+    wallets: [Pubkey; 3],
+    amounts: [u64; 3],
+    proofs: [Vec<[u8; 32]>; 3],
+    expiry: i64,
+}
+
+/// Publish a three-leaf airdrop of 600 tokens funded with `max_total_claim`
+fn new_airdrop(harness: &mut ProgramHarness, max_total_claim: u64) -> Airdrop {
+    let program_id = harness.program_id;
+    let admin = harness.add_wallet();
+    let mint = harness.add_mint(&admin, 0, 6);
+    let source = harness.add_token_account(&mint, &admin, 1_000);
+    let wallets = [harness.add_wallet(), harness.add_wallet(), harness.add_wallet()];
+    let amounts = [100, 200, 300];
+
+    let leaves: Vec<_> = (0..3)
+        .map(|index| merkle::leaf_hash(index as u64, &wallets[index], amounts[index]))
+        .collect();
+    let pair = merkle::node_hash(&leaves[0], &leaves[1]);
+    let root = merkle::node_hash(&pair, &leaves[2]);
+    let proofs = [vec![leaves[1], leaves[2]], vec![leaves[0], leaves[2]], vec![pair]];
+
+    let expiry = harness.clock().unix_timestamp + 30 * DAY;
+    harness
+        .process_instruction(
+            &DistributorInstruction::new_distributor(
+                &program_id,
+                &spl_token::id(),
+                &admin,
+                &mint,
+                &source,
+                0,
+                root,
+                max_total_claim,
+                3,
+                expiry,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let (distributor, _) = Distributor::find_address(&admin, 0, &program_id);
+    let (vault, _) = Distributor::find_vault(&distributor, &program_id);
+    Airdrop {
+        admin,
+        mint,
+        distributor,
+        vault,
+        wallets,
+        amounts,
+        proofs,
+        expiry,
+    }
+}
+
+fn claim(
+    program_id: &Pubkey,
+    airdrop: &Airdrop,
+    claimant: &Pubkey,
+    destination: &Pubkey,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    DistributorInstruction::claim(
+        program_id,
+        &spl_token::id(),
+        claimant,
+        &airdrop.distributor,
+        &airdrop.mint,
+        destination,
+        index,
+        amount,
+        proof,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_wallets_claim_once_with_a_proof() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let airdrop = new_airdrop(&mut harness, 600);
+    assert_eq!(harness.token_balance(&airdrop.vault), 600);
+    let state = harness.get_state::<Distributor>(&airdrop.distributor);
+    assert_eq!(state.claimed, vec![0]);
+    assert_eq!(harness.get_account(&airdrop.distributor).unwrap().data.len(), Distributor::space(3));
+
+    let [alice, bob, _] = airdrop.wallets;
+    let alice_tokens = harness.add_token_account(&airdrop.mint, &alice, 0);
+    let bob_tokens = harness.add_token_account(&airdrop.mint, &bob, 0);
+
+    harness
+        .process_instruction(&claim(&program_id, &airdrop, &alice, &alice_tokens, 0, 100, airdrop.proofs[0].clone()))
+        .unwrap();
+    assert_eq!(harness.token_balance(&alice_tokens), 100);
+    assert_eq!(
+        harness
+            .take_logged_data()
+            .iter()
+            .filter_map(|fields| SoulEvent::from_log_data(fields))
+            .collect::<Vec<_>>(),
+        vec![SoulEvent::AirdropClaimed {
+            distributor: airdrop.distributor,
+            claimant: alice,
+            index: 0,
+            amount: 100,
+            timestamp: harness.clock().unix_timestamp,
+        }]
+    );
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &alice, &alice_tokens, 0, 100, airdrop.proofs[0].clone())),
+        Err(DistributorError::AlreadyClaimed.into())
+    );
+
+    // The leaf commits to the wallet and the amount
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &alice, &alice_tokens, 1, 200, airdrop.proofs[1].clone())),
+        Err(DistributorError::InvalidProof.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &bob, &bob_tokens, 1, 300, airdrop.proofs[1].clone())),
+        Err(DistributorError::InvalidProof.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &bob, &bob_tokens, 3, 200, airdrop.proofs[1].clone())),
+        Err(DistributorError::InvalidIndex.into())
+    );
+    harness
+        .process_instruction(&claim(&program_id, &airdrop, &bob, &bob_tokens, 1, airdrop.amounts[1], airdrop.proofs[1].clone()))
+        .unwrap();
+
+    let state = harness.get_state::<Distributor>(&airdrop.distributor);
+    assert_eq!((state.num_claimed, state.total_claimed), (2, 300));
+    assert!(state.is_claimed(0) && state.is_claimed(1) && !state.is_claimed(2));
+    assert_eq!(harness.token_balance(&airdrop.vault), 300);
+}
+
+#[test]
+fn test_claims_never_exceed_the_funded_total() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let airdrop = new_airdrop(&mut harness, 350);
+
+    let [_, bob, carol] = airdrop.wallets;
+    let bob_tokens = harness.add_token_account(&airdrop.mint, &bob, 0);
+    let carol_tokens = harness.add_token_account(&airdrop.mint, &carol, 0);
+    harness
+        .process_instruction(&claim(&program_id, &airdrop, &carol, &carol_tokens, 2, 300, airdrop.proofs[2].clone()))
+        .unwrap();
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &bob, &bob_tokens, 1, 200, airdrop.proofs[1].clone())),
+        Err(DistributorError::ExceedsMaxClaim.into())
+    );
+}
+
+#[test]
+fn test_distributor_circuit_breaker_caps_claims() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let airdrop = new_airdrop(&mut harness, 600);
+    let admin = harness.initialize_roles();
+    harness
+        .process_instruction(
+            &AccessInstruction::configure_circuit_breaker(&program_id, &admin, ModuleTag::Distributor, 9_000, 250)
+                .unwrap(),
+        )
+        .unwrap();
+
+    let [alice, bob, carol] = airdrop.wallets;
+    let alice_tokens = harness.add_token_account(&airdrop.mint, &alice, 0);
+    let bob_tokens = harness.add_token_account(&airdrop.mint, &bob, 0);
+    let carol_tokens = harness.add_token_account(&airdrop.mint, &carol, 0);
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &carol, &carol_tokens, 2, 300, airdrop.proofs[2].clone())),
+        Err(AccessError::OutflowLimitExceeded.into())
+    );
+    harness
+        .process_instruction(&claim(&program_id, &airdrop, &alice, &alice_tokens, 0, 100, airdrop.proofs[0].clone()))
+        .unwrap();
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &bob, &bob_tokens, 1, 200, airdrop.proofs[1].clone())),
+        Err(AccessError::OutflowLimitExceeded.into())
+    );
+    assert_eq!(harness.token_balance(&airdrop.vault), 500);
+    assert!(!harness.get_state::<Distributor>(&airdrop.distributor).is_claimed(1));
+}
+
+#[test]
+fn test_largest_distributor_fits_an_account_created_by_cpi() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.add_wallet();
+    let mint = harness.add_mint(&admin, 0, 6);
+    let source = harness.add_token_account(&mint, &admin, 1_000);
+    let expiry = harness.clock().unix_timestamp + 30 * DAY;
+    let new_distributor = |nonce, num_nodes| {
+        DistributorInstruction::new_distributor(
+            &program_id,
+            &spl_token::id(),
+            &admin,
+            &mint,
+            &source,
+            nonce,
+            [1; 32],
+            100,
+            num_nodes,
+            expiry,
+        )
+        .unwrap()
+    };
+
+    assert!(Distributor::space(Distributor::MAX_NODES) <= MAX_PERMITTED_DATA_INCREASE);
+    assert_eq!(
+        harness.process_instruction(&new_distributor(0, Distributor::MAX_NODES + 1)),
+        Err(DistributorError::InvalidDistributor.into())
+    );
+
+    harness
+        .process_instruction(&new_distributor(0, Distributor::MAX_NODES))
+        .unwrap();
+    let (distributor, _) = Distributor::find_address(&admin, 0, &program_id);
+    assert_eq!(
+        harness.get_account(&distributor).unwrap().data.len(),
+        Distributor::space(Distributor::MAX_NODES)
+    );
+    let state = harness.get_state::<Distributor>(&distributor);
+    assert!(!state.is_claimed(Distributor::MAX_NODES - 1));
+}
+
+#[test]
+fn test_admin_claws_back_after_expiry() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let airdrop = new_airdrop(&mut harness, 600);
+    let [alice, bob, _] = airdrop.wallets;
+    let alice_tokens = harness.add_token_account(&airdrop.mint, &alice, 0);
+    let bob_tokens = harness.add_token_account(&airdrop.mint, &bob, 0);
+    let returned = harness.add_token_account(&airdrop.mint, &airdrop.admin, 0);
+    let clawback = |admin: &Pubkey| {
+        DistributorInstruction::clawback(&program_id, &spl_token::id(), admin, &airdrop.distributor, &airdrop.mint, &returned)
+            .unwrap()
+    };
+
+    harness
+        .process_instruction(&claim(&program_id, &airdrop, &alice, &alice_tokens, 0, 100, airdrop.proofs[0].clone()))
+        .unwrap();
+    assert_eq!(
+        harness.process_instruction(&clawback(&airdrop.admin)),
+        Err(DistributorError::NotExpired.into())
+    );
+    let close = |admin: &Pubkey| {
+        DistributorInstruction::close_distributor(
+            &program_id,
+            &spl_token::id(),
+            admin,
+            &airdrop.distributor,
+            &airdrop.mint,
+            &returned,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&close(&airdrop.admin)),
+        Err(DistributorError::NotClawedBack.into())
+    );
+
+    harness.warp_to_timestamp(airdrop.expiry);
+    assert_eq!(
+        harness.process_instruction(&claim(&program_id, &airdrop, &bob, &bob_tokens, 1, 200, airdrop.proofs[1].clone())),
+        Err(DistributorError::ClaimExpired.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&clawback(&bob)),
+        Err(DistributorError::InvalidAuthority.into())
+    );
+    harness.process_instruction(&clawback(&airdrop.admin)).unwrap();
+    assert_eq!(harness.token_balance(&returned), 500);
+    assert_eq!(harness.token_balance(&airdrop.vault), 0);
+    assert!(harness.get_state::<Distributor>(&airdrop.distributor).clawed_back);
+    assert_eq!(
+        harness.process_instruction(&clawback(&airdrop.admin)),
+        Err(DistributorError::AlreadyClawedBack.into())
+    );
+
+    // The emptied distributor and vault are closed back to the admin
+    assert_eq!(
+        harness.process_instruction(&close(&bob)),
+        Err(SOuLError::RentPayerMismatch.into())
+    );
+    let mut to_bob = close(&airdrop.admin);
+    to_bob.accounts[3].pubkey = bob_tokens;
+    assert_eq!(harness.process_instruction(&to_bob), Err(DistributorError::InvalidDestination.into()));
+
+    // Tokens sent to the vault after the clawback go to the admin instead of
+    // keeping it open
+    harness.set_token_account(airdrop.vault, &airdrop.mint, &airdrop.vault, 7);
+    let admin_lamports = harness.get_account(&airdrop.admin).unwrap().lamports;
+    let rent = harness.get_account(&airdrop.distributor).unwrap().lamports
+        + harness.get_account(&airdrop.vault).unwrap().lamports;
+    harness.process_instruction(&close(&airdrop.admin)).unwrap();
+    assert_eq!(harness.token_balance(&returned), 507);
+    assert_eq!(harness.get_account(&airdrop.distributor).unwrap().lamports, 0);
+    assert_eq!(harness.get_account(&airdrop.vault).unwrap().lamports, 0);
+    assert_eq!(harness.get_account(&airdrop.admin).unwrap().lamports, admin_lamports + rent);
+}
@@ -0,0 +1,254 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        error::SOuLError,
+        event::SoulEvent,
+        instruction::SOuLInstruction,
+        processor::process_instruction,
+        state::{EmissionBucket, EmissionSchedule, SOuLToken},
+    },
+    solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey},
+    spl_token::state::Mint,
+};
+
+struct EmissionSetup {
+    token_account: Pubkey,
+    mint: Pubkey,
+    authority: Pubkey,
+    holder: Pubkey,
+    schedule: EmissionSchedule,
+}
+
+fn schedule(harness: &mut ProgramHarness, mint: &Pubkey) -> EmissionSchedule {
+    let mut bucket = |basis_points| EmissionBucket {
+        destination: harness.add_token_account(mint, &Pubkey::new_unique(), 0),
+        basis_points,
+    };
+    EmissionSchedule {
+        start_epoch: 1,
+        epoch_emission: 1_000,
+        reduction_interval_epochs: 2,
+        reduction_basis_points: 5_000,
+        staking: bucket(5_000),
+        game_rewards: bucket(3_000),
+        verifier_fund: bucket(2_000),
+        emitted: 0,
+    }
+}
+
+fn initialize(harness: &mut ProgramHarness, total_supply: u64) -> EmissionSetup {
+    let program_id = harness.program_id;
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let holder = harness.add_token_account(&mint, &authority, 0);
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(&program_id, &authority, &mint, &holder, total_supply).unwrap(),
+        )
+        .unwrap();
+
+    EmissionSetup {
+        token_account: SOuLToken::find_address(&mint, &program_id).0,
+        schedule: schedule(harness, &mint),
+        mint,
+        authority,
+        holder,
+    }
+}
+
+fn configure(harness: &mut ProgramHarness, setup: &EmissionSetup, max_supply: u64) -> Result<(), ProgramError> {
+    let program_id = harness.program_id;
+    harness.process_instruction(
+        &SOuLInstruction::configure_emission(
+            &program_id,
+            &setup.authority,
+            &setup.mint,
+            max_supply,
+            setup.schedule.clone(),
+        )
+        .unwrap(),
+    )
+}
+
+fn emit(harness: &mut ProgramHarness, setup: &EmissionSetup) -> Result<(), ProgramError> {
+    let program_id = harness.program_id;
+    harness.process_instruction(
+        &SOuLInstruction::emit(&program_id, &spl_token::id(), &setup.mint, &setup.schedule).unwrap(),
+    )
+}
+
+fn bucket_balances(harness: &ProgramHarness, schedule: &EmissionSchedule) -> [u64; 3] {
+    [
+        harness.token_balance(&schedule.staking.destination),
+        harness.token_balance(&schedule.game_rewards.destination),
+        harness.token_balance(&schedule.verifier_fund.destination),
+    ]
+}
+
+#[test]
+fn test_released_through_follows_the_curve() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let mut schedule = schedule(&mut harness, &Pubkey::new_unique());
+
+    // Halving every two epochs from epoch 1
+    assert_eq!(schedule.released_through(0), 0);
+    assert_eq!(schedule.released_through(1), 1_000);
+    assert_eq!(schedule.released_through(2), 2_000);
+    assert_eq!(schedule.released_through(3), 2_500);
+    assert_eq!(schedule.released_through(5), 3_250);
+    // The emission halves towards zero, bounding the total below 4,000
+    assert_eq!(schedule.released_through(1_000_000), 3_988);
+
+    // A 10% decay every epoch
+    schedule.reduction_interval_epochs = 1;
+    schedule.reduction_basis_points = 1_000;
+    assert_eq!(schedule.released_through(3), 1_000 + 900 + 810);
+
+    // No reduction keeps the emission flat
+    schedule.reduction_basis_points = 0;
+    assert_eq!(schedule.released_through(10), 10_000);
+    assert_eq!(schedule.released_through(u64::MAX - 1), u64::MAX);
+
+    assert_eq!(schedule.split(1_001), [500, 300, 201]);
+}
+
+#[test]
+fn test_configure_emission_is_gated_and_final() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize(&mut harness, 10_000);
+
+    let token = harness.get_state::<SOuLToken>(&setup.token_account);
+    assert_eq!(token.max_supply, u64::MAX);
+    assert_eq!(token.emission, None);
+
+    let impostor = harness.add_wallet();
+    let configure_as = |authority: &Pubkey, schedule: EmissionSchedule, max_supply| {
+        SOuLInstruction::configure_emission(&program_id, authority, &setup.mint, max_supply, schedule).unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&configure_as(&impostor, setup.schedule.clone(), 100_000)),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+
+    // Bucket shares must add up to the whole emission
+    let mut invalid = setup.schedule.clone();
+    invalid.verifier_fund.basis_points = 1_000;
+    assert_eq!(
+        harness.process_instruction(&configure_as(&setup.authority, invalid, 100_000)),
+        Err(SOuLError::InvalidEmissionSchedule.into())
+    );
+    let mut invalid = setup.schedule.clone();
+    invalid.reduction_interval_epochs = 0;
+    assert_eq!(
+        harness.process_instruction(&configure_as(&setup.authority, invalid, 100_000)),
+        Err(SOuLError::InvalidEmissionSchedule.into())
+    );
+    let mut invalid = setup.schedule.clone();
+    invalid.emitted = 1;
+    assert_eq!(
+        harness.process_instruction(&configure_as(&setup.authority, invalid, 100_000)),
+        Err(SOuLError::InvalidEmissionSchedule.into())
+    );
+
+    // The cap cannot sit below what already exists
+    assert_eq!(configure(&mut harness, &setup, 9_999), Err(SOuLError::SupplyCapExceeded.into()));
+
+    configure(&mut harness, &setup, 100_000).unwrap();
+    let token = harness.get_state::<SOuLToken>(&setup.token_account);
+    assert_eq!(token.max_supply, 100_000);
+    assert_eq!(token.emission, Some(setup.schedule.clone()));
+    assert_eq!(configure(&mut harness, &setup, u64::MAX), Err(SOuLError::EmissionAlreadyConfigured.into()));
+}
+
+#[test]
+fn test_emit_mints_released_emission_into_buckets() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize(&mut harness, 10_000);
+
+    assert_eq!(emit(&mut harness, &setup), Err(SOuLError::EmissionNotConfigured.into()));
+    configure(&mut harness, &setup, 100_000).unwrap();
+
+    // Nothing is released before the start epoch
+    assert_eq!(emit(&mut harness, &setup), Err(SOuLError::NothingToEmit.into()));
+
+    harness.warp_to_epoch(1);
+    emit(&mut harness, &setup).unwrap();
+    assert_eq!(bucket_balances(&harness, &setup.schedule), [500, 300, 200]);
+    let timestamp = harness.clock().unix_timestamp;
+    let events = harness
+        .take_logged_data()
+        .iter()
+        .filter_map(|fields| SoulEvent::from_log_data(fields))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![SoulEvent::Emitted {
+            mint: setup.mint,
+            epoch: 1,
+            staking: 500,
+            game_rewards: 300,
+            verifier_fund: 200,
+            emitted: 1_000,
+            total_supply: 11_000,
+            timestamp,
+        }]
+    );
+
+    // Each epoch's emission is minted once
+    assert_eq!(emit(&mut harness, &setup), Err(SOuLError::NothingToEmit.into()));
+
+    // A late crank catches up on every epoch it missed
+    harness.warp_to_epoch(3);
+    emit(&mut harness, &setup).unwrap();
+    assert_eq!(bucket_balances(&harness, &setup.schedule), [1_250, 750, 500]);
+    let token = harness.get_state::<SOuLToken>(&setup.token_account);
+    assert_eq!(token.emission.unwrap().emitted, 2_500);
+    assert_eq!(token.total_supply, 12_500);
+    assert_eq!(harness.mint_supply(&setup.mint), 12_500);
+
+    // Only the configured buckets can receive the emission
+    harness.warp_to_epoch(4);
+    let mut redirected = SOuLInstruction::emit(&program_id, &spl_token::id(), &setup.mint, &setup.schedule).unwrap();
+    redirected.accounts[4].pubkey = setup.holder;
+    assert_eq!(harness.process_instruction(&redirected), Err(SOuLError::InvalidEmissionSchedule.into()));
+}
+
+#[test]
+fn test_supply_cap_bounds_emission_and_minting() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize(&mut harness, 10_000);
+    configure(&mut harness, &setup, 10_600).unwrap();
+
+    let mint_ix = |amount| {
+        SOuLInstruction::mint(&program_id, &spl_token::id(), &setup.authority, &setup.mint, &setup.holder, amount)
+            .unwrap()
+    };
+    assert_eq!(harness.process_instruction(&mint_ix(601)), Err(SOuLError::SupplyCapExceeded.into()));
+    harness.process_instruction(&mint_ix(100)).unwrap();
+
+    // The emission stops at the cap
+    harness.warp_to_epoch(1);
+    emit(&mut harness, &setup).unwrap();
+    assert_eq!(bucket_balances(&harness, &setup.schedule), [250, 150, 100]);
+    assert_eq!(harness.mint_supply(&setup.mint), 10_600);
+    harness.warp_to_epoch(2);
+    assert_eq!(emit(&mut harness, &setup), Err(SOuLError::NothingToEmit.into()));
+    assert_eq!(harness.process_instruction(&mint_ix(1)), Err(SOuLError::SupplyCapExceeded.into()));
+
+    // Burning frees room for what the schedule still owes
+    harness
+        .process_instruction(
+            &SOuLInstruction::burn(&program_id, &spl_token::id(), &setup.holder, &setup.authority, &setup.mint, 1_000)
+                .unwrap(),
+        )
+        .unwrap();
+    emit(&mut harness, &setup).unwrap();
+    assert_eq!(harness.get_state::<SOuLToken>(&setup.token_account).emission.unwrap().emitted, 1_500);
+    assert_eq!(harness.mint_supply(&setup.mint), 10_600);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        access::{AccessError, CircuitBreaker, ProgramConfig},
+        distributor::DistributorError,
+        error::{decode_error, SOuLError, ERROR_RANGE},
+        game::GameError,
+        impact::ImpactError,
+        instruction::ModuleTag,
+        lst::LSTError,
+        multisig::MultisigError,
+        processor::process_instruction,
+        vesting::{VestingError, VestingInstruction, VestingSchedule, VestingType},
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+#[test]
+fn test_module_error_codes_do_not_overlap() {
+    assert_eq!(SOuLError::InvalidInstruction.code(), 0);
+    assert_eq!(SOuLError::AlreadyInitialized.code(), 7);
+    assert_eq!(VestingError::InvalidInstruction.code(), ERROR_RANGE);
+    assert_eq!(ImpactError::InvalidInstruction.code(), 2 * ERROR_RANGE);
+    assert_eq!(LSTError::InvalidInstruction.code(), 3 * ERROR_RANGE);
+    assert_eq!(GameError::InvalidInstruction.code(), 4 * ERROR_RANGE);
+    assert_eq!(MultisigError::InvalidInstruction.code(), 5 * ERROR_RANGE);
+    assert_eq!(AccessError::InvalidInstruction.code(), 6 * ERROR_RANGE);
+    assert_eq!(DistributorError::InvalidInstruction.code(), 7 * ERROR_RANGE);
+
+    assert_eq!(
+        ProgramError::from(VestingError::NoTokensToRelease),
+        ProgramError::Custom(ERROR_RANGE + 7)
+    );
+    assert_eq!(GameError::from_code(4005), Some(GameError::InvalidQuest));
+    assert_eq!(GameError::from_code(3005), None);
+    assert_eq!(LSTError::from_code(3005), Some(LSTError::InvalidAmount));
+}
+
+#[test]
+fn test_decode_error() {
+    assert_eq!(decode_error(SOuLError::InvalidAuthority.code()), "Invalid Authority");
+    assert_eq!(decode_error(VestingError::NoTokensToRelease.code()), "No Tokens To Release");
+    assert_eq!(decode_error(ImpactError::DisputeAlreadyResolved.code()), "Dispute Already Resolved");
+    assert_eq!(decode_error(LSTError::UnstakeRequestNotReady.code()), "Unstake Request Not Ready");
+    assert_eq!(decode_error(GameError::InsufficientEnergy.code()), "Insufficient Energy");
+    assert_eq!(GameError::InsufficientEnergy.to_string(), "Insufficient Energy");
+    assert_eq!(decode_error(MultisigError::NotEnoughApprovals.code()), "Not Enough Approvals");
+    assert_eq!(decode_error(AccessError::NotAuthorized.code()), "Missing Required Role");
+    assert_eq!(decode_error(DistributorError::InvalidProof.code()), "Invalid Merkle Proof");
+
+    assert_eq!(decode_error(999), "Unknown Error");
+    assert_eq!(decode_error(9 * ERROR_RANGE), "Unknown Error");
+}
+
+#[test]
+fn test_processor_reports_module_error_code() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let mint = harness.add_mint(&authority, 1_000, 9);
+    let source = harness.add_token_account(&mint, &authority, 1_000);
+
+    harness
+        .process_instruction(&Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(authority, true),
+                AccountMeta::new(vesting_account, false),
+                AccountMeta::new_readonly(beneficiary, false),
+                AccountMeta::new(vault, false),
+                AccountMeta::new_readonly(mint, false),
+                AccountMeta::new(source, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(&program_id),
+            ],
+            data: ModuleTag::Vesting
+                .pack(&VestingInstruction::Initialize {
+                    nonce: 0,
+                    start_timestamp: now + 100,
+                    total_amount: 1_000,
+                    vesting_type: VestingType::Ecosystem,
+                    cliff_seconds: None,
+                    duration_seconds: Some(100),
+                    release_interval_seconds: None,
+                    revoke_authority: None,
+                })
+                .unwrap(),
+        })
+        .unwrap();
+
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+
+    let result = harness.process_instruction(&Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new(vesting_account, false),
+            AccountMeta::new(vault, false),
+            AccountMeta::new_readonly(mint, false),
+            AccountMeta::new(beneficiary_tokens, false),
+            AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(beneficiary, true),
+            AccountMeta::new(CircuitBreaker::find_address(ModuleTag::Vesting, &program_id).0, false),
+            ProgramConfig::account_meta(&program_id),
+        ],
+        data: ModuleTag::Vesting.pack(&VestingInstruction::Release).unwrap(),
+    });
+
+    let Err(ProgramError::Custom(code)) = result else {
+        panic!("expected a custom error, got {:?}", result);
+    };
+    assert_eq!(code, 1007);
+    assert_eq!(decode_error(code), "No Tokens To Release");
+}
@@ -0,0 +1,236 @@
+mod common;
+
+use {
+    base64::{engine::general_purpose::STANDARD, Engine},
+    borsh::BorshSerialize,
+    common::ProgramHarness,
+    soul_sanctuary::{
+        event::{
+            decoder::{decode_data, decode_logs, EventDecodeError},
+            SoulEvent,
+        },
+        game::{EnvironmentalImpact, GameInstruction, GeoLocation},
+        instruction::SOuLInstruction,
+        processor::process_instruction,
+        vesting::{VestingInstruction, VestingSchedule, VestingType},
+    },
+    solana_program::{program_pack::Pack, pubkey::Pubkey},
+    spl_token::state::Mint,
+};
+
+/// Events logged by the last transaction, in order
+fn logged_events(harness: &ProgramHarness) -> Vec<SoulEvent> {
+    harness
+        .take_logged_data()
+        .iter()
+        .map(|fields| SoulEvent::from_log_data(fields).unwrap())
+        .collect()
+}
+
+fn program_data(event: &SoulEvent) -> String {
+    format!(
+        "Program data: {} {}",
+        STANDARD.encode(SoulEvent::TAG),
+        STANDARD.encode(event.try_to_vec().unwrap())
+    )
+}
+
+#[test]
+fn test_mint_and_burn_log_supply_events() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let treasury = harness.add_token_account(&mint, &authority, 0);
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(&program_id, &authority, &mint, &treasury, 1_000).unwrap(),
+        )
+        .unwrap();
+    let timestamp = harness.clock().unix_timestamp;
+
+    harness
+        .process_instruction(&SOuLInstruction::mint(&program_id, &spl_token::id(), &authority, &mint, &treasury, 500).unwrap())
+        .unwrap();
+    assert_eq!(
+        logged_events(&harness),
+        vec![SoulEvent::Minted {
+            mint,
+            destination: treasury,
+            authority,
+            amount: 500,
+            total_supply: 1_500,
+            timestamp,
+        }]
+    );
+
+    harness
+        .process_instruction(&SOuLInstruction::burn(&program_id, &spl_token::id(), &treasury, &authority, &mint, 200).unwrap())
+        .unwrap();
+    assert_eq!(
+        logged_events(&harness),
+        vec![SoulEvent::Burned {
+            mint,
+            source: treasury,
+            authority,
+            amount: 200,
+            total_supply: 1_300,
+            timestamp,
+        }]
+    );
+
+    // A failed instruction logs nothing
+    assert!(harness
+        .process_instruction(&SOuLInstruction::burn(&program_id, &spl_token::id(), &treasury, &authority, &mint, 5_000).unwrap())
+        .is_err());
+    assert!(logged_events(&harness).is_empty());
+}
+
+#[test]
+fn test_vesting_release_logs_amount_released() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let mint = harness.add_mint(&authority, 1_000, 9);
+    let source = harness.add_token_account(&mint, &authority, 1_000);
+    harness
+        .process_instruction(
+            &VestingInstruction::initialize(
+                &program_id,
+                &spl_token::id(),
+                &authority,
+                &beneficiary,
+                &mint,
+                &source,
+                0,
+                now,
+                1_000,
+                VestingType::Ecosystem,
+                None,
+                Some(100),
+                None,
+                None,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert!(logged_events(&harness).is_empty());
+
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+    let release = VestingInstruction::release(
+        &program_id,
+        &spl_token::id(),
+        &vesting_account,
+        &vault,
+        &mint,
+        &beneficiary_tokens,
+        &beneficiary,
+        &[],
+    )
+    .unwrap();
+
+    for (elapsed, amount, released_amount) in [(25, 250, 250), (75, 500, 750)] {
+        harness.warp_to_timestamp(now + elapsed);
+        harness.process_instruction(&release).unwrap();
+        assert_eq!(
+            logged_events(&harness),
+            vec![SoulEvent::VestingReleased {
+                vesting_account,
+                beneficiary,
+                amount,
+                released_amount,
+                timestamp: now + elapsed,
+            }]
+        );
+    }
+}
+
+#[test]
+fn test_completed_quest_logs_rewards() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let player = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_game(&program_id, &authority).unwrap())
+        .unwrap();
+    harness
+        .process_instruction(&GameInstruction::initialize_player(&program_id, &player).unwrap())
+        .unwrap();
+    harness
+        .process_instruction(&GameInstruction::start_quest(&program_id, &player, 0).unwrap())
+        .unwrap();
+
+    let location = GeoLocation {
+        latitude: 0,
+        longitude: 0,
+        altitude: 0,
+    };
+    let impact = EnvironmentalImpact {
+        carbon_offset: 0,
+        water_saved: 0,
+        energy_saved: 0,
+        biodiversity_impact: 0,
+    };
+    harness
+        .process_instruction(&GameInstruction::complete_quest(&program_id, &player, 0, impact, location).unwrap())
+        .unwrap();
+    assert_eq!(
+        logged_events(&harness),
+        vec![SoulEvent::QuestCompleted {
+            player,
+            quest_id: 0,
+            experience: 100,
+            eco_tokens: 10,
+            timestamp: harness.clock().unix_timestamp,
+        }]
+    );
+}
+
+#[test]
+fn test_decode_logs_keeps_only_this_programs_events() {
+    let program_id = Pubkey::new_unique();
+    let other_program = Pubkey::new_unique();
+    let staked = SoulEvent::Staked {
+        pool: Pubkey::new_unique(),
+        staker: Pubkey::new_unique(),
+        amount: 100,
+        lst_amount: 100,
+        total_staked: 100,
+        timestamp: 1,
+    };
+    let traded = SoulEvent::ItemsTraded {
+        player: Pubkey::new_unique(),
+        target: Pubkey::new_unique(),
+        offered_items: vec![3],
+        requested_items: vec![],
+        timestamp: 2,
+    };
+
+    let logs = vec![
+        format!("Program {} invoke [1]", program_id),
+        "Program log: Instruction: Stake".to_string(),
+        format!("Program {} invoke [2]", other_program),
+        // Same tag, but logged by the program we called
+        program_data(&traded),
+        format!("Program {} consumed 100 of 200000 compute units", other_program),
+        format!("Program {} success", other_program),
+        program_data(&staked),
+        // Data from this program that is not an event
+        format!("Program data: {}", STANDARD.encode(b"other")),
+        format!("Program {} success", program_id),
+        format!("Program {} invoke [1]", program_id),
+        program_data(&traded),
+        format!("Program {} success", program_id),
+    ];
+    assert_eq!(decode_logs(&program_id, &logs), Ok(vec![staked, traded]));
+    assert_eq!(decode_logs(&other_program, &logs).unwrap().len(), 1);
+
+    assert_eq!(decode_data("not base64!"), Err(EventDecodeError::InvalidBase64));
+    let truncated = format!("{} {}", STANDARD.encode(SoulEvent::TAG), STANDARD.encode([9]));
+    assert_eq!(decode_data(&truncated), Err(EventDecodeError::InvalidEvent));
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        game::{
+            DataType, EnvironmentalImpact, GameError, GameInstruction, GameState, GeoLocation,
+            PlayerState,
+        },
+        processor::process_instruction,
+    },
+    solana_program::pubkey::Pubkey,
+};
+
+struct GameSetup {
+    authority: Pubkey,
+    game_state: Pubkey,
+}
+
+fn initialize_game(harness: &mut ProgramHarness) -> GameSetup {
+    let program_id = harness.program_id;
+    let authority = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_game(&program_id, &authority).unwrap())
+        .unwrap();
+
+    GameSetup {
+        authority,
+        game_state: GameState::find_address(&program_id).0,
+    }
+}
+
+fn add_player(harness: &mut ProgramHarness) -> (Pubkey, Pubkey) {
+    let program_id = harness.program_id;
+    let player = harness.add_wallet();
+    harness
+        .process_instruction(&GameInstruction::initialize_player(&program_id, &player).unwrap())
+        .unwrap();
+    (player, PlayerState::find_address(&player, &program_id).0)
+}
+
+fn location() -> GeoLocation {
+    GeoLocation {
+        latitude: 45_000_000,
+        longitude: -122_000_000,
+        altitude: 50,
+    }
+}
+
+fn impact() -> EnvironmentalImpact {
+    EnvironmentalImpact {
+        carbon_offset: 10,
+        water_saved: 0,
+        energy_saved: 0,
+        biodiversity_impact: 100,
+    }
+}
+
+#[test]
+fn test_quest_lifecycle() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_game(&mut harness);
+    let (player, player_state) = add_player(&mut harness);
+    assert_eq!(harness.get_state::<GameState>(&setup.game_state).total_players, 1);
+
+    assert_eq!(
+        harness.process_instruction(
+            &GameInstruction::complete_quest(&program_id, &player, 0, impact(), location()).unwrap()
+        ),
+        Err(GameError::QuestNotStarted.into())
+    );
+
+    harness
+        .process_instruction(&GameInstruction::start_quest(&program_id, &player, 0).unwrap())
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.active_quest, Some(0));
+    assert_eq!(state.energy, PlayerState::MAX_ENERGY - 10);
+
+    harness
+        .process_instruction(&GameInstruction::complete_quest(&program_id, &player, 0, impact(), location()).unwrap())
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.active_quest, None);
+    assert_eq!(state.quests_completed, 1);
+    assert_eq!(state.inventory.resources.eco_tokens, 10);
+    let game = harness.get_state::<GameState>(&setup.game_state);
+    assert_eq!(game.total_quests_completed, 1);
+    assert_eq!(game.total_rewards_distributed, 10);
+
+    harness
+        .process_instruction(
+            &GameInstruction::update_player_stats(&program_id, &setup.authority, &player, 900, true).unwrap(),
+        )
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.experience, 1_000);
+    assert_eq!(state.level, 3);
+
+    assert_eq!(
+        harness.process_instruction(
+            &GameInstruction::update_player_stats(&program_id, &player, &player, 900, true).unwrap()
+        ),
+        Err(GameError::InvalidPlayer.into())
+    );
+}
+
+#[test]
+fn test_collect_craft_use_and_restore() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    initialize_game(&mut harness);
+    let (player, player_state) = add_player(&mut harness);
+
+    for _ in 0..10 {
+        harness
+            .process_instruction(
+                &GameInstruction::collect_data(&program_id, &player, DataType::ForestDensity, location(), impact())
+                    .unwrap(),
+            )
+            .unwrap();
+    }
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.inventory.collectibles.len(), 10);
+    assert_eq!(state.inventory.resources.research_points, 100);
+    assert_eq!(state.energy, PlayerState::MAX_ENERGY - 50);
+
+    harness
+        .process_instruction(&GameInstruction::claim_achievement(&program_id, &player, 0).unwrap())
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.achievements.len(), 1);
+    assert_eq!(state.inventory.resources.eco_tokens, 100);
+    assert_eq!(
+        harness.process_instruction(&GameInstruction::claim_achievement(&program_id, &player, 0).unwrap()),
+        Err(GameError::InvalidAchievement.into())
+    );
+
+    let mut state = harness.get_state::<PlayerState>(&player_state);
+    state.inventory.resources.energy_crystals = 30;
+    harness.set_state(&player_state, &state);
+
+    harness
+        .process_instruction(&GameInstruction::craft_item(&program_id, &player, 0).unwrap())
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.inventory.items.len(), 1);
+    assert_eq!(state.inventory.resources.research_points, 0);
+    assert_eq!(state.inventory.resources.energy_crystals, 20);
+
+    harness
+        .process_instruction(&GameInstruction::use_item(&program_id, &player, 0).unwrap())
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert!(state.inventory.items.is_empty());
+    assert_eq!(state.energy, PlayerState::MAX_ENERGY - 45);
+
+    harness
+        .process_instruction(&GameInstruction::restore_energy(&program_id, &player, 15).unwrap())
+        .unwrap();
+    let state = harness.get_state::<PlayerState>(&player_state);
+    assert_eq!(state.energy, PlayerState::MAX_ENERGY - 30);
+    assert_eq!(state.inventory.resources.energy_crystals, 5);
+}
+
+#[test]
+fn test_trade_items_needs_both_players() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    initialize_game(&mut harness);
+    let (player, player_state) = add_player(&mut harness);
+    let (target, target_state) = add_player(&mut harness);
+
+    let mut state = harness.get_state::<PlayerState>(&player_state);
+    state.inventory.resources.research_points = 100;
+    state.inventory.resources.energy_crystals = 10;
+    harness.set_state(&player_state, &state);
+    harness
+        .process_instruction(&GameInstruction::craft_item(&program_id, &player, 0).unwrap())
+        .unwrap();
+
+    let trade = GameInstruction::trade_items(&program_id, &player, &target, vec![0], vec![]).unwrap();
+    assert!(trade.accounts[0].is_signer && trade.accounts[3].is_signer);
+    harness.process_instruction(&trade).unwrap();
+
+    assert!(harness.get_state::<PlayerState>(&player_state).inventory.items.is_empty());
+    assert_eq!(harness.get_state::<PlayerState>(&target_state).inventory.items.len(), 1);
+    assert_eq!(harness.process_instruction(&trade), Err(GameError::InvalidTrade.into()));
+}
@@ -0,0 +1,436 @@
+mod common;
+
+use {
+    common::{with_token_program, ProgramHarness},
+    soul_sanctuary::{
+        access::{AccessError, AccessInstruction, Role},
+        error::SOuLError,
+        impact::{
+            DisputeCase, DisputeStatus, GeoLocation, ImpactError, ImpactInstruction, ImpactMetrics,
+            ImpactReport, VerificationStatus, VerifierState,
+        },
+        instruction::SOuLInstruction,
+        lst::LSTType,
+        processor::process_instruction,
+    },
+    solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey},
+    spl_token::state::Mint,
+};
+
+/// One whole SOuL in base units
+const SOUL: u64 = 1_000_000_000;
+
+/// Initialize a SOuL token with its whole supply of `amount` in a token
+/// account of `owner`, and return its mint and that account
+fn initialize_soul(harness: &mut ProgramHarness, owner: &Pubkey, amount: u64) -> (Pubkey, Pubkey) {
+    let program_id = harness.program_id;
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let tokens = harness.add_token_account(&mint, owner, 0);
+    harness
+        .process_instruction(&SOuLInstruction::initialize_soul(&program_id, owner, &mint, &tokens, amount).unwrap())
+        .unwrap();
+    (mint, tokens)
+}
+
+/// Initialize SOuL as above and have `admin` pin it as the canonical mint
+fn add_soul(harness: &mut ProgramHarness, admin: &Pubkey, owner: &Pubkey, amount: u64) -> (Pubkey, Pubkey) {
+    let program_id = harness.program_id;
+    let (mint, tokens) = initialize_soul(harness, owner, amount);
+    harness
+        .process_instruction(&AccessInstruction::pin_soul_mint(&program_id, admin, &mint).unwrap())
+        .unwrap();
+    (mint, tokens)
+}
+
+fn empty_metrics() -> ImpactMetrics {
+    ImpactMetrics {
+        is_initialized: false,
+        authority: Pubkey::default(),
+        last_update: 0,
+        forest_area: 10,
+        carbon_offset: 20,
+        water_saved: 0,
+        biodiversity_score: 0,
+        energy_saved: 0,
+        waste_recycled: 0,
+        impact_score: 0,
+        bump: 0,
+    }
+}
+
+#[test]
+fn test_register_verifier_locks_stake_in_pda_vault() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let verifier = harness.add_wallet();
+    let admin = harness.initialize_roles();
+    let (mint, verifier_tokens) = add_soul(&mut harness, &admin, &verifier, 5_000 * SOUL);
+    let (verifier_state, bump) = VerifierState::find_address(&verifier, &program_id);
+    let stake_vault = harness.add_token_account(&mint, &verifier_state, 0);
+
+    // A stake in any other token puts nothing at risk
+    let other_mint = harness.add_mint(&verifier, 5_000, 9);
+    let other_tokens = harness.add_token_account(&other_mint, &verifier, 5_000);
+    let other_vault = harness.add_token_account(&other_mint, &verifier_state, 0);
+    assert_eq!(
+        harness.process_instruction(
+            &ImpactInstruction::register_verifier(&program_id, &verifier, &mint, &other_tokens, &other_vault, 2_000 * SOUL)
+                .unwrap(),
+        ),
+        Err(ImpactError::InvalidStakeMint.into())
+    );
+
+    // Nor does a SOuL token config anyone can create for a mint of their own
+    let (own_mint, own_tokens) = initialize_soul(&mut harness, &verifier, 5_000 * SOUL);
+    let own_vault = harness.add_token_account(&own_mint, &verifier_state, 0);
+    assert_eq!(
+        harness.process_instruction(
+            &ImpactInstruction::register_verifier(&program_id, &verifier, &own_mint, &own_tokens, &own_vault, 2_000 * SOUL)
+                .unwrap(),
+        ),
+        Err(ImpactError::InvalidStakeMint.into())
+    );
+
+    let register = |stake_vault: &Pubkey, stake_amount: u64| {
+        ImpactInstruction::register_verifier(&program_id, &verifier, &mint, &verifier_tokens, stake_vault, stake_amount)
+            .unwrap()
+    };
+
+    // The minimum stake is counted in whole SOuL
+    assert_eq!(
+        harness.process_instruction(&register(&stake_vault, VerifierState::MIN_STAKE * SOUL - 1)),
+        Err(ImpactError::InsufficientStake.into())
+    );
+
+    // The stake must move through the token program into a real token account
+    let fake_program = harness.add_wallet();
+    assert_eq!(
+        harness.process_instruction(&with_token_program(register(&stake_vault, 2_000 * SOUL), &fake_program)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    let mut forged = harness.get_account(&stake_vault).unwrap().clone();
+    forged.owner = fake_program;
+    let forged_vault = Pubkey::new_unique();
+    harness.add_account(forged_vault, forged);
+    assert_eq!(
+        harness.process_instruction(&register(&forged_vault, 2_000 * SOUL)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    harness.process_instruction(&register(&stake_vault, 2_000 * SOUL)).unwrap();
+
+    let state = harness.get_state::<VerifierState>(&verifier_state);
+    assert_eq!(state.verifier_pubkey, verifier);
+    assert_eq!(state.stake_amount, 2_000 * SOUL);
+    assert_eq!(state.bump, bump);
+    assert_eq!(harness.token_balance(&stake_vault), 2_000 * SOUL);
+    assert_eq!(harness.token_balance(&verifier_tokens), 3_000 * SOUL);
+}
+
+#[test]
+fn test_verifier_cannot_verify_their_own_report() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let (metrics, _) = ImpactMetrics::find_address(&authority, &program_id);
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &authority).unwrap())
+        .unwrap();
+    let location = GeoLocation {
+        latitude: 0,
+        longitude: 0,
+        altitude: 0,
+    };
+    let (report, _) = ImpactReport::find_address(&metrics, &[3; 32], &program_id);
+    harness
+        .process_instruction(
+            &ImpactInstruction::submit_report(&program_id, &authority, location, empty_metrics(), [3; 32], LSTType::Forest).unwrap(),
+        )
+        .unwrap();
+
+    // The project's authority registers as a verifier of its own reports
+    let admin = harness.initialize_roles();
+    let (mint, tokens) = add_soul(&mut harness, &admin, &authority, 1_000 * SOUL);
+    let (verifier_state, _) = VerifierState::find_address(&authority, &program_id);
+    let stake_vault = harness.add_token_account(&mint, &verifier_state, 0);
+    harness
+        .process_instruction(
+            &ImpactInstruction::register_verifier(&program_id, &authority, &mint, &tokens, &stake_vault, 1_000 * SOUL)
+                .unwrap(),
+        )
+        .unwrap();
+    let verify = |metrics_account: &Pubkey| {
+        ImpactInstruction::verify_report(&program_id, &authority, &report, metrics_account, true, [0; 32]).unwrap()
+    };
+    assert_eq!(harness.process_instruction(&verify(&metrics)), Err(ImpactError::SelfVerification.into()));
+
+    // Naming another project's metrics account does not get around it
+    let other = harness.add_wallet();
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &other).unwrap())
+        .unwrap();
+    let (other_metrics, _) = ImpactMetrics::find_address(&other, &program_id);
+    assert_eq!(harness.process_instruction(&verify(&other_metrics)), Err(ImpactError::InvalidReport.into()));
+    assert_eq!(harness.get_state::<ImpactReport>(&report).verification_status, VerificationStatus::Pending);
+}
+
+#[test]
+fn test_dispute_escrow_is_released_by_the_dispute_pda() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let verifier = harness.add_wallet();
+    let challenger = harness.add_wallet();
+    let admin = harness.initialize_roles();
+    let (mint, verifier_tokens) = add_soul(&mut harness, &admin, &verifier, 1_000 * SOUL);
+
+    let (metrics, _) = ImpactMetrics::find_address(&authority, &program_id);
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &authority).unwrap())
+        .unwrap();
+
+    let proof_hash = [7; 32];
+    let (report, report_bump) = ImpactReport::find_address(&metrics, &proof_hash, &program_id);
+    let location = GeoLocation {
+        latitude: 0,
+        longitude: 0,
+        altitude: 0,
+    };
+    harness
+        .process_instruction(
+            &ImpactInstruction::submit_report(&program_id, &authority, location, empty_metrics(), proof_hash, LSTType::Forest)
+                .unwrap(),
+        )
+        .unwrap();
+    let stored = harness.get_state::<ImpactReport>(&report);
+    assert_eq!(stored.bump, report_bump);
+    assert_eq!(stored.category, Some(LSTType::Forest));
+
+    // Register and verify with a verifier whose state is a PDA
+    let (verifier_state, _) = VerifierState::find_address(&verifier, &program_id);
+    let stake_vault = harness.add_token_account(&mint, &verifier_state, 0);
+    harness
+        .process_instruction(
+            &ImpactInstruction::register_verifier(&program_id, &verifier, &mint, &verifier_tokens, &stake_vault, 1_000 * SOUL)
+                .unwrap(),
+        )
+        .unwrap();
+    harness
+        .process_instruction(
+            &ImpactInstruction::verify_report(&program_id, &verifier, &report, &metrics, true, [0; 32]).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.get_state::<VerifierState>(&verifier_state).verified_reports, 1);
+
+    let (dispute, _) = DisputeCase::find_address(&report, &challenger, &program_id);
+    let submit = |challenger_tokens: &Pubkey, escrow: &Pubkey| {
+        ImpactInstruction::submit_dispute(&program_id, &challenger, &report, challenger_tokens, escrow, [1; 32], 300)
+            .unwrap()
+    };
+
+    // The challenger stakes SOuL like verifiers do, not any token
+    let other_mint = harness.add_mint(&challenger, 300, 9);
+    let other_tokens = harness.add_token_account(&other_mint, &challenger, 300);
+    let other_escrow = harness.add_token_account(&other_mint, &dispute, 0);
+    assert_eq!(
+        harness.process_instruction(&submit(&other_tokens, &other_escrow)),
+        Err(ImpactError::InvalidStakeMint.into())
+    );
+
+    let challenger_tokens = harness.add_token_account(&mint, &challenger, 300);
+    let escrow = harness.add_token_account(&mint, &dispute, 0);
+
+    // A transfer through any other program would leave the escrow empty
+    let fake_program = harness.add_wallet();
+    assert_eq!(
+        harness.process_instruction(&with_token_program(submit(&challenger_tokens, &escrow), &fake_program)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+    harness.process_instruction(&submit(&challenger_tokens, &escrow)).unwrap();
+    assert_eq!(harness.token_balance(&escrow), 300);
+    let close = |payer: &Pubkey| {
+        ImpactInstruction::close_dispute(&program_id, &report, &challenger, &escrow, &mint, &challenger_tokens, payer)
+            .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&close(&challenger)),
+        Err(ImpactError::DisputeNotResolved.into())
+    );
+
+    let resolver = harness.add_wallet();
+    harness
+        .process_instruction(
+            &AccessInstruction::grant_role(&program_id, &admin, &resolver, Role::DisputeResolver).unwrap(),
+        )
+        .unwrap();
+    let resolve = |signer: &Pubkey, challenger_stake_account: &Pubkey| {
+        ImpactInstruction::resolve_dispute(
+            &program_id,
+            signer,
+            &report,
+            &challenger,
+            &verifier,
+            challenger_stake_account,
+            &verifier_tokens,
+            &escrow,
+            false,
+            [0; 32],
+        )
+        .unwrap()
+    };
+
+    // The dispute PDA signs only for the token program
+    assert_eq!(
+        harness.process_instruction(&with_token_program(resolve(&resolver, &challenger_tokens), &fake_program)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    // The report's own authority cannot decide a dispute over it
+    assert_eq!(
+        harness.process_instruction(&resolve(&authority, &challenger_tokens)),
+        Err(AccessError::NotAuthorized.into())
+    );
+
+    // The stake goes only to the winner, in the escrowed mint
+    assert_eq!(
+        harness.process_instruction(&resolve(&resolver, &verifier_tokens)),
+        Err(ImpactError::InvalidRecipient.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&resolve(&resolver, &other_tokens)),
+        Err(ImpactError::InvalidRecipient.into())
+    );
+
+    harness.process_instruction(&resolve(&resolver, &challenger_tokens)).unwrap();
+
+    assert_eq!(harness.token_balance(&escrow), 0);
+    assert_eq!(harness.token_balance(&challenger_tokens), 300);
+    let case = harness.get_state::<DisputeCase>(&dispute);
+    assert_eq!(case.status, DisputeStatus::ResolvedInFavorOfChallenger);
+    let stored = harness.get_state::<ImpactReport>(&report);
+    assert_eq!(stored.verification_status, VerificationStatus::Rejected);
+
+    // The resolved dispute and its escrow are closed back to the challenger
+    assert_eq!(case.payer, challenger);
+    assert_eq!(
+        harness.process_instruction(&close(&verifier)),
+        Err(SOuLError::RentPayerMismatch.into())
+    );
+    let mut to_verifier = close(&challenger);
+    to_verifier.accounts[3].pubkey = verifier_tokens;
+    assert_eq!(harness.process_instruction(&to_verifier), Err(ImpactError::InvalidDispute.into()));
+    let mut of_other_mint = close(&challenger);
+    of_other_mint.accounts[2].pubkey = other_mint;
+    assert_eq!(harness.process_instruction(&of_other_mint), Err(ImpactError::InvalidDispute.into()));
+
+    // Tokens sent to the escrow after the stake was paid out go to the
+    // challenger instead of keeping it open
+    harness.set_token_account(escrow, &mint, &dispute, 5);
+    let challenger_lamports = harness.get_account(&challenger).unwrap().lamports;
+    let rent = harness.get_account(&dispute).unwrap().lamports + harness.get_account(&escrow).unwrap().lamports;
+    harness.process_instruction(&close(&challenger)).unwrap();
+    assert_eq!(harness.token_balance(&challenger_tokens), 305);
+    assert_eq!(harness.get_account(&dispute).unwrap().lamports, 0);
+    assert!(harness.get_account(&dispute).unwrap().data.is_empty());
+    assert_eq!(harness.get_account(&escrow).unwrap().lamports, 0);
+    assert_eq!(harness.get_account(&challenger).unwrap().lamports, challenger_lamports + rent);
+}
+
+#[test]
+fn test_update_metrics_recomputes_impact_score() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let (metrics, bump) = ImpactMetrics::find_address(&authority, &program_id);
+
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &authority).unwrap())
+        .unwrap();
+    let stored = harness.get_state::<ImpactMetrics>(&metrics);
+    assert_eq!(stored.authority, authority);
+    assert_eq!(stored.bump, bump);
+
+    let mut new_metrics = empty_metrics();
+    new_metrics.biodiversity_score = 500;
+    harness
+        .process_instruction(&ImpactInstruction::update_metrics(&program_id, &authority, new_metrics).unwrap())
+        .unwrap();
+    let stored = harness.get_state::<ImpactMetrics>(&metrics);
+    assert_eq!(stored.forest_area, 10);
+    assert_eq!(stored.biodiversity_score, 500);
+    assert_eq!(stored.impact_score, stored.calculate_impact_score());
+
+    let mut out_of_range = empty_metrics();
+    out_of_range.biodiversity_score = 1_001;
+    assert_eq!(
+        harness.process_instruction(&ImpactInstruction::update_metrics(&program_id, &authority, out_of_range).unwrap()),
+        Err(ImpactError::InvalidMetricsUpdate.into())
+    );
+}
+
+#[test]
+fn test_verified_report_can_only_be_disputed_within_its_window() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let verifier = harness.add_wallet();
+    let challenger = harness.add_wallet();
+    let admin = harness.initialize_roles();
+    let (mint, verifier_tokens) = add_soul(&mut harness, &admin, &verifier, 1_000 * SOUL);
+    let now = harness.clock().unix_timestamp;
+
+    let (metrics, _) = ImpactMetrics::find_address(&authority, &program_id);
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &authority).unwrap())
+        .unwrap();
+    let location = GeoLocation {
+        latitude: 0,
+        longitude: 0,
+        altitude: 0,
+    };
+    let (report, _) = ImpactReport::find_address(&metrics, &[9; 32], &program_id);
+    harness
+        .process_instruction(
+            &ImpactInstruction::submit_report(&program_id, &authority, location, empty_metrics(), [9; 32], LSTType::Forest).unwrap(),
+        )
+        .unwrap();
+
+    let (verifier_state, _) = VerifierState::find_address(&verifier, &program_id);
+    let stake_vault = harness.add_token_account(&mint, &verifier_state, 0);
+    harness
+        .process_instruction(
+            &ImpactInstruction::register_verifier(&program_id, &verifier, &mint, &verifier_tokens, &stake_vault, 1_000 * SOUL)
+                .unwrap(),
+        )
+        .unwrap();
+    harness
+        .process_instruction(
+            &ImpactInstruction::verify_report(&program_id, &verifier, &report, &metrics, true, [0; 32]).unwrap(),
+        )
+        .unwrap();
+    let stored = harness.get_state::<ImpactReport>(&report);
+    assert_eq!(stored.verified_at, Some(now));
+    assert!(!stored.is_final(now + ImpactReport::DISPUTE_WINDOW_SECONDS - 1));
+
+    // Once the window has passed the report is final
+    harness.warp_to_timestamp(now + ImpactReport::DISPUTE_WINDOW_SECONDS);
+    assert!(harness.get_state::<ImpactReport>(&report).is_final(now + ImpactReport::DISPUTE_WINDOW_SECONDS));
+    let challenger_tokens = harness.add_token_account(&mint, &challenger, 300);
+    let (dispute, _) = DisputeCase::find_address(&report, &challenger, &program_id);
+    let escrow = harness.add_token_account(&mint, &dispute, 0);
+    assert_eq!(
+        harness.process_instruction(
+            &ImpactInstruction::submit_dispute(
+                &program_id,
+                &challenger,
+                &report,
+                &challenger_tokens,
+                &escrow,
+                [1; 32],
+                300,
+            )
+            .unwrap(),
+        ),
+        Err(ImpactError::DisputeWindowClosed.into())
+    );
+}
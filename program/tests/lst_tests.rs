@@ -0,0 +1,652 @@
+mod common;
+
+use {
+    common::{with_token_program, ProgramHarness},
+    soul_sanctuary::{
+        access::{AccessError, AccessInstruction, CircuitBreaker, Role},
+        account::AccountState,
+        error::SOuLError,
+        event::SoulEvent,
+        instruction::ModuleTag,
+        lst::{LSTError, LSTInstruction, LSTPool, LSTType, StakerInfo},
+        processor::process_instruction,
+    },
+    solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey},
+    spl_token::state::{Account as TokenAccount, Mint},
+};
+
+const SECONDS_PER_YEAR: i64 = 365 * 24 * 60 * 60;
+
+struct PoolSetup {
+    admin: Pubkey,
+    authority: Pubkey,
+    pool: Pubkey,
+    soul_mint: Pubkey,
+    lst_mint: Pubkey,
+    pool_soul: Pubkey,
+}
+
+/// Create the Forest pool with a PoolManager as its authority
+fn initialize_pool(harness: &mut ProgramHarness, fee_rate: u64) -> PoolSetup {
+    let program_id = harness.program_id;
+    let admin = harness.initialize_roles();
+    let authority = harness.add_wallet();
+    harness
+        .process_instruction(&AccessInstruction::grant_role(&program_id, &admin, &authority, Role::PoolManager).unwrap())
+        .unwrap();
+    let soul_mint = harness.add_mint(&authority, 0, 9);
+    let lst_mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let (pool, _) = LSTPool::find_address(LSTType::Forest, &program_id);
+
+    harness
+        .process_instruction(
+            &LSTInstruction::initialize_pool(
+                &program_id,
+                &authority,
+                &soul_mint,
+                &lst_mint,
+                LSTType::Forest,
+                fee_rate,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let (pool_soul, _) = LSTPool::find_vault(&pool, &program_id);
+    PoolSetup {
+        admin,
+        authority,
+        pool,
+        soul_mint,
+        lst_mint,
+        pool_soul,
+    }
+}
+
+#[test]
+fn test_only_admins_and_pool_managers_create_pools() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let admin = harness.initialize_roles();
+    let stranger = harness.add_wallet();
+    let soul_mint = harness.add_mint(&admin, 0, 9);
+    let initialize = |authority: &Pubkey, lst_mint: &Pubkey| {
+        LSTInstruction::initialize_pool(&program_id, authority, &soul_mint, lst_mint, LSTType::Water, 0).unwrap()
+    };
+
+    let lst_mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    assert_eq!(
+        harness.process_instruction(&initialize(&stranger, &lst_mint)),
+        Err(AccessError::NotAuthorized.into())
+    );
+
+    harness.process_instruction(&initialize(&admin, &lst_mint)).unwrap();
+    let (pool, _) = LSTPool::find_address(LSTType::Water, &program_id);
+    assert_eq!(harness.get_state::<LSTPool>(&pool).authority, admin);
+}
+
+#[test]
+fn test_pool_pda_holds_lst_mint_authority() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+
+    let account = harness.get_account(&setup.pool).unwrap();
+    assert_eq!(account.owner, program_id);
+    let pool = LSTPool::unpack_account(&account.data).unwrap();
+    assert_eq!(pool.bump, LSTPool::find_address(LSTType::Forest, &program_id).1);
+    assert_eq!(pool.soul_mint, setup.soul_mint);
+    assert_eq!(pool.soul_vault, setup.pool_soul);
+
+    let vault = TokenAccount::unpack(&harness.get_account(&setup.pool_soul).unwrap().data).unwrap();
+    assert_eq!(vault.owner, setup.pool);
+    assert_eq!(vault.mint, setup.soul_mint);
+
+    let lst_mint = Mint::unpack(&harness.get_account(&setup.lst_mint).unwrap().data).unwrap();
+    assert_eq!(lst_mint.mint_authority, Some(setup.pool).into());
+}
+
+#[test]
+fn test_stake_creates_staker_info_and_unstake_is_signed_by_pool() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 10_000_000); // 1%
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&setup.soul_mint, &staker, 1_000);
+    let staker_lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+    let (staker_info, bump) = StakerInfo::find_address(&setup.pool, &staker, &program_id);
+
+    for amount in [300, 100] {
+        harness
+            .process_instruction(
+                &LSTInstruction::stake(
+                    &program_id,
+                    &staker,
+                    LSTType::Forest,
+                    &staker_soul,
+                    &setup.pool_soul,
+                    &staker_lst,
+                    &setup.lst_mint,
+                    amount,
+                )
+                .unwrap(),
+            )
+            .unwrap();
+    }
+
+    let info = harness.get_state::<StakerInfo>(&staker_info);
+    assert_eq!(info.owner, staker);
+    assert_eq!(info.bump, bump);
+    assert_eq!(info.staked_amount, 400);
+    assert_eq!(harness.token_balance(&staker_lst), 400);
+    assert_eq!(harness.token_balance(&setup.pool_soul), 400);
+
+    harness
+        .process_instruction(
+            &LSTInstruction::request_unstake(&program_id, &staker, LSTType::Forest, &staker_lst, 400).unwrap(),
+        )
+        .unwrap();
+    let close = LSTInstruction::close_staker_info(&program_id, LSTType::Forest, &staker, &staker).unwrap();
+    assert_eq!(harness.process_instruction(&close), Err(LSTError::StakeNotEmpty.into()));
+
+    let now = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(now + StakerInfo::UNSTAKE_COOLDOWN);
+    harness
+        .process_instruction(
+            &LSTInstruction::execute_unstake(
+                &program_id,
+                &staker,
+                LSTType::Forest,
+                &staker_lst,
+                &staker_soul,
+                &setup.pool_soul,
+                &setup.lst_mint,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&staker_lst), 0);
+    assert_eq!(harness.token_balance(&staker_soul), 996);
+    assert_eq!(harness.token_balance(&setup.pool_soul), 4);
+
+    // Rewards earned during the cooldown must be claimed before closing
+    assert_eq!(harness.process_instruction(&close), Err(LSTError::StakeNotEmpty.into()));
+    harness
+        .process_instruction(
+            &LSTInstruction::claim_rewards(&program_id, &staker, LSTType::Forest, &staker_soul, &setup.pool_soul)
+                .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&staker_soul), 997);
+
+    // With nothing left staked or to claim, anyone may close the staker info back to its payer
+    assert_eq!(harness.get_state::<StakerInfo>(&staker_info).payer, staker);
+    let staker_lamports = harness.get_account(&staker).unwrap().lamports;
+    let rent = harness.get_account(&staker_info).unwrap().lamports;
+    let mut wrong_payer = close.clone();
+    wrong_payer.accounts[1].pubkey = setup.authority;
+    assert_eq!(harness.process_instruction(&wrong_payer), Err(SOuLError::RentPayerMismatch.into()));
+    harness.process_instruction(&close).unwrap();
+    assert_eq!(harness.get_account(&staker_info).unwrap().lamports, 0);
+    assert_eq!(harness.get_account(&staker).unwrap().lamports, staker_lamports + rent);
+}
+
+#[test]
+fn test_claim_rewards_is_paid_by_pool() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&setup.soul_mint, &staker, 1_000_000);
+    let staker_lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+    harness
+        .process_instruction(
+            &LSTInstruction::stake(
+                &program_id,
+                &staker,
+                LSTType::Forest,
+                &staker_soul,
+                &setup.pool_soul,
+                &staker_lst,
+                &setup.lst_mint,
+                1_000_000,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let claim =
+        LSTInstruction::claim_rewards(&program_id, &staker, LSTType::Forest, &staker_soul, &setup.pool_soul).unwrap();
+    assert_eq!(
+        harness.process_instruction(&claim),
+        Err(LSTError::NoRewardsToHarvest.into())
+    );
+
+    // 15% a year on the forest pool, which the staked SOuL alone cannot pay
+    let now = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(now + SECONDS_PER_YEAR);
+    assert_eq!(harness.process_instruction(&claim), Err(LSTError::InsufficientFunds.into()));
+
+    harness.set_token_account(setup.pool_soul, &setup.soul_mint, &setup.pool, 2_000_000);
+    harness.process_instruction(&claim).unwrap();
+    assert_eq!(harness.token_balance(&staker_soul), 150_000);
+    assert_eq!(harness.token_balance(&setup.pool_soul), 1_850_000);
+    assert_eq!(
+        harness.process_instruction(&claim),
+        Err(LSTError::NoRewardsToHarvest.into())
+    );
+}
+
+#[test]
+fn test_rewards_accrue_only_while_staked() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+
+    let [early, late] = [harness.add_wallet(), harness.add_wallet()];
+    let accounts = [early, late].map(|staker| {
+        let soul = harness.add_token_account(&setup.soul_mint, &staker, 2_000_000_000);
+        let lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+        (soul, lst)
+    });
+    let stake = |staker: &Pubkey, (soul, lst): (Pubkey, Pubkey), amount: u64| {
+        LSTInstruction::stake(
+            &program_id,
+            staker,
+            LSTType::Forest,
+            &soul,
+            &setup.pool_soul,
+            &lst,
+            &setup.lst_mint,
+            amount,
+        )
+        .unwrap()
+    };
+    let claim = |staker: &Pubkey, (soul, _): (Pubkey, Pubkey)| {
+        LSTInstruction::claim_rewards(&program_id, staker, LSTType::Forest, &soul, &setup.pool_soul).unwrap()
+    };
+
+    harness.process_instruction(&stake(&early, accounts[0], 1_000_000_000)).unwrap();
+    let start = harness.clock().unix_timestamp;
+
+    // A year on, a late staker earns only for the minute it has been staked
+    harness.warp_to_timestamp(start + SECONDS_PER_YEAR);
+    harness.process_instruction(&stake(&late, accounts[1], 1_000_000_000)).unwrap();
+    harness.set_token_account(setup.pool_soul, &setup.soul_mint, &setup.pool, 3_000_000_000);
+    harness.warp_to_timestamp(start + SECONDS_PER_YEAR + 60);
+    harness.process_instruction(&claim(&late, accounts[1])).unwrap();
+    assert_eq!(harness.token_balance(&accounts[1].0), 1_000_000_285);
+
+    // Staking more settles what the earlier stake earned before it grows
+    harness.process_instruction(&stake(&early, accounts[0], 1_000_000_000)).unwrap();
+    harness.warp_to_timestamp(start + SECONDS_PER_YEAR + 120);
+    harness.process_instruction(&claim(&early, accounts[0])).unwrap();
+    assert_eq!(harness.token_balance(&accounts[0].0), 150_000_855);
+    let info = harness.get_state::<StakerInfo>(&StakerInfo::find_address(&setup.pool, &early, &program_id).0);
+    assert_eq!(info.accrued_rewards, 0);
+    assert_eq!(info.rewards_debt, 150_000_855);
+}
+
+#[test]
+fn test_restaking_after_close_earns_nothing_twice() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&setup.soul_mint, &staker, 1_000_000);
+    let staker_lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+    let stake = LSTInstruction::stake(
+        &program_id,
+        &staker,
+        LSTType::Forest,
+        &staker_soul,
+        &setup.pool_soul,
+        &staker_lst,
+        &setup.lst_mint,
+        1_000_000,
+    )
+    .unwrap();
+    let claim =
+        LSTInstruction::claim_rewards(&program_id, &staker, LSTType::Forest, &staker_soul, &setup.pool_soul).unwrap();
+    let request_unstake =
+        LSTInstruction::request_unstake(&program_id, &staker, LSTType::Forest, &staker_lst, 1_000_000).unwrap();
+    let execute_unstake = LSTInstruction::execute_unstake(
+        &program_id,
+        &staker,
+        LSTType::Forest,
+        &staker_lst,
+        &staker_soul,
+        &setup.pool_soul,
+        &setup.lst_mint,
+    )
+    .unwrap();
+    let close = LSTInstruction::close_staker_info(&program_id, LSTType::Forest, &staker, &staker).unwrap();
+
+    harness.process_instruction(&stake).unwrap();
+    harness.set_token_account(setup.pool_soul, &setup.soul_mint, &setup.pool, 10_000_000);
+    let start = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(start + SECONDS_PER_YEAR);
+    harness.process_instruction(&claim).unwrap();
+    harness.process_instruction(&request_unstake).unwrap();
+    harness.warp_to_timestamp(start + SECONDS_PER_YEAR + StakerInfo::UNSTAKE_COOLDOWN);
+    harness.process_instruction(&execute_unstake).unwrap();
+
+    // The cooldown's rewards are still owed, so the account cannot be closed yet
+    assert_eq!(harness.process_instruction(&close), Err(LSTError::StakeNotEmpty.into()));
+    harness.process_instruction(&claim).unwrap();
+    harness.process_instruction(&close).unwrap();
+    let paid = harness.token_balance(&staker_soul);
+    assert_eq!(paid, 1_000_000 + 150_000 + 2_876);
+
+    // A fresh staker info starts accruing when it is created
+    harness.process_instruction(&stake).unwrap();
+    assert_eq!(harness.process_instruction(&claim), Err(LSTError::NoRewardsToHarvest.into()));
+    assert_eq!(harness.token_balance(&staker_soul), paid - 1_000_000);
+}
+
+#[test]
+fn test_pool_authority_updates_fee_and_withdraws() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+
+    harness
+        .process_instruction(
+            &LSTInstruction::update_pool(&program_id, &setup.authority, LSTType::Forest, Some(5_000_000)).unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.get_state::<LSTPool>(&setup.pool).fee_rate, 5_000_000);
+
+    let stranger = harness.add_wallet();
+    assert_eq!(
+        harness.process_instruction(
+            &LSTInstruction::update_pool(&program_id, &stranger, LSTType::Forest, Some(0)).unwrap()
+        ),
+        Err(LSTError::InvalidAuthority.into())
+    );
+    assert_eq!(
+        harness.process_instruction(
+            &LSTInstruction::update_pool(&program_id, &setup.authority, LSTType::Forest, Some(100_000_001))
+                .unwrap()
+        ),
+        Err(LSTError::InvalidFeeRate.into())
+    );
+
+    let pool_soul = setup.pool_soul;
+    harness.set_token_account(pool_soul, &setup.soul_mint, &setup.pool, 500);
+    let authority_soul = harness.add_token_account(&setup.soul_mint, &setup.authority, 0);
+    harness.take_logged_data();
+    harness
+        .process_instruction(
+            &LSTInstruction::emergency_withdraw(
+                &program_id,
+                &setup.authority,
+                LSTType::Forest,
+                &pool_soul,
+                &authority_soul,
+                200,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&pool_soul), 300);
+    assert_eq!(harness.token_balance(&authority_soul), 200);
+    assert_eq!(
+        harness
+            .take_logged_data()
+            .iter()
+            .filter_map(|fields| SoulEvent::from_log_data(fields))
+            .collect::<Vec<_>>(),
+        vec![SoulEvent::EmergencyWithdrawn {
+            pool: setup.pool,
+            authority: setup.authority,
+            destination: authority_soul,
+            amount: 200,
+            timestamp: harness.clock().unix_timestamp,
+        }]
+    );
+}
+
+#[test]
+fn test_lst_circuit_breaker_caps_soul_leaving_pools() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+    harness.set_token_account(setup.pool_soul, &setup.soul_mint, &setup.pool, 1_000_000);
+
+    let admin = setup.admin;
+    let configure = |max_outflow: u64| {
+        AccessInstruction::configure_circuit_breaker(&program_id, &admin, ModuleTag::LST, 9_000, max_outflow)
+            .unwrap()
+    };
+    harness.process_instruction(&configure(100_000)).unwrap();
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&setup.soul_mint, &staker, 1_000_000);
+    let staker_lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+    harness
+        .process_instruction(
+            &LSTInstruction::stake(
+                &program_id,
+                &staker,
+                LSTType::Forest,
+                &staker_soul,
+                &setup.pool_soul,
+                &staker_lst,
+                &setup.lst_mint,
+                1_000_000,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    let now = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(now + SECONDS_PER_YEAR);
+    let claim =
+        LSTInstruction::claim_rewards(&program_id, &staker, LSTType::Forest, &staker_soul, &setup.pool_soul).unwrap();
+    assert_eq!(
+        harness.process_instruction(&claim),
+        Err(AccessError::OutflowLimitExceeded.into())
+    );
+    assert_eq!(harness.token_balance(&setup.pool_soul), 2_000_000);
+
+    // Raising the limit lets the claim through and counts it in the window
+    harness.process_instruction(&configure(200_000)).unwrap();
+    harness.process_instruction(&claim).unwrap();
+    assert_eq!(harness.token_balance(&staker_soul), 150_000);
+    let (breaker, _) = CircuitBreaker::find_address(ModuleTag::LST, &program_id);
+    assert_eq!(harness.get_state::<CircuitBreaker>(&breaker).outflow, 150_000);
+
+    // An emergency withdrawal counts against the same window
+    let authority_soul = harness.add_token_account(&setup.soul_mint, &setup.authority, 0);
+    let withdraw = |amount| {
+        LSTInstruction::emergency_withdraw(
+            &program_id,
+            &setup.authority,
+            LSTType::Forest,
+            &setup.pool_soul,
+            &authority_soul,
+            amount,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&withdraw(50_001)),
+        Err(AccessError::OutflowLimitExceeded.into())
+    );
+    harness.process_instruction(&withdraw(50_000)).unwrap();
+    assert_eq!(harness.token_balance(&authority_soul), 50_000);
+    assert_eq!(harness.get_state::<CircuitBreaker>(&breaker).outflow, 200_000);
+}
+
+#[test]
+fn test_pool_signs_only_for_the_token_program() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+    harness.set_token_account(setup.pool_soul, &setup.soul_mint, &setup.pool, 1_000);
+    let fake_program = harness.add_wallet();
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&setup.soul_mint, &staker, 1_000);
+    let staker_lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+    let stake = |source: &Pubkey| {
+        LSTInstruction::stake(
+            &program_id,
+            &staker,
+            LSTType::Forest,
+            source,
+            &setup.pool_soul,
+            &staker_lst,
+            &setup.lst_mint,
+            500,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&with_token_program(stake(&staker_soul), &fake_program)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    // A SOuL account must be held by the token program, whatever its data says
+    let mut forged = harness.get_account(&staker_soul).unwrap().clone();
+    forged.owner = fake_program;
+    let forged_soul = Pubkey::new_unique();
+    harness.add_account(forged_soul, forged);
+    assert_eq!(
+        harness.process_instruction(&stake(&forged_soul)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    harness.process_instruction(&stake(&staker_soul)).unwrap();
+    harness
+        .process_instruction(
+            &LSTInstruction::request_unstake(&program_id, &staker, LSTType::Forest, &staker_lst, 500).unwrap(),
+        )
+        .unwrap();
+    let now = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(now + StakerInfo::UNSTAKE_COOLDOWN);
+
+    let unstake = LSTInstruction::execute_unstake(
+        &program_id,
+        &staker,
+        LSTType::Forest,
+        &staker_lst,
+        &staker_soul,
+        &setup.pool_soul,
+        &setup.lst_mint,
+    )
+    .unwrap();
+    let claim =
+        LSTInstruction::claim_rewards(&program_id, &staker, LSTType::Forest, &staker_soul, &setup.pool_soul).unwrap();
+    let authority_soul = harness.add_token_account(&setup.soul_mint, &setup.authority, 0);
+    let withdraw = LSTInstruction::emergency_withdraw(
+        &program_id,
+        &setup.authority,
+        LSTType::Forest,
+        &setup.pool_soul,
+        &authority_soul,
+        1_000,
+    )
+    .unwrap();
+    for instruction in [unstake, claim, withdraw] {
+        assert_eq!(
+            harness.process_instruction(&with_token_program(instruction, &fake_program)),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+    assert_eq!(harness.token_balance(&setup.pool_soul), 1_500);
+}
+
+#[test]
+fn test_pool_rejects_foreign_vault_and_mint() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_pool(&mut harness, 0);
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&setup.soul_mint, &staker, 1_000);
+    let staker_lst = harness.add_token_account(&setup.lst_mint, &staker, 0);
+    let other_mint = harness.add_mint(&staker, 0, 9);
+    let staker_other = harness.add_token_account(&other_mint, &staker, 1_000);
+    // Owned by the pool PDA, but not the vault the pool was created with
+    let decoy_vault = harness.add_token_account(&setup.soul_mint, &setup.pool, 1_000);
+
+    let stake = |source: &Pubkey, vault: &Pubkey| {
+        LSTInstruction::stake(
+            &program_id,
+            &staker,
+            LSTType::Forest,
+            source,
+            vault,
+            &staker_lst,
+            &setup.lst_mint,
+            500,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&stake(&staker_soul, &decoy_vault)),
+        Err(LSTError::InvalidVault.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&stake(&staker_other, &setup.pool_soul)),
+        Err(LSTError::InvalidMint.into())
+    );
+    harness.process_instruction(&stake(&staker_soul, &setup.pool_soul)).unwrap();
+
+    harness
+        .process_instruction(
+            &LSTInstruction::request_unstake(&program_id, &staker, LSTType::Forest, &staker_lst, 500).unwrap(),
+        )
+        .unwrap();
+    let now = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(now + StakerInfo::UNSTAKE_COOLDOWN);
+
+    let unstake = |destination: &Pubkey, vault: &Pubkey| {
+        LSTInstruction::execute_unstake(
+            &program_id,
+            &staker,
+            LSTType::Forest,
+            &staker_lst,
+            destination,
+            vault,
+            &setup.lst_mint,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&unstake(&staker_soul, &decoy_vault)),
+        Err(LSTError::InvalidVault.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&unstake(&staker_other, &setup.pool_soul)),
+        Err(LSTError::InvalidMint.into())
+    );
+
+    let claim = |destination: &Pubkey, vault: &Pubkey| {
+        LSTInstruction::claim_rewards(&program_id, &staker, LSTType::Forest, destination, vault).unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&claim(&staker_soul, &decoy_vault)),
+        Err(LSTError::InvalidVault.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&claim(&staker_other, &setup.pool_soul)),
+        Err(LSTError::InvalidMint.into())
+    );
+
+    let withdraw = |vault: &Pubkey| {
+        LSTInstruction::emergency_withdraw(&program_id, &setup.authority, LSTType::Forest, vault, &staker_soul, 100)
+            .unwrap()
+    };
+    assert_eq!(harness.process_instruction(&withdraw(&decoy_vault)), Err(LSTError::InvalidVault.into()));
+
+    harness.process_instruction(&unstake(&staker_soul, &setup.pool_soul)).unwrap();
+    assert_eq!(harness.token_balance(&staker_soul), 1_000);
+    assert_eq!(harness.token_balance(&decoy_vault), 1_000);
+}
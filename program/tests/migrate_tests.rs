@@ -0,0 +1,616 @@
+mod common;
+
+use {
+    borsh::BorshSerialize,
+    common::{Account, ProgramHarness},
+    soul_sanctuary::{
+        access::ProgramConfig,
+        account::{AccountState, AccountType},
+        error::SOuLError,
+        game::{GameInstruction, GameState, PlayerState},
+        instruction::{ModuleTag, SOuLInstruction},
+        lst::{LSTPool, LSTType, StakerInfo},
+        processor::process_instruction,
+        state::SOuLToken,
+        impact::{GeoLocation, ImpactMetric, ImpactMetrics, ImpactReport, VerificationStatus},
+        vesting::{TrancheMode, VestingMilestone, VestingSchedule, VestingTranche, VestingType},
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+};
+
+fn game_state(authority: Pubkey, bump: u8) -> GameState {
+    GameState {
+        is_initialized: true,
+        authority,
+        total_players: 3,
+        total_quests_completed: 5,
+        total_rewards_distributed: 700,
+        last_update: 42,
+        bump,
+    }
+}
+
+/// Store `body` without a header, as version 0 of the program wrote it
+fn add_v0_account(harness: &mut ProgramHarness, key: Pubkey, data: Vec<u8>) {
+    let lamports = ProgramHarness::rent_exempt(data.len());
+    harness.add_account(
+        key,
+        Account {
+            lamports,
+            data,
+            owner: harness.program_id,
+            executable: false,
+        },
+    );
+}
+
+#[test]
+fn test_load_rejects_other_account_types_and_versions() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let (game, bump) = GameState::find_address(&program_id);
+
+    let mut data = vec![0; GameState::SPACE];
+    data[..8].copy_from_slice(&GameState::DISCRIMINATOR);
+    data[8] = GameState::VERSION;
+    game_state(authority, bump).serialize(&mut &mut data[9..]).unwrap();
+
+    // A game state is not a player state, even though both are program owned
+    assert_eq!(
+        PlayerState::unpack_account(&data).err(),
+        Some(SOuLError::InvalidAccountType.into())
+    );
+
+    data[8] = GameState::VERSION + 1;
+    assert_eq!(
+        GameState::unpack_account(&data).err(),
+        Some(SOuLError::AccountVersionMismatch.into())
+    );
+
+    let lamports = ProgramHarness::rent_exempt(data.len());
+    harness.add_account(
+        game,
+        Account {
+            lamports,
+            data,
+            owner: program_id,
+            executable: false,
+        },
+    );
+
+    let player = harness.add_wallet();
+    let (player_state, _) = PlayerState::find_address(&player, &program_id);
+    assert_eq!(
+        harness.process_instruction(&Instruction {
+            program_id,
+            accounts: vec![
+                AccountMeta::new(player, true),
+                AccountMeta::new(player_state, false),
+                AccountMeta::new(game, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+                ProgramConfig::account_meta(&program_id),
+            ],
+            data: ModuleTag::Game.pack(&GameInstruction::InitializePlayer).unwrap(),
+        }),
+        Err(SOuLError::AccountVersionMismatch.into())
+    );
+}
+
+#[test]
+fn test_migrate_adds_header_to_v0_account() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let payer = harness.add_wallet();
+    let (game, bump) = GameState::find_address(&program_id);
+    let state = game_state(payer, bump);
+    add_v0_account(&mut harness, game, state.try_to_vec().unwrap());
+
+    let migrate = SOuLInstruction::migrate(&program_id, &payer, &game, AccountType::GameState, None).unwrap();
+    harness.process_instruction(&migrate).unwrap();
+
+    let account = harness.get_account(&game).unwrap();
+    assert_eq!(account.data.len(), GameState::SPACE);
+    assert_eq!(account.lamports, ProgramHarness::rent_exempt(GameState::SPACE));
+    let migrated = harness.get_state::<GameState>(&game);
+    assert_eq!(migrated.authority, state.authority);
+    assert_eq!(migrated.total_rewards_distributed, state.total_rewards_distributed);
+    assert_eq!(migrated.last_update, state.last_update);
+    assert_eq!(migrated.bump, state.bump);
+
+    // Running it again leaves the account as it is
+    let before = harness.get_account(&game).cloned();
+    harness.process_instruction(&migrate).unwrap();
+    assert_eq!(harness.get_account(&game).cloned(), before);
+}
+
+#[test]
+fn test_migrate_fills_fields_added_since_v0() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let payer = harness.add_wallet();
+    let owner = Pubkey::new_unique();
+    let (pool, _) = LSTPool::find_address(LSTType::Ocean, &program_id);
+    let (staker_info, bump) = StakerInfo::find_address(&pool, &owner, &program_id);
+
+    // Version 0 had no `pool` field
+    let mut v0 = Vec::new();
+    owner.serialize(&mut v0).unwrap();
+    250u64.serialize(&mut v0).unwrap();
+    0u64.serialize(&mut v0).unwrap();
+    17i64.serialize(&mut v0).unwrap();
+    bump.serialize(&mut v0).unwrap();
+    None::<()>.serialize(&mut v0).unwrap();
+    add_v0_account(&mut harness, staker_info, v0);
+
+    let migrate = |pool: &Pubkey| {
+        SOuLInstruction::migrate(&program_id, &payer, &staker_info, AccountType::StakerInfo, Some(pool)).unwrap()
+    };
+
+    // The pool is part of the address, so the wrong pool cannot be filled in
+    let (other_pool, _) = LSTPool::find_address(LSTType::Forest, &program_id);
+    assert_eq!(
+        harness.process_instruction(&migrate(&other_pool)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+
+    harness.process_instruction(&migrate(&pool)).unwrap();
+
+    let info = harness.get_state::<StakerInfo>(&staker_info);
+    assert_eq!(info.owner, owner);
+    assert_eq!(info.pool, pool);
+    assert_eq!(info.staked_amount, 250);
+    assert_eq!(info.last_stake_time, 17);
+    assert_eq!(info.bump, bump);
+    assert_eq!(info.payer, owner);
+    assert_eq!(info.accrued_rewards, 0);
+    assert_eq!(info.last_accrual_time, harness.clock().unix_timestamp);
+    assert_eq!(harness.get_account(&staker_info).unwrap().data.len(), StakerInfo::SPACE);
+}
+
+#[test]
+fn test_migrate_binds_v1_pool_to_its_soul_vault() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let soul_mint = harness.add_mint(&authority, 0, 9);
+    let other_mint = harness.add_mint(&authority, 0, 9);
+    let lst_mint = Pubkey::new_unique();
+    let (pool, bump) = LSTPool::find_address(LSTType::Water, &program_id);
+    let vault = harness.add_token_account(&soul_mint, &pool, 700);
+
+    // Version 1 did not record the vault holding the pool's SOuL
+    let mut data = LSTPool::DISCRIMINATOR.to_vec();
+    data.push(1);
+    true.serialize(&mut data).unwrap();
+    authority.serialize(&mut data).unwrap();
+    soul_mint.serialize(&mut data).unwrap();
+    lst_mint.serialize(&mut data).unwrap();
+    700u64.serialize(&mut data).unwrap();
+    1_000_000_000u64.serialize(&mut data).unwrap();
+    0u64.serialize(&mut data).unwrap();
+    5i64.serialize(&mut data).unwrap();
+    LSTType::Water.serialize(&mut data).unwrap();
+    2_000u64.serialize(&mut data).unwrap();
+    bump.serialize(&mut data).unwrap();
+    add_v0_account(&mut harness, pool, data);
+
+    let migrate = |payer: &Pubkey, vault: &Pubkey| {
+        SOuLInstruction::migrate(&program_id, payer, &pool, AccountType::LSTPool, Some(vault)).unwrap()
+    };
+
+    // Only the pool authority names the vault, which must hold the pool's SOuL
+    let stranger = harness.add_wallet();
+    assert_eq!(
+        harness.process_instruction(&migrate(&stranger, &vault)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+    let foreign = harness.add_token_account(&other_mint, &pool, 0);
+    assert_eq!(
+        harness.process_instruction(&migrate(&authority, &foreign)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+    let unowned = harness.add_token_account(&soul_mint, &authority, 0);
+    assert_eq!(
+        harness.process_instruction(&migrate(&authority, &unowned)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+
+    harness.process_instruction(&migrate(&authority, &vault)).unwrap();
+    let migrated = harness.get_state::<LSTPool>(&pool);
+    assert_eq!(migrated.soul_vault, vault);
+    assert_eq!(migrated.soul_mint, soul_mint);
+    assert_eq!(migrated.lst_mint, lst_mint);
+    assert_eq!(migrated.total_staked, 700);
+    assert_eq!(migrated.fee_rate, 2_000);
+    assert_eq!(migrated.bump, bump);
+    assert_eq!(harness.get_account(&pool).unwrap().data.len(), LSTPool::SPACE);
+}
+
+#[test]
+fn test_migrate_fills_payer_and_terms_of_v1_vesting_schedule() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let beneficiary = harness.add_wallet();
+    let (schedule, bump) = VestingSchedule::find_address(&beneficiary, 3, &program_id);
+    let mint = harness.add_mint(&beneficiary, 1_000, 9);
+
+    // Version 1 did not record who paid the schedule's rent
+    let mut data = VestingSchedule::DISCRIMINATOR.to_vec();
+    data.push(1);
+    true.serialize(&mut data).unwrap();
+    Pubkey::new_unique().serialize(&mut data).unwrap();
+    10i64.serialize(&mut data).unwrap();
+    110i64.serialize(&mut data).unwrap();
+    1_000u64.serialize(&mut data).unwrap();
+    250u64.serialize(&mut data).unwrap();
+    VestingType::Advisor.serialize(&mut data).unwrap();
+    beneficiary.serialize(&mut data).unwrap();
+    3u64.serialize(&mut data).unwrap();
+    bump.serialize(&mut data).unwrap();
+    add_v0_account(&mut harness, schedule, data);
+
+    let migrate = |vault: &Pubkey| {
+        SOuLInstruction::migrate(&program_id, &beneficiary, &schedule, AccountType::VestingSchedule, Some(vault))
+            .unwrap()
+    };
+
+    // The vault must be held by the schedule and cover what is left to release
+    let stranger_tokens = harness.add_token_account(&mint, &beneficiary, 750);
+    assert_eq!(
+        harness.process_instruction(&migrate(&stranger_tokens)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+    let short_vault = harness.add_token_account(&mint, &schedule, 749);
+    assert_eq!(
+        harness.process_instruction(&migrate(&short_vault)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+
+    let vault = harness.add_token_account(&mint, &schedule, 750);
+    harness.process_instruction(&migrate(&vault)).unwrap();
+
+    // The paying authority was never stored, so the rent goes to the beneficiary
+    let migrated = harness.get_state::<VestingSchedule>(&schedule);
+    assert_eq!(migrated.original_beneficiary, beneficiary);
+    assert_eq!(migrated.released_amount, 250);
+    assert_eq!(migrated.bump, bump);
+    assert_eq!(migrated.payer, beneficiary);
+    // The schedule keeps the end it was created with, and its type's cliff
+    // no longer than that
+    assert_eq!(migrated.duration_seconds, 100);
+    assert_eq!(migrated.end_timestamp, 110);
+    assert_eq!(migrated.cliff_seconds, 100);
+    assert_eq!(migrated.release_interval_seconds, VestingSchedule::DEFAULT_RELEASE_INTERVAL);
+    assert!(migrated.tranches.is_empty() && migrated.milestones.is_empty());
+    // The beneficiary recorded as payer cannot revoke their own schedule
+    assert_eq!(migrated.revoke_authority, None);
+    assert_eq!(migrated.revoked_at, None);
+    assert_eq!(migrated.vault, vault);
+    assert_eq!(migrated.mint, mint);
+    assert_eq!(harness.get_account(&schedule).unwrap().data.len(), VestingSchedule::SPACE);
+}
+
+/// Header and fixed fields of a version 3 vesting schedule, whose later
+/// versions append their fields
+fn vesting_v3_data(
+    version: u8,
+    vesting_type: VestingType,
+    beneficiary: Pubkey,
+    payer: Pubkey,
+    nonce: u64,
+    bump: u8,
+) -> Vec<u8> {
+    let mut data = VestingSchedule::DISCRIMINATOR.to_vec();
+    data.push(version);
+    true.serialize(&mut data).unwrap();
+    beneficiary.serialize(&mut data).unwrap();
+    10i64.serialize(&mut data).unwrap();
+    60i64.serialize(&mut data).unwrap();
+    500u64.serialize(&mut data).unwrap();
+    100u64.serialize(&mut data).unwrap();
+    vesting_type.serialize(&mut data).unwrap();
+    beneficiary.serialize(&mut data).unwrap();
+    nonce.serialize(&mut data).unwrap();
+    bump.serialize(&mut data).unwrap();
+    payer.serialize(&mut data).unwrap();
+    0i64.serialize(&mut data).unwrap();
+    50i64.serialize(&mut data).unwrap();
+    1i64.serialize(&mut data).unwrap();
+    data
+}
+
+#[test]
+fn test_migrate_grows_v4_tranched_schedule_to_fit_new_fields() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let beneficiary = harness.add_wallet();
+    let (schedule, bump) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let mint = harness.add_mint(&beneficiary, 1_000, 9);
+    let vault = harness.add_token_account(&mint, &schedule, 400);
+
+    // Version 4 accounts were sized to their tranches, past `SPACE`
+    let tranches: Vec<_> = (1..=10)
+        .map(|step| VestingTranche {
+            unlock_timestamp: 10 + 5 * step,
+            amount: 50,
+            mode: TrancheMode::Instant,
+        })
+        .collect();
+    let mut data = vesting_v3_data(4, VestingType::Ecosystem, beneficiary, beneficiary, 0, bump);
+    tranches.serialize(&mut data).unwrap();
+    assert!(data.len() > VestingSchedule::SPACE);
+    add_v0_account(&mut harness, schedule, data);
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::migrate(&program_id, &beneficiary, &schedule, AccountType::VestingSchedule, Some(&vault))
+                .unwrap(),
+        )
+        .unwrap();
+
+    let migrated = harness.get_state::<VestingSchedule>(&schedule);
+    assert_eq!(migrated.tranches, tranches);
+    assert_eq!(migrated.released_amount, 100);
+    assert!(migrated.milestones.is_empty());
+    assert_eq!(migrated.vault, vault);
+    assert_eq!(harness.get_account(&schedule).unwrap().data.len(), VestingSchedule::space(10, 0));
+}
+
+#[test]
+fn test_migrate_grows_v5_milestone_schedule_to_fit_new_fields() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let beneficiary = harness.add_wallet();
+    let (schedule, bump) = VestingSchedule::find_address(&beneficiary, 1, &program_id);
+    let mint = harness.add_mint(&beneficiary, 1_000, 9);
+    let vault = harness.add_token_account(&mint, &schedule, 400);
+
+    let project = Pubkey::new_unique();
+    let milestones: Vec<_> = (1..=5)
+        .map(|step| VestingMilestone {
+            metric: ImpactMetric::CarbonOffset,
+            target: step,
+            amount: 100,
+            report: (step == 1).then(Pubkey::new_unique),
+        })
+        .collect();
+    let mut data = vesting_v3_data(5, VestingType::ImpactMilestone, beneficiary, beneficiary, 1, bump);
+    Vec::<VestingTranche>::new().serialize(&mut data).unwrap();
+    project.serialize(&mut data).unwrap();
+    milestones.serialize(&mut data).unwrap();
+    assert!(data.len() > VestingSchedule::SPACE);
+    add_v0_account(&mut harness, schedule, data);
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::migrate(&program_id, &beneficiary, &schedule, AccountType::VestingSchedule, Some(&vault))
+                .unwrap(),
+        )
+        .unwrap();
+
+    let migrated = harness.get_state::<VestingSchedule>(&schedule);
+    assert_eq!(migrated.project, project);
+    assert_eq!(migrated.milestones, milestones);
+    assert_eq!(migrated.vault, vault);
+    assert_eq!(harness.get_account(&schedule).unwrap().data.len(), VestingSchedule::space(0, 5));
+}
+
+#[test]
+fn test_migrate_lets_only_the_revoke_authority_pick_a_vault() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let creator = harness.add_wallet();
+    let beneficiary = harness.add_wallet();
+    let (schedule, bump) = VestingSchedule::find_address(&beneficiary, 2, &program_id);
+    let mint = harness.add_mint(&creator, 1_000, 9);
+    let vault = harness.add_token_account(&mint, &schedule, 400);
+
+    let mut data = vesting_v3_data(5, VestingType::Team, beneficiary, creator, 2, bump);
+    Vec::<VestingTranche>::new().serialize(&mut data).unwrap();
+    Pubkey::default().serialize(&mut data).unwrap();
+    Vec::<VestingMilestone>::new().serialize(&mut data).unwrap();
+    add_v0_account(&mut harness, schedule, data);
+    let migrate = |payer: &Pubkey, vault: &Pubkey| {
+        SOuLInstruction::migrate(&program_id, payer, &schedule, AccountType::VestingSchedule, Some(vault)).unwrap()
+    };
+
+    // A beneficiary could otherwise bind a vault of a worthless mint and
+    // strand the unvested tokens the creator can recover
+    let junk_mint = harness.add_mint(&beneficiary, 1_000, 9);
+    let junk_vault = harness.add_token_account(&junk_mint, &schedule, 1_000);
+    assert_eq!(
+        harness.process_instruction(&migrate(&beneficiary, &junk_vault)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&migrate(&beneficiary, &vault)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+
+    harness.process_instruction(&migrate(&creator, &vault)).unwrap();
+    let migrated = harness.get_state::<VestingSchedule>(&schedule);
+    assert_eq!(migrated.revoke_authority, Some(creator));
+    assert_eq!(migrated.vault, vault);
+    assert_eq!(migrated.mint, mint);
+}
+
+#[test]
+fn test_migrate_upgrades_v1_soul_token_to_legacy_token_program() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let payer = harness.add_wallet();
+    let authority = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (token, bump) = SOuLToken::find_address(&mint, &program_id);
+
+    // Version 1 predates Token-2022 support and its transfer fee fields
+    let mut data = SOuLToken::DISCRIMINATOR.to_vec();
+    data.push(1);
+    true.serialize(&mut data).unwrap();
+    authority.serialize(&mut data).unwrap();
+    mint.serialize(&mut data).unwrap();
+    5_000u64.serialize(&mut data).unwrap();
+    9u8.serialize(&mut data).unwrap();
+    bump.serialize(&mut data).unwrap();
+    7u8.serialize(&mut data).unwrap();
+    add_v0_account(&mut harness, token, data);
+    assert_eq!(
+        SOuLToken::unpack_account(&harness.get_account(&token).unwrap().data).err(),
+        Some(SOuLError::AccountVersionMismatch.into())
+    );
+
+    let migrate = SOuLInstruction::migrate(&program_id, &payer, &token, AccountType::SOuLToken, None).unwrap();
+    harness.process_instruction(&migrate).unwrap();
+
+    let migrated = harness.get_state::<SOuLToken>(&token);
+    assert_eq!(migrated.mint_authority, authority);
+    assert_eq!(migrated.mint, mint);
+    assert_eq!(migrated.total_supply, 5_000);
+    assert_eq!(migrated.mint_authority_bump, 7);
+    assert_eq!(migrated.token_program, spl_token::id());
+    assert_eq!(migrated.transfer_fee_basis_points, 0);
+    assert_eq!(migrated.maximum_fee, 0);
+    assert_eq!(migrated.max_supply, u64::MAX);
+    assert_eq!(harness.get_account(&token).unwrap().data.len(), SOuLToken::SPACE);
+
+    // A layout newer than this build is left alone
+    let account = harness.get_account(&token).cloned().unwrap();
+    let mut newer = account.clone();
+    newer.data[8] = SOuLToken::VERSION + 1;
+    harness.add_account(token, newer);
+    assert_eq!(
+        harness.process_instruction(&migrate),
+        Err(SOuLError::AccountVersionMismatch.into())
+    );
+}
+
+#[test]
+fn test_migrate_upgrades_v2_soul_token_without_supply_cap() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let payer = harness.add_wallet();
+    let authority = Pubkey::new_unique();
+    let mint = Pubkey::new_unique();
+    let (token, bump) = SOuLToken::find_address(&mint, &program_id);
+
+    // Version 2 predates the supply cap and emission schedule
+    let mut data = SOuLToken::DISCRIMINATOR.to_vec();
+    data.push(2);
+    true.serialize(&mut data).unwrap();
+    authority.serialize(&mut data).unwrap();
+    mint.serialize(&mut data).unwrap();
+    5_000u64.serialize(&mut data).unwrap();
+    9u8.serialize(&mut data).unwrap();
+    bump.serialize(&mut data).unwrap();
+    7u8.serialize(&mut data).unwrap();
+    spl_token_2022::id().serialize(&mut data).unwrap();
+    150u16.serialize(&mut data).unwrap();
+    1_000u64.serialize(&mut data).unwrap();
+    253u8.serialize(&mut data).unwrap();
+    add_v0_account(&mut harness, token, data);
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::migrate(&program_id, &payer, &token, AccountType::SOuLToken, None).unwrap(),
+        )
+        .unwrap();
+
+    let migrated = harness.get_state::<SOuLToken>(&token);
+    assert_eq!(migrated.mint_authority, authority);
+    assert_eq!(migrated.total_supply, 5_000);
+    assert_eq!(migrated.token_program, spl_token_2022::id());
+    assert_eq!(migrated.transfer_fee_basis_points, 150);
+    assert_eq!(migrated.maximum_fee, 1_000);
+    assert_eq!(migrated.treasury_bump, 253);
+    assert_eq!(migrated.max_supply, u64::MAX);
+    assert_eq!(migrated.emission, None);
+    assert_eq!(harness.get_account(&token).unwrap().data.len(), SOuLToken::SPACE);
+}
+
+#[test]
+fn test_migrate_upgrades_v2_impact_report_to_back_no_category() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let payer = harness.add_wallet();
+    let metrics_account = Pubkey::new_unique();
+    let proof_hash = [5; 32];
+    let (report, bump) = ImpactReport::find_address(&metrics_account, &proof_hash, &program_id);
+
+    // Version 2 predates reports naming the category of credits they back
+    let mut data = ImpactReport::DISCRIMINATOR.to_vec();
+    data.push(2);
+    metrics_account.serialize(&mut data).unwrap();
+    100i64.serialize(&mut data).unwrap();
+    GeoLocation {
+        latitude: 1,
+        longitude: 2,
+        altitude: 3,
+    }
+    .serialize(&mut data)
+    .unwrap();
+    ImpactMetrics {
+        is_initialized: true,
+        authority: Pubkey::new_unique(),
+        last_update: 100,
+        forest_area: 0,
+        carbon_offset: 40_000,
+        water_saved: 0,
+        biodiversity_score: 0,
+        energy_saved: 0,
+        waste_recycled: 0,
+        impact_score: 0,
+        bump: 0,
+    }
+    .serialize(&mut data)
+    .unwrap();
+    Pubkey::new_unique().serialize(&mut data).unwrap();
+    VerificationStatus::Verified.serialize(&mut data).unwrap();
+    proof_hash.serialize(&mut data).unwrap();
+    bump.serialize(&mut data).unwrap();
+    Some(200i64).serialize(&mut data).unwrap();
+    add_v0_account(&mut harness, report, data);
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::migrate(&program_id, &payer, &report, AccountType::ImpactReport, Some(&metrics_account))
+                .unwrap(),
+        )
+        .unwrap();
+
+    let migrated = harness.get_state::<ImpactReport>(&report);
+    assert_eq!(migrated.metrics.carbon_offset, 40_000);
+    assert_eq!(migrated.verified_at, Some(200));
+    assert_eq!(migrated.category, None);
+    assert_eq!(harness.get_account(&report).unwrap().data.len(), ImpactReport::SPACE);
+}
+
+#[test]
+fn test_migrate_rejects_type_that_does_not_match_address() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let payer = harness.add_wallet();
+    let (game, bump) = GameState::find_address(&program_id);
+    add_v0_account(&mut harness, game, game_state(payer, bump).try_to_vec().unwrap());
+
+    let migrate = |account: &Pubkey, account_type| {
+        SOuLInstruction::migrate(&program_id, &payer, account, account_type, None).unwrap()
+    };
+
+    // The game state body decodes as a player state, but derives another address
+    assert_eq!(
+        harness.process_instruction(&migrate(&game, AccountType::PlayerState)),
+        Err(SOuLError::InvalidAccountType.into())
+    );
+
+    let stranger = harness.add_funded_account(&Pubkey::new_unique(), GameState::LEN);
+    assert_eq!(
+        harness.process_instruction(&migrate(&stranger, AccountType::GameState)),
+        Err(ProgramError::IncorrectProgramId)
+    );
+}
@@ -0,0 +1,329 @@
+mod common;
+
+use {
+    common::{Account, ProgramHarness},
+    soul_sanctuary::{
+        error::SOuLError,
+        event::SoulEvent,
+        instruction::SOuLInstruction,
+        multisig::{Multisig, MultisigError, MultisigInstruction, Proposal},
+        processor::process_instruction,
+        state::SOuLToken,
+    },
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        system_program,
+    },
+    spl_token::state::Mint,
+};
+
+struct MultisigSetup {
+    multisig: Pubkey,
+    signer: Pubkey,
+    signers: Vec<Pubkey>,
+}
+
+fn create_multisig(harness: &mut ProgramHarness, count: usize, threshold: u8) -> MultisigSetup {
+    let program_id = harness.program_id;
+    let creator = harness.add_wallet();
+    let signers = (0..count).map(|_| harness.add_wallet()).collect::<Vec<_>>();
+    harness
+        .process_instruction(
+            &MultisigInstruction::create(&program_id, &creator, 0, signers.clone(), threshold).unwrap(),
+        )
+        .unwrap();
+
+    let (multisig, _) = Multisig::find_address(&creator, 0, &program_id);
+    let (signer, _) = Multisig::find_signer(&multisig, &program_id);
+    // The signer PDA pays rent for accounts it creates
+    harness.add_account(signer, Account::new(10_000_000_000, 0, &system_program::id()));
+
+    MultisigSetup {
+        multisig,
+        signer,
+        signers,
+    }
+}
+
+/// Propose `instruction` as `proposer` and return the proposal address
+fn propose(
+    harness: &mut ProgramHarness,
+    setup: &MultisigSetup,
+    proposer: &Pubkey,
+    instruction: &Instruction,
+) -> Result<Pubkey, ProgramError> {
+    let program_id = harness.program_id;
+    let index = harness.get_state::<Multisig>(&setup.multisig).proposal_count;
+    harness.process_instruction(
+        &MultisigInstruction::propose(&program_id, proposer, &setup.multisig, index, instruction).unwrap(),
+    )?;
+    Ok(Proposal::find_address(&setup.multisig, index, &program_id).0)
+}
+
+fn approve(harness: &mut ProgramHarness, setup: &MultisigSetup, signer: &Pubkey, proposal: &Pubkey) -> Result<(), ProgramError> {
+    let program_id = harness.program_id;
+    harness.process_instruction(
+        &MultisigInstruction::approve(&program_id, signer, &setup.multisig, proposal).unwrap(),
+    )
+}
+
+fn execute(
+    harness: &mut ProgramHarness,
+    setup: &MultisigSetup,
+    proposal: &Pubkey,
+    instruction: &Instruction,
+) -> Result<(), ProgramError> {
+    let program_id = harness.program_id;
+    harness.process_instruction(
+        &MultisigInstruction::execute(&program_id, &setup.multisig, proposal, instruction).unwrap(),
+    )
+}
+
+fn events(harness: &ProgramHarness) -> Vec<SoulEvent> {
+    harness
+        .take_logged_data()
+        .iter()
+        .filter_map(|fields| SoulEvent::from_log_data(fields))
+        .collect()
+}
+
+#[test]
+fn test_create_multisig() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    let setup = create_multisig(&mut harness, 3, 2);
+
+    let multisig = harness.get_state::<Multisig>(&setup.multisig);
+    assert!(multisig.is_initialized);
+    assert_eq!(multisig.signers, setup.signers);
+    assert_eq!(multisig.threshold, 2);
+    assert_eq!(multisig.proposal_count, 0);
+    assert_eq!(multisig.signer_bump, Multisig::find_signer(&setup.multisig, &program_id).1);
+}
+
+#[test]
+fn test_create_rejects_unusable_signer_sets() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let creator = harness.add_wallet();
+    let signer = Pubkey::new_unique();
+
+    for (nonce, signers, threshold) in [
+        (0, vec![signer, Pubkey::new_unique()], 0),
+        (1, vec![signer, Pubkey::new_unique()], 3),
+        (2, vec![signer, signer], 2),
+        (3, (0..=Multisig::MAX_SIGNERS).map(|_| Pubkey::new_unique()).collect(), 1),
+    ] {
+        assert_eq!(
+            harness.process_instruction(
+                &MultisigInstruction::create(&program_id, &creator, nonce, signers, threshold).unwrap()
+            ),
+            Err(MultisigError::InvalidSigners.into())
+        );
+    }
+}
+
+#[test]
+fn test_multisig_initializes_and_mints_soul_through_proposals() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = create_multisig(&mut harness, 3, 2);
+    let [alice, bob, carol] = [setup.signers[0], setup.signers[1], setup.signers[2]];
+
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let holder = harness.add_token_account(&mint, &Pubkey::new_unique(), 0);
+    let initialize = SOuLInstruction::initialize_soul(&program_id, &setup.signer, &mint, &holder, 1_000).unwrap();
+
+    let proposal = propose(&mut harness, &setup, &alice, &initialize).unwrap();
+    let timestamp = harness.clock().unix_timestamp;
+    assert_eq!(
+        events(&harness),
+        vec![SoulEvent::ProposalCreated {
+            multisig: setup.multisig,
+            proposal,
+            proposer: alice,
+            index: 0,
+            timestamp,
+        }]
+    );
+
+    // The proposer's approval alone is below the threshold
+    assert_eq!(
+        execute(&mut harness, &setup, &proposal, &initialize),
+        Err(MultisigError::NotEnoughApprovals.into())
+    );
+    assert_eq!(
+        approve(&mut harness, &setup, &alice, &proposal),
+        Err(MultisigError::AlreadyApproved.into())
+    );
+    approve(&mut harness, &setup, &carol, &proposal).unwrap();
+    assert_eq!(
+        events(&harness),
+        vec![SoulEvent::ProposalApproved {
+            multisig: setup.multisig,
+            proposal,
+            signer: carol,
+            approvals: 2,
+            timestamp,
+        }]
+    );
+
+    execute(&mut harness, &setup, &proposal, &initialize).unwrap();
+    assert_eq!(
+        events(&harness).last(),
+        Some(&SoulEvent::ProposalExecuted {
+            multisig: setup.multisig,
+            proposal,
+            timestamp,
+        })
+    );
+    assert!(harness.get_state::<Proposal>(&proposal).executed);
+    let (token_account, _) = SOuLToken::find_address(&mint, &program_id);
+    assert_eq!(harness.get_state::<SOuLToken>(&token_account).mint_authority, setup.signer);
+    assert_eq!(harness.token_balance(&holder), 1_000);
+
+    assert_eq!(
+        execute(&mut harness, &setup, &proposal, &initialize),
+        Err(MultisigError::AlreadyExecuted.into())
+    );
+    assert_eq!(
+        approve(&mut harness, &setup, &bob, &proposal),
+        Err(MultisigError::AlreadyExecuted.into())
+    );
+
+    // A single signer is no longer the mint authority
+    let mint_as = |authority: &Pubkey| {
+        SOuLInstruction::mint(&program_id, &spl_token::id(), authority, &mint, &holder, 500).unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&mint_as(&bob)),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+
+    let mint_ix = mint_as(&setup.signer);
+    let proposal = propose(&mut harness, &setup, &bob, &mint_ix).unwrap();
+    approve(&mut harness, &setup, &alice, &proposal).unwrap();
+    execute(&mut harness, &setup, &proposal, &mint_ix).unwrap();
+    assert_eq!(harness.token_balance(&holder), 1_500);
+    assert_eq!(harness.get_state::<Multisig>(&setup.multisig).proposal_count, 2);
+}
+
+#[test]
+fn test_only_signers_propose_and_approve() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = create_multisig(&mut harness, 2, 2);
+    let outsider = harness.add_wallet();
+    let instruction = MultisigInstruction::set_signers(&program_id, &setup.multisig, vec![outsider], 1).unwrap();
+
+    assert_eq!(
+        propose(&mut harness, &setup, &outsider, &instruction),
+        Err(MultisigError::NotASigner.into())
+    );
+    let proposal = propose(&mut harness, &setup, &setup.signers[0], &instruction).unwrap();
+    assert_eq!(
+        approve(&mut harness, &setup, &outsider, &proposal),
+        Err(MultisigError::NotASigner.into())
+    );
+
+    // The program can only sign as the multisig, never as another account
+    let mut foreign_signer = instruction.clone();
+    foreign_signer.accounts.push(AccountMeta::new_readonly(outsider, true));
+    assert_eq!(
+        propose(&mut harness, &setup, &setup.signers[0], &foreign_signer),
+        Err(MultisigError::InvalidProposal.into())
+    );
+
+    // Execution must pass the proposed accounts in order
+    approve(&mut harness, &setup, &setup.signers[1], &proposal).unwrap();
+    let mut swapped = instruction.clone();
+    swapped.accounts.reverse();
+    assert_eq!(
+        execute(&mut harness, &setup, &proposal, &swapped),
+        Err(MultisigError::InvalidProposal.into())
+    );
+    execute(&mut harness, &setup, &proposal, &instruction).unwrap();
+    assert_eq!(harness.get_state::<Multisig>(&setup.multisig).signers, vec![outsider]);
+}
+
+#[test]
+fn test_set_signers_voids_pending_proposals() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = create_multisig(&mut harness, 3, 2);
+    let [alice, bob, carol] = [setup.signers[0], setup.signers[1], setup.signers[2]];
+
+    let rotate = MultisigInstruction::set_signers(&program_id, &setup.multisig, vec![alice, bob], 1).unwrap();
+    let pending = MultisigInstruction::set_signers(&program_id, &setup.multisig, vec![carol], 1).unwrap();
+    let rotation = propose(&mut harness, &setup, &alice, &rotate).unwrap();
+    let stale = propose(&mut harness, &setup, &carol, &pending).unwrap();
+
+    approve(&mut harness, &setup, &bob, &rotation).unwrap();
+    execute(&mut harness, &setup, &rotation, &rotate).unwrap();
+    let multisig = harness.get_state::<Multisig>(&setup.multisig);
+    assert_eq!(multisig.signers, vec![alice, bob]);
+    assert_eq!(multisig.threshold, 1);
+    assert_eq!(multisig.signers_version, 1);
+
+    assert_eq!(
+        approve(&mut harness, &setup, &bob, &stale),
+        Err(MultisigError::StaleProposal.into())
+    );
+    assert_eq!(
+        execute(&mut harness, &setup, &stale, &pending),
+        Err(MultisigError::StaleProposal.into())
+    );
+
+    // The new 1-of-2 set executes on the proposer's approval alone
+    let restore = MultisigInstruction::set_signers(&program_id, &setup.multisig, vec![alice, bob, carol], 3).unwrap();
+    let proposal = propose(&mut harness, &setup, &bob, &restore).unwrap();
+    execute(&mut harness, &setup, &proposal, &restore).unwrap();
+    assert_eq!(harness.get_state::<Multisig>(&setup.multisig).threshold, 3);
+
+    // An unusable set is rejected when the proposal executes
+    let invalid = MultisigInstruction::set_signers(&program_id, &setup.multisig, vec![alice], 2).unwrap();
+    let proposal = propose(&mut harness, &setup, &alice, &invalid).unwrap();
+    approve(&mut harness, &setup, &bob, &proposal).unwrap();
+    approve(&mut harness, &setup, &carol, &proposal).unwrap();
+    assert_eq!(
+        execute(&mut harness, &setup, &proposal, &invalid),
+        Err(MultisigError::InvalidSigners.into())
+    );
+    assert!(!harness.get_state::<Proposal>(&proposal).executed);
+}
+
+#[test]
+fn test_finished_proposals_close_back_to_the_proposer() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = create_multisig(&mut harness, 2, 1);
+    let [alice, bob] = [setup.signers[0], setup.signers[1]];
+    let close = |proposal: &Pubkey, proposer: &Pubkey| {
+        MultisigInstruction::close_proposal(&program_id, &setup.multisig, proposal, proposer).unwrap()
+    };
+
+    let rotate = MultisigInstruction::set_signers(&program_id, &setup.multisig, vec![alice, bob], 2).unwrap();
+    let rotation = propose(&mut harness, &setup, &alice, &rotate).unwrap();
+    let stale = propose(&mut harness, &setup, &bob, &rotate).unwrap();
+    assert_eq!(
+        harness.process_instruction(&close(&rotation, &alice)),
+        Err(MultisigError::ProposalPending.into())
+    );
+
+    execute(&mut harness, &setup, &rotation, &rotate).unwrap();
+    assert_eq!(
+        harness.process_instruction(&close(&rotation, &bob)),
+        Err(SOuLError::RentPayerMismatch.into())
+    );
+    for (proposal, proposer) in [(rotation, alice), (stale, bob)] {
+        let proposer_lamports = harness.get_account(&proposer).unwrap().lamports;
+        let rent = harness.get_account(&proposal).unwrap().lamports;
+        harness.process_instruction(&close(&proposal, &proposer)).unwrap();
+        assert_eq!(harness.get_account(&proposal).unwrap().lamports, 0);
+        assert_eq!(harness.get_account(&proposer).unwrap().lamports, proposer_lamports + rent);
+    }
+}
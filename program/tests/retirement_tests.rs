@@ -0,0 +1,389 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        access::{AccessError, AccessInstruction},
+        account::AccountState,
+        error::SOuLError,
+        event::SoulEvent,
+        impact::{GeoLocation, ImpactMetrics, ImpactReport, VerificationStatus},
+        instruction::SOuLInstruction,
+        lst::{LSTInstruction, LSTPool, LSTType},
+        processor::process_instruction,
+        state::{ReportRetirement, RetirementCertificate, SOuLToken},
+    },
+    solana_program::{
+        instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+    },
+    spl_token::state::Mint,
+};
+
+/// Base units of one whole SOuL or LST, which a tonne of offset carbon backs
+const TOKEN: u64 = 1_000_000_000;
+
+/// Add a forest impact report in `status` offsetting `carbon_offset` kg, as
+/// if it had gone through verification, past its dispute window if it was
+/// verified
+fn add_report(harness: &mut ProgramHarness, status: VerificationStatus, carbon_offset: u64) -> Pubkey {
+    let program_id = harness.program_id;
+    let verified_at = (status == VerificationStatus::Verified)
+        .then(|| harness.clock().unix_timestamp - ImpactReport::DISPUTE_WINDOW_SECONDS);
+    let report = harness.add_funded_account(&program_id, ImpactReport::SPACE);
+    harness.set_state(
+        &report,
+        &ImpactReport {
+            metrics_account: Pubkey::new_unique(),
+            timestamp: 0,
+            location: GeoLocation {
+                latitude: -1_300_000,
+                longitude: 36_800_000,
+                altitude: 0,
+            },
+            metrics: ImpactMetrics {
+                is_initialized: true,
+                authority: Pubkey::new_unique(),
+                last_update: 0,
+                forest_area: 10_000,
+                carbon_offset,
+                water_saved: 0,
+                biodiversity_score: 0,
+                energy_saved: 0,
+                waste_recycled: 0,
+                impact_score: 0,
+                bump: 0,
+            },
+            verifier: Pubkey::new_unique(),
+            verification_status: status,
+            proof_hash: [7; 32],
+            bump: 0,
+            verified_at,
+            category: Some(LSTType::Forest),
+        },
+    );
+    report
+}
+
+/// Initialize a SOuL token and return its mint, config and a holder's
+/// funded token account
+fn create_soul(harness: &mut ProgramHarness, holder: &Pubkey) -> (Pubkey, Pubkey, Pubkey) {
+    let program_id = harness.program_id;
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let holdings = Pubkey::new_unique();
+    harness.set_token_account(holdings, &mint, holder, 0);
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(&program_id, &authority, &mint, &holdings, 1_000 * TOKEN).unwrap(),
+        )
+        .unwrap();
+    let (token, _) = SOuLToken::find_address(&mint, &program_id);
+    (mint, token, holdings)
+}
+
+/// Create a SOuL token as above and have the admin pin it as the canonical
+/// mint
+fn initialize_soul(harness: &mut ProgramHarness, holder: &Pubkey) -> (Pubkey, Pubkey, Pubkey) {
+    let program_id = harness.program_id;
+    let admin = harness.initialize_roles();
+    let (mint, token, holdings) = create_soul(harness, holder);
+    harness
+        .process_instruction(&AccessInstruction::pin_soul_mint(&program_id, &admin, &mint).unwrap())
+        .unwrap();
+    (mint, token, holdings)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn retire(
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    source: &Pubkey,
+    mint: &Pubkey,
+    token_config: &Pubkey,
+    nonce: u64,
+    amount: u64,
+    category: LSTType,
+    reports: &[Pubkey],
+) -> Instruction {
+    SOuLInstruction::retire(
+        program_id,
+        &spl_token::id(),
+        owner,
+        source,
+        mint,
+        token_config,
+        nonce,
+        amount,
+        category,
+        [9; 32],
+        reports,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_retire_soul_burns_and_issues_a_certificate_once() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let owner = harness.add_wallet();
+    let (mint, token, holdings) = initialize_soul(&mut harness, &owner);
+    let reports = [
+        add_report(&mut harness, VerificationStatus::Verified, 300_000),
+        add_report(&mut harness, VerificationStatus::Verified, 300_000),
+    ];
+    let retire = |nonce, amount, reports: &[Pubkey]| {
+        retire(&program_id, &owner, &holdings, &mint, &token, nonce, amount, LSTType::Forest, reports)
+    };
+
+    harness.process_instruction(&retire(0, 400 * TOKEN, &reports)).unwrap();
+    assert_eq!(harness.token_balance(&holdings), 600 * TOKEN);
+    assert_eq!(harness.mint_supply(&mint), 600 * TOKEN);
+    assert_eq!(harness.get_state::<SOuLToken>(&token).total_supply, 600 * TOKEN);
+
+    let (certificate, bump) = RetirementCertificate::find_address(&owner, 0, &program_id);
+    let timestamp = harness.clock().unix_timestamp;
+    assert_eq!(
+        harness.get_state::<RetirementCertificate>(&certificate),
+        RetirementCertificate {
+            owner,
+            nonce: 0,
+            mint,
+            amount: 400 * TOKEN,
+            category: LSTType::Forest,
+            beneficiary_hash: [9; 32],
+            reports: reports.to_vec(),
+            timestamp,
+            bump,
+        }
+    );
+    assert_eq!(
+        harness
+            .take_logged_data()
+            .iter()
+            .filter_map(|fields| SoulEvent::from_log_data(fields))
+            .collect::<Vec<_>>(),
+        vec![SoulEvent::Retired {
+            certificate,
+            owner,
+            source: holdings,
+            mint,
+            category: LSTType::Forest,
+            amount: 400 * TOKEN,
+            beneficiary_hash: [9; 32],
+            reports: reports.to_vec(),
+            timestamp,
+        }]
+    );
+
+    // The credits are counted against the reports in order
+    let tally = |report: &Pubkey| {
+        let (tally, _) = ReportRetirement::find_address(report, &program_id);
+        harness.get_state::<ReportRetirement>(&tally).retired_amount
+    };
+    assert_eq!((tally(&reports[0]), tally(&reports[1])), (300 * TOKEN, 100 * TOKEN));
+
+    // The certificate of a nonce is issued once
+    assert_eq!(
+        harness.process_instruction(&retire(0, 100 * TOKEN, &reports[1..])),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+    harness.process_instruction(&retire(1, 100 * TOKEN, &reports[1..])).unwrap();
+    assert_eq!(harness.token_balance(&holdings), 500 * TOKEN);
+}
+
+#[test]
+fn test_retire_requires_distinct_verified_reports() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let owner = harness.add_wallet();
+    let (mint, token, holdings) = initialize_soul(&mut harness, &owner);
+    let verified = add_report(&mut harness, VerificationStatus::Verified, 1_000_000);
+    let pending = add_report(&mut harness, VerificationStatus::Pending, 1_000_000);
+    let retire = |amount, reports: &[Pubkey]| {
+        retire(&program_id, &owner, &holdings, &mint, &token, 0, amount, LSTType::Forest, reports)
+    };
+
+    assert_eq!(
+        harness.process_instruction(&retire(100, &[verified, pending])),
+        Err(SOuLError::ReportNotVerified.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&retire(100, &[verified, verified])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&retire(100, &[])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&retire(0, &[verified])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&retire(1_000 * TOKEN + 1, &[verified])),
+        Err(SOuLError::InsufficientFunds.into())
+    );
+    let reports = [verified; RetirementCertificate::MAX_REPORTS + 1];
+    assert_eq!(
+        harness.process_instruction(&retire(100, &reports)),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+
+    // A report that can still be disputed may yet be overturned
+    let mut disputable = harness.get_state::<ImpactReport>(&verified);
+    disputable.verified_at = Some(harness.clock().unix_timestamp);
+    harness.set_state(&verified, &disputable);
+    assert_eq!(
+        harness.process_instruction(&retire(100, &[verified])),
+        Err(SOuLError::ReportNotFinal.into())
+    );
+    assert_eq!(harness.token_balance(&holdings), 1_000 * TOKEN);
+}
+
+#[test]
+fn test_retire_only_the_canonical_soul_mint() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let owner = harness.add_wallet();
+    let report = add_report(&mut harness, VerificationStatus::Verified, 1_000_000);
+
+    // Nothing can be retired as SOuL before the canonical mint is pinned
+    let (mint, token, holdings) = create_soul(&mut harness, &owner);
+    let retire_soul = |mint, token, holdings| {
+        retire(&program_id, &owner, &holdings, &mint, &token, 0, 100 * TOKEN, LSTType::Forest, &[report])
+    };
+    assert_eq!(
+        harness.process_instruction(&retire_soul(mint, token, holdings)),
+        Err(AccessError::InvalidCanonicalMint.into())
+    );
+
+    // Nor a SOuL token anyone can initialize for a mint of their own
+    let admin = harness.initialize_roles();
+    harness
+        .process_instruction(&AccessInstruction::pin_soul_mint(&program_id, &admin, &mint).unwrap())
+        .unwrap();
+    let (foreign_mint, foreign_token, foreign_holdings) = create_soul(&mut harness, &owner);
+    assert_eq!(
+        harness.process_instruction(&retire_soul(foreign_mint, foreign_token, foreign_holdings)),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    assert_eq!(harness.token_balance(&foreign_holdings), 1_000 * TOKEN);
+
+    harness.process_instruction(&retire_soul(mint, token, holdings)).unwrap();
+    assert_eq!(harness.token_balance(&holdings), 900 * TOKEN);
+}
+
+#[test]
+fn test_report_backs_credits_once_and_only_of_its_category() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let owner = harness.add_wallet();
+    let (mint, token, holdings) = initialize_soul(&mut harness, &owner);
+    let report = add_report(&mut harness, VerificationStatus::Verified, 250_000);
+    let other_report = add_report(&mut harness, VerificationStatus::Verified, 100_000);
+    let retire = |nonce, amount, category, reports: &[Pubkey]| {
+        retire(&program_id, &owner, &holdings, &mint, &token, nonce, amount, category, reports)
+    };
+
+    // A forest report backs no other category, nor does a report that
+    // names none
+    assert_eq!(
+        harness.process_instruction(&retire(0, 10 * TOKEN, LSTType::Water, &[report])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    let mut uncategorized = harness.get_state::<ImpactReport>(&other_report);
+    uncategorized.category = None;
+    harness.set_state(&other_report, &uncategorized);
+    assert_eq!(
+        harness.process_instruction(&retire(0, 10 * TOKEN, LSTType::Forest, &[other_report])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    uncategorized.category = Some(LSTType::Forest);
+    harness.set_state(&other_report, &uncategorized);
+
+    // 250 t of offset carbon back 250 whole tokens, however they are split
+    harness
+        .process_instruction(&retire(0, 200 * TOKEN, LSTType::Forest, &[report]))
+        .unwrap();
+    assert_eq!(
+        harness.process_instruction(&retire(1, 50 * TOKEN + 1, LSTType::Forest, &[report])),
+        Err(SOuLError::RetirementExceedsOffset.into())
+    );
+    harness
+        .process_instruction(&retire(1, 50 * TOKEN, LSTType::Forest, &[report]))
+        .unwrap();
+
+    // An exhausted report cannot be listed as backing more credits
+    assert_eq!(
+        harness.process_instruction(&retire(2, 10 * TOKEN, LSTType::Forest, &[other_report, report])),
+        Err(SOuLError::RetirementExceedsOffset.into())
+    );
+    assert_eq!(harness.token_balance(&holdings), 750 * TOKEN);
+    let (tally, bump) = ReportRetirement::find_address(&report, &program_id);
+    assert_eq!(
+        harness.get_state::<ReportRetirement>(&tally),
+        ReportRetirement {
+            report,
+            category: LSTType::Forest,
+            retired_amount: 250 * TOKEN,
+            bump,
+        }
+    );
+}
+
+#[test]
+fn test_retire_lst_under_its_pool_category() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.initialize_roles();
+    let soul_mint = harness.add_mint(&authority, 0, 9);
+    let lst_mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let (pool, _) = LSTPool::find_address(LSTType::Forest, &program_id);
+    harness
+        .process_instruction(
+            &LSTInstruction::initialize_pool(&program_id, &authority, &soul_mint, &lst_mint, LSTType::Forest, 0)
+                .unwrap(),
+        )
+        .unwrap();
+    let (pool_soul, _) = LSTPool::find_vault(&pool, &program_id);
+
+    let staker = harness.add_wallet();
+    let staker_soul = harness.add_token_account(&soul_mint, &staker, 1_000 * TOKEN);
+    let staker_lst = harness.add_token_account(&lst_mint, &staker, 0);
+    harness
+        .process_instruction(
+            &LSTInstruction::stake(
+                &program_id,
+                &staker,
+                LSTType::Forest,
+                &staker_soul,
+                &pool_soul,
+                &staker_lst,
+                &lst_mint,
+                500 * TOKEN,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let report = add_report(&mut harness, VerificationStatus::Verified, 300_000);
+
+    assert_eq!(
+        harness.process_instruction(&retire(&program_id, &staker, &staker_lst, &lst_mint, &pool, 0, 200 * TOKEN, LSTType::Water, &[report])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+    // SOuL cannot be passed off as the pool's LST
+    assert_eq!(
+        harness.process_instruction(&retire(&program_id, &staker, &staker_soul, &soul_mint, &pool, 0, 200 * TOKEN, LSTType::Forest, &[report])),
+        Err(SOuLError::InvalidRetirement.into())
+    );
+
+    harness
+        .process_instruction(&retire(&program_id, &staker, &staker_lst, &lst_mint, &pool, 0, 200 * TOKEN, LSTType::Forest, &[report]))
+        .unwrap();
+    assert_eq!(harness.token_balance(&staker_lst), 300 * TOKEN);
+    assert_eq!(harness.mint_supply(&lst_mint), 300 * TOKEN);
+    let (certificate, _) = RetirementCertificate::find_address(&staker, 0, &program_id);
+    let certificate = harness.get_state::<RetirementCertificate>(&certificate);
+    assert_eq!((certificate.mint, certificate.amount), (lst_mint, 200 * TOKEN));
+    assert_eq!(certificate.category, LSTType::Forest);
+}
@@ -1,109 +1,436 @@
+mod common;
+
 use {
+    borsh::BorshDeserialize,
+    common::{Account, ProgramHarness},
     soul_sanctuary::{
+        access::{AccessError, AccessInstruction, Role},
+        account::AccountState,
+        error::SOuLError,
         instruction::SOuLInstruction,
         processor::process_instruction,
-        state::{SOuLToken, LSTToken},
+        state::{
+            ComplianceAction, ComplianceEntry, ComplianceHistory, ComplianceRecord, SOuLToken, LSTToken, LSTTokenType,
+            TokenMetadata,
+        },
     },
     solana_program::{
-        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        program_pack::Pack,
         pubkey::Pubkey,
+        system_program,
     },
-    solana_program_test::*,
-    solana_sdk::{
-        account::Account,
-        signature::{Keypair, Signer},
-        transaction::Transaction,
-    },
+    spl_token::state::Mint,
 };
 
-#[tokio::test]
-async fn test_soul_token_initialization() {
+struct SoulSetup {
+    token_account: Pubkey,
+    mint: Pubkey,
+    authority: Pubkey,
+    treasury: Pubkey,
+}
+
+fn initialize_soul(harness: &mut ProgramHarness, total_supply: u64) -> SoulSetup {
+    let program_id = harness.program_id;
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let (token_account, _) = SOuLToken::find_address(&mint, &program_id);
+    let treasury = Pubkey::new_unique();
+    harness.set_token_account(treasury, &mint, &authority, 0);
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(
+                &program_id,
+                &authority,
+                &mint,
+                &treasury,
+                total_supply,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    SoulSetup {
+        token_account,
+        mint,
+        authority,
+        treasury,
+    }
+}
+
+fn soul_token(harness: &ProgramHarness, setup: &SoulSetup) -> SOuLToken {
+    harness.get_state::<SOuLToken>(&setup.token_account)
+}
+
+#[test]
+fn test_soul_token_initialization() {
     let program_id = Pubkey::new_unique();
-    let mut program_test = ProgramTest::new(
-        "soul_sanctuary",
-        program_id,
-        processor!(process_instruction),
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let total_supply = 1_000_000_000;
+
+    let setup = initialize_soul(&mut harness, total_supply);
+
+    let token = soul_token(&harness, &setup);
+    assert!(token.is_initialized);
+    assert_eq!(token.bump, SOuLToken::find_address(&setup.mint, &program_id).1);
+    assert_eq!(
+        token.mint_authority_bump,
+        SOuLToken::find_mint_authority(&setup.token_account, &program_id).1
     );
+    assert_eq!(token.mint_authority, setup.authority);
+    assert_eq!(token.mint, setup.mint);
+    assert_eq!(token.total_supply, total_supply);
+    assert_eq!(harness.mint_supply(&setup.mint), total_supply);
+    assert_eq!(harness.token_balance(&setup.treasury), total_supply);
+}
 
-    let token_account = Keypair::new();
-    let initializer = Keypair::new();
-    let total_supply = 1_000_000_000;
+#[test]
+fn test_mint_enforces_authority_and_tracks_supply() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+    let recipient = harness.add_token_account(&setup.mint, &Pubkey::new_unique(), 0);
+    let mint = |minter: &Pubkey, amount| {
+        SOuLInstruction::mint(&program_id, &spl_token::id(), minter, &setup.mint, &recipient, amount).unwrap()
+    };
 
-    program_test.add_account(
-        token_account.pubkey(),
-        Account {
-            lamports: 5000000,
-            data: vec![0; 1000],
-            owner: program_id,
-            ..Account::default()
-        },
+    harness.process_instruction(&mint(&setup.authority, 500)).unwrap();
+    assert_eq!(harness.token_balance(&recipient), 500);
+    assert_eq!(soul_token(&harness, &setup).total_supply, 1_500);
+    assert_eq!(harness.mint_supply(&setup.mint), 1_500);
+
+    let impostor = harness.add_wallet();
+    let result = harness.process_instruction(&mint(&impostor, 500));
+    assert_eq!(result, Err(SOuLError::InvalidAuthority.into()));
+
+    let result = harness.process_instruction(&mint(&setup.authority, u64::MAX));
+    assert_eq!(result, Err(SOuLError::AmountOverflow.into()));
+    assert_eq!(soul_token(&harness, &setup).total_supply, 1_500);
+}
+
+#[test]
+fn test_burn_reduces_supply() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+    let burn = |amount| {
+        SOuLInstruction::burn(&program_id, &spl_token::id(), &setup.treasury, &setup.authority, &setup.mint, amount).unwrap()
+    };
+
+    harness.process_instruction(&burn(400)).unwrap();
+    assert_eq!(harness.token_balance(&setup.treasury), 600);
+    assert_eq!(soul_token(&harness, &setup).total_supply, 600);
+
+    let result = harness.process_instruction(&burn(601));
+    assert_eq!(result, Err(SOuLError::InsufficientFunds.into()));
+}
+
+#[test]
+fn test_transfer_moves_tokens() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+    let recipient = harness.add_token_account(&setup.mint, &Pubkey::new_unique(), 0);
+
+    let transfer = |amount| {
+        SOuLInstruction::transfer(&program_id, &spl_token::id(), &setup.treasury, &recipient, &setup.authority, &setup.mint, amount)
+            .unwrap()
+    };
+
+    harness.process_instruction(&transfer(250)).unwrap();
+    assert_eq!(harness.token_balance(&setup.treasury), 750);
+    assert_eq!(harness.token_balance(&recipient), 250);
+
+    assert_eq!(
+        harness.process_instruction(&transfer(751)),
+        Err(SOuLError::InsufficientFunds.into())
     );
+}
 
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
+#[test]
+fn test_lst_token_initialization() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
 
-    let mut transaction = Transaction::new_with_payer(
-        &[Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(initializer.pubkey(), true),
-                AccountMeta::new(token_account.pubkey(), false),
-            ],
-            data: SOuLInstruction::InitializeSOuL {
-                total_supply,
-            }
-            .try_to_vec()
+    let admin = harness.initialize_roles();
+    let initializer = harness.add_wallet();
+    let total_supply = 1_000_000;
+    let (token_account, bump) = LSTToken::find_address(LSTTokenType::ForestSOuL, &program_id);
+    let initialize = |initializer: &Pubkey| {
+        SOuLInstruction::initialize_lst(&program_id, initializer, LSTTokenType::ForestSOuL, total_supply).unwrap()
+    };
+
+    // Only an Admin or PoolManager creates LST tokens
+    assert_eq!(
+        harness.process_instruction(&initialize(&initializer)),
+        Err(AccessError::NotAuthorized.into())
+    );
+    harness
+        .process_instruction(
+            &AccessInstruction::grant_role(&program_id, &admin, &initializer, Role::PoolManager).unwrap(),
+        )
+        .unwrap();
+    harness.process_instruction(&initialize(&initializer)).unwrap();
+
+    let account = harness.get_account(&token_account).unwrap();
+    assert_eq!(account.owner, program_id);
+    let token = LSTToken::unpack_account(&account.data).unwrap();
+    assert_eq!(token.token_type, LSTTokenType::ForestSOuL);
+    assert_eq!(token.total_supply, total_supply);
+    assert_eq!(token.bump, bump);
+}
+
+#[test]
+fn test_soul_token_config_is_created_once_at_its_pda() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+
+    let account = harness.get_account(&setup.token_account).unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.data.len(), SOuLToken::SPACE);
+    assert_eq!(account.lamports, ProgramHarness::rent_exempt(SOuLToken::SPACE));
+
+    let result = harness.process_instruction(
+        &SOuLInstruction::initialize_soul(&program_id, &setup.authority, &setup.mint, &setup.treasury, 1)
             .unwrap(),
-        }],
-        Some(&payer.pubkey()),
     );
-    transaction.sign(&[&payer, &initializer], recent_blockhash);
+    assert_eq!(result, Err(SOuLError::AlreadyInitialized.into()));
+
+    // A config account at any other address is rejected
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let mut instruction =
+        SOuLInstruction::initialize_soul(&program_id, &setup.authority, &mint, &setup.treasury, 1).unwrap();
+    instruction.accounts[1].pubkey = Pubkey::new_unique();
+    assert_eq!(
+        harness.process_instruction(&instruction),
+        Err(ProgramError::InvalidSeeds)
+    );
+}
+
+#[test]
+fn test_soul_token_config_pda_may_hold_lamports_before_creation() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token::id(), Mint::LEN);
+    let treasury = harness.add_token_account(&mint, &authority, 0);
+
+    // Someone sends lamports to the config address before it is created
+    let (token_account, _) = SOuLToken::find_address(&mint, &program_id);
+    harness.add_account(token_account, Account::new(1_000, 0, &system_program::id()));
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(&program_id, &authority, &mint, &treasury, 10).unwrap(),
+        )
+        .unwrap();
 
-    banks_client.process_transaction(transaction).await.unwrap();
+    let account = harness.get_account(&token_account).unwrap();
+    assert_eq!(account.owner, program_id);
+    assert_eq!(account.lamports, ProgramHarness::rent_exempt(SOuLToken::SPACE));
+    assert_eq!(harness.token_balance(&treasury), 10);
 }
 
-#[tokio::test]
-async fn test_lst_token_initialization() {
+#[test]
+fn test_initialization_attaches_default_metadata() {
     let program_id = Pubkey::new_unique();
-    let mut program_test = ProgramTest::new(
-        "soul_sanctuary",
-        program_id,
-        processor!(process_instruction),
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+
+    let (metadata_account, bump) = TokenMetadata::find_address(&setup.token_account, &program_id);
+    let metadata = harness.get_state::<TokenMetadata>(&metadata_account);
+    assert_eq!(metadata.token, setup.token_account);
+    assert_eq!(metadata.update_authority, setup.authority);
+    assert_eq!(metadata.name, "SOuL Sanctuary");
+    assert_eq!(metadata.symbol, "SOuL");
+    assert_eq!(metadata.uri, "");
+    assert_eq!(metadata.bump, bump);
+
+    // Every LST token type gets its own name and symbol
+    let initializer = harness.initialize_roles();
+    let mut symbols = Vec::new();
+    for token_type in 0..12 {
+        let token_type = LSTTokenType::try_from_slice(&[token_type]).unwrap();
+        harness
+            .process_instruction(&SOuLInstruction::initialize_lst(&program_id, &initializer, token_type, 0).unwrap())
+            .unwrap();
+
+        let (token_account, _) = LSTToken::find_address(token_type, &program_id);
+        let (metadata_account, _) = TokenMetadata::find_address(&token_account, &program_id);
+        let metadata = harness.get_state::<TokenMetadata>(&metadata_account);
+        assert_eq!(metadata.token, token_account);
+        assert_eq!(metadata.update_authority, initializer);
+        assert_eq!(metadata.name, token_type.name());
+        assert_eq!(metadata.symbol, token_type.symbol());
+        assert!(metadata.name.len() <= TokenMetadata::MAX_NAME_LEN);
+        assert!(metadata.symbol.len() <= TokenMetadata::MAX_SYMBOL_LEN);
+        symbols.push(metadata.symbol);
+    }
+    assert_eq!(symbols[1], "forestSOuL");
+    symbols.dedup();
+    assert_eq!(symbols.len(), 12);
+}
+
+#[test]
+fn test_update_metadata_uri_is_authority_gated() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+    let (metadata_account, _) = TokenMetadata::find_address(&setup.token_account, &program_id);
+    let update = |authority: &Pubkey, uri: &str| {
+        SOuLInstruction::update_metadata_uri(&program_id, authority, &setup.token_account, uri.to_string()).unwrap()
+    };
+
+    let uri = "https://soulsanctuary.io/tokens/soul.json";
+    harness.process_instruction(&update(&setup.authority, uri)).unwrap();
+    assert_eq!(harness.get_state::<TokenMetadata>(&metadata_account).uri, uri);
+
+    let impostor = harness.add_wallet();
+    assert_eq!(
+        harness.process_instruction(&update(&impostor, "https://example.com")),
+        Err(SOuLError::InvalidAuthority.into())
     );
 
-    let token_account = Keypair::new();
-    let initializer = Keypair::new();
-    let total_supply = 1_000_000;
-    let token_type = 1; // forestSOuL
-
-    program_test.add_account(
-        token_account.pubkey(),
-        Account {
-            lamports: 5000000,
-            data: vec![0; 1000],
-            owner: program_id,
-            ..Account::default()
-        },
+    let too_long = "a".repeat(TokenMetadata::MAX_URI_LEN + 1);
+    assert_eq!(
+        harness.process_instruction(&update(&setup.authority, &too_long)),
+        Err(SOuLError::InvalidMetadata.into())
     );
+    assert_eq!(harness.get_state::<TokenMetadata>(&metadata_account).uri, uri);
+}
 
-    let (mut banks_client, payer, recent_blockhash) = program_test.start().await;
-
-    let mut transaction = Transaction::new_with_payer(
-        &[Instruction {
-            program_id,
-            accounts: vec![
-                AccountMeta::new(initializer.pubkey(), true),
-                AccountMeta::new(token_account.pubkey(), false),
-            ],
-            data: SOuLInstruction::InitializeLST {
-                token_type,
-                total_supply,
-            }
-            .try_to_vec()
-            .unwrap(),
-        }],
-        Some(&payer.pubkey()),
+#[test]
+fn test_compliance_role_freezes_and_thaws_holders() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+    let admin = harness.initialize_roles();
+    let officer = harness.add_wallet();
+    harness
+        .process_instruction(&AccessInstruction::grant_role(&program_id, &admin, &officer, Role::Compliance).unwrap())
+        .unwrap();
+    let holder = harness.add_wallet();
+    let held = harness.add_token_account(&setup.mint, &holder, 0);
+    let freeze = |signer: &Pubkey, reason_hash| {
+        SOuLInstruction::freeze(&program_id, &spl_token::id(), signer, &setup.mint, &held, reason_hash, None).unwrap()
+    };
+    let thaw = |signer: &Pubkey, reason_hash| {
+        SOuLInstruction::thaw(&program_id, &spl_token::id(), signer, &setup.mint, &held, reason_hash, None).unwrap()
+    };
+
+    // The mint authority PDA is also the freeze authority
+    let mint = Mint::unpack(&harness.get_account(&setup.mint).unwrap().data).unwrap();
+    let (mint_authority, _) = SOuLToken::find_mint_authority(&setup.token_account, &program_id);
+    assert_eq!(mint.freeze_authority, Some(mint_authority).into());
+
+    assert_eq!(
+        harness.process_instruction(&freeze(&setup.authority, [1; 32])),
+        Err(AccessError::NotAuthorized.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&thaw(&officer, [1; 32])),
+        Err(SOuLError::AccountNotFrozen.into())
+    );
+
+    harness.process_instruction(&freeze(&officer, [1; 32])).unwrap();
+    let transfer = SOuLInstruction::transfer(
+        &program_id,
+        &spl_token::id(),
+        &setup.treasury,
+        &held,
+        &setup.authority,
+        &setup.mint,
+        100,
+    )
+    .unwrap();
+    assert_eq!(
+        harness.process_instruction(&transfer),
+        Err(ProgramError::Custom(spl_token::error::TokenError::AccountFrozen as u32))
+    );
+    assert_eq!(
+        harness.process_instruction(&freeze(&officer, [2; 32])),
+        Err(SOuLError::AccountFrozen.into())
     );
-    transaction.sign(&[&payer, &initializer], recent_blockhash);
 
-    banks_client.process_transaction(transaction).await.unwrap();
+    let frozen_at = harness.clock().unix_timestamp;
+    harness.warp_to_timestamp(frozen_at + 60);
+    harness.process_instruction(&thaw(&officer, [3; 32])).unwrap();
+    harness.process_instruction(&transfer).unwrap();
+    assert_eq!(harness.token_balance(&held), 100);
+
+    let (record, _) = ComplianceRecord::find_address(&held, &program_id);
+    let record = harness.get_state::<ComplianceRecord>(&record);
+    assert!(!record.frozen);
+    assert_eq!(record.action_count, 2);
+    assert_eq!(record.payer, officer);
+    assert_eq!(
+        record.entries,
+        vec![
+            ComplianceEntry {
+                action: ComplianceAction::Freeze,
+                reason_hash: [1; 32],
+                officer,
+                timestamp: frozen_at,
+            },
+            ComplianceEntry {
+                action: ComplianceAction::Thaw,
+                reason_hash: [3; 32],
+                officer,
+                timestamp: frozen_at + 60,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_full_compliance_record_pages_its_history() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_soul(&mut harness, 1_000);
+    let admin = harness.initialize_roles();
+    let officer = harness.add_wallet();
+    harness
+        .process_instruction(&AccessInstruction::grant_role(&program_id, &admin, &officer, Role::Compliance).unwrap())
+        .unwrap();
+    let holder = harness.add_wallet();
+    let held = harness.add_token_account(&setup.mint, &holder, 0);
+    let hold = |action: usize, archive_page: Option<u64>| {
+        let build = if action.is_multiple_of(2) { SOuLInstruction::freeze } else { SOuLInstruction::thaw };
+        build(&program_id, &spl_token::id(), &officer, &setup.mint, &held, [action as u8; 32], archive_page).unwrap()
+    };
+
+    // The record must be the PDA of the held account
+    let other = harness.add_token_account(&setup.mint, &holder, 0);
+    let mut foreign_record = hold(0, None);
+    foreign_record.accounts[6].pubkey = ComplianceRecord::find_address(&other, &program_id).0;
+    assert_eq!(harness.process_instruction(&foreign_record), Err(ProgramError::InvalidSeeds));
+
+    for action in 0..ComplianceRecord::MAX_ENTRIES {
+        harness.process_instruction(&hold(action, None)).unwrap();
+    }
+    let (record, _) = ComplianceRecord::find_address(&held, &program_id);
+    let full = harness.get_state::<ComplianceRecord>(&record);
+    assert_eq!(full.archive_page(), Some(0));
+
+    // Another action needs the page the full record moves into
+    let next = ComplianceRecord::MAX_ENTRIES;
+    assert_eq!(harness.process_instruction(&hold(next, None)), Err(ProgramError::NotEnoughAccountKeys));
+    assert_eq!(harness.process_instruction(&hold(next, Some(1))), Err(ProgramError::InvalidSeeds));
+    harness.process_instruction(&hold(next, Some(0))).unwrap();
+
+    let (page, bump) = ComplianceHistory::find_address(&held, 0, &program_id);
+    let history = harness.get_state::<ComplianceHistory>(&page);
+    assert_eq!(history.token_account, held);
+    assert_eq!(history.page, 0);
+    assert_eq!(history.entries, full.entries);
+    assert_eq!(history.payer, officer);
+    assert_eq!(history.bump, bump);
+
+    let record = harness.get_state::<ComplianceRecord>(&record);
+    assert_eq!(record.action_count, ComplianceRecord::MAX_ENTRIES as u64 + 1);
+    assert_eq!(record.entries.len(), 1);
+    assert_eq!(record.entries[0].reason_hash, [next as u8; 32]);
+    assert_eq!(record.archive_page(), None);
 }
@@ -0,0 +1,367 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        distributor::{Distributor, DistributorInstruction},
+        error::SOuLError,
+        event::SoulEvent,
+        instruction::SOuLInstruction,
+        processor::process_instruction,
+        state::SOuLToken,
+        vesting::{VestingInstruction, VestingSchedule, VestingType},
+    },
+    solana_program::{instruction::Instruction, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey},
+    spl_token_2022::{
+        extension::{
+            transfer_fee::TransferFeeConfig, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
+        state::{Account as TokenAccount, Mint},
+    },
+};
+
+struct FeeSetup {
+    token_account: Pubkey,
+    mint: Pubkey,
+    authority: Pubkey,
+    holder: Pubkey,
+    treasury: Pubkey,
+}
+
+fn mint_space() -> usize {
+    ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap()
+}
+
+fn initialize_with_fee(
+    harness: &mut ProgramHarness,
+    total_supply: u64,
+    transfer_fee_basis_points: u16,
+    maximum_fee: u64,
+) -> FeeSetup {
+    let program_id = harness.program_id;
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token_2022::id(), mint_space());
+    let holder = harness.add_token_2022_account(&mint, &authority, 0);
+    let (token_account, _) = SOuLToken::find_address(&mint, &program_id);
+    let (treasury, _) = SOuLToken::find_treasury(&token_account, &program_id);
+
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul_with_transfer_fee(
+                &program_id,
+                &authority,
+                &mint,
+                &holder,
+                total_supply,
+                transfer_fee_basis_points,
+                maximum_fee,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    FeeSetup {
+        token_account,
+        mint,
+        authority,
+        holder,
+        treasury,
+    }
+}
+
+fn transfer(program_id: &Pubkey, setup: &FeeSetup, destination: &Pubkey, amount: u64) -> Instruction {
+    SOuLInstruction::transfer(
+        program_id,
+        &spl_token_2022::id(),
+        &setup.holder,
+        destination,
+        &setup.authority,
+        &setup.mint,
+        amount,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_initialize_with_transfer_fee() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    let setup = initialize_with_fee(&mut harness, 1_000_000, 150, 5_000);
+
+    let token = harness.get_state::<SOuLToken>(&setup.token_account);
+    assert_eq!(token.token_program, spl_token_2022::id());
+    assert_eq!(token.transfer_fee_basis_points, 150);
+    assert_eq!(token.maximum_fee, 5_000);
+    assert_eq!(token.treasury_bump, SOuLToken::find_treasury(&setup.token_account, &program_id).1);
+    assert_eq!(token.total_supply, 1_000_000);
+    assert_eq!(harness.token_balance(&setup.holder), 1_000_000);
+
+    // The mint authority PDA controls the fee and its withdrawal
+    let (mint_authority, _) = SOuLToken::find_mint_authority(&setup.token_account, &program_id);
+    let mint_data = harness.get_account(&setup.mint).unwrap().data.clone();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap();
+    let fee_config = mint.get_extension::<TransferFeeConfig>().unwrap();
+    assert_eq!(Option::<Pubkey>::from(fee_config.transfer_fee_config_authority), Some(mint_authority));
+    assert_eq!(Option::<Pubkey>::from(fee_config.withdraw_withheld_authority), Some(mint_authority));
+    assert_eq!(u16::from(fee_config.newer_transfer_fee.transfer_fee_basis_points), 150);
+    assert_eq!(u64::from(fee_config.newer_transfer_fee.maximum_fee), 5_000);
+
+    // The treasury is a token account of the mint that owns itself
+    let treasury = harness.get_account(&setup.treasury).unwrap();
+    assert_eq!(treasury.owner, spl_token_2022::id());
+    let treasury_state = StateWithExtensions::<TokenAccount>::unpack(&treasury.data).unwrap().base;
+    assert_eq!(treasury_state.mint, setup.mint);
+    assert_eq!(treasury_state.owner, setup.treasury);
+    assert_eq!(treasury_state.amount, 0);
+}
+
+#[test]
+fn test_initialize_rejects_fee_above_one_hundred_percent() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let authority = harness.add_wallet();
+    let mint = harness.add_funded_account(&spl_token_2022::id(), mint_space());
+    let holder = harness.add_token_2022_account(&mint, &authority, 0);
+
+    let initialize = |transfer_fee_basis_points| {
+        SOuLInstruction::initialize_soul_with_transfer_fee(
+            &program_id,
+            &authority,
+            &mint,
+            &holder,
+            0,
+            transfer_fee_basis_points,
+            u64::MAX,
+        )
+        .unwrap()
+    };
+    assert_eq!(
+        harness.process_instruction(&initialize(10_001)),
+        Err(SOuLError::InvalidTransferFee.into())
+    );
+    harness.process_instruction(&initialize(10_000)).unwrap();
+}
+
+#[test]
+fn test_transfers_withhold_fee_and_harvest_sweeps_it_to_treasury() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_with_fee(&mut harness, 100_000, 100, 50);
+    let recipient = harness.add_token_2022_account(&setup.mint, &Pubkey::new_unique(), 0);
+
+    // 1% of 2,000 is withheld, while 1% of 10,000 is capped at the maximum fee
+    harness.process_instruction(&transfer(&program_id, &setup, &recipient, 2_000)).unwrap();
+    harness.process_instruction(&transfer(&program_id, &setup, &recipient, 10_000)).unwrap();
+    assert_eq!(harness.token_balance(&setup.holder), 88_000);
+    assert_eq!(harness.token_balance(&recipient), 12_000 - 70);
+    assert_eq!(harness.withheld_fees(&recipient), 70);
+
+    // Fee-bearing transfers cannot go through the legacy program
+    let mut legacy = transfer(&program_id, &setup, &recipient, 1_000);
+    legacy.accounts[5].pubkey = spl_token::id();
+    assert_eq!(harness.process_instruction(&legacy), Err(ProgramError::IncorrectProgramId));
+
+    let timestamp = harness.clock().unix_timestamp;
+    harness
+        .process_instruction(&SOuLInstruction::harvest_fees(&program_id, &setup.mint, &[recipient]).unwrap())
+        .unwrap();
+    assert_eq!(harness.token_balance(&setup.treasury), 70);
+    assert_eq!(harness.withheld_fees(&recipient), 0);
+    let events = harness
+        .take_logged_data()
+        .iter()
+        .filter_map(|fields| SoulEvent::from_log_data(fields))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![SoulEvent::FeesHarvested {
+            mint: setup.mint,
+            treasury: setup.treasury,
+            amount: 70,
+            timestamp,
+        }]
+    );
+
+    // Nothing left to sweep
+    harness
+        .process_instruction(&SOuLInstruction::harvest_fees(&program_id, &setup.mint, &[recipient]).unwrap())
+        .unwrap();
+    assert_eq!(harness.token_balance(&setup.treasury), 70);
+}
+
+#[test]
+fn test_harvest_requires_transfer_fee_mint_and_its_treasury() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+
+    let authority = harness.add_wallet();
+    let legacy_mint = harness.add_funded_account(&spl_token::id(), spl_token::state::Mint::LEN);
+    let destination = harness.add_token_account(&legacy_mint, &authority, 0);
+    harness
+        .process_instruction(
+            &SOuLInstruction::initialize_soul(&program_id, &authority, &legacy_mint, &destination, 10).unwrap(),
+        )
+        .unwrap();
+    let token = harness.get_state::<SOuLToken>(&SOuLToken::find_address(&legacy_mint, &program_id).0);
+    assert_eq!(token.token_program, spl_token::id());
+    assert_eq!(token.transfer_fee_basis_points, 0);
+    assert_eq!(
+        harness.process_instruction(&SOuLInstruction::harvest_fees(&program_id, &legacy_mint, &[]).unwrap()),
+        Err(SOuLError::TransferFeeNotEnabled.into())
+    );
+
+    let setup = initialize_with_fee(&mut harness, 1_000, 100, 50);
+    let mut harvest = SOuLInstruction::harvest_fees(&program_id, &setup.mint, &[]).unwrap();
+    harvest.accounts[2].pubkey = setup.holder;
+    assert_eq!(harness.process_instruction(&harvest), Err(ProgramError::InvalidSeeds));
+}
+
+#[test]
+fn test_set_transfer_fee_is_gated_and_applies_two_epochs_later() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_with_fee(&mut harness, 100_000, 100, 1_000);
+    let recipient = harness.add_token_2022_account(&setup.mint, &Pubkey::new_unique(), 0);
+    let set_fee = |authority: &Pubkey, transfer_fee_basis_points| {
+        SOuLInstruction::set_transfer_fee(&program_id, authority, &setup.mint, transfer_fee_basis_points, 1_000)
+            .unwrap()
+    };
+
+    let impostor = harness.add_wallet();
+    assert_eq!(
+        harness.process_instruction(&set_fee(&impostor, 200)),
+        Err(SOuLError::InvalidAuthority.into())
+    );
+    assert_eq!(
+        harness.process_instruction(&set_fee(&setup.authority, 10_001)),
+        Err(SOuLError::InvalidTransferFee.into())
+    );
+
+    harness.process_instruction(&set_fee(&setup.authority, 200)).unwrap();
+    let token = harness.get_state::<SOuLToken>(&setup.token_account);
+    assert_eq!(token.transfer_fee_basis_points, 200);
+    assert_eq!(token.maximum_fee, 1_000);
+
+    // The old fee still applies until two epochs have passed
+    harness.process_instruction(&transfer(&program_id, &setup, &recipient, 10_000)).unwrap();
+    assert_eq!(harness.withheld_fees(&recipient), 100);
+
+    harness.warp_to_epoch(2);
+    harness.process_instruction(&transfer(&program_id, &setup, &recipient, 10_000)).unwrap();
+    assert_eq!(harness.withheld_fees(&recipient), 300);
+}
+
+#[test]
+fn test_vesting_vault_of_a_fee_mint_holds_the_schedule_total() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_with_fee(&mut harness, 1_000_000, 100, 1_000_000);
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let initialize = |token_program: &Pubkey| {
+        VestingInstruction::initialize(
+            &program_id,
+            token_program,
+            &setup.authority,
+            &beneficiary,
+            &setup.mint,
+            &setup.holder,
+            0,
+            now,
+            99_000,
+            VestingType::Ecosystem,
+            None,
+            Some(100),
+            None,
+            None,
+        )
+        .unwrap()
+    };
+
+    assert_eq!(
+        harness.process_instruction(&initialize(&spl_token::id())),
+        Err(ProgramError::IncorrectProgramId)
+    );
+
+    // The authority pays the fee on top, so the vault holds the whole total
+    harness.process_instruction(&initialize(&spl_token_2022::id())).unwrap();
+    assert_eq!(harness.token_balance(&vault), 99_000);
+    assert_eq!(harness.withheld_fees(&vault), 1_000);
+    assert_eq!(harness.token_balance(&setup.holder), 900_000);
+
+    harness.warp_to_timestamp(now + 100);
+    let beneficiary_tokens = harness.add_token_2022_account(&setup.mint, &beneficiary, 0);
+    harness
+        .process_instruction(
+            &VestingInstruction::release(
+                &program_id,
+                &spl_token_2022::id(),
+                &vesting_account,
+                &vault,
+                &setup.mint,
+                &beneficiary_tokens,
+                &beneficiary,
+                &[],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&vault), 0);
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 98_010);
+
+    // Fees withheld in the vault are moved to the mint so it can be closed
+    harness
+        .process_instruction(
+            &VestingInstruction::close_schedule(
+                &program_id,
+                &spl_token_2022::id(),
+                &vesting_account,
+                &vault,
+                &setup.mint,
+                &beneficiary_tokens,
+                &setup.authority,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.get_account(&vault).unwrap().lamports, 0);
+    let mint_data = harness.get_account(&setup.mint).unwrap().data.clone();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap();
+    assert_eq!(u64::from(mint.get_extension::<TransferFeeConfig>().unwrap().withheld_amount), 1_000);
+}
+
+#[test]
+fn test_distributor_vault_of_a_fee_mint_holds_max_total_claim() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let setup = initialize_with_fee(&mut harness, 1_000_000, 100, 1_000_000);
+    let expiry = harness.clock().unix_timestamp + 24 * 60 * 60;
+    let (distributor, _) = Distributor::find_address(&setup.authority, 0, &program_id);
+    let (vault, _) = Distributor::find_vault(&distributor, &program_id);
+
+    // The admin pays the fee on top, so every claim is backed
+    harness
+        .process_instruction(
+            &DistributorInstruction::new_distributor(
+                &program_id,
+                &spl_token_2022::id(),
+                &setup.authority,
+                &setup.mint,
+                &setup.holder,
+                0,
+                [1; 32],
+                99_000,
+                3,
+                expiry,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&vault), 99_000);
+    assert_eq!(harness.withheld_fees(&vault), 1_000);
+    assert_eq!(harness.token_balance(&setup.holder), 900_000);
+    assert_eq!(harness.get_state::<Distributor>(&distributor).max_total_claim, 99_000);
+}
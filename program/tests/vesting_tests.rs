@@ -0,0 +1,1097 @@
+mod common;
+
+use {
+    common::ProgramHarness,
+    soul_sanctuary::{
+        access::{AccessError, AccessInstruction, CircuitBreaker},
+        account::AccountState,
+        error::SOuLError,
+        impact::{GeoLocation, ImpactInstruction, ImpactMetric, ImpactMetrics, ImpactReport, VerificationStatus},
+        instruction::ModuleTag,
+        lst::LSTType,
+        processor::process_instruction,
+        vesting::{
+            TrancheMode, VestingError, VestingInstruction, VestingMilestone, VestingSchedule, VestingTranche,
+            VestingType,
+        },
+    },
+    solana_program::{instruction::Instruction, program_error::ProgramError, pubkey::Pubkey},
+};
+
+/// An authority holding tokens to fund schedules with
+struct Funder {
+    authority: Pubkey,
+    mint: Pubkey,
+    source: Pubkey,
+}
+
+fn add_funder(harness: &mut ProgramHarness) -> Funder {
+    let authority = harness.add_wallet();
+    let mint = harness.add_mint(&authority, 10_000, 9);
+    let source = harness.add_token_account(&mint, &authority, 10_000);
+    Funder {
+        authority,
+        mint,
+        source,
+    }
+}
+
+/// Initialize a schedule of 1,000 tokens that vests over 100 seconds if it is
+/// an Ecosystem schedule, or on its type's preset otherwise, revocable by the
+/// funder if its type may be
+fn initialize_instruction(
+    program_id: Pubkey,
+    funder: &Funder,
+    beneficiary: Pubkey,
+    nonce: u64,
+    start_timestamp: i64,
+    vesting_type: VestingType,
+) -> Instruction {
+    let duration_seconds = matches!(vesting_type, VestingType::Ecosystem).then_some(100);
+    let revoke_authority = vesting_type.is_revocable().then_some(funder.authority);
+    VestingInstruction::initialize(
+        &program_id,
+        &spl_token::id(),
+        &funder.authority,
+        &beneficiary,
+        &funder.mint,
+        &funder.source,
+        nonce,
+        start_timestamp,
+        1_000,
+        vesting_type,
+        None,
+        duration_seconds,
+        None,
+        revoke_authority,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_vesting_schedule_is_created_at_its_pda() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let beneficiary = Pubkey::new_unique();
+    let now = harness.clock().unix_timestamp;
+
+    let (first, first_bump) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (second, _) = VestingSchedule::find_address(&beneficiary, 1, &program_id);
+    assert_ne!(first, second);
+
+    for nonce in [0, 1] {
+        harness
+            .process_instruction(&initialize_instruction(
+                program_id,
+                &funder,
+                beneficiary,
+                nonce,
+                now,
+                VestingType::Ecosystem,
+            ))
+            .unwrap();
+    }
+
+    let account = harness.get_account(&first).unwrap();
+    assert_eq!(account.owner, program_id);
+    let schedule = VestingSchedule::unpack_account(&account.data).unwrap();
+    assert_eq!(schedule.original_beneficiary, beneficiary);
+    assert_eq!(schedule.nonce, 0);
+    assert_eq!(schedule.bump, first_bump);
+
+    // The nonce must match the address it derives
+    let mut instruction =
+        initialize_instruction(program_id, &funder, beneficiary, 2, now, VestingType::Ecosystem);
+    instruction.accounts[1].pubkey = Pubkey::new_unique();
+    assert_eq!(harness.process_instruction(&instruction), Err(ProgramError::InvalidSeeds));
+    assert_eq!(
+        harness.process_instruction(&initialize_instruction(
+            program_id,
+            &funder,
+            beneficiary,
+            0,
+            now,
+            VestingType::Ecosystem,
+        )),
+        Err(ProgramError::AccountAlreadyInitialized)
+    );
+}
+
+#[test]
+fn test_release_is_signed_by_the_vesting_pda() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 7, &program_id);
+    harness
+        .process_instruction(&initialize_instruction(
+            program_id,
+            &funder,
+            beneficiary,
+            7,
+            now,
+            VestingType::Ecosystem,
+        ))
+        .unwrap();
+
+    let mint = funder.mint;
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+
+    harness.warp_to_timestamp(now + 50);
+    harness
+        .process_instruction(
+            &VestingInstruction::release(
+                &program_id,
+                &spl_token::id(),
+                &vesting_account,
+                &vault,
+                &mint,
+                &beneficiary_tokens,
+                &beneficiary,
+                &[],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 500);
+    assert_eq!(harness.token_balance(&vault), 500);
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.released_amount, 500);
+}
+
+#[test]
+fn test_schedule_only_moves_tokens_through_its_own_vault() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    harness
+        .process_instruction(&initialize_instruction(program_id, &funder, beneficiary, 0, now, VestingType::Team))
+        .unwrap();
+
+    // Initialize moves the whole amount out of the authority's tokens
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    assert_eq!(harness.token_balance(&vault), 1_000);
+    assert_eq!(harness.token_balance(&funder.source), 9_000);
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.vault, vault);
+    assert_eq!(schedule.mint, funder.mint);
+
+    // Another account of the vesting PDA is not the schedule's vault
+    let decoy = harness.add_token_account(&funder.mint, &vesting_account, 1_000);
+    let beneficiary_tokens = harness.add_token_account(&funder.mint, &beneficiary, 0);
+    let recovery = harness.add_token_account(&funder.mint, &funder.authority, 0);
+    harness.warp_to_timestamp(now + 400 * 24 * 60 * 60);
+    for instruction in [
+        VestingInstruction::release(
+            &program_id,
+            &spl_token::id(),
+            &vesting_account,
+            &decoy,
+            &funder.mint,
+            &beneficiary_tokens,
+            &beneficiary,
+            &[],
+        ),
+        VestingInstruction::revoke(
+            &program_id,
+            &spl_token::id(),
+            &funder.authority,
+            &vesting_account,
+            &decoy,
+            &funder.mint,
+            &beneficiary_tokens,
+            &recovery,
+            &[],
+        ),
+    ] {
+        assert_eq!(
+            harness.process_instruction(&instruction.unwrap()),
+            Err(VestingError::InvalidTokenAccount.into())
+        );
+    }
+    assert_eq!(harness.token_balance(&vault), 1_000);
+
+    // Once revoked through its vault, only that vault can be closed
+    harness
+        .process_instruction(
+            &VestingInstruction::revoke(
+                &program_id,
+                &spl_token::id(),
+                &funder.authority,
+                &vesting_account,
+                &vault,
+                &funder.mint,
+                &beneficiary_tokens,
+                &recovery,
+                &[],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&vault), 0);
+    assert_eq!(
+        harness.process_instruction(
+            &VestingInstruction::close_schedule(
+                &program_id,
+                &spl_token::id(),
+                &vesting_account,
+                &decoy,
+                &funder.mint,
+                &beneficiary_tokens,
+                &funder.authority,
+            )
+            .unwrap()
+        ),
+        Err(VestingError::InvalidTokenAccount.into())
+    );
+    assert_eq!(harness.token_balance(&decoy), 1_000);
+}
+
+#[test]
+fn test_update_beneficiary_moves_release_rights() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let beneficiary = harness.add_wallet();
+    let new_beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    harness
+        .process_instruction(&initialize_instruction(
+            program_id,
+            &funder,
+            beneficiary,
+            0,
+            now,
+            VestingType::Ecosystem,
+        ))
+        .unwrap();
+
+    harness
+        .process_instruction(
+            &VestingInstruction::update_beneficiary(&program_id, &beneficiary, &vesting_account, &new_beneficiary)
+                .unwrap(),
+        )
+        .unwrap();
+
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.beneficiary, new_beneficiary);
+    assert_eq!(schedule.original_beneficiary, beneficiary);
+
+    // The PDA still signs with the original beneficiary's seeds
+    let mint = funder.mint;
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let old_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+    let new_tokens = harness.add_token_account(&mint, &new_beneficiary, 0);
+    harness.warp_to_timestamp(now + 100);
+
+    assert_eq!(
+        harness.process_instruction(
+            &VestingInstruction::release(
+                &program_id,
+                &spl_token::id(),
+                &vesting_account,
+                &vault,
+                &mint,
+                &old_tokens,
+                &beneficiary,
+                &[],
+            )
+            .unwrap()
+        ),
+        Err(ProgramError::InvalidAccountData)
+    );
+    harness
+        .process_instruction(
+            &VestingInstruction::release(
+                &program_id,
+                &spl_token::id(),
+                &vesting_account,
+                &vault,
+                &mint,
+                &new_tokens,
+                &new_beneficiary,
+                &[],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&new_tokens), 1_000);
+}
+
+#[test]
+fn test_revoke_pays_vested_tokens_and_returns_the_rest() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let authority = funder.authority;
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+    let mint = funder.mint;
+    let recovery = harness.add_token_account(&mint, &authority, 0);
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+
+    // A Team schedule over 100 seconds with a 10 second cliff
+    harness
+        .process_instruction(
+            &VestingInstruction::initialize(
+                &program_id,
+                &spl_token::id(),
+                &authority,
+                &beneficiary,
+                &funder.mint,
+                &funder.source,
+                0,
+                now,
+                1_000,
+                VestingType::Team,
+                Some(10),
+                Some(100),
+                None,
+                Some(authority),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    harness
+        .process_instruction(&initialize_instruction(
+            program_id,
+            &funder,
+            beneficiary,
+            1,
+            now,
+            VestingType::Ecosystem,
+        ))
+        .unwrap();
+
+    let (team_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (team_vault, _) = VestingSchedule::find_vault(&team_account, &program_id);
+    assert_eq!(harness.get_state::<VestingSchedule>(&team_account).revoke_authority, Some(authority));
+    let revoke = |authority: &Pubkey| {
+        VestingInstruction::revoke(
+            &program_id,
+            &spl_token::id(),
+            authority,
+            &team_account,
+            &team_vault,
+            &mint,
+            &beneficiary_tokens,
+            &recovery,
+            &[],
+        )
+        .unwrap()
+    };
+    let release = VestingInstruction::release(
+        &program_id,
+        &spl_token::id(),
+        &team_account,
+        &team_vault,
+        &mint,
+        &beneficiary_tokens,
+        &beneficiary,
+        &[],
+    )
+    .unwrap();
+
+    harness.warp_to_timestamp(now + 20);
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 200);
+
+    // Only the recorded authority may revoke
+    assert_eq!(harness.process_instruction(&revoke(&beneficiary)), Err(VestingError::InvalidAuthority.into()));
+
+    // The vested payout counts against the vesting circuit breaker
+    let admin = harness.initialize_roles();
+    let configure = |max_outflow: u64| {
+        AccessInstruction::configure_circuit_breaker(&program_id, &admin, ModuleTag::Vesting, 9_000, max_outflow)
+            .unwrap()
+    };
+    harness.process_instruction(&configure(300)).unwrap();
+    harness.warp_to_timestamp(now + 60);
+    assert_eq!(harness.process_instruction(&revoke(&authority)), Err(AccessError::OutflowLimitExceeded.into()));
+
+    harness.process_instruction(&configure(1_000)).unwrap();
+    harness.process_instruction(&revoke(&authority)).unwrap();
+    let (breaker, _) = CircuitBreaker::find_address(ModuleTag::Vesting, &program_id);
+    assert_eq!(harness.get_state::<CircuitBreaker>(&breaker).outflow, 400);
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 600);
+    assert_eq!(harness.token_balance(&recovery), 400);
+    assert_eq!(harness.token_balance(&team_vault), 0);
+    let schedule = harness.get_state::<VestingSchedule>(&team_account);
+    assert_eq!(schedule.revoked_at, Some(now + 60));
+    assert_eq!(schedule.released_amount, 600);
+    assert!(schedule.is_complete());
+
+    // Nothing more vests once revoked
+    harness.warp_to_timestamp(now + 100);
+    assert_eq!(harness.process_instruction(&release), Err(VestingError::ScheduleRevoked.into()));
+    assert_eq!(harness.process_instruction(&revoke(&authority)), Err(VestingError::ScheduleRevoked.into()));
+
+    let (ecosystem_account, _) = VestingSchedule::find_address(&beneficiary, 1, &program_id);
+    let (ecosystem_vault, _) = VestingSchedule::find_vault(&ecosystem_account, &program_id);
+    assert_eq!(harness.get_state::<VestingSchedule>(&ecosystem_account).revoke_authority, None);
+    assert_eq!(
+        harness.process_instruction(
+            &VestingInstruction::revoke(
+                &program_id,
+                &spl_token::id(),
+                &authority,
+                &ecosystem_account,
+                &ecosystem_vault,
+                &mint,
+                &beneficiary_tokens,
+                &recovery,
+                &[],
+            )
+            .unwrap()
+        ),
+        Err(VestingError::InvalidRevocation.into())
+    );
+
+    // Nor can an Ecosystem schedule be created with a revoke authority
+    assert_eq!(
+        harness.process_instruction(
+            &VestingInstruction::initialize(
+                &program_id,
+                &spl_token::id(),
+                &authority,
+                &beneficiary,
+                &funder.mint,
+                &funder.source,
+                2,
+                now,
+                1_000,
+                VestingType::Ecosystem,
+                None,
+                Some(100),
+                None,
+                Some(authority),
+            )
+            .unwrap()
+        ),
+        Err(VestingError::InvalidRevocation.into())
+    );
+}
+
+#[test]
+fn test_close_schedule_refunds_the_payer_once_fully_released() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let authority = funder.authority;
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    harness
+        .process_instruction(&initialize_instruction(
+            program_id,
+            &funder,
+            beneficiary,
+            0,
+            now,
+            VestingType::Ecosystem,
+        ))
+        .unwrap();
+    assert_eq!(harness.get_state::<VestingSchedule>(&vesting_account).payer, authority);
+
+    let mint = funder.mint;
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+    let release = VestingInstruction::release(
+        &program_id,
+        &spl_token::id(),
+        &vesting_account,
+        &vault,
+        &mint,
+        &beneficiary_tokens,
+        &beneficiary,
+        &[],
+    )
+    .unwrap();
+    let close = |payer: &Pubkey| {
+        VestingInstruction::close_schedule(
+            &program_id,
+            &spl_token::id(),
+            &vesting_account,
+            &vault,
+            &mint,
+            &beneficiary_tokens,
+            payer,
+        )
+        .unwrap()
+    };
+
+    harness.warp_to_timestamp(now + 50);
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(
+        harness.process_instruction(&close(&authority)),
+        Err(VestingError::ScheduleNotComplete.into())
+    );
+
+    harness.warp_to_timestamp(now + 100);
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(
+        harness.process_instruction(&close(&beneficiary)),
+        Err(SOuLError::RentPayerMismatch.into())
+    );
+    let mut to_authority = close(&authority);
+    to_authority.accounts[3].pubkey = funder.source;
+    assert_eq!(harness.process_instruction(&to_authority), Err(VestingError::InvalidTokenAccount.into()));
+
+    // Tokens sent to the emptied vault go to the beneficiary instead of
+    // keeping it open
+    harness.set_token_account(vault, &mint, &vesting_account, 3);
+    let authority_lamports = harness.get_account(&authority).unwrap().lamports;
+    let rent = harness.get_account(&vesting_account).unwrap().lamports + harness.get_account(&vault).unwrap().lamports;
+    harness.process_instruction(&close(&authority)).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 1_003);
+    assert_eq!(harness.get_account(&vesting_account).unwrap().lamports, 0);
+    assert!(harness.get_account(&vesting_account).unwrap().data.is_empty());
+    assert_eq!(harness.get_account(&vault).unwrap().lamports, 0);
+    assert_eq!(harness.get_account(&authority).unwrap().lamports, authority_lamports + rent);
+    assert_eq!(harness.process_instruction(&close(&authority)), Err(ProgramError::IncorrectProgramId));
+}
+
+#[test]
+fn test_schedule_terms_default_to_the_vesting_type_preset() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let authority = funder.authority;
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+    let initialize = |nonce, vesting_type, cliff_seconds, duration_seconds, release_interval_seconds| {
+        VestingInstruction::initialize(
+            &program_id,
+            &spl_token::id(),
+            &authority,
+            &beneficiary,
+            &funder.mint,
+            &funder.source,
+            nonce,
+            now,
+            1_000,
+            vesting_type,
+            cliff_seconds,
+            duration_seconds,
+            release_interval_seconds,
+            None,
+        )
+        .unwrap()
+    };
+
+    harness.process_instruction(&initialize(0, VestingType::Team, None, None, None)).unwrap();
+    let (team, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let schedule = harness.get_state::<VestingSchedule>(&team);
+    assert_eq!(schedule.cliff_seconds, 31_536_000);
+    assert_eq!(schedule.duration_seconds, 94_608_000);
+    assert_eq!(schedule.release_interval_seconds, 1);
+    assert_eq!(schedule.end_timestamp, now + 94_608_000);
+
+    // An advisor agreement outside the presets: a 10 second cliff, then
+    // quarterly unlocks over 100 seconds
+    harness
+        .process_instruction(&initialize(1, VestingType::Advisor, Some(10), Some(100), Some(25)))
+        .unwrap();
+    let (advisor, _) = VestingSchedule::find_address(&beneficiary, 1, &program_id);
+    let schedule = harness.get_state::<VestingSchedule>(&advisor);
+    assert_eq!(schedule.end_timestamp, now + 100);
+    assert_eq!(schedule.calculate_releasable_amount(now + 9), 0);
+    assert_eq!(schedule.calculate_releasable_amount(now + 24), 0);
+    assert_eq!(schedule.calculate_releasable_amount(now + 30), 250);
+    assert_eq!(schedule.calculate_releasable_amount(now + 99), 750);
+    assert_eq!(schedule.calculate_releasable_amount(now + 100), 1_000);
+
+    for (vesting_type, cliff_seconds, duration_seconds, release_interval_seconds) in [
+        (VestingType::Ecosystem, None, None, None),
+        (VestingType::Team, None, Some(100), None),
+        (VestingType::Ecosystem, Some(101), Some(100), None),
+        (VestingType::Ecosystem, None, Some(0), None),
+        (VestingType::Ecosystem, None, Some(100), Some(0)),
+        (VestingType::Ecosystem, None, Some(100), Some(101)),
+    ] {
+        assert_eq!(
+            harness.process_instruction(&initialize(
+                2,
+                vesting_type,
+                cliff_seconds,
+                duration_seconds,
+                release_interval_seconds,
+            )),
+            Err(VestingError::InvalidVestingSchedule.into())
+        );
+    }
+}
+
+#[test]
+fn test_tranched_schedule_releases_the_sum_unlocked_across_tranches() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let authority = funder.authority;
+    let beneficiary = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+    let tranche = |unlock_timestamp, amount, mode| VestingTranche {
+        unlock_timestamp,
+        amount,
+        mode,
+    };
+    let initialize = |nonce, tranches| {
+        VestingInstruction::initialize_tranched(
+            &program_id,
+            &spl_token::id(),
+            &authority,
+            &beneficiary,
+            &funder.mint,
+            &funder.source,
+            nonce,
+            now,
+            VestingType::PrivateSale,
+            tranches,
+            None,
+        )
+        .unwrap()
+    };
+
+    // 10% at the token generation event, 60% linearly over the next 100
+    // seconds and a 30% balloon 50 seconds later
+    let tranches = vec![
+        tranche(now, 100, TrancheMode::Instant),
+        tranche(now + 100, 600, TrancheMode::Linear),
+        tranche(now + 150, 300, TrancheMode::Instant),
+    ];
+    harness.process_instruction(&initialize(0, tranches.clone())).unwrap();
+
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let account = harness.get_account(&vesting_account).unwrap();
+    assert_eq!(account.data.len(), VestingSchedule::space(3, 0));
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.total_amount, 1_000);
+    assert_eq!(schedule.end_timestamp, now + 150);
+    assert_eq!(schedule.tranches, tranches);
+
+    let mint = funder.mint;
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+    let release = VestingInstruction::release(
+        &program_id,
+        &spl_token::id(),
+        &vesting_account,
+        &vault,
+        &mint,
+        &beneficiary_tokens,
+        &beneficiary,
+        &[],
+    )
+    .unwrap();
+
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 100);
+
+    harness.warp_to_timestamp(now + 50);
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 400);
+
+    harness.warp_to_timestamp(now + 149);
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 700);
+
+    harness.warp_to_timestamp(now + 150);
+    harness.process_instruction(&release).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 1_000);
+    assert!(harness.get_state::<VestingSchedule>(&vesting_account).is_complete());
+
+    for tranches in [
+        vec![],
+        vec![tranche(now - 1, 100, TrancheMode::Instant)],
+        vec![tranche(now, 100, TrancheMode::Linear)],
+        vec![tranche(now + 10, 100, TrancheMode::Instant), tranche(now + 5, 100, TrancheMode::Instant)],
+        vec![tranche(now + 10, 0, TrancheMode::Instant)],
+        vec![tranche(now + 10, u64::MAX, TrancheMode::Instant), tranche(now + 20, 1, TrancheMode::Instant)],
+        vec![tranche(now, 1, TrancheMode::Instant); VestingSchedule::MAX_TRANCHES + 1],
+    ] {
+        assert_eq!(
+            harness.process_instruction(&initialize(1, tranches)),
+            Err(VestingError::InvalidVestingSchedule.into())
+        );
+    }
+}
+
+/// Add a report of `project` with `metrics`, verified at `verified_at`
+fn add_report(harness: &mut ProgramHarness, project: Pubkey, metrics: ImpactMetrics, verified_at: i64) -> Pubkey {
+    let program_id = harness.program_id;
+    let report = harness.add_funded_account(&program_id, ImpactReport::SPACE);
+    harness.set_state(
+        &report,
+        &ImpactReport {
+            metrics_account: project,
+            timestamp: verified_at,
+            location: GeoLocation {
+                latitude: 0,
+                longitude: 0,
+                altitude: 0,
+            },
+            metrics,
+            verifier: Pubkey::new_unique(),
+            verification_status: VerificationStatus::Verified,
+            proof_hash: [3; 32],
+            bump: 0,
+            verified_at: Some(verified_at),
+            category: Some(LSTType::Forest),
+        },
+    );
+    report
+}
+
+#[test]
+fn test_impact_milestones_unlock_on_final_reports_of_the_project() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let authority = funder.authority;
+    let beneficiary = harness.add_wallet();
+    let partner = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (project, _) = ImpactMetrics::find_address(&partner, &program_id);
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &partner).unwrap())
+        .unwrap();
+    let milestone = |metric, target, amount| VestingMilestone {
+        metric,
+        target,
+        amount,
+        report: None,
+    };
+    let initialize = |nonce, project: &Pubkey, milestones| {
+        VestingInstruction::initialize_milestones(
+            &program_id,
+            &spl_token::id(),
+            &authority,
+            &beneficiary,
+            &funder.mint,
+            &funder.source,
+            nonce,
+            project,
+            milestones,
+            None,
+        )
+        .unwrap()
+    };
+
+    // 40% once 5 t of carbon are offset, 60% once 2 ha of forest are restored
+    harness
+        .process_instruction(&initialize(
+            0,
+            &project,
+            vec![
+                milestone(ImpactMetric::CarbonOffset, 5_000, 400),
+                milestone(ImpactMetric::ForestArea, 20_000, 600),
+            ],
+        ))
+        .unwrap();
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    assert_eq!(harness.get_account(&vesting_account).unwrap().data.len(), VestingSchedule::space(0, 2));
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.total_amount, 1_000);
+    assert_eq!(schedule.project, project);
+
+    let mint = funder.mint;
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    let beneficiary_tokens = harness.add_token_account(&mint, &beneficiary, 0);
+    let release = |reports: &[Pubkey]| {
+        VestingInstruction::release(
+            &program_id,
+            &spl_token::id(),
+            &vesting_account,
+            &vault,
+            &mint,
+            &beneficiary_tokens,
+            &beneficiary,
+            reports,
+        )
+        .unwrap()
+    };
+    assert_eq!(harness.process_instruction(&release(&[])), Err(VestingError::NoTokensToRelease.into()));
+
+    let metrics = |carbon_offset, forest_area| ImpactMetrics {
+        is_initialized: true,
+        authority: partner,
+        last_update: 0,
+        forest_area,
+        carbon_offset,
+        water_saved: 0,
+        biodiversity_score: 0,
+        energy_saved: 0,
+        waste_recycled: 0,
+        impact_score: 0,
+        bump: 0,
+    };
+    let final_at = now - ImpactReport::DISPUTE_WINDOW_SECONDS;
+
+    // Reports still open to dispute, or of another project, are no evidence
+    let disputable = add_report(&mut harness, project, metrics(5_000, 0), now);
+    let other_project = add_report(&mut harness, Pubkey::new_unique(), metrics(5_000, 0), final_at);
+    for report in [disputable, other_project] {
+        assert_eq!(
+            harness.process_instruction(&release(&[report])),
+            Err(VestingError::InvalidImpactEvidence.into())
+        );
+    }
+
+    let carbon_report = add_report(&mut harness, project, metrics(6_000, 10_000), final_at);
+    harness.process_instruction(&release(&[carbon_report])).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 400);
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.milestones[0].report, Some(carbon_report));
+    assert_eq!(schedule.milestones[1].report, None);
+
+    let forest_report = add_report(&mut harness, project, metrics(0, 25_000), final_at);
+    harness.process_instruction(&release(&[carbon_report, forest_report])).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 1_000);
+    assert!(harness.get_state::<VestingSchedule>(&vesting_account).is_complete());
+
+    // Milestones must be new and able to vest, and impact milestone
+    // schedules do not vest over time
+    for milestones in [
+        vec![],
+        vec![milestone(ImpactMetric::WaterSaved, 1, 0)],
+        vec![VestingMilestone {
+            report: Some(carbon_report),
+            ..milestone(ImpactMetric::WaterSaved, 1, 1)
+        }],
+    ] {
+        assert_eq!(
+            harness.process_instruction(&initialize(1, &project, milestones)),
+            Err(VestingError::InvalidVestingSchedule.into())
+        );
+    }
+    assert_eq!(
+        harness.process_instruction(&initialize_instruction(
+            program_id,
+            &funder,
+            beneficiary,
+            1,
+            now,
+            VestingType::ImpactMilestone,
+        )),
+        Err(VestingError::InvalidVestingSchedule.into())
+    );
+}
+
+#[test]
+fn test_revoking_impact_milestones_returns_the_unmet_amount() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let beneficiary = harness.add_wallet();
+    let partner = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (project, _) = ImpactMetrics::find_address(&partner, &program_id);
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &partner).unwrap())
+        .unwrap();
+    let milestone = |metric, target, amount| VestingMilestone {
+        metric,
+        target,
+        amount,
+        report: None,
+    };
+    harness
+        .process_instruction(
+            &VestingInstruction::initialize_milestones(
+                &program_id,
+                &spl_token::id(),
+                &funder.authority,
+                &beneficiary,
+                &funder.mint,
+                &funder.source,
+                0,
+                &project,
+                vec![
+                    milestone(ImpactMetric::CarbonOffset, 5_000, 400),
+                    milestone(ImpactMetric::ForestArea, 20_000, 600),
+                ],
+                Some(funder.authority),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+    assert_eq!(harness.get_state::<VestingSchedule>(&vesting_account).revoke_authority, Some(funder.authority));
+
+    let beneficiary_tokens = harness.add_token_account(&funder.mint, &beneficiary, 0);
+    let recovery = harness.add_token_account(&funder.mint, &funder.authority, 0);
+    let report = add_report(
+        &mut harness,
+        project,
+        ImpactMetrics {
+            is_initialized: true,
+            authority: partner,
+            last_update: 0,
+            forest_area: 0,
+            carbon_offset: 5_000,
+            water_saved: 0,
+            biodiversity_score: 0,
+            energy_saved: 0,
+            waste_recycled: 0,
+            impact_score: 0,
+            bump: 0,
+        },
+        now - ImpactReport::DISPUTE_WINDOW_SECONDS,
+    );
+    harness
+        .process_instruction(
+            &VestingInstruction::release(
+                &program_id,
+                &spl_token::id(),
+                &vesting_account,
+                &vault,
+                &funder.mint,
+                &beneficiary_tokens,
+                &beneficiary,
+                &[report],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+    // A forest milestone that is never met would otherwise hold its share
+    // in the vault, and the schedule could never be closed
+    let close = VestingInstruction::close_schedule(
+        &program_id,
+        &spl_token::id(),
+        &vesting_account,
+        &vault,
+        &funder.mint,
+        &beneficiary_tokens,
+        &funder.authority,
+    )
+    .unwrap();
+    assert_eq!(harness.process_instruction(&close), Err(VestingError::ScheduleNotComplete.into()));
+    harness
+        .process_instruction(
+            &VestingInstruction::revoke(
+                &program_id,
+                &spl_token::id(),
+                &funder.authority,
+                &vesting_account,
+                &vault,
+                &funder.mint,
+                &beneficiary_tokens,
+                &recovery,
+                &[],
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 400);
+    assert_eq!(harness.token_balance(&recovery), 600);
+    harness.process_instruction(&close).unwrap();
+    assert_eq!(harness.get_account(&vesting_account).unwrap().lamports, 0);
+}
+
+#[test]
+fn test_revoke_pays_milestones_met_by_unreleased_final_reports() {
+    let program_id = Pubkey::new_unique();
+    let mut harness = ProgramHarness::new(program_id, process_instruction);
+    let funder = add_funder(&mut harness);
+    let beneficiary = harness.add_wallet();
+    let partner = harness.add_wallet();
+    let now = harness.clock().unix_timestamp;
+
+    let (project, _) = ImpactMetrics::find_address(&partner, &program_id);
+    harness
+        .process_instruction(&ImpactInstruction::initialize_metrics(&program_id, &partner).unwrap())
+        .unwrap();
+    let milestone = |metric, target, amount| VestingMilestone {
+        metric,
+        target,
+        amount,
+        report: None,
+    };
+    harness
+        .process_instruction(
+            &VestingInstruction::initialize_milestones(
+                &program_id,
+                &spl_token::id(),
+                &funder.authority,
+                &beneficiary,
+                &funder.mint,
+                &funder.source,
+                0,
+                &project,
+                vec![
+                    milestone(ImpactMetric::CarbonOffset, 5_000, 400),
+                    milestone(ImpactMetric::ForestArea, 20_000, 600),
+                ],
+                Some(funder.authority),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let (vesting_account, _) = VestingSchedule::find_address(&beneficiary, 0, &program_id);
+    let (vault, _) = VestingSchedule::find_vault(&vesting_account, &program_id);
+
+    let beneficiary_tokens = harness.add_token_account(&funder.mint, &beneficiary, 0);
+    let recovery = harness.add_token_account(&funder.mint, &funder.authority, 0);
+    let metrics = ImpactMetrics {
+        is_initialized: true,
+        authority: partner,
+        last_update: 0,
+        forest_area: 0,
+        carbon_offset: 5_000,
+        water_saved: 0,
+        biodiversity_score: 0,
+        energy_saved: 0,
+        waste_recycled: 0,
+        impact_score: 0,
+        bump: 0,
+    };
+    let open = add_report(&mut harness, project, metrics.clone(), now);
+    let report = add_report(&mut harness, project, metrics, now - ImpactReport::DISPUTE_WINDOW_SECONDS);
+    let revoke = |reports: &[Pubkey]| {
+        VestingInstruction::revoke(
+            &program_id,
+            &spl_token::id(),
+            &funder.authority,
+            &vesting_account,
+            &vault,
+            &funder.mint,
+            &beneficiary_tokens,
+            &recovery,
+            reports,
+        )
+        .unwrap()
+    };
+
+    // A report still open to dispute is no evidence
+    assert_eq!(
+        harness.process_instruction(&revoke(&[open])),
+        Err(VestingError::InvalidImpactEvidence.into())
+    );
+
+    // The carbon milestone was met before the revoke, though never released
+    harness.process_instruction(&revoke(&[report])).unwrap();
+    assert_eq!(harness.token_balance(&beneficiary_tokens), 400);
+    assert_eq!(harness.token_balance(&recovery), 600);
+
+    let schedule = harness.get_state::<VestingSchedule>(&vesting_account);
+    assert_eq!(schedule.released_amount, 400);
+    assert_eq!(schedule.milestones[0].report, Some(report));
+    assert!(schedule.revoked_at.is_some());
+}